@@ -1,4 +1,4 @@
-#[derive(Debug, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone)]
 pub enum Datos {
     String(String),
     Integer(i64),
@@ -1,11 +1,12 @@
 use std::fmt::{self, Display, Formatter};
 
 /// Enum que representa los tipos de errores en el sistema.
-/// Contiene variantes para errores de tabla, columna, sintaxis y errores generales.
+/// Contiene variantes para errores de tabla, columna, sintaxis, restricciones y errores generales.
 pub enum ErrorType {
     InvalidTable(String),
     InvalidColumn(String),
     InvalidSyntax(String),
+    ConstraintViolation(String),
     Error(String),
 }
 
@@ -15,6 +16,9 @@ impl Display for ErrorType {
             ErrorType::InvalidTable(description) => write!(f, "[INVALID_TABLE]: {}", description),
             ErrorType::InvalidColumn(description) => write!(f, "[INVALID_COLUMN]: {}", description),
             ErrorType::InvalidSyntax(description) => write!(f, "[INVALID_SYNTAX]: {}", description),
+            ErrorType::ConstraintViolation(description) => {
+                write!(f, "[CONSTRAINT_VIOLATION]: {}", description)
+            }
             ErrorType::Error(description) => write!(f, "[ERROR]: {}", description),
         }
     }
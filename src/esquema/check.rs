@@ -0,0 +1,104 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    queries::where_clause::{
+        evaluar::Evaluar, expresion_booleana::ExpresionBooleana, valor::Valor,
+    },
+};
+
+/// Representa una restricción CHECK: una condición con nombre que deben cumplir todas las filas de la tabla.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Check {
+    pub nombre: String,
+    pub expresion: ExpresionBooleana,
+}
+
+/// Agrega a la lista las columnas que aparecen en un valor.
+fn columnas_valor<'a>(valor: &'a Valor, columnas: &mut Vec<&'a str>) {
+    match valor {
+        Valor::String(s) if s.parse::<i64>().is_err() => columnas.push(s),
+        Valor::Funcion { argumentos, .. } => {
+            for argumento in argumentos {
+                columnas_valor(argumento, columnas);
+            }
+        }
+        Valor::Case(case) => {
+            for (condicion, valor) in &case.ramas {
+                columnas_expresion(condicion, columnas);
+                columnas_valor(valor, columnas);
+            }
+            if let Some(sino) = &case.sino {
+                columnas_valor(sino, columnas);
+            }
+        }
+        Valor::Operacion { izq, der, .. } => {
+            columnas_valor(izq, columnas);
+            columnas_valor(der, columnas);
+        }
+        _ => {}
+    }
+}
+
+/// Agrega a la lista las columnas que aparecen en una expresión booleana.
+fn columnas_expresion<'a>(expresion: &'a ExpresionBooleana, columnas: &mut Vec<&'a str>) {
+    match expresion {
+        ExpresionBooleana::Comparacion { izq, der, .. } => {
+            columnas_valor(izq, columnas);
+            columnas_valor(der, columnas);
+        }
+        ExpresionBooleana::In { valor, .. } => columnas_valor(valor, columnas),
+        ExpresionBooleana::Exists(_) => {}
+        ExpresionBooleana::And(expr1, expr2) | ExpresionBooleana::Or(expr1, expr2) => {
+            columnas_expresion(expr1, columnas);
+            columnas_expresion(expr2, columnas);
+        }
+        ExpresionBooleana::Not(expr) => columnas_expresion(expr, columnas),
+    }
+}
+
+impl Check {
+    /// Crea una nueva instancia de `Check`.
+    pub fn new(nombre: &str, expresion: ExpresionBooleana) -> Self {
+        Check {
+            nombre: nombre.to_string(),
+            expresion,
+        }
+    }
+
+    /// Devuelve las columnas que usa la condición.
+    pub fn columnas(&self) -> Vec<&str> {
+        let mut columnas = Vec::new();
+        columnas_expresion(&self.expresion, &mut columnas);
+        columnas
+    }
+
+    /// Verifica que la fila cumpla la condición.
+    /// Como en SQL, si alguna de las columnas que usa la condición es nula la restricción no se viola.
+    pub fn verificar(&self, fila: &HashMap<String, Datos>) -> Result<(), ErrorType> {
+        if self.expresion.evaluar(fila)? {
+            return Ok(());
+        }
+        let hay_nulos = self
+            .columnas()
+            .iter()
+            .any(|c| fila.get(*c) == Some(&Datos::String("".to_string())));
+        if hay_nulos {
+            return Ok(());
+        }
+        Err(ErrorType::ConstraintViolation(format!(
+            "La fila no cumple la restricción CHECK '{}': {}",
+            self.nombre, self.expresion
+        )))
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CONSTRAINT {} CHECK ({})", self.nombre, self.expresion)
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    queries::where_clause::{comparadores::evaluar_valor, valor::Valor},
+};
+
+/// Enum que representa los tipos de datos que se pueden declarar para una columna.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TipoDato {
+    Integer,
+    String,
+}
+
+impl TipoDato {
+    /// Matchea el nombre de un tipo SQL con su `TipoDato`, si es un tipo conocido.
+    pub fn desde_nombre(nombre: &str) -> Option<TipoDato> {
+        match nombre {
+            "INT" | "INTEGER" | "BIGINT" | "SMALLINT" => Some(TipoDato::Integer),
+            "TEXT" | "VARCHAR" | "CHAR" | "STRING" | "DATE" => Some(TipoDato::String),
+            _ => None,
+        }
+    }
+
+    /// Verifica que el dato sea compatible con el tipo. Los valores vacíos (nulos) son compatibles con todos los tipos.
+    pub fn admite(&self, dato: &Datos) -> bool {
+        match (self, dato) {
+            (TipoDato::Integer, Datos::String(s)) => s.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+impl Display for TipoDato {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TipoDato::Integer => write!(f, "INT"),
+            TipoDato::String => write!(f, "TEXT"),
+        }
+    }
+}
+
+/// Representa la definición de una columna dentro del esquema de una tabla.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColumnaDef {
+    pub nombre: String,
+    pub tipo: Option<TipoDato>,
+    pub not_null: bool,
+    pub default: Option<Valor>,
+    pub autoincrement: bool,
+}
+
+impl ColumnaDef {
+    /// Crea una nueva instancia de `ColumnaDef` sin restricciones.
+    pub fn new(nombre: &str, tipo: Option<TipoDato>) -> Self {
+        ColumnaDef {
+            nombre: nombre.to_string(),
+            tipo,
+            not_null: false,
+            default: None,
+            autoincrement: false,
+        }
+    }
+
+    /// Verifica que el dato a escribir en la columna respete el tipo declarado.
+    pub fn validar_tipo(&self, dato: &Datos) -> Result<(), ErrorType> {
+        match &self.tipo {
+            Some(tipo) if !tipo.admite(dato) => Err(ErrorType::InvalidColumn(format!(
+                "Tipo invalido para la columna '{}', se esperaba {}",
+                self.nombre, tipo
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verifica que el valor escrito en la columna no sea nulo si la columna es NOT NULL.
+    pub fn validar_nulo(&self, valor: &str) -> Result<(), ErrorType> {
+        if self.not_null && valor.trim().is_empty() {
+            return Err(ErrorType::ConstraintViolation(format!(
+                "La columna '{}' no admite valores nulos",
+                self.nombre
+            )));
+        }
+        Ok(())
+    }
+
+    /// Evalúa el valor por defecto de la columna, si tiene uno.
+    pub fn valor_default(&self) -> Result<Option<Datos>, ErrorType> {
+        match &self.default {
+            Some(default) => Ok(Some(evaluar_valor(default, &HashMap::new())?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Display for ColumnaDef {
+    /// Muestra la definición de la columna con su tipo y restricciones, como se escribe en CREATE TABLE.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.nombre)?;
+        if let Some(tipo) = &self.tipo {
+            write!(f, " {}", tipo)?;
+        }
+        if self.not_null {
+            write!(f, " NOT NULL")?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, " DEFAULT {}", default)?;
+        }
+        if self.autoincrement {
+            write!(f, " AUTOINCREMENT")?;
+        }
+        Ok(())
+    }
+}
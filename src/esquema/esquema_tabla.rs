@@ -0,0 +1,233 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    dato::Datos, errores::error::ErrorType, executer::manejo_csv::string_to_columns,
+    lexers::lexer::lexer, parsers::parser::parser, queries::sql_query::SQLQuery,
+};
+
+use super::{
+    check::Check,
+    columna::{ColumnaDef, TipoDato},
+    foreign_key::ForeignKey,
+};
+
+/// Genera la ruta del archivo de esquema de una tabla, que se guarda junto al `.csv` con extensión `.esquema`.
+pub fn ruta_esquema(path: &str, tabla: &str) -> String {
+    format!("{}/{}.esquema", path, tabla)
+}
+
+/// Representa el esquema de una tabla: sus columnas y las restricciones declaradas sobre ellas.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Esquema {
+    pub tabla: String,
+    pub columnas: Vec<ColumnaDef>,
+    pub primary_key: Vec<String>,
+    pub unique: Vec<Vec<String>>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub checks: Vec<Check>,
+}
+
+impl Esquema {
+    /// Crea una nueva instancia de `Esquema` sin columnas ni restricciones.
+    pub fn new(tabla: &str) -> Self {
+        Esquema {
+            tabla: tabla.to_string(),
+            columnas: Vec::new(),
+            primary_key: Vec::new(),
+            unique: Vec::new(),
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Lee el esquema de la tabla desde su archivo `.esquema`, que contiene la sentencia CREATE TABLE.
+    /// Devuelve `None` si la tabla no tiene esquema, en cuyo caso no se valida ninguna restricción.
+    pub fn cargar(path: &str, tabla: &str) -> Result<Option<Esquema>, ErrorType> {
+        let ruta = ruta_esquema(path, tabla);
+        if !Path::new(&ruta).exists() {
+            return Ok(None);
+        }
+        let Ok(texto) = fs::read_to_string(&ruta) else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer el esquema de la tabla".to_string(),
+            ));
+        };
+        let texto = texto.trim().trim_end_matches(';').replace('\n', " ");
+        match parser(&lexer(&texto)?)? {
+            SQLQuery::Create(query) => Ok(Some(query.esquema)),
+            _ => Err(ErrorType::InvalidTable(
+                "El archivo de esquema no contiene un CREATE TABLE".to_string(),
+            )),
+        }
+    }
+
+    /// Escribe el esquema en el archivo `.esquema` de la tabla, reemplazando el anterior si existe.
+    pub fn guardar(&self, path: &str) -> Result<(), ErrorType> {
+        match fs::write(ruta_esquema(path, &self.tabla), format!("{}\n", self)) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ErrorType::InvalidTable(
+                "Error al guardar el esquema de la tabla".to_string(),
+            )),
+        }
+    }
+
+    /// Devuelve los nombres de las columnas en el orden en el que fueron declaradas.
+    pub fn nombres_columnas(&self) -> Vec<String> {
+        self.columnas.iter().map(|c| c.nombre.to_string()).collect()
+    }
+
+    /// Busca la definición de una columna por su nombre.
+    pub fn columna(&self, nombre: &str) -> Option<&ColumnaDef> {
+        self.columnas.iter().find(|c| c.nombre == nombre)
+    }
+
+    /// Verifica que los datos a escribir respeten los tipos declarados de cada columna.
+    pub fn validar_tipos(&self, datos: &HashMap<String, Datos>) -> Result<(), ErrorType> {
+        for (nombre, dato) in datos {
+            if let Some(columna) = self.columna(nombre) {
+                columna.validar_tipo(dato)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Completa los datos a insertar con el valor por defecto de las columnas que no se especificaron.
+    pub fn completar_defaults(&self, datos: &mut HashMap<String, Datos>) -> Result<(), ErrorType> {
+        for columna in &self.columnas {
+            if datos.contains_key(&columna.nombre) {
+                continue;
+            }
+            if let Some(default) = columna.valor_default()? {
+                datos.insert(columna.nombre.to_string(), default);
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifica que una línea de CSV no tenga valores nulos en las columnas NOT NULL.
+    fn verificar_no_nulos(&self, linea: &str, columnas: &[String]) -> Result<(), ErrorType> {
+        let values: Vec<&str> = linea.split(',').collect();
+        for (i, nombre) in columnas.iter().enumerate() {
+            if let Some(columna) = self.columna(nombre) {
+                columna.validar_nulo(values.get(i).unwrap_or(&""))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifica que una línea de CSV cumpla las restricciones NOT NULL y CHECK de la tabla.
+    pub fn verificar_fila(&self, linea: &str, columnas: &[String]) -> Result<(), ErrorType> {
+        self.verificar_no_nulos(linea, columnas)?;
+        if self.checks.is_empty() {
+            return Ok(());
+        }
+        let fila = string_to_columns(linea, columnas)?;
+        for check in &self.checks {
+            check.verificar(&fila)?;
+        }
+        Ok(())
+    }
+
+    /// Verifica que las restricciones referencien columnas declaradas y que no haya columnas repetidas.
+    pub fn validar(&self) -> Result<(), ErrorType> {
+        let nombres = self.nombres_columnas();
+        for (i, nombre) in nombres.iter().enumerate() {
+            if nombres[..i].contains(nombre) {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "Columna '{}' repetida en CREATE TABLE",
+                    nombre
+                )));
+            }
+        }
+        let columnas_fk = self.foreign_keys.iter().map(|fk| &fk.columnas);
+        for columnas in std::iter::once(&self.primary_key)
+            .chain(self.unique.iter())
+            .chain(columnas_fk)
+        {
+            if let Some(columna) = columnas.iter().find(|c| !nombres.contains(c)) {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "La restricción referencia la columna '{}' que no pertenece a la tabla",
+                    columna
+                )));
+            }
+        }
+        for columna in &self.columnas {
+            if let Some(default) = columna.valor_default()? {
+                columna.validar_tipo(&default)?;
+            }
+        }
+        let autoincrementales: Vec<&ColumnaDef> =
+            self.columnas.iter().filter(|c| c.autoincrement).collect();
+        if autoincrementales.len() > 1 {
+            return Err(ErrorType::InvalidSyntax(
+                "Solo puede haber una columna AUTOINCREMENT por tabla".to_string(),
+            ));
+        }
+        if let Some(columna) = autoincrementales
+            .iter()
+            .find(|c| c.tipo == Some(TipoDato::String))
+        {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "La columna AUTOINCREMENT '{}' debe ser de tipo INT",
+                columna.nombre
+            )));
+        }
+        for (i, check) in self.checks.iter().enumerate() {
+            if self.checks[..i].iter().any(|c| c.nombre == check.nombre) {
+                return Err(ErrorType::InvalidSyntax(format!(
+                    "Restricción CHECK '{}' repetida en CREATE TABLE",
+                    check.nombre
+                )));
+            }
+            if let Some(columna) = check
+                .columnas()
+                .iter()
+                .find(|c| !nombres.contains(&c.to_string()))
+            {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "La restricción CHECK '{}' referencia la columna '{}' que no pertenece a la tabla",
+                    check.nombre, columna
+                )));
+            }
+        }
+        for fk in &self.foreign_keys {
+            if fk.columnas.len() != fk.columnas_ref.len() {
+                return Err(ErrorType::InvalidSyntax(format!(
+                    "La cantidad de columnas no coincide en {}",
+                    fk.descripcion()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Esquema {
+    /// Muestra el esquema como la sentencia CREATE TABLE que lo define.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut definiciones: Vec<String> = self.columnas.iter().map(|c| c.to_string()).collect();
+        if !self.primary_key.is_empty() {
+            definiciones.push(format!("PRIMARY KEY ({})", self.primary_key.join(", ")));
+        }
+        for columnas in &self.unique {
+            definiciones.push(format!("UNIQUE ({})", columnas.join(", ")));
+        }
+        for fk in &self.foreign_keys {
+            definiciones.push(fk.to_string());
+        }
+        for check in &self.checks {
+            definiciones.push(check.to_string());
+        }
+        write!(
+            f,
+            "CREATE TABLE {} ({})",
+            self.tabla,
+            definiciones.join(", ")
+        )
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    fs,
+    io::BufRead,
+};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::manejo_csv::{get_reader, leer_encabezado},
+};
+
+use super::esquema_tabla::Esquema;
+
+/// Enum que representa la acción a realizar sobre las filas hijas cuando se elimina la fila referenciada.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AccionReferencial {
+    Restrict,
+    Cascade,
+    SetNull,
+}
+
+/// Representa una restricción FOREIGN KEY: las columnas de la tabla que referencian columnas de otra tabla.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForeignKey {
+    pub columnas: Vec<String>,
+    pub tabla: String,
+    pub columnas_ref: Vec<String>,
+    pub on_delete: AccionReferencial,
+}
+
+impl ForeignKey {
+    /// Crea una nueva instancia de `ForeignKey`.
+    pub fn new(
+        columnas: Vec<String>,
+        tabla: &str,
+        columnas_ref: Vec<String>,
+        on_delete: AccionReferencial,
+    ) -> Self {
+        ForeignKey {
+            columnas,
+            tabla: tabla.to_string(),
+            columnas_ref,
+            on_delete,
+        }
+    }
+
+    /// Descripción de la restricción para los mensajes de error.
+    pub fn descripcion(&self) -> String {
+        format!(
+            "FOREIGN KEY ({}) REFERENCES {} ({})",
+            self.columnas.join(", "),
+            self.tabla,
+            self.columnas_ref.join(", ")
+        )
+    }
+}
+
+impl Display for ForeignKey {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.on_delete {
+            AccionReferencial::Restrict => write!(f, "{}", self.descripcion()),
+            AccionReferencial::Cascade => write!(f, "{} ON DELETE CASCADE", self.descripcion()),
+            AccionReferencial::SetNull => write!(f, "{} ON DELETE SET NULL", self.descripcion()),
+        }
+    }
+}
+
+/// Busca la posición de cada columna en el encabezado de la tabla.
+pub fn posiciones_columnas(
+    columnas_buscadas: &[String],
+    columnas: &[String],
+) -> Result<Vec<usize>, ErrorType> {
+    let mut posiciones = Vec::new();
+    for buscada in columnas_buscadas {
+        match columnas.iter().position(|c| c == buscada) {
+            Some(posicion) => posiciones.push(posicion),
+            None => {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "La columna '{}' no pertenece a la tabla",
+                    buscada
+                )))
+            }
+        }
+    }
+    Ok(posiciones)
+}
+
+/// Extrae de una línea de CSV los valores en las posiciones dadas.
+/// Devuelve `None` si alguno es nulo, ya que una clave con nulos no referencia ninguna fila.
+pub fn valores_clave(linea: &str, posiciones: &[usize]) -> Option<Vec<String>> {
+    let values: Vec<&str> = linea.split(',').map(|s| s.trim()).collect();
+    let mut clave = Vec::new();
+    for &posicion in posiciones {
+        match values.get(posicion) {
+            Some(valor) if !valor.is_empty() => clave.push(valor.to_string()),
+            _ => return None,
+        }
+    }
+    Some(clave)
+}
+
+/// Lee un archivo CSV y devuelve el conjunto de valores no nulos de las columnas dadas.
+pub fn claves_archivo(
+    path_archivo: &String,
+    columnas_clave: &[String],
+) -> Result<HashSet<Vec<String>>, ErrorType> {
+    let mut lines = get_reader(path_archivo)?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    let posiciones = posiciones_columnas(columnas_clave, &columnas)?;
+    let mut claves = HashSet::new();
+    for line in lines {
+        match line {
+            Ok(line) => {
+                if let Some(clave) = valores_clave(&line, &posiciones) {
+                    claves.insert(clave);
+                }
+            }
+            Err(_) => {
+                return Err(ErrorType::InvalidTable(
+                    "Error al leer una linea".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(claves)
+}
+
+/// Verifica que la fila referencie una clave existente de la tabla padre. Las claves con nulos no se verifican.
+pub fn verificar_referencia(
+    fk: &ForeignKey,
+    linea: &str,
+    posiciones: &[usize],
+    claves_padre: &HashSet<Vec<String>>,
+) -> Result<(), ErrorType> {
+    match valores_clave(linea, posiciones) {
+        Some(clave) if !claves_padre.contains(&clave) => {
+            Err(ErrorType::ConstraintViolation(format!(
+                "No existe ({}) en {} para {}",
+                clave.join(", "),
+                fk.tabla,
+                fk.descripcion()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Busca en la carpeta de tablas los esquemas con foreign keys que referencian a la tabla dada.
+/// Devuelve el nombre de cada tabla hija junto con la foreign key correspondiente.
+pub fn referencias_a(path: &str, tabla: &str) -> Result<Vec<(String, ForeignKey)>, ErrorType> {
+    let Ok(entradas) = fs::read_dir(path) else {
+        return Err(ErrorType::InvalidTable(
+            "Error al leer la carpeta de tablas".to_string(),
+        ));
+    };
+    let mut referencias = Vec::new();
+    for entrada in entradas.flatten() {
+        let nombre = entrada.file_name().to_string_lossy().to_string();
+        let Some(hija) = nombre.strip_suffix(".esquema") else {
+            continue;
+        };
+        if let Some(esquema) = Esquema::cargar(path, hija)? {
+            for fk in esquema.foreign_keys {
+                if fk.tabla == tabla {
+                    referencias.push((hija.to_string(), fk));
+                }
+            }
+        }
+    }
+    Ok(referencias)
+}
@@ -0,0 +1,6 @@
+pub mod check;
+pub mod columna;
+pub mod esquema_tabla;
+pub mod foreign_key;
+pub mod restricciones;
+pub mod secuencia;
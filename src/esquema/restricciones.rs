@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{errores::error::ErrorType, executer::manejo_csv::agregar_path};
+
+use super::{
+    esquema_tabla::Esquema,
+    foreign_key::{
+        claves_archivo, posiciones_columnas, valores_clave, verificar_referencia, ForeignKey,
+    },
+};
+
+/// Representa una restricción PRIMARY KEY o UNIQUE junto con los valores ya registrados para ella.
+struct Clave {
+    descripcion: String,
+    posiciones: Vec<usize>,
+    primaria: bool,
+    valores: HashSet<Vec<String>>,
+}
+
+impl Clave {
+    /// Crea la clave buscando la posición de cada una de sus columnas en el encabezado de la tabla.
+    fn new(
+        descripcion: &str,
+        columnas_clave: &[String],
+        columnas: &[String],
+        primaria: bool,
+    ) -> Result<Self, ErrorType> {
+        Ok(Clave {
+            descripcion: descripcion.to_string(),
+            posiciones: posiciones_columnas(columnas_clave, columnas)?,
+            primaria,
+            valores: HashSet::new(),
+        })
+    }
+}
+
+/// Verifica las restricciones PRIMARY KEY y UNIQUE de una tabla sobre las filas que se van escribiendo.
+/// Cada fila registrada se compara contra las anteriores, por lo que el archivo resultante no puede tener claves repetidas.
+pub struct VerificadorClaves {
+    claves: Vec<Clave>,
+}
+
+impl VerificadorClaves {
+    /// Crea un verificador para las claves del esquema. Si la tabla no tiene esquema no verifica nada.
+    pub fn new(esquema: &Option<Esquema>, columnas: &[String]) -> Result<Self, ErrorType> {
+        let mut claves = Vec::new();
+        if let Some(esquema) = esquema {
+            if !esquema.primary_key.is_empty() {
+                let descripcion = format!("PRIMARY KEY ({})", esquema.primary_key.join(", "));
+                claves.push(Clave::new(
+                    &descripcion,
+                    &esquema.primary_key,
+                    columnas,
+                    true,
+                )?);
+            }
+            for unique in &esquema.unique {
+                let descripcion = format!("UNIQUE ({})", unique.join(", "));
+                claves.push(Clave::new(&descripcion, unique, columnas, false)?);
+            }
+        }
+        Ok(VerificadorClaves { claves })
+    }
+
+    /// Devuelve si no hay ninguna clave para verificar.
+    pub fn vacio(&self) -> bool {
+        self.claves.is_empty()
+    }
+
+    /// Registra las claves de una línea de CSV. Devuelve un error si alguna ya estaba registrada
+    /// o si la clave primaria tiene valores nulos.
+    pub fn registrar(&mut self, linea: &str) -> Result<(), ErrorType> {
+        if self.claves.is_empty() {
+            return Ok(());
+        }
+        let values: Vec<&str> = linea.split(',').map(|s| s.trim()).collect();
+        for clave in &mut self.claves {
+            let valores: Vec<String> = clave
+                .posiciones
+                .iter()
+                .map(|&i| values.get(i).unwrap_or(&"").to_string())
+                .collect();
+            if valores.iter().any(|v| v.is_empty()) {
+                if clave.primaria {
+                    return Err(ErrorType::ConstraintViolation(format!(
+                        "La {} no admite valores nulos",
+                        clave.descripcion
+                    )));
+                }
+                continue;
+            }
+            if clave.valores.contains(&valores) {
+                return Err(ErrorType::ConstraintViolation(format!(
+                    "Valor duplicado para {}: ({})",
+                    clave.descripcion,
+                    valores.join(", ")
+                )));
+            }
+            clave.valores.insert(valores);
+        }
+        Ok(())
+    }
+}
+
+/// Representa una foreign key de la tabla junto con las claves existentes en la tabla padre.
+struct Referencia {
+    fk: ForeignKey,
+    posiciones: Vec<usize>,
+    posiciones_ref: Vec<usize>,
+    propia: bool,
+    claves_padre: HashSet<Vec<String>>,
+}
+
+/// Verifica que las filas escritas en una tabla referencien claves existentes de las tablas padre.
+pub struct VerificadorReferencias {
+    referencias: Vec<Referencia>,
+}
+
+impl VerificadorReferencias {
+    /// Crea un verificador para las foreign keys del esquema, leyendo las claves de cada tabla padre.
+    /// Si una foreign key referencia a la misma tabla, las claves se leen de `path_propio`.
+    pub fn new(
+        path: &str,
+        esquema: &Option<Esquema>,
+        columnas: &[String],
+        path_propio: &String,
+    ) -> Result<Self, ErrorType> {
+        let mut referencias = Vec::new();
+        if let Some(esquema) = esquema {
+            for fk in &esquema.foreign_keys {
+                let propia = fk.tabla == esquema.tabla;
+                let path_padre = if propia {
+                    path_propio.to_string()
+                } else {
+                    agregar_path(path, &fk.tabla)
+                };
+                let posiciones_ref = if propia {
+                    posiciones_columnas(&fk.columnas_ref, columnas)?
+                } else {
+                    Vec::new()
+                };
+                referencias.push(Referencia {
+                    fk: fk.clone(),
+                    posiciones: posiciones_columnas(&fk.columnas, columnas)?,
+                    posiciones_ref,
+                    propia,
+                    claves_padre: claves_archivo(&path_padre, &fk.columnas_ref)?,
+                });
+            }
+        }
+        Ok(VerificadorReferencias { referencias })
+    }
+
+    /// Registra una fila nueva como posible padre de las foreign keys que referencian a la misma tabla
+    /// y verifica que todas sus foreign keys referencien claves existentes.
+    pub fn registrar(&mut self, linea: &str) -> Result<(), ErrorType> {
+        for referencia in &mut self.referencias {
+            if referencia.propia {
+                if let Some(clave) = valores_clave(linea, &referencia.posiciones_ref) {
+                    referencia.claves_padre.insert(clave);
+                }
+            }
+            verificar_referencia(
+                &referencia.fk,
+                linea,
+                &referencia.posiciones,
+                &referencia.claves_padre,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Verifica que las foreign keys de una fila referencien claves existentes, sin registrarla.
+    pub fn verificar(&self, linea: &str) -> Result<(), ErrorType> {
+        for referencia in &self.referencias {
+            verificar_referencia(
+                &referencia.fk,
+                linea,
+                &referencia.posiciones,
+                &referencia.claves_padre,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Verifica que las filas de la tabla hija, leídas de `path_hija`, no referencien ninguna de las claves dadas.
+pub fn verificar_no_referenciadas(
+    tabla: &str,
+    hija: &str,
+    fk: &ForeignKey,
+    claves: &HashSet<Vec<String>>,
+    path_hija: &String,
+) -> Result<(), ErrorType> {
+    if claves.is_empty() {
+        return Ok(());
+    }
+    let referenciadas = claves_archivo(path_hija, &fk.columnas)?;
+    match claves.iter().find(|c| referenciadas.contains(*c)) {
+        Some(clave) => Err(ErrorType::ConstraintViolation(format!(
+            "La clave ({}) de {} está referenciada por {} en la tabla {}",
+            clave.join(", "),
+            tabla,
+            fk.descripcion(),
+            hija
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Verifica que ninguna tabla hija referencie las claves que dejan de existir en la tabla.
+/// `claves_quitadas` contiene, por cada grupo de columnas referenciado, las claves que se eliminan o modifican.
+/// Las filas hijas de la misma tabla se leen de `path_propio`, que tiene el estado resultante de la sentencia.
+pub fn verificar_sin_referencias(
+    path: &str,
+    tabla: &str,
+    referencias: &[(String, ForeignKey)],
+    claves_quitadas: &HashMap<Vec<String>, HashSet<Vec<String>>>,
+    path_propio: &String,
+) -> Result<(), ErrorType> {
+    for (hija, fk) in referencias {
+        let Some(claves) = claves_quitadas.get(&fk.columnas_ref) else {
+            continue;
+        };
+        let path_hija = if hija == tabla {
+            path_propio.to_string()
+        } else {
+            agregar_path(path, hija)
+        };
+        verificar_no_referenciadas(tabla, hija, fk, claves, &path_hija)?;
+    }
+    Ok(())
+}
+
+/// Acumula las claves referenciadas por otras tablas que se eliminan o modifican durante una sentencia,
+/// agrupadas por las columnas referenciadas.
+pub struct ClavesQuitadas {
+    grupos: Vec<(Vec<String>, Vec<usize>)>,
+    claves: HashMap<Vec<String>, HashSet<Vec<String>>>,
+}
+
+impl ClavesQuitadas {
+    /// Crea el acumulador para las columnas referenciadas por las foreign keys dadas.
+    pub fn new(
+        referencias: &[(String, ForeignKey)],
+        columnas: &[String],
+    ) -> Result<Self, ErrorType> {
+        let mut grupos = Vec::new();
+        let mut claves = HashMap::new();
+        for (_, fk) in referencias {
+            if !claves.contains_key(&fk.columnas_ref) {
+                grupos.push((
+                    fk.columnas_ref.clone(),
+                    posiciones_columnas(&fk.columnas_ref, columnas)?,
+                ));
+                claves.insert(fk.columnas_ref.clone(), HashSet::new());
+            }
+        }
+        Ok(ClavesQuitadas { grupos, claves })
+    }
+
+    /// Registra las claves de una fila eliminada.
+    pub fn registrar(&mut self, linea: &str) {
+        for (columnas, posiciones) in &self.grupos {
+            if let (Some(clave), Some(claves)) = (
+                valores_clave(linea, posiciones),
+                self.claves.get_mut(columnas),
+            ) {
+                claves.insert(clave);
+            }
+        }
+    }
+
+    /// Registra las claves de una fila modificada cuyos valores referenciados cambiaron.
+    pub fn registrar_cambio(&mut self, anterior: &str, nueva: &str) {
+        for (columnas, posiciones) in &self.grupos {
+            let clave_anterior = valores_clave(anterior, posiciones);
+            if clave_anterior == valores_clave(nueva, posiciones) {
+                continue;
+            }
+            if let (Some(clave), Some(claves)) = (clave_anterior, self.claves.get_mut(columnas)) {
+                claves.insert(clave);
+            }
+        }
+    }
+
+    /// Devuelve las claves quitadas de cada grupo de columnas referenciadas.
+    pub fn claves(&self) -> &HashMap<Vec<String>, HashSet<Vec<String>>> {
+        &self.claves
+    }
+}
@@ -0,0 +1,102 @@
+use std::{fs, path::Path};
+
+use crate::errores::error::ErrorType;
+
+use super::{esquema_tabla::Esquema, foreign_key::posiciones_columnas};
+
+/// Genera la ruta del archivo donde se guarda el último valor de la columna AUTOINCREMENT de una tabla.
+pub fn ruta_secuencia(path: &str, tabla: &str) -> String {
+    format!("{}/{}.secuencia", path, tabla)
+}
+
+/// Contador persistido de la columna AUTOINCREMENT de una tabla.
+pub struct Secuencia {
+    columna: String,
+    posicion: usize,
+    ultimo: i64,
+    persistido: bool,
+}
+
+impl Secuencia {
+    /// Carga el contador de la columna AUTOINCREMENT del esquema desde su archivo `.secuencia`.
+    /// Devuelve `None` si la tabla no tiene una columna AUTOINCREMENT.
+    pub fn cargar(
+        path: &str,
+        esquema: &Option<Esquema>,
+        columnas: &[String],
+    ) -> Result<Option<Secuencia>, ErrorType> {
+        let Some(esquema) = esquema else {
+            return Ok(None);
+        };
+        let Some(columna) = esquema.columnas.iter().find(|c| c.autoincrement) else {
+            return Ok(None);
+        };
+        let posicion = posiciones_columnas(&[columna.nombre.to_string()], columnas)?[0];
+        let ruta = ruta_secuencia(path, &esquema.tabla);
+        let mut secuencia = Secuencia {
+            columna: columna.nombre.to_string(),
+            posicion,
+            ultimo: 0,
+            persistido: false,
+        };
+        if Path::new(&ruta).exists() {
+            let contenido = fs::read_to_string(&ruta).unwrap_or_default();
+            let Ok(ultimo) = contenido.trim().parse::<i64>() else {
+                return Err(ErrorType::InvalidTable(
+                    "El archivo de secuencia de la tabla es invalido".to_string(),
+                ));
+            };
+            secuencia.ultimo = ultimo;
+            secuencia.persistido = true;
+        }
+        Ok(Some(secuencia))
+    }
+
+    /// Devuelve el nombre de la columna AUTOINCREMENT.
+    pub fn columna(&self) -> &str {
+        &self.columna
+    }
+
+    /// Indica si el contador nunca se guardó, en cuyo caso se inicializa con los valores existentes de la tabla.
+    pub fn requiere_inicializar(&self) -> bool {
+        !self.persistido
+    }
+
+    /// Actualiza el contador para que no quede por debajo de un valor ya usado en la columna.
+    pub fn observar_valor(&mut self, valor: i64) {
+        self.ultimo = self.ultimo.max(valor);
+    }
+
+    /// Actualiza el contador con el valor de la columna en una línea existente de la tabla.
+    pub fn observar(&mut self, linea: &str) {
+        if let Some(Ok(valor)) = linea
+            .split(',')
+            .nth(self.posicion)
+            .map(|v| v.trim().parse::<i64>())
+        {
+            self.observar_valor(valor);
+        }
+    }
+
+    /// Avanza el contador y devuelve el nuevo valor, o un error si ya llegó al mayor entero posible.
+    pub fn siguiente(&mut self) -> Result<i64, ErrorType> {
+        let Some(siguiente) = self.ultimo.checked_add(1) else {
+            return Err(ErrorType::ConstraintViolation(format!(
+                "La columna AUTOINCREMENT '{}' no tiene más valores disponibles",
+                self.columna
+            )));
+        };
+        self.ultimo = siguiente;
+        Ok(siguiente)
+    }
+
+    /// Guarda el último valor asignado en el archivo `.secuencia` de la tabla.
+    pub fn guardar(&self, path: &str, tabla: &str) -> Result<(), ErrorType> {
+        match fs::write(ruta_secuencia(path, tabla), format!("{}\n", self.ultimo)) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ErrorType::InvalidTable(
+                "Error al guardar la secuencia de la tabla".to_string(),
+            )),
+        }
+    }
+}
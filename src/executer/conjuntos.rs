@@ -0,0 +1,408 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    fs::{File, OpenOptions},
+    hash::{Hash, Hasher},
+    io::{BufRead, BufWriter, Write},
+};
+
+use crate::{errores::error::ErrorType, queries::compound_query::OperadorConjunto};
+
+use super::manejo_csv::{
+    agregar_path, crear_archivo, eliminar_archivo, get_reader, nombre_auxiliar,
+};
+
+/// Cantidad máxima de filas distintas que se guardan en memoria al combinar dos conjuntos de filas.
+/// Si se supera, las filas se reparten según su hash en particiones en disco que se combinan por separado.
+pub const FILAS_EN_MEMORIA: usize = 100_000;
+
+/// Cantidad de particiones en las que se reparten las filas cuando no entran en memoria.
+const PARTICIONES: usize = 16;
+
+/// Cantidad máxima de veces que se vuelve a particionar una partición que sigue sin entrar en memoria.
+const NIVEL_MAXIMO: u32 = 6;
+
+/// Devuelve el error de escritura del resultado.
+fn error_escritura() -> ErrorType {
+    ErrorType::Error("Error al escribir en archivo".to_string())
+}
+
+/// Abre el archivo para agregar líneas al final.
+pub fn abrir_para_agregar(path: &String) -> Result<BufWriter<File>, ErrorType> {
+    match OpenOptions::new().append(true).open(path) {
+        Ok(file) => Ok(BufWriter::new(file)),
+        Err(_) => Err(ErrorType::Error("Error al abrir archivo".to_string())),
+    }
+}
+
+/// Escribe una línea en el archivo.
+pub fn escribir(escritor: &mut BufWriter<File>, linea: &str) -> Result<(), ErrorType> {
+    match writeln!(escritor, "{}", linea) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_escritura()),
+    }
+}
+
+/// Escribe en disco lo que quedó pendiente en el escritor.
+pub fn terminar(mut escritor: BufWriter<File>) -> Result<(), ErrorType> {
+    match escritor.flush() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_escritura()),
+    }
+}
+
+/// Recorre las filas de un archivo sin encabezado, llamando a `procesar` con cada una.
+/// Si `procesar` devuelve `false`, deja de recorrer y devuelve `false`.
+fn recorrer(
+    path: &String,
+    procesar: &mut dyn FnMut(String) -> Result<bool, ErrorType>,
+) -> Result<bool, ErrorType> {
+    for linea in get_reader(path)?.lines() {
+        let Ok(linea) = linea else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        if !procesar(linea)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Devuelve la longitud del archivo.
+pub fn longitud(path: &String) -> Result<u64, ErrorType> {
+    match std::fs::metadata(path) {
+        Ok(metadata) => Ok(metadata.len()),
+        Err(_) => Err(ErrorType::Error("Error al abrir archivo".to_string())),
+    }
+}
+
+/// Trunca el archivo a la longitud dada, descartando lo que se haya escrito después.
+fn truncar(path: &String, longitud: u64) -> Result<(), ErrorType> {
+    let truncado = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len(longitud));
+    match truncado {
+        Ok(_) => Ok(()),
+        Err(_) => Err(error_escritura()),
+    }
+}
+
+/// Combina los dos archivos guardando en memoria las filas distintas necesarias.
+/// Devuelve `false`, sin terminar, si hace falta guardar más de `limite` filas.
+fn combinar_en_memoria(
+    izq: &String,
+    der: &String,
+    salida: &String,
+    operador: &OperadorConjunto,
+    limite: Option<usize>,
+) -> Result<bool, ErrorType> {
+    let mut conjunto: HashSet<String> = HashSet::new();
+    let entra = |conjunto: &HashSet<String>| limite.is_none_or(|limite| conjunto.len() <= limite);
+    let mut escritor = abrir_para_agregar(salida)?;
+    let completo = match operador {
+        OperadorConjunto::Union | OperadorConjunto::UnionAll => {
+            let mut agregar = |linea: String| {
+                if conjunto.insert(linea.to_string()) {
+                    escribir(&mut escritor, &linea)?;
+                }
+                Ok(entra(&conjunto))
+            };
+            recorrer(izq, &mut agregar)? && recorrer(der, &mut agregar)?
+        }
+        OperadorConjunto::Intersect => {
+            recorrer(der, &mut |linea| {
+                conjunto.insert(linea);
+                Ok(entra(&conjunto))
+            })? && recorrer(izq, &mut |linea| {
+                if conjunto.remove(&linea) {
+                    escribir(&mut escritor, &linea)?;
+                }
+                Ok(true)
+            })?
+        }
+        OperadorConjunto::Except => {
+            recorrer(der, &mut |linea| {
+                conjunto.insert(linea);
+                Ok(entra(&conjunto))
+            })? && recorrer(izq, &mut |linea| {
+                if conjunto.insert(linea.to_string()) {
+                    escribir(&mut escritor, &linea)?;
+                }
+                Ok(entra(&conjunto))
+            })?
+        }
+    };
+    terminar(escritor)?;
+    Ok(completo)
+}
+
+/// Devuelve la partición que le corresponde a la fila en el nivel dado.
+fn particion(linea: &str, nivel: u32) -> usize {
+    let mut hasher = DefaultHasher::new();
+    nivel.hash(&mut hasher);
+    linea.hash(&mut hasher);
+    (hasher.finish() % PARTICIONES as u64) as usize
+}
+
+/// Reparte las filas del archivo en archivos auxiliares según su hash, de modo que las filas iguales
+/// quedan en la misma partición. Devuelve las rutas de las particiones.
+fn particionar(path: &str, origen: &String, nivel: u32) -> Result<Vec<String>, ErrorType> {
+    let mut particiones = Vec::new();
+    let mut escritores = Vec::new();
+    for _ in 0..PARTICIONES {
+        let path_particion = agregar_path(path, &nombre_auxiliar("particion"));
+        crear_archivo(&path_particion)?;
+        escritores.push(abrir_para_agregar(&path_particion)?);
+        particiones.push(path_particion);
+    }
+    let resultado = recorrer(origen, &mut |linea| {
+        escribir(&mut escritores[particion(&linea, nivel)], &linea)?;
+        Ok(true)
+    })
+    .and_then(|_| escritores.into_iter().try_for_each(terminar));
+    if let Err(e) = resultado {
+        for path_particion in &particiones {
+            eliminar_archivo(path_particion)?;
+        }
+        return Err(e);
+    }
+    Ok(particiones)
+}
+
+/// Combina los archivos por particiones, agregando el resultado de cada par de particiones a la salida.
+fn combinar_particionado(
+    path: &str,
+    izq: &String,
+    der: &String,
+    salida: &String,
+    operador: &OperadorConjunto,
+    limite: usize,
+    nivel: u32,
+) -> Result<(), ErrorType> {
+    let particiones_izq = particionar(path, izq, nivel)?;
+    let resultado = particionar(path, der, nivel).and_then(|particiones_der| {
+        let resultado = particiones_izq
+            .iter()
+            .zip(particiones_der.iter())
+            .try_for_each(|(izq, der)| {
+                combinar_nivel(path, izq, der, salida, operador, limite, nivel + 1)
+            });
+        for path_particion in &particiones_der {
+            eliminar_archivo(path_particion)?;
+        }
+        resultado
+    });
+    for path_particion in &particiones_izq {
+        eliminar_archivo(path_particion)?;
+    }
+    resultado
+}
+
+/// Combina los archivos en memoria si las filas distintas entran en el límite, o si no por particiones.
+fn combinar_nivel(
+    path: &str,
+    izq: &String,
+    der: &String,
+    salida: &String,
+    operador: &OperadorConjunto,
+    limite: usize,
+    nivel: u32,
+) -> Result<(), ErrorType> {
+    let longitud_inicial = longitud(salida)?;
+    let limite_nivel = if nivel < NIVEL_MAXIMO {
+        Some(limite)
+    } else {
+        None
+    };
+    if combinar_en_memoria(izq, der, salida, operador, limite_nivel)? {
+        return Ok(());
+    }
+    truncar(salida, longitud_inicial)?;
+    combinar_particionado(path, izq, der, salida, operador, limite, nivel)
+}
+
+/// Copia las filas del archivo al final de la salida.
+pub fn copiar_filas(origen: &String, salida: &String) -> Result<(), ErrorType> {
+    let mut escritor = abrir_para_agregar(salida)?;
+    recorrer(origen, &mut |linea| {
+        escribir(&mut escritor, &linea)?;
+        Ok(true)
+    })?;
+    terminar(escritor)
+}
+
+/// Combina las filas de dos archivos sin encabezado con el operador de conjuntos y agrega el resultado
+/// al final de la salida. Salvo UNION ALL, el resultado no tiene filas repetidas.
+/// Guarda en memoria a lo sumo `limite` filas distintas; si no alcanza, usa archivos auxiliares en `path`.
+pub fn combinar_con_limite(
+    path: &str,
+    izq: &String,
+    der: &String,
+    salida: &String,
+    operador: &OperadorConjunto,
+    limite: usize,
+) -> Result<(), ErrorType> {
+    if *operador == OperadorConjunto::UnionAll {
+        copiar_filas(izq, salida)?;
+        return copiar_filas(der, salida);
+    }
+    combinar_nivel(path, izq, der, salida, operador, limite, 0)
+}
+
+/// Combina las filas de dos archivos sin encabezado con el operador de conjuntos y agrega el resultado
+/// al final de la salida, guardando en memoria a lo sumo `FILAS_EN_MEMORIA` filas distintas.
+pub fn combinar(
+    path: &str,
+    izq: &String,
+    der: &String,
+    salida: &String,
+    operador: &OperadorConjunto,
+) -> Result<(), ErrorType> {
+    combinar_con_limite(path, izq, der, salida, operador, FILAS_EN_MEMORIA)
+}
+
+/// Agrega a la salida las filas distintas del archivo, guardando en memoria a lo sumo `limite` filas
+/// o, si no alcanza, repartiéndolas en particiones. Devuelve la cantidad de filas agregadas.
+fn distintas_nivel(
+    path: &str,
+    origen: &String,
+    salida: &String,
+    limite: usize,
+    nivel: u32,
+) -> Result<usize, ErrorType> {
+    let longitud_inicial = longitud(salida)?;
+    let mut conjunto: HashSet<String> = HashSet::new();
+    let mut escritor = abrir_para_agregar(salida)?;
+    let completo = recorrer(origen, &mut |linea| {
+        if conjunto.insert(linea.to_string()) {
+            escribir(&mut escritor, &linea)?;
+        }
+        Ok(nivel >= NIVEL_MAXIMO || conjunto.len() <= limite)
+    })?;
+    terminar(escritor)?;
+    if completo {
+        return Ok(conjunto.len());
+    }
+    drop(conjunto);
+    truncar(salida, longitud_inicial)?;
+    let particiones = particionar(path, origen, nivel)?;
+    let resultado = particiones.iter().try_fold(0, |cantidad, particion| {
+        Ok(cantidad + distintas_nivel(path, particion, salida, limite, nivel + 1)?)
+    });
+    for path_particion in &particiones {
+        eliminar_archivo(path_particion)?;
+    }
+    resultado
+}
+
+/// Agrega al final de la salida las filas distintas de un archivo sin encabezado y devuelve cuántas son.
+/// Guarda en memoria a lo sumo `limite` filas distintas; si no alcanza, usa archivos auxiliares en `path`.
+pub fn distintas_con_limite(
+    path: &str,
+    origen: &String,
+    salida: &String,
+    limite: usize,
+) -> Result<usize, ErrorType> {
+    distintas_nivel(path, origen, salida, limite, 0)
+}
+
+/// Agrega al final de la salida las filas distintas de un archivo sin encabezado y devuelve cuántas son,
+/// guardando en memoria a lo sumo `FILAS_EN_MEMORIA` filas distintas.
+pub fn distintas(path: &str, origen: &String, salida: &String) -> Result<usize, ErrorType> {
+    distintas_con_limite(path, origen, salida, FILAS_EN_MEMORIA)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Crea un directorio para la prueba con los archivos de filas de cada lado.
+    fn preparar(nombre: &str, izq: &[String], der: &[String]) -> (String, String, String, String) {
+        let dir = std::env::temp_dir().join(format!("sql_rustico_conjuntos_{}", nombre));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.to_string_lossy().to_string();
+        let path_izq = format!("{}/izq.csv", path);
+        let path_der = format!("{}/der.csv", path);
+        let path_salida = format!("{}/salida.csv", path);
+        fs::write(&path_izq, izq.join("\n") + "\n").unwrap();
+        fs::write(&path_der, der.join("\n") + "\n").unwrap();
+        fs::write(&path_salida, "").unwrap();
+        (path, path_izq, path_der, path_salida)
+    }
+
+    /// Devuelve las filas de la salida ordenadas, para comparar sin depender del orden en que se escribieron.
+    fn filas_ordenadas(path: &str) -> Vec<String> {
+        let mut filas: Vec<String> = fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        filas.sort();
+        filas
+    }
+
+    fn filas(rango: std::ops::Range<usize>) -> Vec<String> {
+        rango.map(|i| format!("{},fila{}", i, i % 7)).collect()
+    }
+
+    #[test]
+    fn test_combinar_particionando_en_disco() {
+        // Con un límite de 5 filas en memoria, las 40 filas distintas se reparten en particiones.
+        let mut izq = filas(0..30);
+        izq.extend(filas(0..10));
+        let der = filas(20..40);
+        let casos = [
+            (OperadorConjunto::Union, filas(0..40)),
+            (OperadorConjunto::Intersect, filas(20..30)),
+            (OperadorConjunto::Except, filas(0..20)),
+        ];
+        for (operador, esperado) in casos {
+            let (path, path_izq, path_der, path_salida) =
+                preparar(&format!("{}", operador).replace(' ', "_"), &izq, &der);
+            assert!(
+                combinar_con_limite(&path, &path_izq, &path_der, &path_salida, &operador, 5)
+                    .is_ok()
+            );
+            let mut esperado = esperado;
+            esperado.sort();
+            assert_eq!(filas_ordenadas(&path_salida), esperado);
+            // Solo quedan los archivos de la prueba, sin particiones auxiliares.
+            assert_eq!(fs::read_dir(&path).unwrap().count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_combinar_union_all_conserva_repetidas() {
+        let (path, path_izq, path_der, path_salida) =
+            preparar("union_all", &filas(0..3), &filas(2..4));
+        let operador = OperadorConjunto::UnionAll;
+        assert!(
+            combinar_con_limite(&path, &path_izq, &path_der, &path_salida, &operador, 1).is_ok()
+        );
+        let mut esperado = filas(0..3);
+        esperado.extend(filas(2..4));
+        esperado.sort();
+        assert_eq!(filas_ordenadas(&path_salida), esperado);
+    }
+
+    #[test]
+    fn test_distintas_particionando_en_disco() {
+        let mut origen = filas(0..30);
+        origen.extend(filas(10..20));
+        origen.extend(filas(0..5));
+        let (path, path_origen, _, path_salida) = preparar("distintas", &origen, &[]);
+        let cantidad = distintas_con_limite(&path, &path_origen, &path_salida, 4);
+        assert!(matches!(cantidad, Ok(30)));
+        assert_eq!(filas_ordenadas(&path_salida), {
+            let mut esperado = filas(0..30);
+            esperado.sort();
+            esperado
+        });
+        assert_eq!(fs::read_dir(&path).unwrap().count(), 3);
+    }
+}
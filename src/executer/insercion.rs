@@ -0,0 +1,311 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::errores::error::ErrorType;
+
+/// Genera la ruta del archivo que registra una inserción en curso sobre una tabla,
+/// que se guarda junto al `.csv` con extensión `.insercion`.
+pub fn ruta_insercion(path_tabla: &str) -> String {
+    let base = path_tabla.strip_suffix(".csv").unwrap_or(path_tabla);
+    format!("{}.insercion", base)
+}
+
+/// Devuelve el error de escritura de la tabla.
+fn error_escritura() -> ErrorType {
+    ErrorType::InvalidTable("Error al agregar las filas a la tabla".to_string())
+}
+
+/// Trunca la tabla a la longitud dada, descartando lo que se haya agregado después.
+fn truncar(path_tabla: &str, longitud: u64) -> Result<(), ErrorType> {
+    let truncado = OpenOptions::new()
+        .write(true)
+        .open(path_tabla)
+        .and_then(|file| file.set_len(longitud).and_then(|_| file.sync_all()));
+    match truncado {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ErrorType::InvalidTable(
+            "Error al recuperar la tabla de una inserción incompleta".to_string(),
+        )),
+    }
+}
+
+/// Lee el contenido del registro de una inserción desde el archivo ya abierto.
+fn leer_registro(registro: &mut File) -> Option<String> {
+    let mut contenido = String::new();
+    registro.seek(SeekFrom::Start(0)).ok()?;
+    registro.read_to_string(&mut contenido).ok()?;
+    Some(contenido)
+}
+
+/// Si quedó registrada una inserción que no terminó, por ejemplo porque el proceso se interrumpió,
+/// trunca la tabla a la longitud que tenía antes de empezar, descartando lo que se haya agregado.
+/// Mientras la inserción está en curso, quien la hace mantiene bloqueado su registro, por lo que
+/// se espera a que termine: si terminó, su registro ya no existe y la tabla no se modifica.
+/// Solo se recupera la tabla si el registro sigue siendo el mismo cuando se libera el bloqueo,
+/// es decir, si el proceso que insertaba se interrumpió.
+pub fn recuperar_insercion(path_tabla: &str) -> Result<(), ErrorType> {
+    let ruta = ruta_insercion(path_tabla);
+    loop {
+        let Ok(mut registro) = File::open(&ruta) else {
+            return Ok(());
+        };
+        if registro.lock().is_err() {
+            return Err(ErrorType::InvalidTable(
+                "Error al esperar una inserción en curso".to_string(),
+            ));
+        }
+        let Some(contenido) = leer_registro(&mut registro) else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer el registro de inserción".to_string(),
+            ));
+        };
+        match fs::read_to_string(&ruta) {
+            Err(_) => return Ok(()),
+            Ok(actual) if actual != contenido => continue,
+            Ok(_) => {}
+        }
+        let longitud = contenido
+            .lines()
+            .last()
+            .and_then(|linea| linea.trim().parse::<u64>().ok());
+        if let Some(longitud) = longitud {
+            truncar(path_tabla, longitud)?;
+        }
+        return match fs::remove_file(&ruta) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ErrorType::InvalidTable(
+                "Error al eliminar el registro de inserción".to_string(),
+            )),
+        };
+    }
+}
+
+/// Genera un identificador distinto para cada inserción, con el proceso y el momento en que empieza.
+fn identificador_insercion() -> String {
+    static CONTADOR: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duracion| duracion.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "{}-{}-{}",
+        process::id(),
+        nanos,
+        CONTADOR.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Crea el registro de una inserción sobre la tabla, ya bloqueado, esperando a que termine
+/// cualquier otra inserción en curso. El registro se escribe con otro nombre y se enlaza con el suyo
+/// recién cuando está bloqueado, para que nadie lo encuentre sin bloquear.
+fn crear_registro(path_tabla: &str) -> Result<File, ErrorType> {
+    let ruta = ruta_insercion(path_tabla);
+    let identificador = identificador_insercion();
+    let ruta_nueva = format!("{}.{}", ruta, identificador);
+    let creado = File::create(&ruta_nueva).and_then(|mut registro| {
+        registro.lock()?;
+        writeln!(registro, "{}", identificador)?;
+        Ok(registro)
+    });
+    let Ok(registro) = creado else {
+        let _ = fs::remove_file(&ruta_nueva);
+        return Err(error_escritura());
+    };
+    let enlazado = loop {
+        if let Err(e) = recuperar_insercion(path_tabla) {
+            break Err(e);
+        }
+        match fs::hard_link(&ruta_nueva, &ruta) {
+            Ok(_) => break Ok(registro),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(_) => break Err(error_escritura()),
+        }
+    };
+    let _ = fs::remove_file(&ruta_nueva);
+    enlazado
+}
+
+/// Devuelve si el archivo termina en un salto de línea, o si está vacío.
+fn termina_en_salto(file: &mut File, longitud: u64) -> bool {
+    if longitud == 0 {
+        return true;
+    }
+    let mut ultimo = [0u8; 1];
+    file.seek(SeekFrom::Start(longitud - 1)).is_ok()
+        && file.read_exact(&mut ultimo).is_ok()
+        && ultimo[0] == b'\n'
+}
+
+/// Cantidad de filas que se acumulan en memoria antes de escribirlas al final de la tabla.
+const FILAS_POR_LOTE: usize = 1000;
+
+/// Inserción de filas al final de una tabla, sin reescribir las filas existentes. Las filas se escriben
+/// en lotes de `FILAS_POR_LOTE`, por lo que la memoria no crece con la cantidad de filas insertadas.
+/// Al iniciarla registra la longitud original de la tabla, de modo que si la inserción no termina
+/// la próxima lectura de la tabla la deja como estaba. El registro queda bloqueado hasta que termina,
+/// por lo que las lecturas de la tabla esperan y nunca ven filas de una inserción a medio hacer.
+pub struct Insercion {
+    registro: File,
+    file: File,
+    path_tabla: String,
+    longitud: u64,
+    lote: String,
+    filas: usize,
+}
+
+impl Insercion {
+    /// Abre la tabla para agregar filas al final y registra su longitud original,
+    /// después de que termine cualquier otra inserción en curso sobre la tabla.
+    pub fn iniciar(path_tabla: &str) -> Result<Self, ErrorType> {
+        let mut registro = crear_registro(path_tabla)?;
+        let abierto = OpenOptions::new().read(true).append(true).open(path_tabla);
+        let medido = abierto.and_then(|file| Ok((file.metadata()?.len(), file)));
+        let registrado = medido.and_then(|(longitud, file)| {
+            write!(registro, "{}", longitud)?;
+            registro.sync_all()?;
+            Ok((longitud, file))
+        });
+        let Ok((longitud, mut file)) = registrado else {
+            let _ = fs::remove_file(ruta_insercion(path_tabla));
+            return Err(error_escritura());
+        };
+        let mut lote = String::new();
+        if !termina_en_salto(&mut file, longitud) {
+            lote.push('\n');
+        }
+        Ok(Insercion {
+            registro,
+            file,
+            path_tabla: path_tabla.to_string(),
+            longitud,
+            lote,
+            filas: 0,
+        })
+    }
+
+    /// Agrega la fila al lote en curso, y escribe el lote al final de la tabla si está completo.
+    pub fn agregar(&mut self, fila: &str) -> Result<(), ErrorType> {
+        self.lote.push_str(fila);
+        self.lote.push('\n');
+        self.filas += 1;
+        if self.filas < FILAS_POR_LOTE {
+            return Ok(());
+        }
+        self.escribir_lote()
+    }
+
+    /// Escribe el lote en curso al final de la tabla y lo vacía.
+    fn escribir_lote(&mut self) -> Result<(), ErrorType> {
+        if self.file.write_all(self.lote.as_bytes()).is_err() {
+            return Err(error_escritura());
+        }
+        self.lote.clear();
+        self.filas = 0;
+        Ok(())
+    }
+
+    /// Escribe las filas que quedan en el lote y termina la inserción, eliminando su registro antes
+    /// de liberarlo. Si la escritura falla, la tabla vuelve a su longitud original.
+    pub fn terminar(mut self) -> Result<(), ErrorType> {
+        let escrito = self
+            .escribir_lote()
+            .and_then(|_| self.file.sync_all().map_err(|_| error_escritura()));
+        if let Err(e) = escrito {
+            self.deshacer()?;
+            return Err(e);
+        }
+        let eliminado = fs::remove_file(ruta_insercion(&self.path_tabla));
+        let _ = self.registro.unlock();
+        match eliminado {
+            Ok(_) => Ok(()),
+            Err(_) => Err(error_escritura()),
+        }
+    }
+
+    /// Descarta las filas agregadas, dejando la tabla con su longitud original.
+    pub fn deshacer(self) -> Result<(), ErrorType> {
+        truncar(&self.path_tabla, self.longitud)?;
+        let _ = fs::remove_file(ruta_insercion(&self.path_tabla));
+        let _ = self.registro.unlock();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Read, path::Path, thread, time::Duration};
+
+    use super::*;
+    use crate::executer::manejo_csv::get_reader;
+
+    /// Crea un directorio para la prueba con una tabla de dos filas y devuelve la ruta de la tabla.
+    fn preparar(nombre: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("sql_rustico_insercion_{}", nombre));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path_tabla = dir.join("tabla.csv").to_string_lossy().to_string();
+        fs::write(&path_tabla, "id,nombre\n1,a\n2,b\n").unwrap();
+        path_tabla
+    }
+
+    /// Agrega filas suficientes para que al menos un lote se escriba en la tabla.
+    fn agregar_lotes(insercion: &mut Insercion) {
+        for id in 3..FILAS_POR_LOTE + 10 {
+            assert!(insercion.agregar(&format!("{},x", id)).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_lectura_espera_insercion_en_curso() {
+        let path_tabla = preparar("en_curso");
+        let Ok(mut insercion) = Insercion::iniciar(&path_tabla) else {
+            panic!("Error al iniciar la inserción");
+        };
+        agregar_lotes(&mut insercion);
+
+        let path_lector = path_tabla.clone();
+        let lector = thread::spawn(move || {
+            let mut contenido = String::new();
+            let leido =
+                get_reader(&path_lector).map(|mut reader| reader.read_to_string(&mut contenido));
+            (leido.is_ok(), contenido)
+        });
+        thread::sleep(Duration::from_millis(200));
+        assert!(!lector.is_finished());
+        assert!(Path::new(&ruta_insercion(&path_tabla)).exists());
+
+        assert!(insercion.terminar().is_ok());
+        let (leido, contenido) = lector.join().unwrap();
+        assert!(leido);
+        assert_eq!(contenido.lines().count(), FILAS_POR_LOTE + 10);
+        assert!(contenido.ends_with(&format!("{},x\n", FILAS_POR_LOTE + 9)));
+        assert!(!Path::new(&ruta_insercion(&path_tabla)).exists());
+    }
+
+    #[test]
+    fn test_lectura_recupera_insercion_interrumpida() {
+        let path_tabla = preparar("interrumpida");
+        let Ok(mut insercion) = Insercion::iniciar(&path_tabla) else {
+            panic!("Error al iniciar la inserción");
+        };
+        agregar_lotes(&mut insercion);
+        drop(insercion);
+        assert!(fs::read_to_string(&path_tabla).unwrap().len() > 20);
+
+        assert!(get_reader(&path_tabla).is_ok());
+        assert_eq!(
+            fs::read_to_string(&path_tabla).unwrap(),
+            "id,nombre\n1,a\n2,b\n"
+        );
+        assert!(!Path::new(&ruta_insercion(&path_tabla)).exists());
+        let archivos = fs::read_dir(Path::new(&path_tabla).parent().unwrap())
+            .unwrap()
+            .count();
+        assert_eq!(archivos, 1);
+    }
+}
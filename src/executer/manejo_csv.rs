@@ -1,12 +1,17 @@
 use std::{
     collections::HashMap,
     fs::{self, File, OpenOptions},
-    io::{BufRead, BufReader, Lines, Write},
+    io::{BufRead, BufReader, BufWriter, Lines, Write},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use crate::{
     dato::Datos,
     errores::error::ErrorType,
+    executer::{
+        conjuntos::{abrir_para_agregar, escribir, terminar},
+        insercion::recuperar_insercion,
+    },
     queries::{
         order_clause::{OrderClause, OrderDirection},
         where_clause::{evaluar::Evaluar, expresion_booleana::ExpresionBooleana},
@@ -14,7 +19,9 @@ use crate::{
 };
 
 /// Abre un archivo en la ruta dada y devuelve un `BufReader` para leer el contenido. Retorna un error si el archivo no se puede abrir.
+/// Si el archivo es una tabla con una inserción que no terminó, primero la deshace.
 pub fn get_reader(path: &String) -> Result<BufReader<File>, ErrorType> {
+    recuperar_insercion(path)?;
     match File::open(path) {
         Ok(file) => Ok(BufReader::new(file)),
         Err(_) => Err(ErrorType::InvalidTable(
@@ -48,6 +55,15 @@ pub fn agregar_path(path: &str, agregado: &String) -> String {
     format!("{}/{}.csv", path, agregado)
 }
 
+static CONTADOR_AUXILIARES: AtomicUsize = AtomicUsize::new(0);
+
+/// Genera un nombre único para una tabla auxiliar, para que las consultas que se ejecutan
+/// al mismo tiempo no compartan el mismo archivo.
+pub fn nombre_auxiliar(prefijo: &str) -> String {
+    let numero = CONTADOR_AUXILIARES.fetch_add(1, Ordering::SeqCst);
+    format!("{}_{}_{}", prefijo, std::process::id(), numero)
+}
+
 /// Crea un nuevo archivo en la ruta dada. Retorna un error si la creación del archivo falla.
 pub fn crear_archivo(path: &String) -> Result<File, ErrorType> {
     match File::create(path) {
@@ -77,7 +93,7 @@ pub fn agregar_linea(path: &String, line: &String) -> Result<(), ErrorType> {
 /// Convierte un `HashMap` de datos en una fila de CSV, separando los valores por comas.
 pub fn datos_to_row(
     datos: &HashMap<String, Datos>,
-    columnas: &Vec<String>,
+    columnas: &[String],
 ) -> Result<String, ErrorType> {
     for columna in datos.keys() {
         if datos.get(columna).is_none() {
@@ -86,19 +102,15 @@ pub fn datos_to_row(
             ));
         }
     }
-    let mut result = String::new();
-    for columna in columnas {
-        let value = match datos.get(columna) {
+    let valores: Vec<String> = columnas
+        .iter()
+        .map(|columna| match datos.get(columna) {
             Some(Datos::Integer(i)) => i.to_string(),
             Some(Datos::String(s)) => s.to_string(),
             None => "".to_string(),
-        };
-        if !result.is_empty() {
-            result.push(',');
-        }
-        result.push_str(&value);
-    }
-    Ok(result)
+        })
+        .collect();
+    Ok(valores.join(","))
 }
 
 /// Filtra y devuelve solo las columnas seleccionadas en formato CSV y las posiciones de las mismas.
@@ -182,27 +194,51 @@ pub fn string_to_columns(
     line: &str,
     columnas: &[String],
 ) -> Result<HashMap<String, Datos>, ErrorType> {
-    let mut result = HashMap::new();
-    let values: Vec<&str> = line.split(',').collect();
+    let todas: Vec<usize> = (0..columnas.len()).collect();
+    string_to_columns_podadas(line, columnas, &todas)
+}
 
+/// Igual que `string_to_columns`, pero solo con los datos de las columnas en las posiciones `usadas`,
+/// para no convertir los valores que no se van a leer.
+pub fn string_to_columns_podadas(
+    line: &str,
+    columnas: &[String],
+    usadas: &[usize],
+) -> Result<HashMap<String, Datos>, ErrorType> {
+    let values: Vec<&str> = line.split(',').collect();
     if values.len() != columnas.len() {
         return Err(ErrorType::InvalidColumn(
             "Error al escribir una linea".to_string(),
         ));
     }
-
-    for (i, columna) in columnas.iter().enumerate() {
+    let mut result = HashMap::new();
+    for &i in usadas {
         let value = values[i].trim();
-        let dato = match value.parse::<i32>() {
-            Ok(num) => Datos::Integer(num.into()),
+        let dato = match value.parse::<i64>() {
+            Ok(num) => Datos::Integer(num),
             Err(_) => Datos::String(value.to_string()),
         };
-        result.insert(columna.to_string(), dato);
+        result.insert(columnas[i].to_string(), dato);
     }
-
     Ok(result)
 }
 
+/// Devuelve las posiciones de las columnas que pueden ser alguno de los nombres, calificados o no
+/// con el nombre de su tabla.
+pub fn posiciones_usadas(columnas: &[String], nombres: &[String]) -> Vec<usize> {
+    let sin_tabla = |nombre: &str| match nombre.split_once('.') {
+        Some((_, columna)) => columna.to_string(),
+        None => nombre.to_string(),
+    };
+    (0..columnas.len())
+        .filter(|&i| {
+            nombres.iter().any(|nombre| {
+                *nombre == columnas[i] || sin_tabla(nombre) == sin_tabla(&columnas[i])
+            })
+        })
+        .collect()
+}
+
 /// Modifica una línea de CSV de acuerdo a los cambios especificados y devuelve la línea modificada.
 pub fn modificar_linea(
     linea: &str,
@@ -229,7 +265,7 @@ pub fn modificar_linea(
 }
 
 /// Cambia el orden de los valores de las columnas para la query SELECT
-fn ordenar_linea(linea: &str, orden: &Vec<usize>) -> Result<String, ErrorType> {
+pub fn ordenar_linea(linea: &str, orden: &Vec<usize>) -> Result<String, ErrorType> {
     let values: Vec<String> = linea.split(',').map(|s| s.trim().to_string()).collect();
     if values.len() < orden.len() {
         return Err(ErrorType::InvalidColumn(
@@ -251,6 +287,37 @@ fn ordenar_linea(linea: &str, orden: &Vec<usize>) -> Result<String, ErrorType> {
     Ok(ordenados.join(","))
 }
 
+/// Agrega al final de la salida las filas del archivo de origen, sin su encabezado,
+/// con los valores de las columnas en el orden de las posiciones.
+pub fn proyectar_archivo(
+    origen: &String,
+    posiciones: &Vec<usize>,
+    salida: &String,
+) -> Result<(), ErrorType> {
+    let Ok(file) = OpenOptions::new().append(true).open(salida) else {
+        return Err(ErrorType::Error(("Error al abrir archivo").to_string()));
+    };
+    let mut escritor = BufWriter::new(file);
+    for linea in get_reader(origen)?.lines().skip(1) {
+        let Ok(linea) = linea else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        if writeln!(escritor, "{}", ordenar_linea(&linea, posiciones)?).is_err() {
+            return Err(ErrorType::Error(
+                ("Error al escribir en archivo").to_string(),
+            ));
+        }
+    }
+    match escritor.flush() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ErrorType::Error(
+            ("Error al escribir en archivo").to_string(),
+        )),
+    }
+}
+
 /// Imprime el contenido del archivo en la salida estándar, usando las columnas seleccionadas como encabezado y imprimiendo en el orden de las posiciones.
 pub fn imprimir_archivo(
     path: &String,
@@ -275,6 +342,29 @@ pub fn imprimir_archivo(
     Ok(())
 }
 
+/// Imprime las filas de la tabla como el resultado de un SELECT de las columnas seleccionadas,
+/// escribiéndolas primero en un archivo auxiliar con el encabezado de la tabla.
+pub fn imprimir_filas(
+    path: &str,
+    columnas: &[String],
+    filas: &[String],
+    columnas_selected: &Vec<String>,
+) -> Result<(), ErrorType> {
+    let (columnas_filtradas, posiciones) = filtrar_columnas(columnas_selected, columnas)?;
+    let path_aux = agregar_path(path, &nombre_auxiliar("auxiliar"));
+    let mut contenido = columnas.join(",");
+    for fila in filas {
+        contenido.push('\n');
+        contenido.push_str(fila);
+    }
+    if fs::write(&path_aux, contenido + "\n").is_err() {
+        return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+    }
+    let resultado = imprimir_archivo(&path_aux, columnas_filtradas, posiciones);
+    eliminar_archivo(&path_aux)?;
+    resultado
+}
+
 /// Compara dos líneas según las cláusulas de orden y las columnas especificadas.
 /// Retorna `true` si `linea_1` es posterior que `linea_2` de acuerdo con las cláusulas de orden.
 fn comparar_linea(
@@ -286,14 +376,7 @@ fn comparar_linea(
     let linea_1 = string_to_columns(linea_1, columnas)?;
     let linea_2 = string_to_columns(linea_2, columnas)?;
     for clause in order_by {
-        let cmp = match clause.direccion {
-            OrderDirection::Asc => linea_1
-                .get(&clause.column)
-                .cmp(&linea_2.get(&clause.column)),
-            OrderDirection::Desc => linea_2
-                .get(&clause.column)
-                .cmp(&linea_1.get(&clause.column)),
-        };
+        let cmp = clause.comparar(&linea_1, &linea_2);
         if cmp != std::cmp::Ordering::Equal {
             return Ok(cmp == std::cmp::Ordering::Greater);
         }
@@ -314,6 +397,15 @@ fn leer_primera_linea(lines: &mut Lines<BufReader<File>>) -> Result<String, Erro
     }
 }
 
+/// Lee el encabezado de una tabla y devuelve los nombres de sus columnas, dejando el iterador en la primera fila de datos.
+pub fn leer_encabezado(lines: &mut Lines<BufReader<File>>) -> Result<Vec<String>, ErrorType> {
+    let encabezado = leer_primera_linea(lines)?;
+    Ok(encabezado
+        .split(',')
+        .map(|column_name| column_name.trim().to_string())
+        .collect())
+}
+
 /// Procesa una pasada del algoritmo de bubble sort en el archivo especificado.
 /// Reescribe las líneas en el archivo auxiliar y marca si hubo cambios.
 fn pasada_bubble_sort_archivo(
@@ -364,24 +456,163 @@ fn bubble_sort_archivo(
         let (path_original, reader, path_aux) =
             preparar_archivos(path, table, &"auxiliar_tmp".to_string())?;
         let lines = reader.lines();
-        let (mut lines, columnas) = listar_columnas(&path_aux, lines)?;
-
-        pasada_bubble_sort_archivo(&mut lines, &path_aux, &columnas, order_by, &mut hay_cambios)?;
-
-        reemplazar_archivo(&path_aux, &path_original)?;
+        let resultado = listar_columnas(&path_aux, lines)
+            .and_then(|(mut lines, columnas)| {
+                pasada_bubble_sort_archivo(
+                    &mut lines,
+                    &path_aux,
+                    &columnas,
+                    order_by,
+                    &mut hay_cambios,
+                )
+            })
+            .and_then(|_| reemplazar_archivo(&path_aux, &path_original));
         eliminar_archivo(&path_aux)?;
+        resultado?;
     }
     Ok(())
 }
 
-/// Ordena un archivo según las cláusulas de orden especificadas.
+/// Indica si el archivo no tiene filas después de su encabezado.
+fn sin_filas(path: &String) -> Result<bool, ErrorType> {
+    let mut lines = get_reader(path)?.lines();
+    leer_encabezado(&mut lines)?;
+    Ok(lines.next().is_none())
+}
+
+/// Deja en el archivo solo el encabezado y las primeras `limite` filas, truncándolo donde termina la última.
+pub fn limitar_archivo(path: &String, limite: usize) -> Result<(), ErrorType> {
+    let mut reader = get_reader(path)?;
+    let mut longitud: u64 = 0;
+    let mut linea = Vec::new();
+    for _ in 0..=limite {
+        linea.clear();
+        match reader.read_until(b'\n', &mut linea) {
+            Ok(0) => return Ok(()),
+            Ok(leidos) => longitud += leidos as u64,
+            Err(_) => {
+                return Err(ErrorType::InvalidTable(
+                    "Error al leer una línea".to_string(),
+                ))
+            }
+        }
+    }
+    let truncado = OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len(longitud));
+    match truncado {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ErrorType::Error("Error al escribir en archivo".to_string())),
+    }
+}
+
+/// Ordena un archivo según las cláusulas de orden especificadas. Un archivo sin filas queda como está.
 pub fn ordenar_archivo(
     path: &str,
     table: &String,
     order_by: &Option<Vec<OrderClause>>,
 ) -> Result<(), ErrorType> {
     if let Some(order_by) = order_by {
-        bubble_sort_archivo(path, table, order_by)?;
+        if !sin_filas(&agregar_path(path, table))? {
+            bubble_sort_archivo(path, table, order_by)?;
+        }
+    }
+    Ok(())
+}
+
+/// Función que recibe las filas de una partición, ya ordenadas, y devuelve el valor de cada una para una nueva columna.
+pub type CalcularParticion<'a> =
+    dyn FnMut(&[HashMap<String, Datos>]) -> Result<Vec<Datos>, ErrorType> + 'a;
+
+/// Ordena el archivo por las columnas de `particion` y luego según `orden`, como `ordenar_archivo`,
+/// y agrega al final de cada fila una columna `nombre` con los valores que devuelve `calcular` para su partición.
+/// Solo se guarda en memoria una partición por vez. Si el archivo no tiene filas, solo se agrega la columna al encabezado.
+pub fn particionar_archivo(
+    path: &str,
+    table: &String,
+    particion: &[String],
+    orden: &[OrderClause],
+    nombre: &str,
+    calcular: &mut CalcularParticion,
+) -> Result<(), ErrorType> {
+    let mut order_by: Vec<OrderClause> = particion
+        .iter()
+        .map(|columna| OrderClause {
+            column: columna.to_string(),
+            direccion: OrderDirection::Asc,
+            expresion: None,
+            nulos: None,
+        })
+        .collect();
+    order_by.extend(orden.iter().cloned());
+    let path_table = agregar_path(path, table);
+    if !sin_filas(&path_table)? {
+        bubble_sort_archivo(path, table, &order_by)?;
+    }
+    let path_aux = agregar_path(path, &nombre_auxiliar("auxiliar"));
+    crear_archivo(&path_aux)?;
+    let resultado =
+        agregar_columna_particiones(&path_table, &path_aux, particion, nombre, calcular)
+            .and_then(|_| reemplazar_archivo(&path_aux, &path_table));
+    eliminar_archivo(&path_aux)?;
+    resultado
+}
+
+/// Copia las filas de `origen` en `salida` agregando la columna calculada, llamando a `calcular`
+/// con cada grupo de filas consecutivas que tienen los mismos valores en las columnas de `particion`.
+fn agregar_columna_particiones(
+    origen: &String,
+    salida: &String,
+    particion: &[String],
+    nombre: &str,
+    calcular: &mut CalcularParticion,
+) -> Result<(), ErrorType> {
+    let mut lines = get_reader(origen)?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    let mut escritor = abrir_para_agregar(salida)?;
+    escribir(&mut escritor, &format!("{},{}", columnas.join(","), nombre))?;
+    let mut lineas: Vec<String> = Vec::new();
+    let mut filas: Vec<HashMap<String, Datos>> = Vec::new();
+    for line in lines {
+        let Ok(line) = line else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        let fila = string_to_columns(&line, &columnas)?;
+        let nueva_particion = filas
+            .last()
+            .is_some_and(|anterior| particion.iter().any(|c| anterior.get(c) != fila.get(c)));
+        if nueva_particion {
+            escribir_particion(&mut escritor, &lineas, &filas, calcular)?;
+            lineas.clear();
+            filas.clear();
+        }
+        lineas.push(line);
+        filas.push(fila);
+    }
+    escribir_particion(&mut escritor, &lineas, &filas, calcular)?;
+    terminar(escritor)
+}
+
+/// Escribe las líneas de una partición, cada una seguida del valor que calcula `calcular` para su fila.
+fn escribir_particion(
+    escritor: &mut BufWriter<File>,
+    lineas: &[String],
+    filas: &[HashMap<String, Datos>],
+    calcular: &mut CalcularParticion,
+) -> Result<(), ErrorType> {
+    if filas.is_empty() {
+        return Ok(());
+    }
+    let valores = calcular(filas)?;
+    for (linea, valor) in lineas.iter().zip(valores) {
+        let valor = match valor {
+            Datos::Integer(numero) => numero.to_string(),
+            Datos::String(texto) => texto,
+        };
+        escribir(escritor, &format!("{},{}", linea, valor))?;
     }
     Ok(())
 }
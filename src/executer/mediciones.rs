@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    io::BufRead,
+    time::{Duration, Instant},
+};
+
+use crate::errores::error::ErrorType;
+
+use super::manejo_csv::get_reader;
+
+/// Filas que recibe y devuelve un operador de una consulta, y el tiempo que tardó en ejecutarse.
+/// Los operadores que leen directamente una tabla no tienen filas de entrada.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Medicion {
+    pub filas_entrada: Option<usize>,
+    pub filas_salida: usize,
+    pub tiempo: Duration,
+}
+
+/// Mediciones de los operadores de una consulta ejecutada con `EXPLAIN ANALYZE`, por la clave de cada operador.
+/// Si no están activas, como al ejecutar una consulta normalmente, solo se ejecutan las etapas sin contar sus filas.
+#[derive(Debug, Default)]
+pub struct Mediciones {
+    activas: bool,
+    mediciones: HashMap<String, Medicion>,
+}
+
+/// Devuelve la clave de un operador dentro de la consulta identificada por `prefijo`.
+pub fn clave(prefijo: &str, operador: &str) -> String {
+    format!("{}{}", prefijo, operador)
+}
+
+/// Devuelve la clave de la lectura de la tabla en la posición `posicion` de los JOIN de un SELECT,
+/// contando desde 0 por la tabla del FROM.
+pub fn clave_lectura(prefijo: &str, posicion: usize) -> String {
+    clave(prefijo, &format!("scan{}", posicion))
+}
+
+/// Cuenta las líneas del archivo.
+fn contar_lineas(path: &String) -> Result<usize, ErrorType> {
+    let mut lineas = 0;
+    for line in get_reader(path)?.lines() {
+        if line.is_err() {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        }
+        lineas += 1;
+    }
+    Ok(lineas)
+}
+
+/// Cuenta las filas del archivo, sin su encabezado.
+pub fn contar_filas(path: &String) -> Result<usize, ErrorType> {
+    Ok(contar_lineas(path)?.saturating_sub(1))
+}
+
+impl Mediciones {
+    /// Crea mediciones activas, que registran las filas y el tiempo de cada etapa.
+    pub fn para_analizar() -> Self {
+        Mediciones {
+            activas: true,
+            mediciones: HashMap::new(),
+        }
+    }
+
+    /// Indica si las mediciones registran las etapas que se ejecutan.
+    pub fn activas(&self) -> bool {
+        self.activas
+    }
+
+    /// Ejecuta la etapa del operador `clave`, que lee las filas de los archivos `entradas` y escribe
+    /// su resultado en `salida`. Si las mediciones están activas, registra cuántas filas había en las
+    /// entradas antes de ejecutarla, cuántas quedaron en la salida y cuánto tardó. Sin entradas,
+    /// como al leer una tabla, solo registra las filas de la salida.
+    pub fn medir<T>(
+        &mut self,
+        clave: &str,
+        entradas: &[&String],
+        salida: &String,
+        etapa: impl FnOnce() -> Result<T, ErrorType>,
+    ) -> Result<T, ErrorType> {
+        self.medir_contando(clave, entradas, salida, etapa, contar_filas)
+    }
+
+    /// Igual que `medir`, para etapas cuyos archivos no tienen encabezado.
+    pub fn medir_sin_encabezado<T>(
+        &mut self,
+        clave: &str,
+        entradas: &[&String],
+        salida: &String,
+        etapa: impl FnOnce() -> Result<T, ErrorType>,
+    ) -> Result<T, ErrorType> {
+        self.medir_contando(clave, entradas, salida, etapa, contar_lineas)
+    }
+
+    /// Ejecuta la etapa y, si las mediciones están activas, registra su medición contando las filas con `contar`.
+    fn medir_contando<T>(
+        &mut self,
+        clave: &str,
+        entradas: &[&String],
+        salida: &String,
+        etapa: impl FnOnce() -> Result<T, ErrorType>,
+        contar: fn(&String) -> Result<usize, ErrorType>,
+    ) -> Result<T, ErrorType> {
+        if !self.activas {
+            return etapa();
+        }
+        let mut filas_entrada = None;
+        for entrada in entradas {
+            filas_entrada = Some(filas_entrada.unwrap_or(0) + contar(entrada)?);
+        }
+        let inicio = Instant::now();
+        let resultado = etapa()?;
+        let tiempo = inicio.elapsed();
+        let medicion = Medicion {
+            filas_entrada,
+            filas_salida: contar(salida)?,
+            tiempo,
+        };
+        self.mediciones.insert(clave.to_string(), medicion);
+        Ok(resultado)
+    }
+
+    /// Igual que `medir` para una etapa sin entradas, pero si el operador ya tiene una medición
+    /// le suma el tiempo de la etapa en lugar de reemplazarla.
+    pub fn medir_sumando<T>(
+        &mut self,
+        clave: &str,
+        salida: &String,
+        etapa: impl FnOnce() -> Result<T, ErrorType>,
+    ) -> Result<T, ErrorType> {
+        if !self.activas {
+            return etapa();
+        }
+        let inicio = Instant::now();
+        let resultado = etapa()?;
+        let tiempo = inicio.elapsed();
+        let medicion = Medicion {
+            filas_entrada: None,
+            filas_salida: contar_filas(salida)?,
+            tiempo,
+        };
+        self.sumar(clave, medicion);
+        Ok(resultado)
+    }
+
+    /// Registra la medición del operador `clave` si todavía no tiene una, o le suma su tiempo si ya la tiene,
+    /// como una lectura que se hace en varias etapas. Solo si las mediciones están activas.
+    pub fn sumar(&mut self, clave: &str, medicion: Medicion) {
+        if !self.activas {
+            return;
+        }
+        match self.mediciones.get_mut(clave) {
+            Some(actual) => actual.tiempo += medicion.tiempo,
+            None => {
+                self.mediciones.insert(clave.to_string(), medicion);
+            }
+        }
+    }
+
+    /// Registra la medición del operador `clave`, si las mediciones están activas.
+    pub fn registrar(&mut self, clave: &str, medicion: Medicion) {
+        if self.activas {
+            self.mediciones.insert(clave.to_string(), medicion);
+        }
+    }
+
+    /// Devuelve la medición del operador `clave`, si se registró.
+    pub fn medicion(&self, clave: &str) -> Option<&Medicion> {
+        self.mediciones.get(clave)
+    }
+}
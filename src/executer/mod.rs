@@ -1,2 +1,7 @@
+pub mod conjuntos;
 pub mod execute;
+pub mod insercion;
 pub mod manejo_csv;
+pub mod mediciones;
+pub mod primeras_filas;
+pub mod transaccion;
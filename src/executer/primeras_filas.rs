@@ -0,0 +1,210 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    io::BufRead,
+};
+
+use crate::{dato::Datos, errores::error::ErrorType, queries::order_clause::OrderClause};
+
+use super::{
+    conjuntos::{abrir_para_agregar, escribir, terminar},
+    manejo_csv::{
+        agregar_path, crear_archivo, eliminar_archivo, get_reader, leer_encabezado,
+        nombre_auxiliar, reemplazar_archivo, string_to_columns,
+    },
+};
+
+/// Una fila candidata a estar entre las primeras, con su posición en la lectura para desempatar
+/// y que las filas iguales según el orden queden en el orden en que se leyeron.
+struct Candidata<'a> {
+    orden: &'a [OrderClause],
+    fila: HashMap<String, Datos>,
+    numero: usize,
+    linea: String,
+}
+
+impl Candidata<'_> {
+    /// Compara dos filas según las cláusulas de orden, y por el orden en que se leyeron si empatan.
+    fn comparar(&self, otra: &Self) -> Ordering {
+        self.orden
+            .iter()
+            .map(|clause| clause.comparar(&self.fila, &otra.fila))
+            .find(|cmp| *cmp != Ordering::Equal)
+            .unwrap_or_else(|| self.numero.cmp(&otra.numero))
+    }
+}
+
+impl PartialEq for Candidata<'_> {
+    fn eq(&self, otra: &Self) -> bool {
+        self.comparar(otra) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidata<'_> {}
+
+impl PartialOrd for Candidata<'_> {
+    fn partial_cmp(&self, otra: &Self) -> Option<Ordering> {
+        Some(self.cmp(otra))
+    }
+}
+
+impl Ord for Candidata<'_> {
+    fn cmp(&self, otra: &Self) -> Ordering {
+        self.comparar(otra)
+    }
+}
+
+/// Guarda las primeras `limite` filas según un ORDER BY mientras se leen las filas una por una,
+/// para resolver un `ORDER BY ... LIMIT n` sin ordenar todas las filas. Las guarda en un heap
+/// cuya raíz es la última de las primeras, que se descarta cuando llega una fila anterior a ella,
+/// por lo que nunca tiene más de `limite` filas en memoria.
+pub struct PrimerasFilas<'a> {
+    orden: &'a [OrderClause],
+    limite: usize,
+    filas: BinaryHeap<Candidata<'a>>,
+    leidas: usize,
+}
+
+impl<'a> PrimerasFilas<'a> {
+    /// Crea una nueva instancia de `PrimerasFilas`, sin filas. El heap crece a medida que guarda filas,
+    /// ya que el límite puede ser mucho mayor que la cantidad de filas de la tabla.
+    pub fn new(orden: &'a [OrderClause], limite: usize) -> Self {
+        PrimerasFilas {
+            orden,
+            limite,
+            filas: BinaryHeap::new(),
+            leidas: 0,
+        }
+    }
+
+    /// Agrega la línea, con los valores de su fila, si está entre las primeras leídas hasta el momento.
+    pub fn agregar(&mut self, linea: String, fila: HashMap<String, Datos>) {
+        let candidata = Candidata {
+            orden: self.orden,
+            fila,
+            numero: self.leidas,
+            linea,
+        };
+        self.leidas += 1;
+        if self.filas.len() < self.limite {
+            self.filas.push(candidata);
+        } else if self.filas.peek().is_some_and(|ultima| candidata < *ultima) {
+            self.filas.pop();
+            self.filas.push(candidata);
+        }
+    }
+
+    /// Devuelve las líneas de las primeras filas, ordenadas.
+    pub fn lineas(self) -> Vec<String> {
+        self.filas
+            .into_sorted_vec()
+            .into_iter()
+            .map(|candidata| candidata.linea)
+            .collect()
+    }
+}
+
+/// Reemplaza las filas del archivo por sus primeras `limite` filas según las cláusulas de orden,
+/// leyéndolo una única vez en lugar de ordenarlo entero.
+pub fn primeras_filas_archivo(
+    path: &str,
+    table: &String,
+    order_by: &[OrderClause],
+    limite: usize,
+) -> Result<(), ErrorType> {
+    let path_table = agregar_path(path, table);
+    let mut lines = get_reader(&path_table)?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    let mut primeras = PrimerasFilas::new(order_by, limite);
+    for line in lines {
+        let Ok(line) = line else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        let fila = string_to_columns(&line, &columnas)?;
+        primeras.agregar(line, fila);
+    }
+    let path_aux = agregar_path(path, &nombre_auxiliar("auxiliar"));
+    crear_archivo(&path_aux)?;
+    let resultado = abrir_para_agregar(&path_aux)
+        .and_then(|mut escritor| {
+            escribir(&mut escritor, &columnas.join(","))?;
+            for linea in primeras.lineas() {
+                escribir(&mut escritor, &linea)?;
+            }
+            terminar(escritor)
+        })
+        .and_then(|_| reemplazar_archivo(&path_aux, &path_table));
+    eliminar_archivo(&path_aux)?;
+    resultado
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::PrimerasFilas;
+    use crate::{
+        dato::Datos,
+        queries::order_clause::{OrderClause, OrderDirection},
+    };
+
+    fn fila(id: i64, nombre: &str) -> (String, HashMap<String, Datos>) {
+        let mut fila = HashMap::new();
+        fila.insert("id".to_string(), Datos::Integer(id));
+        fila.insert("nombre".to_string(), Datos::String(nombre.to_string()));
+        (format!("{},{}", id, nombre), fila)
+    }
+
+    fn orden(direccion: OrderDirection) -> Vec<OrderClause> {
+        vec![OrderClause {
+            column: "id".to_string(),
+            direccion,
+            expresion: None,
+            nulos: None,
+        }]
+    }
+
+    #[test]
+    fn test_primeras_filas_descendente() {
+        let orden = orden(OrderDirection::Desc);
+        let mut primeras = PrimerasFilas::new(&orden, 2);
+        for (id, nombre) in [(3, "c"), (1, "a"), (5, "e"), (4, "d"), (2, "b")] {
+            let (linea, fila) = fila(id, nombre);
+            primeras.agregar(linea, fila);
+        }
+        assert_eq!(primeras.lineas(), vec!["5,e", "4,d"]);
+    }
+
+    #[test]
+    fn test_primeras_filas_empates_en_orden_de_lectura() {
+        let orden = orden(OrderDirection::Asc);
+        let mut primeras = PrimerasFilas::new(&orden, 3);
+        for (id, nombre) in [(2, "x"), (1, "a"), (2, "y"), (2, "z"), (1, "b")] {
+            let (linea, fila) = fila(id, nombre);
+            primeras.agregar(linea, fila);
+        }
+        assert_eq!(primeras.lineas(), vec!["1,a", "1,b", "2,x"]);
+    }
+
+    #[test]
+    fn test_primeras_filas_sin_limite() {
+        let orden = orden(OrderDirection::Asc);
+        let mut primeras = PrimerasFilas::new(&orden, 0);
+        let (linea, fila) = fila(1, "a");
+        primeras.agregar(linea, fila);
+        assert!(primeras.lineas().is_empty());
+    }
+
+    #[test]
+    fn test_primeras_filas_limite_enorme() {
+        let orden = orden(OrderDirection::Asc);
+        let mut primeras = PrimerasFilas::new(&orden, usize::MAX);
+        for (id, nombre) in [(2, "b"), (1, "a")] {
+            let (linea, fila) = fila(id, nombre);
+            primeras.agregar(linea, fila);
+        }
+        assert_eq!(primeras.lineas(), vec!["1,a", "2,b"]);
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::errores::error::ErrorType;
+
+use super::manejo_csv::{
+    agregar_path, crear_archivo, eliminar_archivo, nombre_auxiliar, reemplazar_archivo,
+};
+
+/// Agrupa los cambios de una sentencia que modifica varias tablas, como un DELETE con foreign keys en cascada.
+/// El nuevo estado de cada tabla se escribe en un archivo auxiliar y las tablas solo se reemplazan al confirmar,
+/// por lo que si la sentencia falla ninguna tabla queda modificada.
+pub struct Transaccion {
+    path: String,
+    pendientes: HashMap<String, String>,
+    auxiliares: Vec<String>,
+}
+
+impl Transaccion {
+    /// Crea una nueva transacción sobre la carpeta de tablas.
+    pub fn new(path: &str) -> Self {
+        Transaccion {
+            path: path.to_string(),
+            pendientes: HashMap::new(),
+            auxiliares: Vec::new(),
+        }
+    }
+
+    /// Devuelve la carpeta de tablas de la transacción.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Devuelve la ruta del archivo con el estado actual de la tabla dentro de la transacción.
+    pub fn origen(&self, tabla: &str) -> String {
+        match self.pendientes.get(tabla) {
+            Some(path_aux) => path_aux.to_string(),
+            None => agregar_path(&self.path, &tabla.to_string()),
+        }
+    }
+
+    /// Crea un archivo auxiliar vacío donde escribir el nuevo estado de una tabla.
+    pub fn nuevo_auxiliar(&mut self) -> Result<String, ErrorType> {
+        let path_aux = agregar_path(&self.path, &nombre_auxiliar("auxiliar"));
+        crear_archivo(&path_aux)?;
+        self.auxiliares.push(path_aux.to_string());
+        Ok(path_aux)
+    }
+
+    /// Registra el archivo auxiliar como el nuevo estado de la tabla.
+    pub fn registrar(&mut self, tabla: &str, path_aux: &str) {
+        self.pendientes
+            .insert(tabla.to_string(), path_aux.to_string());
+    }
+
+    /// Reemplaza cada tabla modificada por su nuevo estado y elimina los archivos auxiliares.
+    pub fn confirmar(self) -> Result<(), ErrorType> {
+        for (tabla, path_aux) in &self.pendientes {
+            reemplazar_archivo(path_aux, &agregar_path(&self.path, tabla))?;
+        }
+        for path_aux in &self.auxiliares {
+            eliminar_archivo(path_aux)?;
+        }
+        Ok(())
+    }
+
+    /// Elimina los archivos auxiliares sin modificar ninguna tabla.
+    pub fn descartar(self) {
+        for path_aux in &self.auxiliares {
+            let _ = eliminar_archivo(path_aux);
+        }
+    }
+}
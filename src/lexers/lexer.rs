@@ -56,7 +56,7 @@ fn string_rec(
             if espacio && !substring.is_empty() {
                 substring.push(' ');
             }
-            substring.push_str(&caracter.to_string());
+            substring.push(caracter);
             string_rec(string, actual + 1, substring, false)
         }
     }
@@ -103,6 +103,29 @@ fn lexer_rec(
                 lista_open,
             )
         }
+        '(' if string.chars().skip(actual + 1).find(|c| *c != ' ') == Some(')')
+            && (!substring.is_empty()
+                || matches!(operadores.last(), Some(Operador::String(_)) if !lista_open)
+                || matches!(lista.last(), Some(Operador::String(_)) if lista_open)) =>
+        {
+            // llamada a una función sin argumentos, como ROW_NUMBER() u OVER ()
+            agregar_substring(&mut operadores, &mut lista, lista_open, &mut substring);
+            agregar_operador(
+                &mut operadores,
+                &mut lista,
+                lista_open,
+                Operador::Lista(Vec::new()),
+            );
+            let cierre = string.chars().skip(actual + 1).position(|c| c == ')');
+            lexer_rec(
+                string,
+                actual + cierre.unwrap_or(0) + 2,
+                operadores,
+                "".to_string(),
+                lista,
+                lista_open,
+            )
+        }
         '(' => {
             // empieza una lista
             agregar_substring(&mut operadores, &mut lista, lista_open, &mut substring);
@@ -188,16 +211,61 @@ fn lexer_rec(
         }
         _ => {
             // agrego el caracter
-            substring.push_str(&caracter.to_string());
+            substring.push(caracter);
             lexer_rec(string, actual + 1, operadores, substring, lista, lista_open)
         }
     }
 }
 
+/// Parámetros de una consulta encontrados hasta el momento: cuántos `?` y si hubo alguno `$n`.
+#[derive(Default)]
+struct Parametros {
+    posicionales: usize,
+    numerados: bool,
+}
+
+/// Reemplaza las palabras `?` y `$n` por parámetros, también dentro de las listas. Los `?` se numeran
+/// en el orden en que aparecen en la consulta, y no se pueden combinar con parámetros `$n`.
+fn numerar_parametros(
+    operadores: Vec<Operador>,
+    parametros: &mut Parametros,
+) -> Result<Vec<Operador>, ErrorType> {
+    let mut numerados = Vec::new();
+    for operador in operadores {
+        let operador = match operador {
+            Operador::String(s) if s == "?" => {
+                parametros.posicionales += 1;
+                Operador::Parametro(parametros.posicionales)
+            }
+            Operador::String(s) if s.starts_with('$') => match s[1..].parse::<usize>() {
+                Ok(0) => {
+                    return Err(ErrorType::InvalidSyntax(
+                        "Los parámetros '$n' se numeran desde 1".to_string(),
+                    ))
+                }
+                Ok(numero) => {
+                    parametros.numerados = true;
+                    Operador::Parametro(numero)
+                }
+                Err(_) => Operador::String(s),
+            },
+            Operador::Lista(lista) => Operador::Lista(numerar_parametros(lista, parametros)?),
+            _ => operador,
+        };
+        numerados.push(operador);
+    }
+    if parametros.numerados && parametros.posicionales > 0 {
+        return Err(ErrorType::InvalidSyntax(
+            "No se pueden combinar parámetros '?' y '$n' en una misma consulta".to_string(),
+        ));
+    }
+    Ok(numerados)
+}
+
 /// Recibe un string y lo convierte en un vector de operadores validos
 pub fn lexer(string: &String) -> Result<Vec<Operador>, ErrorType> {
     let (operadores, _) = lexer_rec(string, 0, Vec::new(), "".to_string(), Vec::new(), false)?;
-    Ok(operadores)
+    numerar_parametros(operadores, &mut Parametros::default())
 }
 
 // TEST lexer unitarios
@@ -209,7 +277,7 @@ mod tests {
 
     /// Función auxiliar para probar el lexer con un caso de prueba exitoso
     fn probar_lexer_exitoso(caso: &String, esperado: Vec<Operador>) {
-        let resultado = lexer(&caso);
+        let resultado = lexer(caso);
         match resultado {
             Ok(operadores) => {
                 assert_eq!(
@@ -230,22 +298,21 @@ mod tests {
             }
             Err(e) => println!(
                 "Lexer devolvió un error inesperado: {} para el caso: {}",
-                e.to_string(),
-                caso
+                e, caso
             ),
         }
     }
 
     /// Función auxiliar para probar el lexer con un caso de prueba que debería fallar
     fn probar_lexer_error(caso: &String, mensaje_error_esperado: &String) {
-        let resultado = lexer(&caso);
+        let resultado = lexer(caso);
         match resultado {
             Ok(_) => println!("Se esperaba un error para el caso: {}", caso),
             Err(e) => assert!(
                 e.to_string().contains(mensaje_error_esperado),
                 "Se esperaba un error que contenga '{}', pero se obtuvo: '{}' para el caso: {}",
                 mensaje_error_esperado,
-                e.to_string(),
+                e,
                 caso
             ),
         }
@@ -459,6 +526,26 @@ mod tests {
         println!("Error en parentesis encontrado correctamente!");
     }
 
+    #[test]
+    fn test_lexer_funcion_sin_argumentos() {
+        let input = "SELECT ROW_NUMBER() OVER (), RANK() OVER (ORDER BY id) FROM t".to_string();
+        let s = |s: &str| Operador::String(s.to_string());
+        let esperado = vec![
+            s("SELECT"),
+            s("ROW_NUMBER"),
+            Operador::Lista(Vec::new()),
+            s("OVER"),
+            Operador::Lista(Vec::new()),
+            s("RANK"),
+            Operador::Lista(Vec::new()),
+            s("OVER"),
+            Operador::Lista(vec![s("ORDER"), s("BY"), s("id")]),
+            s("FROM"),
+            s("t"),
+        ];
+        probar_lexer_exitoso(&input, esperado);
+    }
+
     #[test]
     fn test_lexer_error_cerrar_texto() {
         let input = "'Ivan".to_string();
@@ -466,4 +553,48 @@ mod tests {
         probar_lexer_error(&input, &error);
         println!("Error en comillas simples encontrado correctamente!");
     }
+
+    #[test]
+    fn test_lexer_parametros() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = "INSERT INTO t VALUES (?, 'a'), (?, ?)".to_string();
+        let esperado = vec![
+            s("INSERT"),
+            s("INTO"),
+            s("t"),
+            s("VALUES"),
+            Operador::Lista(vec![
+                Operador::Parametro(1),
+                Operador::Texto("a".to_string()),
+            ]),
+            Operador::Lista(vec![Operador::Parametro(2), Operador::Parametro(3)]),
+        ];
+        probar_lexer_exitoso(&input, esperado);
+        let input = "SELECT * FROM t WHERE id=$2 AND nombre = $1".to_string();
+        let esperado = vec![
+            s("SELECT"),
+            s("*"),
+            s("FROM"),
+            s("t"),
+            s("WHERE"),
+            s("id"),
+            Operador::Comparador("=".to_string()),
+            Operador::Parametro(2),
+            s("AND"),
+            s("nombre"),
+            Operador::Comparador("=".to_string()),
+            Operador::Parametro(1),
+        ];
+        probar_lexer_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_lexer_error_parametros() {
+        let input = "SELECT * FROM t WHERE id = ? AND nombre = $1".to_string();
+        let error = "No se pueden combinar parámetros '?' y '$n'".to_string();
+        probar_lexer_error(&input, &error);
+        let input = "SELECT * FROM t WHERE id = $0".to_string();
+        let error = "Los parámetros '$n' se numeran desde 1".to_string();
+        probar_lexer_error(&input, &error);
+    }
 }
@@ -1,9 +1,11 @@
 /// Enum que representa diferentes tipos de operadores en una consulta SQL.
-/// Contiene variantes para cadenas de texto, listas de operadores, textos literales y comparadores.
-#[derive(Debug, PartialEq)]
+/// Contiene variantes para cadenas de texto, listas de operadores, textos literales, comparadores
+/// y parámetros `?` o `$n` de una consulta preparada, con su número contando desde 1.
+#[derive(Debug, PartialEq, Clone)]
 pub enum Operador {
     String(String),
     Lista(Vec<Operador>),
     Texto(String),
     Comparador(String),
+    Parametro(usize),
 }
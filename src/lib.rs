@@ -1,7 +1,9 @@
 pub mod dato;
 pub mod errores;
+pub mod esquema;
 pub mod executer;
 pub mod lexers;
 pub mod parsers;
+pub mod plan;
 pub mod queries;
 pub mod utils;
@@ -1 +1,8 @@
 pub mod parser;
+pub mod parser_conjunto;
+pub mod parser_create;
+pub mod parser_explain;
+pub mod parser_insert;
+pub mod parser_ventana;
+pub mod parser_vista;
+pub mod parser_with;
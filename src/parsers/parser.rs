@@ -4,24 +4,45 @@ use crate::{
     dato::Datos,
     errores::error::ErrorType,
     lexers::operador::Operador,
+    parsers::{
+        parser_conjunto::parse_select_query,
+        parser_create::parse_create_query,
+        parser_explain::parse_explain_query,
+        parser_insert::parse_insert_query,
+        parser_ventana::parse_ventana,
+        parser_vista::{parse_create_materialized_view_query, parse_create_view_query},
+        parser_with::parse_with_query,
+    },
     queries::{
+        analyze_query::AnalyzeQuery,
+        conteo::Conteo,
         delete_query::DeleteQuery,
-        insert_query::InsertQuery,
-        order_clause::{OrderClause, OrderDirection},
+        drop_view_query::DropViewQuery,
+        join::Join,
+        order_clause::{OrdenNulos, OrderClause, OrderDirection},
+        refresh_query::RefreshQuery,
         select_query::SelectQuery,
         sql_query::SQLQuery,
         update_query::UpdateQuery,
-        where_clause::expresion_booleana::ExpresionBooleana,
+        valores::Valores,
+        ventana::Ventana,
+        where_clause::{
+            case::Case,
+            expresion_booleana::ExpresionBooleana,
+            funciones::es_funcion_escalar,
+            operador_comparacion::OperadorComparacion,
+            subconsulta::Subconsulta,
+            valor::{OperadorAritmetico, Valor},
+        },
     },
     utils::{
-        operador_to_dato, operador_to_single_dato, operador_to_single_valor, string_to_comparacion,
-        string_to_direccion,
+        operador_to_dato, operador_to_single_valor, string_to_comparacion, string_to_direccion,
     },
 };
 
 /// Transforma una lista de Operadores en una lista de Strings para la lista de columnas
 /// ["column1", "column2,", "..."] a Vec<String>
-fn columns_to_string(lista: &Vec<Operador>) -> Result<Vec<String>, ErrorType> {
+pub fn columns_to_string(lista: &[Operador]) -> Result<Vec<String>, ErrorType> {
     let mut lista_dato: Vec<String> = Vec::new();
     for operador in lista {
         match operador {
@@ -37,63 +58,47 @@ fn columns_to_string(lista: &Vec<Operador>) -> Result<Vec<String>, ErrorType> {
 }
 
 /// Verifica si hay columnas repetidas en un vector de nombres de columnas.
-fn columnas_repetidas(columnas: &[String]) -> bool {
+pub fn columnas_repetidas(columnas: &[String]) -> bool {
     let unique_columns: HashSet<_> = columnas.iter().collect();
     unique_columns.len() != columnas.len()
 }
 
-/// Transforma una lista de Operadores en una lista de Datos para value
-/// ["value1", "value2", "..."] a Vec<Datos>
-fn operador_to_value(
-    lista: &[Operador],
-    columnas: &[String],
-) -> Result<HashMap<String, Datos>, ErrorType> {
-    if lista.len() != columnas.len() {
-        return Err(ErrorType::InvalidSyntax(
-            "El número de columnas y valores no coincide.".to_string(),
-        ));
-    }
-    let mut datos: HashMap<String, Datos> = HashMap::new();
-    for (i, columna) in columnas.iter().enumerate() {
-        let item = operador_to_single_dato(&lista[i])?;
-        datos.insert(columna.to_string(), item);
-    }
-    Ok(datos)
-}
-
-/// Para pasar los values a INSERTAR
-/// ["value1", "value2", "..."] ["value1", "value2", "..."] ... a Vec<HashMap<String, Datos> -> Vec(Columna -> Valor)
-fn operador_to_values(
-    lista: &[Operador],
-    columnas: &[String],
-) -> Result<Vec<HashMap<String, Datos>>, ErrorType> {
-    let mut values: Vec<HashMap<String, Datos>> = Vec::new();
-    for operador in lista {
-        match operador {
-            Operador::Lista(lista) => values.push(operador_to_value(lista, columnas)?),
-            _ => {
-                return Err(ErrorType::InvalidSyntax(
-                    "Faltan valores en la consulta INSERT.".to_string(),
-                ))
-            }
-        }
-    }
-    Ok(values)
-}
+/// Cambios de un UPDATE: los valores constantes y las expresiones CASE o los parámetros `$n` que se calculan en cada fila,
+/// junto con los operadores que siguen.
+type Cambios<'a> = (
+    HashMap<String, Datos>,
+    HashMap<String, Valor>,
+    &'a [Operador],
+);
 
 /// Funcion recursiva para guardar cambios de update
 fn changes_rec(
     mut changes: HashMap<String, Datos>,
+    mut expresiones: HashMap<String, Valor>,
     rest: &[Operador],
-) -> Result<(HashMap<String, Datos>, &[Operador]), ErrorType> {
+) -> Result<Cambios<'_>, ErrorType> {
     match rest {
-        [] => Ok((changes, rest)),
-        [Operador::String(where_str), ..] if where_str == "WHERE" => Ok((changes, rest)),
+        [] => Ok((changes, expresiones, rest)),
+        [Operador::String(where_str), ..] if where_str == "WHERE" => {
+            Ok((changes, expresiones, rest))
+        }
+        [Operador::String(column_str) | Operador::Texto(column_str), Operador::Comparador(igual), Operador::Lista(case), rest @ ..]
+            if igual == "=" && es_case(case) =>
+        {
+            expresiones.insert(column_str.to_string(), parse_case(case)?);
+            changes_rec(changes, expresiones, rest)
+        }
+        [Operador::String(column_str) | Operador::Texto(column_str), Operador::Comparador(igual), Operador::Parametro(numero), rest @ ..]
+            if igual == "=" =>
+        {
+            expresiones.insert(column_str.to_string(), Valor::Parametro(*numero));
+            changes_rec(changes, expresiones, rest)
+        }
         [Operador::String(column_str) | Operador::Texto(column_str), Operador::Comparador(igual), value, rest @ ..]
             if igual == "=" =>
         {
             changes.insert(column_str.to_string(), operador_to_dato(value)?);
-            changes_rec(changes, rest)
+            changes_rec(changes, expresiones, rest)
         }
         _ => Err(ErrorType::InvalidSyntax(
             "Error de sintaxis en el seteo de columnas = value en UPDATE ".to_string(),
@@ -107,12 +112,15 @@ fn precedence(rest: &[Operador]) -> (Vec<Operador>, &[Operador]) {
 
     for (i, operador) in rest.iter().enumerate() {
         match operador {
-            Operador::String(s) if s == "ORDER" => {
+            Operador::String(s) if s == "ORDER" || s == "LIMIT" => {
                 if !current_list.is_empty() {
                     result.push(Operador::Lista(current_list));
                 }
                 return (result, &rest[i..]);
             }
+            Operador::Lista(inner_list) if es_subconsulta(inner_list) || es_case(inner_list) => {
+                current_list.push(Operador::Lista(inner_list.to_vec()));
+            }
             Operador::Lista(inner_list) => {
                 let (processed_inner_list, _) = precedence(inner_list);
                 current_list.push(Operador::Lista(processed_inner_list));
@@ -131,6 +139,9 @@ fn precedence(rest: &[Operador]) -> (Vec<Operador>, &[Operador]) {
             Operador::Texto(c) => {
                 current_list.push(Operador::Texto(c.to_string()));
             }
+            Operador::Parametro(numero) => {
+                current_list.push(Operador::Parametro(*numero));
+            }
         }
     }
     if result.is_empty() {
@@ -140,6 +151,137 @@ fn precedence(rest: &[Operador]) -> (Vec<Operador>, &[Operador]) {
     (result, &rest[rest.len()..])
 }
 
+/// Verifica si una lista entre paréntesis es una subconsulta [SELECT, ...]
+pub fn es_subconsulta(lista: &[Operador]) -> bool {
+    matches!(lista.first(), Some(Operador::String(select)) if select == "SELECT")
+}
+
+/// [SELECT, ..., FROM, tabla, ...] entre paréntesis a Subconsulta
+fn parse_subconsulta(lista: &[Operador]) -> Result<Subconsulta, ErrorType> {
+    match lista {
+        [Operador::String(select), rest @ ..] if select == "SELECT" => {
+            Ok(Subconsulta::Consulta(Box::new(parser_select(rest)?)))
+        }
+        _ => Err(ErrorType::InvalidSyntax(
+            "Se esperaba una subconsulta SELECT entre paréntesis".to_string(),
+        )),
+    }
+}
+
+/// Convierte un operando de una comparación en un Valor, que puede ser una subconsulta escalar
+fn operador_to_valor(operador: &Operador) -> Result<Valor, ErrorType> {
+    match operador {
+        Operador::Lista(lista) if es_subconsulta(lista) => {
+            Ok(Valor::Subconsulta(parse_subconsulta(lista)?))
+        }
+        Operador::Lista(case) if es_case(case) => parse_case(case),
+        Operador::Lista(lista) if lista.len() == 1 => operador_to_valor(&lista[0]),
+        _ => operador_to_single_valor(operador),
+    }
+}
+
+/// Agrupa en una lista cada expresión [CASE, ..., END], también dentro de los paréntesis,
+/// para que se procese como un único operando.
+fn agrupar_case(rest: &[Operador]) -> Result<Vec<Operador>, ErrorType> {
+    let mut abiertos: Vec<Vec<Operador>> = vec![Vec::new()];
+    for operador in rest {
+        let agrupado = match operador {
+            Operador::String(case) if case == "CASE" => {
+                abiertos.push(vec![operador.clone()]);
+                continue;
+            }
+            Operador::String(end) if end == "END" && abiertos.len() > 1 => {
+                let mut case = abiertos.pop().unwrap_or_default();
+                case.push(operador.clone());
+                Operador::Lista(case)
+            }
+            Operador::Lista(lista) => Operador::Lista(agrupar_case(lista)?),
+            _ => operador.clone(),
+        };
+        if let Some(actual) = abiertos.last_mut() {
+            actual.push(agrupado);
+        }
+    }
+    match abiertos.pop() {
+        Some(agrupados) if abiertos.is_empty() => Ok(agrupados),
+        _ => Err(ErrorType::InvalidSyntax(
+            "Falta el END de la expresión CASE".to_string(),
+        )),
+    }
+}
+
+/// Verifica si una lista es una expresión [CASE, ..., END]
+fn es_case(lista: &[Operador]) -> bool {
+    matches!(
+        (lista.first(), lista.last()),
+        (Some(Operador::String(case)), Some(Operador::String(end))) if case == "CASE" && end == "END"
+    )
+}
+
+/// Convierte el resultado de una rama de un CASE en un Valor. `NULL` es un texto vacío.
+fn valor_case(operador: &Operador) -> Result<Valor, ErrorType> {
+    match operador {
+        Operador::String(null) if null == "NULL" => Ok(Valor::Literal("".to_string())),
+        _ => operador_to_valor(operador),
+    }
+}
+
+/// [WHEN, condicion, THEN, valor, ..., ELSE, valor] a las ramas del CASE.
+/// En la forma simple, con un operando, cada WHEN tiene un único valor que se compara por igualdad con el operando.
+fn ramas_case(
+    rest: &[Operador],
+    operando: &Option<Valor>,
+    mut ramas: Vec<(ExpresionBooleana, Valor)>,
+) -> Result<Case, ErrorType> {
+    let posicion_then = rest
+        .iter()
+        .position(|operador| matches!(operador, Operador::String(s) if s == "THEN"));
+    match (rest, posicion_then) {
+        ([Operador::String(when), ..], Some(posicion)) if when == "WHEN" && posicion > 1 => {
+            let condicion = &rest[1..posicion];
+            let condicion = match (operando, condicion) {
+                (Some(operando), [valor]) => ExpresionBooleana::Comparacion {
+                    izq: operando.clone(),
+                    operador: OperadorComparacion::Igual,
+                    der: valor_case(valor)?,
+                },
+                (Some(_), _) => {
+                    return Err(ErrorType::InvalidSyntax(
+                        "En un CASE con operando, cada WHEN debe tener un único valor".to_string(),
+                    ))
+                }
+                (None, condicion) => parse_condicion(condicion)?,
+            };
+            let Some(valor) = rest.get(posicion + 1) else {
+                return Err(ErrorType::InvalidSyntax(
+                    "Falta el valor despues del THEN en CASE".to_string(),
+                ));
+            };
+            ramas.push((condicion, valor_case(valor)?));
+            ramas_case(&rest[posicion + 2..], operando, ramas)
+        }
+        ([Operador::String(else_str), sino], _) if else_str == "ELSE" && !ramas.is_empty() => {
+            Ok(Case::new(ramas, Some(valor_case(sino)?)))
+        }
+        ([], _) if !ramas.is_empty() => Ok(Case::new(ramas, None)),
+        _ => Err(ErrorType::InvalidSyntax(
+            "Sintaxis invalida en CASE, se esperaba 'WHEN condicion THEN valor' y opcionalmente 'ELSE valor' antes del END".to_string(),
+        )),
+    }
+}
+
+/// [CASE, WHEN, condicion, THEN, valor, ..., ELSE, valor, END] o [CASE, operando, WHEN, valor, THEN, valor, ..., END] a Valor
+fn parse_case(lista: &[Operador]) -> Result<Valor, ErrorType> {
+    let interno = &lista[1..lista.len() - 1];
+    let (operando, ramas) = match interno {
+        [Operador::String(when), ..] if when == "WHEN" => (None, interno),
+        [operando, rest @ ..] => (Some(operador_to_valor(operando)?), rest),
+        [] => (None, interno),
+    };
+    let case = ramas_case(ramas, &operando, Vec::new())?;
+    Ok(Valor::Case(Box::new(case)))
+}
+
 /// Crea una comparacion simple del formato [izq, =|<|>|>=|<=, der]
 fn crear_comparacion(
     izq: &Operador,
@@ -147,9 +289,17 @@ fn crear_comparacion(
     der: &Operador,
 ) -> Result<ExpresionBooleana, ErrorType> {
     Ok(ExpresionBooleana::Comparacion {
-        izq: operador_to_single_valor(izq)?,
+        izq: operador_to_valor(izq)?,
         operador: string_to_comparacion(comparador)?,
-        der: operador_to_single_valor(der)?,
+        der: operador_to_valor(der)?,
+    })
+}
+
+/// Crea una condicion [valor, IN, (SELECT, ...)]
+fn crear_in(valor: &Operador, subconsulta: &[Operador]) -> Result<ExpresionBooleana, ErrorType> {
+    Ok(ExpresionBooleana::In {
+        valor: operador_to_valor(valor)?,
+        subconsulta: parse_subconsulta(subconsulta)?,
     })
 }
 
@@ -185,6 +335,26 @@ fn where_cases(rest: &[Operador]) -> Result<(ExpresionBooleana, &[Operador]), Er
             let comparacion = crear_comparacion(izq, comparador, der)?;
             Ok((comparacion, rest))
         }
+        // [izq, IN, (SELECT, ...), ...]
+        [izq, Operador::String(in_str), Operador::Lista(subconsulta), rest @ ..]
+            if in_str == "IN" =>
+        {
+            Ok((crear_in(izq, subconsulta)?, rest))
+        }
+        // [izq, NOT, IN, (SELECT, ...), ...]
+        [izq, Operador::String(not), Operador::String(in_str), Operador::Lista(subconsulta), rest @ ..]
+            if not == "NOT" && in_str == "IN" =>
+        {
+            let expresion = crear_in(izq, subconsulta)?;
+            Ok((ExpresionBooleana::Not(Box::new(expresion)), rest))
+        }
+        // [EXISTS, (SELECT, ...), ...]
+        [Operador::String(exists), Operador::Lista(subconsulta), rest @ ..]
+            if exists == "EXISTS" =>
+        {
+            let expresion = ExpresionBooleana::Exists(parse_subconsulta(subconsulta)?);
+            Ok((expresion, rest))
+        }
         // [lista, ...]
         [Operador::Lista(lista), rest @ ..] => {
             let expresion = where_clause_rec(lista)?;
@@ -206,6 +376,17 @@ fn where_clause_rec(rest: &[Operador]) -> Result<ExpresionBooleana, ErrorType> {
     where_and_or(expresion_inicial, rest)
 }
 
+/// Procesa una condición completa, como la de un WHERE o un CHECK, en una expresión booleana.
+pub fn parse_condicion(rest: &[Operador]) -> Result<ExpresionBooleana, ErrorType> {
+    let (condicion, rest) = precedence(rest);
+    if !rest.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Sintaxis inválida en la condición.".to_string(),
+        ));
+    }
+    where_clause_rec(&condicion)
+}
+
 /// Se fija si tiene el operador WHERE y si esta devuelve sus valores
 fn where_clause(rest: &[Operador]) -> Result<(Option<ExpresionBooleana>, &[Operador]), ErrorType> {
     match rest {
@@ -223,59 +404,284 @@ fn where_clause(rest: &[Operador]) -> Result<(Option<ExpresionBooleana>, &[Opera
     }
 }
 
-/// [..., FROM, ...] devuelve las columnas hasta FROM
+/// Palabras que terminan las columnas de un SELECT sin FROM.
+const FIN_COLUMNAS_SIN_FROM: [&str; 3] = ["WHERE", "ORDER", "LIMIT"];
+
+/// [valor, ...] devuelve un operando de una expresión aritmética: un valor, una expresión CASE,
+/// una función escalar con sus argumentos o una expresión entre paréntesis.
+fn operando_expresion(rest: &[Operador]) -> Result<(Valor, &[Operador]), ErrorType> {
+    match rest {
+        [Operador::Lista(case), rest @ ..] if es_case(case) => Ok((parse_case(case)?, rest)),
+        [Operador::String(funcion), Operador::Lista(argumentos), rest @ ..]
+            if es_funcion_escalar(funcion) =>
+        {
+            let mut valores = Vec::new();
+            let mut pendientes = argumentos.as_slice();
+            while !pendientes.is_empty() {
+                let (valor, siguientes) = parse_expresion(pendientes)?;
+                valores.push(valor);
+                pendientes = siguientes;
+            }
+            let funcion = Valor::Funcion {
+                nombre: funcion.to_string(),
+                argumentos: valores,
+            };
+            Ok((funcion, rest))
+        }
+        [Operador::Lista(lista), rest @ ..] if !es_subconsulta(lista) => {
+            match parse_expresion(lista)? {
+                (valor, []) => Ok((valor, rest)),
+                _ => Err(ErrorType::InvalidSyntax(
+                    "Se esperaba una única expresión entre paréntesis".to_string(),
+                )),
+            }
+        }
+        [operador @ (Operador::String(_) | Operador::Texto(_) | Operador::Parametro(_)), rest @ ..] => {
+            Ok((operador_to_single_valor(operador)?, rest))
+        }
+        _ => Err(ErrorType::InvalidSyntax(
+            "Se esperaba un valor en la expresión".to_string(),
+        )),
+    }
+}
+
+/// [valor, +|-|*|/, valor, ...] devuelve la expresión aritmética que empieza en `rest` y los operadores que la siguen.
+/// La multiplicación y la división se agrupan antes que la suma y la resta, y operadores de igual precedencia
+/// se agrupan de izquierda a derecha.
+fn parse_expresion(rest: &[Operador]) -> Result<(Valor, &[Operador]), ErrorType> {
+    expresion_rec(rest, 1)
+}
+
+/// Devuelve la expresión que empieza en `rest` formada por operadores de al menos la precedencia `minima`.
+fn expresion_rec(rest: &[Operador], minima: u8) -> Result<(Valor, &[Operador]), ErrorType> {
+    let (mut izq, mut rest) = match minima {
+        1 => expresion_rec(rest, 2)?,
+        _ => operando_expresion(rest)?,
+    };
+    while let [Operador::String(simbolo), siguientes @ ..] = rest {
+        let Some(operador) = OperadorAritmetico::desde(simbolo) else {
+            break;
+        };
+        if operador.precedencia() != minima {
+            break;
+        }
+        let (der, siguientes) = match minima {
+            1 => expresion_rec(siguientes, 2)?,
+            _ => operando_expresion(siguientes)?,
+        };
+        izq = Valor::Operacion {
+            izq: Box::new(izq),
+            operador,
+            der: Box::new(der),
+        };
+        rest = siguientes;
+    }
+    Ok((izq, rest))
+}
+
+/// Columnas con sus alias, conteos, expresiones calculadas y funciones de ventana seleccionados en un SELECT,
+/// junto con los operadores que siguen al FROM.
+type ColumnasSeleccionadas<'a> = (
+    Vec<String>,
+    Vec<Option<String>>,
+    Vec<Conteo>,
+    Vec<Valor>,
+    Vec<Ventana>,
+    &'a [Operador],
+);
+
+/// [..., FROM, ...] devuelve las columnas con sus alias, los COUNT, las expresiones calculadas y las funciones de ventana
+/// hasta FROM. Cada expresión calculada y cada función de ventana también se agrega a las columnas, con su texto como nombre.
+/// Las expresiones calculadas son los literales, las funciones escalares, las expresiones CASE y las aritméticas.
+/// Sin FROM, las columnas terminan al final de la consulta o en su WHERE, ORDER BY o LIMIT.
 fn columns_select_rec(
     rest: &[Operador],
     mut columns: Vec<String>,
-) -> Result<(Vec<String>, &[Operador]), ErrorType> {
+    mut alias_columnas: Vec<Option<String>>,
+    mut conteos: Vec<Conteo>,
+    mut calculadas: Vec<Valor>,
+    mut ventanas: Vec<Ventana>,
+) -> Result<ColumnasSeleccionadas<'_>, ErrorType> {
+    let rest = match rest {
+        [Operador::String(from), rest @ ..] if from == "FROM" => {
+            return Ok((columns, alias_columnas, conteos, calculadas, ventanas, rest))
+        }
+        [Operador::String(fin), ..] if FIN_COLUMNAS_SIN_FROM.contains(&fin.as_str()) => {
+            return Ok((columns, alias_columnas, conteos, calculadas, ventanas, rest))
+        }
+        [] => return Ok((columns, alias_columnas, conteos, calculadas, ventanas, rest)),
+        // [funcion, (argumentos), OVER, (PARTITION, BY, ..., ORDER, BY, ..., ROWS, ...), rest]
+        [Operador::String(funcion), Operador::Lista(argumentos), Operador::String(over), Operador::Lista(definicion), rest @ ..]
+            if over == "OVER" =>
+        {
+            let ventana = parse_ventana(funcion, argumentos, definicion)?;
+            columns.push(ventana.nombre_columna());
+            ventanas.push(ventana);
+            rest
+        }
+        [Operador::String(count), Operador::Lista(argumentos), rest @ ..] if count == "COUNT" => {
+            let mut conteo = conteo(argumentos)?;
+            let (alias, rest) = alias_columna(rest)?;
+            conteo.alias = alias;
+            conteos.push(conteo);
+            return columns_select_rec(
+                rest,
+                columns,
+                alias_columnas,
+                conteos,
+                calculadas,
+                ventanas,
+            );
+        }
+        [Operador::String(_) | Operador::Texto(_) | Operador::Lista(_) | Operador::Parametro(_), ..] => {
+            match parse_expresion(rest)? {
+                (Valor::String(column), rest) if column.parse::<i64>().is_err() => {
+                    columns.push(column);
+                    rest
+                }
+                (expresion, rest) => {
+                    columns.push(expresion.to_string());
+                    calculadas.push(expresion);
+                    rest
+                }
+            }
+        }
+        _ => {
+            return Err(ErrorType::InvalidSyntax(
+                "Se esperaba 'FROM' luego de las columnas en SELECT".to_string(),
+            ))
+        }
+    };
+    let (alias, rest) = alias_columna(rest)?;
+    alias_columnas.push(alias);
+    columns_select_rec(rest, columns, alias_columnas, conteos, calculadas, ventanas)
+}
+
+/// [AS, alias, rest] devuelve el alias de la columna anterior, si lo tiene.
+fn alias_columna(rest: &[Operador]) -> Result<(Option<String>, &[Operador]), ErrorType> {
     match rest {
-        [Operador::String(from), rest @ ..] if from == "FROM" => Ok((columns, rest)),
-        [Operador::String(column) | Operador::Texto(column), rest @ ..] => {
-            columns.push(column.to_string());
-            columns_select_rec(rest, columns)
+        [Operador::String(as_str), Operador::String(alias) | Operador::Texto(alias), rest @ ..]
+            if as_str == "AS" && alias != "FROM" =>
+        {
+            Ok((Some(alias.to_string()), rest))
         }
-        _ => Err(ErrorType::InvalidSyntax(
-            "Se esperaba 'FROM' luego de las columnas en SELECT".to_string(),
+        [Operador::String(as_str), ..] if as_str == "AS" => Err(ErrorType::InvalidSyntax(
+            "Se esperaba un alias despues de AS".to_string(),
         )),
+        _ => Ok((None, rest)),
     }
 }
 
-/// Procesa la lista de operadores para extraer las columnas a ordenar.
-fn order_by_rec(
-    rest: &[Operador],
-    mut order_by: Vec<OrderClause>,
-) -> Result<(Vec<OrderClause>, &[Operador]), ErrorType> {
+/// [AS, alias, rest] o [alias, rest] devuelve el alias de la tabla anterior, si lo tiene.
+fn alias_tabla(rest: &[Operador]) -> Result<(Option<String>, &[Operador]), ErrorType> {
+    const SIGUIENTES: [&str; 10] = [
+        "JOIN",
+        "INNER",
+        "ON",
+        "WHERE",
+        "ORDER",
+        "LIMIT",
+        "UNION",
+        "INTERSECT",
+        "EXCEPT",
+        "OPTION",
+    ];
     match rest {
-        [] => Ok((order_by, rest)),
-        // [columna, direccion, rest]
-        [Operador::String(column) | Operador::Texto(column), Operador::String(direccion), rest @ ..]
-            if direccion == "ASC" || direccion == "DESC" =>
+        [Operador::String(as_str), ..] if as_str == "AS" => alias_columna(rest),
+        [Operador::String(alias), rest @ ..] if !SIGUIENTES.contains(&alias.as_str()) => {
+            Ok((Some(alias.to_string()), rest))
+        }
+        _ => Ok((None, rest)),
+    }
+}
+
+/// Procesa los argumentos de un COUNT: `*`, una columna o `DISTINCT` y una columna.
+fn conteo(argumentos: &[Operador]) -> Result<Conteo, ErrorType> {
+    match argumentos {
+        [Operador::String(asterisco)] if asterisco == "*" => Ok(Conteo::new(None, false)),
+        [Operador::String(distinct), Operador::String(column) | Operador::Texto(column)]
+            if distinct == "DISTINCT" && column != "*" =>
         {
-            let direccion = string_to_direccion(direccion)?;
-            let order_clause = OrderClause {
-                column: column.to_string(),
-                direccion,
-            };
-            order_by.push(order_clause);
-            Ok(order_by_rec(rest, order_by)?)
+            Ok(Conteo::new(Some(column.to_string()), true))
         }
-        // [columna, rest]
-        [Operador::String(column) | Operador::Texto(column), rest @ ..] => {
-            let order_clause = OrderClause {
-                column: column.to_string(),
-                direccion: OrderDirection::Asc,
-            };
-            order_by.push(order_clause);
-            Ok(order_by_rec(rest, order_by)?)
+        [Operador::String(column) | Operador::Texto(column)] if column != "DISTINCT" => {
+            Ok(Conteo::new(Some(column.to_string()), false))
         }
         _ => Err(ErrorType::InvalidSyntax(
-            "Variables invalidas en WHERE_CLAUSE".to_string(),
+            "Argumentos invalidos en COUNT, se esperaba '*', una columna o 'DISTINCT columna'"
+                .to_string(),
         )),
     }
 }
 
+/// Procesa la lista de operadores para extraer las columnas a ordenar. Cada una puede ser una columna,
+/// un alias, la posición de una columna seleccionada o una expresión, seguida de su dirección
+/// y de `NULLS FIRST` o `NULLS LAST`.
+pub fn order_by_rec(
+    rest: &[Operador],
+    mut order_by: Vec<OrderClause>,
+) -> Result<(Vec<OrderClause>, &[Operador]), ErrorType> {
+    let (column, expresion, rest) = match rest {
+        [] => return Ok((order_by, rest)),
+        [Operador::String(limit), ..] if limit == "LIMIT" => return Ok((order_by, rest)),
+        [Operador::String(_) | Operador::Texto(_) | Operador::Lista(_), ..] => {
+            match parse_expresion(rest)? {
+                // [posicion, rest]
+                (Valor::String(posicion), _) if posicion.parse::<i64>().is_ok_and(|n| n < 1) => {
+                    return Err(ErrorType::InvalidSyntax(format!(
+                        "La posición {} del ORDER BY debe ser mayor a cero",
+                        posicion
+                    )))
+                }
+                // [columna, rest]
+                (Valor::String(column), rest) => (column, None, rest),
+                (expresion, rest) => (expresion.to_string(), Some(expresion), rest),
+            }
+        }
+        _ => {
+            return Err(ErrorType::InvalidSyntax(
+                "Variables invalidas en WHERE_CLAUSE".to_string(),
+            ))
+        }
+    };
+    // [direccion, rest]
+    let (direccion, rest) = match rest {
+        [Operador::String(direccion), rest @ ..] if direccion == "ASC" || direccion == "DESC" => {
+            (string_to_direccion(direccion)?, rest)
+        }
+        _ => (OrderDirection::Asc, rest),
+    };
+    // [NULLS, FIRST | LAST, rest]
+    let (nulos, rest) = match rest {
+        [Operador::String(nulls), Operador::String(posicion), rest @ ..] if nulls == "NULLS" => {
+            match posicion.as_str() {
+                "FIRST" => (Some(OrdenNulos::Primero), rest),
+                "LAST" => (Some(OrdenNulos::Ultimo), rest),
+                _ => {
+                    return Err(ErrorType::InvalidSyntax(
+                        "Se esperaba 'NULLS FIRST' o 'NULLS LAST' en el ORDER BY".to_string(),
+                    ))
+                }
+            }
+        }
+        [Operador::String(nulls), ..] if nulls == "NULLS" => {
+            return Err(ErrorType::InvalidSyntax(
+                "Se esperaba 'NULLS FIRST' o 'NULLS LAST' en el ORDER BY".to_string(),
+            ))
+        }
+        _ => (None, rest),
+    };
+    order_by.push(OrderClause {
+        column,
+        direccion,
+        expresion,
+        nulos,
+    });
+    order_by_rec(rest, order_by)
+}
+
 /// Procesa la lista de operadores para extraer las columnas a ordenar.
-fn order_by(rest: &[Operador]) -> Result<(Option<Vec<OrderClause>>, &[Operador]), ErrorType> {
+pub fn order_by(rest: &[Operador]) -> Result<(Option<Vec<OrderClause>>, &[Operador]), ErrorType> {
     match rest {
         [Operador::String(order), Operador::String(by), rest @ ..]
             if order == "ORDER" && by == "BY" =>
@@ -294,32 +700,50 @@ fn order_by(rest: &[Operador]) -> Result<(Option<Vec<OrderClause>>, &[Operador])
     }
 }
 
-/// PARSERS PARA CADA OPERACION
+/// Procesa la cantidad máxima de filas de [LIMIT, n, ...]
+pub fn limit(rest: &[Operador]) -> Result<(Option<usize>, &[Operador]), ErrorType> {
+    match rest {
+        [Operador::String(limit), Operador::String(cantidad), rest @ ..] if limit == "LIMIT" => {
+            match cantidad.parse::<usize>() {
+                Ok(cantidad) => Ok((Some(cantidad), rest)),
+                Err(_) => Err(ErrorType::InvalidSyntax(
+                    "El LIMIT debe ser un numero entero no negativo".to_string(),
+                )),
+            }
+        }
+        [Operador::String(limit), ..] if limit == "LIMIT" => Err(ErrorType::InvalidSyntax(
+            "Falta la cantidad despues del LIMIT".to_string(),
+        )),
+        _ => Ok((None, rest)), // ya que no es un campo obligatorio
+    }
+}
 
-/// Table, [ "colum1", "column2,", "..."], ["value1", "value2", "..."] ["value1", "value2", "..."] ... a InsertQuery
-fn parser_insert(
-    table: &String,
-    columns: &Vec<Operador>,
-    values: &[Operador],
-) -> Result<InsertQuery, ErrorType> {
-    let columns_parsed: Vec<String> = columns_to_string(columns)?;
-    if columnas_repetidas(&columns_parsed) {
-        return Err(ErrorType::InvalidSyntax(
-            "Columnas repetidas en INSERT".to_string(),
-        ));
+/// Separa la cláusula RETURNING del final de un INSERT, UPDATE o DELETE y devuelve sus columnas.
+/// [..., RETURNING, *] o [..., RETURNING, column1, column2, ...]
+pub fn returning(rest: &[Operador]) -> Result<(&[Operador], Option<Vec<String>>), ErrorType> {
+    let posicion = rest
+        .iter()
+        .position(|operador| matches!(operador, Operador::String(s) if s == "RETURNING"));
+    match posicion {
+        Some(posicion) => {
+            let columnas = columns_to_string(&rest[posicion + 1..])?;
+            if columnas.is_empty() {
+                return Err(ErrorType::InvalidSyntax(
+                    "Faltan columnas despues del RETURNING".to_string(),
+                ));
+            }
+            Ok((&rest[..posicion], Some(columnas)))
+        }
+        None => Ok((rest, None)),
     }
-    let values_parsed: Vec<HashMap<String, Datos>> = operador_to_values(values, &columns_parsed)?;
-    Ok(InsertQuery::new(
-        &table.to_string(),
-        columns_parsed,
-        values_parsed,
-    ))
 }
 
+// PARSERS PARA CADA OPERACION
+
 /// Table y [value1, = ,column1, ...] a UpdateQuery
 fn parser_update(table: &String, rest: &[Operador]) -> Result<UpdateQuery, ErrorType> {
-    let (changes, rest) = changes_rec(HashMap::new(), rest)?;
-    if changes.is_empty() {
+    let (changes, expresiones, rest) = changes_rec(HashMap::new(), HashMap::new(), rest)?;
+    if changes.is_empty() && expresiones.is_empty() {
         return Err(ErrorType::InvalidSyntax(
             "Faltan columnas y valores en la consulta UPDATE.".to_string(),
         ));
@@ -330,11 +754,9 @@ fn parser_update(table: &String, rest: &[Operador]) -> Result<UpdateQuery, Error
             "Sintaxis invalida en UPDATE".to_string(),
         ));
     }
-    Ok(UpdateQuery::new(
-        table.to_string(),
-        changes,
-        where_condition,
-    ))
+    let mut update_query = UpdateQuery::new(table.to_string(), changes, where_condition);
+    update_query.expresiones = expresiones;
+    Ok(update_query)
 }
 
 /// Table y [...] a DeleteQuery
@@ -348,16 +770,160 @@ fn parser_delete(table: &str, rest: &[Operador]) -> Result<DeleteQuery, ErrorTyp
     Ok(DeleteQuery::new(table, where_condition))
 }
 
-/// [..., FROM, tabla, WHERE, ..., ORDER, BY] a SelectQuery
-fn parser_select(rest: &[Operador]) -> Result<SelectQuery, ErrorType> {
-    let (columns, rest) = columns_select_rec(rest, Vec::new())?;
-    if columns.is_empty() {
+/// [JOIN, tabla, ON, condicion, ...] o [INNER, JOIN, tabla, ON, condicion, ...]
+/// devuelve los JOIN que siguen a la tabla del FROM, hasta el WHERE, el ORDER BY o el LIMIT
+fn joins_rec(
+    rest: &[Operador],
+    mut joins: Vec<Join>,
+) -> Result<(Vec<Join>, &[Operador]), ErrorType> {
+    let rest = match rest {
+        [Operador::String(inner), Operador::String(join), rest @ ..]
+            if inner == "INNER" && join == "JOIN" =>
+        {
+            rest
+        }
+        [Operador::String(join), rest @ ..] if join == "JOIN" => rest,
+        _ => return Ok((joins, rest)),
+    };
+    let error =
+        || ErrorType::InvalidSyntax("Se esperaba 'JOIN tabla ON condicion' en SELECT".to_string());
+    let [Operador::String(tabla) | Operador::Texto(tabla), rest @ ..] = rest else {
+        return Err(error());
+    };
+    let (alias, rest) = alias_tabla(rest)?;
+    let rest = match rest {
+        [Operador::String(on), rest @ ..] if on == "ON" => rest,
+        _ => return Err(error()),
+    };
+    let fin = rest
+        .iter()
+        .position(|operador| {
+            matches!(operador, Operador::String(s)
+                if ["JOIN", "INNER", "WHERE", "ORDER", "LIMIT"].contains(&s.as_str()))
+        })
+        .unwrap_or(rest.len());
+    if fin == 0 {
+        return Err(ErrorType::InvalidSyntax(
+            "Faltan valores despues del ON".to_string(),
+        ));
+    }
+    let mut join = Join::new(tabla, parse_condicion(&rest[..fin])?);
+    join.alias = alias;
+    joins.push(join);
+    joins_rec(&rest[fin..], joins)
+}
+
+/// [(VALUES, (fila), ...), AS, tabla, (columnas), rest] devuelve el nombre de la tabla de los VALUES y sus filas.
+/// Sin la lista de columnas, se llaman `column1`, `column2`, etc.
+fn tabla_valores<'a>(
+    lista: &[Operador],
+    rest: &'a [Operador],
+) -> Result<(String, Valores, &'a [Operador]), ErrorType> {
+    let mut filas = Vec::new();
+    for fila in &lista[1..] {
+        match fila {
+            Operador::Lista(valores) => filas.push(
+                valores
+                    .iter()
+                    .map(operador_to_dato)
+                    .collect::<Result<Vec<Datos>, ErrorType>>()?,
+            ),
+            _ => {
+                return Err(ErrorType::InvalidSyntax(
+                    "Cada fila de VALUES debe estar entre paréntesis".to_string(),
+                ))
+            }
+        }
+    }
+    let Some(cantidad) = filas.first().map(Vec::len) else {
+        return Err(ErrorType::InvalidSyntax(
+            "Se esperaba al menos una fila en VALUES".to_string(),
+        ));
+    };
+    if filas.iter().any(|fila| fila.len() != cantidad) {
+        return Err(ErrorType::InvalidSyntax(
+            "Todas las filas de VALUES deben tener la misma cantidad de valores".to_string(),
+        ));
+    }
+    let (Some(tabla), rest) = alias_tabla(rest)? else {
+        return Err(ErrorType::InvalidSyntax(
+            "Se esperaba un nombre para la tabla de VALUES, como '(VALUES ...) AS tabla'"
+                .to_string(),
+        ));
+    };
+    let (columnas, rest) = match rest {
+        [Operador::Lista(columnas), rest @ ..] => (columns_to_string(columnas)?, rest),
+        _ => (
+            (1..=cantidad).map(|i| format!("column{}", i)).collect(),
+            rest,
+        ),
+    };
+    if columnas.len() != cantidad {
+        return Err(ErrorType::InvalidSyntax(format!(
+            "La tabla '{}' de VALUES tiene {} columnas pero sus filas tienen {} valores",
+            tabla,
+            columnas.len(),
+            cantidad
+        )));
+    }
+    if columnas_repetidas(&columnas) {
+        return Err(ErrorType::InvalidSyntax(format!(
+            "La tabla '{}' de VALUES tiene columnas repetidas",
+            tabla
+        )));
+    }
+    Ok((tabla, Valores::new(columnas, filas), rest))
+}
+
+/// [..., FROM, tabla, JOIN, ..., WHERE, ..., ORDER, BY] a SelectQuery.
+/// La tabla puede ser una lista de VALUES con nombre, y sin FROM la consulta lee una única fila sin columnas.
+pub fn parser_select(rest: &[Operador]) -> Result<SelectQuery, ErrorType> {
+    let tiene_from = rest
+        .iter()
+        .any(|operador| matches!(operador, Operador::String(from) if from == "FROM"));
+    let (distinct, rest) = match rest {
+        [Operador::String(distinct), rest @ ..] if distinct == "DISTINCT" => (true, rest),
+        _ => (false, rest),
+    };
+    let (columns, alias_columnas, conteos, calculadas, ventanas, rest) = columns_select_rec(
+        rest,
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+        Vec::new(),
+    )?;
+    if columns.is_empty() && conteos.is_empty() {
         return Err(ErrorType::InvalidSyntax(
             "Sin columnas seleccioanadas en SELECT".to_string(),
         ));
     }
-    let (table, rest) = match rest {
-        [Operador::String(table) | Operador::Texto(table), rest @ ..] => (table, rest),
+    if !columns.is_empty() && !conteos.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "No se pueden seleccionar columnas junto con COUNT".to_string(),
+        ));
+    }
+    let (table, valores, alias, joins, rest) = match rest {
+        _ if !tiene_from => {
+            if columns.iter().any(|c| c == "*") {
+                return Err(ErrorType::InvalidSyntax(
+                    "No se puede seleccionar '*' en un SELECT sin FROM".to_string(),
+                ));
+            }
+            let valores = Some(Valores::sin_tabla());
+            (String::new(), valores, None, Vec::new(), rest)
+        }
+        [Operador::Lista(lista), rest @ ..] if matches!(lista.first(), Some(Operador::String(v)) if v == "VALUES") =>
+        {
+            let (table, valores, rest) = tabla_valores(lista, rest)?;
+            let (joins, rest) = joins_rec(rest, Vec::new())?;
+            (table, Some(valores), None, joins, rest)
+        }
+        [Operador::String(table) | Operador::Texto(table), rest @ ..] => {
+            let (alias, rest) = alias_tabla(rest)?;
+            let (joins, rest) = joins_rec(rest, Vec::new())?;
+            (table.to_string(), None, alias, joins, rest)
+        }
         _ => {
             return Err(ErrorType::InvalidSyntax(
                 "Se esperaba el nombre de la tabla en SELECT".to_string(),
@@ -366,42 +932,32 @@ fn parser_select(rest: &[Operador]) -> Result<SelectQuery, ErrorType> {
     };
     let (where_condition, rest) = where_clause(rest)?;
     let (order_by, rest) = order_by(rest)?;
+    let (limit, rest) = limit(rest)?;
     if !rest.is_empty() {
         return Err(ErrorType::InvalidSyntax(
             "Sintaxis invalida en SELECT".to_string(),
         ));
     }
-    Ok(SelectQuery::new(
-        columns,
-        table.to_string(),
-        where_condition,
-        order_by,
-    ))
-}
-
-/// Procesar INSERT
-fn parse_insert_query(
-    table: &String,
-    columns: &Vec<Operador>,
-    values: &Operador,
-    rest: &[Operador],
-) -> Result<SQLQuery, ErrorType> {
-    match values {
-        Operador::String(value) if value == "VALUES" => {
-            let insert_query = parser_insert(table, columns, rest)?;
-            Ok(SQLQuery::Insert(insert_query))
-        }
-        _ => Err(ErrorType::InvalidSyntax(
-            "Falta 'VALUES' en la consulta INSERT.".to_string(),
-        )),
-    }
+    let mut select_query = SelectQuery::new(columns, table, where_condition, order_by);
+    select_query.valores = valores;
+    select_query.alias_columnas = alias_columnas;
+    select_query.alias = alias;
+    select_query.joins = joins;
+    select_query.distinct = distinct;
+    select_query.conteos = conteos;
+    select_query.calculadas = calculadas;
+    select_query.ventanas = ventanas;
+    select_query.limit = limit;
+    Ok(select_query)
 }
 
 /// Procesar UPDATE
 fn parse_update_query(table: &String, rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
     match rest.first() {
         Some(Operador::String(set)) if set == "SET" => {
-            let update_query = parser_update(table, &rest[1..])?;
+            let (rest, returning) = returning(&rest[1..])?;
+            let mut update_query = parser_update(table, rest)?;
+            update_query.returning = returning;
             Ok(SQLQuery::Update(update_query))
         }
         _ => Err(ErrorType::InvalidSyntax(
@@ -412,24 +968,27 @@ fn parse_update_query(table: &String, rest: &[Operador]) -> Result<SQLQuery, Err
 
 /// Procesar DELETE
 fn parse_delete_query(table: &str, rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
-    let delete_query = parser_delete(table, rest)?;
+    let (rest, returning) = returning(rest)?;
+    let mut delete_query = parser_delete(table, rest)?;
+    delete_query.returning = returning;
     Ok(SQLQuery::Delete(delete_query))
 }
 
-/// Procesar SELECT
-fn parse_select_query(rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
-    let select_query = parser_select(rest)?;
-    Ok(SQLQuery::Select(select_query))
-}
-
 /// Recibe un string e intenta matchearlo con una Query valida, sino devuelve el error
-pub fn parser(query: &Vec<Operador>) -> Result<SQLQuery, ErrorType> {
+pub fn parser(query: &[Operador]) -> Result<SQLQuery, ErrorType> {
+    // [EXPLAIN, ANALYZE, SELECT, ...]
+    if let [Operador::String(explain), rest @ ..] = query {
+        if explain == "EXPLAIN" {
+            return parse_explain_query(rest);
+        }
+    }
+    let query = &agrupar_case(query)?;
     match query.as_slice() {
-        // [INSERT, INTO, tabla, columns, VALUES, values1, values2 ...]
-        [Operador::String(insert), Operador::String(into), Operador::String(table) | Operador::Texto(table), Operador::Lista(columns), values, rest @ ..]
+        // [INSERT, INTO, tabla, columns, VALUES, values1, values2 ...] o [INSERT, INTO, tabla, columns, SELECT, ...]
+        [Operador::String(insert), Operador::String(into), Operador::String(table) | Operador::Texto(table), rest @ ..]
             if insert == "INSERT" && into == "INTO" =>
         {
-            parse_insert_query(table, columns, values, rest)
+            parse_insert_query(table, rest)
         }
         // [UPDATE, tabla, SET, column1, valor1, column1, valor1, ..., WHERE, ...]
         [Operador::String(update), Operador::String(table), rest @ ..] if update == "UPDATE" => {
@@ -442,7 +1001,56 @@ pub fn parser(query: &Vec<Operador>) -> Result<SQLQuery, ErrorType> {
             parse_delete_query(table, rest)
         }
         // [SELECT, ..., FROM, tabla, WHERE, ..., ORDER, BY, ...]
-        [Operador::String(select), rest @ ..] if select == "SELECT" => parse_select_query(rest),
+        [Operador::String(select), ..] if select == "SELECT" => parse_select_query(query),
+        // [(SELECT, ...), UNION, ...]
+        [Operador::Lista(lista), ..] if es_subconsulta(lista) => parse_select_query(query),
+        // [WITH, RECURSIVE, nombre, AS, (SELECT, ...), ..., SELECT, ..., OPTION, (MAXRECURSION, n)]
+        [Operador::String(with), rest @ ..] if with == "WITH" => parse_with_query(rest),
+        // [CREATE, TABLE, tabla, definiciones]
+        [Operador::String(create), Operador::String(table_str), Operador::String(table) | Operador::Texto(table), Operador::Lista(definiciones), rest @ ..]
+            if create == "CREATE" && table_str == "TABLE" =>
+        {
+            parse_create_query(table, definiciones, rest)
+        }
+        // [CREATE, VIEW, nombre, AS, SELECT, ...]
+        [Operador::String(create), Operador::String(view), Operador::String(nombre) | Operador::Texto(nombre), Operador::String(as_str), rest @ ..]
+            if create == "CREATE" && view == "VIEW" && as_str == "AS" =>
+        {
+            parse_create_view_query(nombre, rest)
+        }
+        // [DROP, VIEW, nombre]
+        [Operador::String(drop), Operador::String(view), Operador::String(nombre) | Operador::Texto(nombre)]
+            if drop == "DROP" && view == "VIEW" =>
+        {
+            Ok(SQLQuery::DropView(DropViewQuery::new(nombre, false)))
+        }
+        // [CREATE, MATERIALIZED, VIEW, nombre, AUTO, REFRESH, AS, SELECT, ...]
+        [Operador::String(create), Operador::String(materialized), Operador::String(view), Operador::String(nombre) | Operador::Texto(nombre), rest @ ..]
+            if create == "CREATE" && materialized == "MATERIALIZED" && view == "VIEW" =>
+        {
+            parse_create_materialized_view_query(nombre, rest)
+        }
+        // [REFRESH, MATERIALIZED, VIEW, nombre]
+        [Operador::String(refresh), Operador::String(materialized), Operador::String(view), Operador::String(nombre) | Operador::Texto(nombre)]
+            if refresh == "REFRESH" && materialized == "MATERIALIZED" && view == "VIEW" =>
+        {
+            Ok(SQLQuery::Refresh(RefreshQuery::new(nombre)))
+        }
+        // [DROP, MATERIALIZED, VIEW, nombre]
+        [Operador::String(drop), Operador::String(materialized), Operador::String(view), Operador::String(nombre) | Operador::Texto(nombre)]
+            if drop == "DROP" && materialized == "MATERIALIZED" && view == "VIEW" =>
+        {
+            Ok(SQLQuery::DropView(DropViewQuery::new(nombre, true)))
+        }
+        // [ANALYZE] o [ANALYZE, tabla]
+        [Operador::String(analyze)] if analyze == "ANALYZE" => {
+            Ok(SQLQuery::Analyze(AnalyzeQuery::new(None)))
+        }
+        [Operador::String(analyze), Operador::String(tabla) | Operador::Texto(tabla)]
+            if analyze == "ANALYZE" =>
+        {
+            Ok(SQLQuery::Analyze(AnalyzeQuery::new(Some(tabla))))
+        }
         _ => Err(ErrorType::InvalidSyntax("Query invalida".to_string())),
     }
 }
@@ -454,18 +1062,41 @@ mod tests {
     use super::parser;
     use crate::{
         dato::Datos,
+        esquema::{
+            check::Check,
+            columna::{ColumnaDef, TipoDato},
+            esquema_tabla::Esquema,
+            foreign_key::{AccionReferencial, ForeignKey},
+        },
         lexers::operador::Operador,
         queries::{
+            analyze_query::AnalyzeQuery,
+            compound_query::{CompoundQuery, Conjunto, OperadorConjunto},
+            conteo::Conteo,
+            create_materialized_view_query::CreateMaterializedViewQuery,
+            create_query::CreateQuery,
+            create_view_query::CreateViewQuery,
             delete_query::DeleteQuery,
-            insert_query::InsertQuery,
-            order_clause::{OrderClause, OrderDirection},
+            drop_view_query::DropViewQuery,
+            explain_query::ExplainQuery,
+            insert_query::{InsertQuery, ParametroValor},
+            join::Join,
+            on_conflict::{AccionConflicto, OnConflict},
+            order_clause::{OrdenNulos, OrderClause, OrderDirection},
+            refresh_query::RefreshQuery,
             select_query::SelectQuery,
             sql_query::SQLQuery,
             update_query::UpdateQuery,
+            valores::Valores,
+            ventana::{FuncionVentana, LimiteMarco, Ventana},
             where_clause::{
-                expresion_booleana::ExpresionBooleana, operador_comparacion::OperadorComparacion,
-                valor::Valor,
+                case::Case,
+                expresion_booleana::ExpresionBooleana,
+                operador_comparacion::OperadorComparacion,
+                subconsulta::Subconsulta,
+                valor::{OperadorAritmetico, Valor},
             },
+            with_query::{Consulta, Cte, WithQuery},
         },
     };
 
@@ -480,8 +1111,7 @@ mod tests {
             ),
             Err(e) => println!(
                 "Parser devolvió un error inesperado: {} para el caso: {:?}",
-                e.to_string(),
-                caso
+                e, caso
             ),
         }
     }
@@ -495,7 +1125,7 @@ mod tests {
                 e.to_string().contains(mensaje_error_esperado),
                 "Se esperaba un error que contenga '{}', pero se obtuvo: '{}' para el caso: {:?}",
                 mensaje_error_esperado,
-                e.to_string(),
+                e,
                 caso
             ),
         }
@@ -517,14 +1147,16 @@ mod tests {
                 Operador::Texto("Ivan".to_string()),
             ]),
         ];
-        let mut values_map = HashMap::new();
-        values_map.insert("id".to_string(), Datos::Integer(1));
-        values_map.insert("name".to_string(), Datos::String("Ivan".to_string()));
+        let values = vec![Datos::Integer(1), Datos::String("Ivan".to_string())];
 
         let esperado = SQLQuery::Insert(InsertQuery {
             table: "users".to_string(),
             columns: vec!["id".to_string(), "name".to_string()],
-            values: vec![values_map],
+            values: vec![values],
+            select: None,
+            on_conflict: None,
+            returning: None,
+            parametros: Vec::new(),
         });
         probar_parser_exitoso(&input, esperado);
         println!("Parser insert simple ejecutado correctamente!");
@@ -554,21 +1186,20 @@ mod tests {
                 )])]),
             ]),
         ];
-        let mut values1 = HashMap::new();
-        values1.insert("id".to_string(), Datos::Integer(1));
-        values1.insert(
-            "name".to_string(),
+        let values1 = vec![
+            Datos::Integer(1),
             Datos::String("Ivan Maximoff".to_string()),
-        );
-
-        let mut values2 = HashMap::new();
-        values2.insert("id".to_string(), Datos::Integer(2));
-        values2.insert("name".to_string(), Datos::String("Alexis".to_string()));
+        ];
+        let values2 = vec![Datos::Integer(2), Datos::String("Alexis".to_string())];
 
         let esperado = SQLQuery::Insert(InsertQuery {
             table: "users".to_string(),
             columns: vec!["id".to_string(), "name".to_string()],
             values: vec![values1, values2],
+            select: None,
+            on_conflict: None,
+            returning: None,
+            parametros: Vec::new(),
         });
 
         probar_parser_exitoso(&input, esperado);
@@ -590,14 +1221,23 @@ mod tests {
         ];
 
         let esperado = SQLQuery::Select(SelectQuery {
+            distinct: false,
             columns_select: vec!["id".to_string(), "name".to_string()],
+            alias_columnas: vec![None, None],
+            conteos: Vec::new(),
+            calculadas: Vec::new(),
+            ventanas: Vec::new(),
             table: "users".to_string(),
+            valores: None,
+            alias: None,
+            joins: Vec::new(),
             where_clause: Some(ExpresionBooleana::Comparacion {
                 izq: Valor::String("age".to_string()),
                 operador: OperadorComparacion::Mayor,
                 der: Valor::String("30".to_string()),
             }),
             order_by: None,
+            limit: None,
         });
 
         probar_parser_exitoso(&input, esperado);
@@ -631,8 +1271,16 @@ mod tests {
         ];
 
         let esperado = SQLQuery::Select(SelectQuery {
+            distinct: false,
             columns_select: vec!["id".to_string(), "name".to_string()],
+            alias_columnas: vec![None, None],
+            conteos: Vec::new(),
+            calculadas: Vec::new(),
+            ventanas: Vec::new(),
             table: "users".to_string(),
+            valores: None,
+            alias: None,
+            joins: Vec::new(),
             where_clause: Some(ExpresionBooleana::And(
                 Box::new(ExpresionBooleana::Comparacion {
                     izq: Valor::Literal("age".to_string()),
@@ -648,7 +1296,10 @@ mod tests {
             order_by: Some(vec![OrderClause {
                 column: "name".to_string(),
                 direccion: OrderDirection::Desc,
+                expresion: None,
+                nulos: None,
             }]),
+            limit: None,
         });
 
         probar_parser_exitoso(&input, esperado);
@@ -682,7 +1333,9 @@ mod tests {
         let esperado = SQLQuery::Update(UpdateQuery {
             table: "users".to_string(),
             changes,
+            expresiones: HashMap::new(),
             where_condition,
+            returning: None,
         });
         probar_parser_exitoso(&input, esperado);
         println!("Parser update simple ejecutado correctamente!");
@@ -730,7 +1383,9 @@ mod tests {
         let esperado = SQLQuery::Update(UpdateQuery {
             table: "users".to_string(),
             changes,
+            expresiones: HashMap::new(),
             where_condition,
+            returning: None,
         });
         probar_parser_exitoso(&input, esperado);
         println!("Parser update complejo ejecutado correctamente!");
@@ -755,6 +1410,7 @@ mod tests {
         let esperado = SQLQuery::Delete(DeleteQuery {
             table: "users".to_string(),
             where_clause,
+            returning: None,
         });
         probar_parser_exitoso(&input, esperado);
     }
@@ -802,6 +1458,7 @@ mod tests {
         let esperado = SQLQuery::Delete(DeleteQuery {
             table: "users".to_string(),
             where_clause,
+            returning: None,
         });
         probar_parser_exitoso(&input, esperado);
     }
@@ -834,4 +1491,1242 @@ mod tests {
         let error = "Falta 'SET' en la consulta UPDATE.".to_string();
         probar_parser_error(&input, &error);
     }
+
+    #[test]
+    fn test_parser_create_table() {
+        let input = vec![
+            Operador::String("CREATE".to_string()),
+            Operador::String("TABLE".to_string()),
+            Operador::String("users".to_string()),
+            Operador::Lista(vec![
+                Operador::String("id".to_string()),
+                Operador::String("INT".to_string()),
+                Operador::String("PRIMARY".to_string()),
+                Operador::String("KEY".to_string()),
+                Operador::String("email".to_string()),
+                Operador::String("VARCHAR".to_string()),
+                Operador::Lista(vec![Operador::String("50".to_string())]),
+                Operador::String("UNIQUE".to_string()),
+                Operador::String("name".to_string()),
+                Operador::String("UNIQUE".to_string()),
+                Operador::Lista(vec![
+                    Operador::String("name".to_string()),
+                    Operador::String("email".to_string()),
+                ]),
+            ]),
+        ];
+        let esquema = Esquema {
+            tabla: "users".to_string(),
+            columnas: vec![
+                ColumnaDef::new("id", Some(TipoDato::Integer)),
+                ColumnaDef::new("email", Some(TipoDato::String)),
+                ColumnaDef::new("name", None),
+            ],
+            primary_key: vec!["id".to_string()],
+            unique: vec![
+                vec!["email".to_string()],
+                vec!["name".to_string(), "email".to_string()],
+            ],
+            foreign_keys: vec![],
+            checks: vec![],
+        };
+        let esperado = SQLQuery::Create(CreateQuery { esquema });
+        probar_parser_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_parser_error_create_dos_primary_key() {
+        let input = vec![
+            Operador::String("CREATE".to_string()),
+            Operador::String("TABLE".to_string()),
+            Operador::String("users".to_string()),
+            Operador::Lista(vec![
+                Operador::String("id".to_string()),
+                Operador::String("PRIMARY".to_string()),
+                Operador::String("KEY".to_string()),
+                Operador::String("PRIMARY".to_string()),
+                Operador::String("KEY".to_string()),
+                Operador::Lista(vec![Operador::String("id".to_string())]),
+            ]),
+        ];
+        let error = "Se declaró más de una PRIMARY KEY en CREATE TABLE";
+        probar_parser_error(&input, error);
+    }
+
+    #[test]
+    fn test_parser_create_table_foreign_key() {
+        let input = vec![
+            Operador::String("CREATE".to_string()),
+            Operador::String("TABLE".to_string()),
+            Operador::String("ordenes".to_string()),
+            Operador::Lista(vec![
+                Operador::String("id".to_string()),
+                Operador::String("INT".to_string()),
+                Operador::String("id_cliente".to_string()),
+                Operador::String("INT".to_string()),
+                Operador::String("REFERENCES".to_string()),
+                Operador::String("clientes".to_string()),
+                Operador::Lista(vec![Operador::String("id".to_string())]),
+                Operador::String("ON".to_string()),
+                Operador::String("DELETE".to_string()),
+                Operador::String("SET".to_string()),
+                Operador::String("NULL".to_string()),
+                Operador::String("FOREIGN".to_string()),
+                Operador::String("KEY".to_string()),
+                Operador::Lista(vec![Operador::String("id".to_string())]),
+                Operador::String("REFERENCES".to_string()),
+                Operador::String("facturas".to_string()),
+                Operador::Lista(vec![Operador::String("id_orden".to_string())]),
+            ]),
+        ];
+        let mut esquema = Esquema::new("ordenes");
+        esquema.columnas = vec![
+            ColumnaDef::new("id", Some(TipoDato::Integer)),
+            ColumnaDef::new("id_cliente", Some(TipoDato::Integer)),
+        ];
+        esquema.foreign_keys = vec![
+            ForeignKey::new(
+                vec!["id_cliente".to_string()],
+                "clientes",
+                vec!["id".to_string()],
+                AccionReferencial::SetNull,
+            ),
+            ForeignKey::new(
+                vec!["id".to_string()],
+                "facturas",
+                vec!["id_orden".to_string()],
+                AccionReferencial::Restrict,
+            ),
+        ];
+        let esperado = SQLQuery::Create(CreateQuery { esquema });
+        probar_parser_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_parser_create_table_default_not_null_autoincrement() {
+        let input = vec![
+            Operador::String("CREATE".to_string()),
+            Operador::String("TABLE".to_string()),
+            Operador::String("ordenes".to_string()),
+            Operador::Lista(vec![
+                Operador::String("id".to_string()),
+                Operador::String("INT".to_string()),
+                Operador::String("AUTOINCREMENT".to_string()),
+                Operador::String("producto".to_string()),
+                Operador::String("TEXT".to_string()),
+                Operador::String("NOT".to_string()),
+                Operador::String("NULL".to_string()),
+                Operador::String("DEFAULT".to_string()),
+                Operador::Texto("ninguno".to_string()),
+                Operador::String("fecha".to_string()),
+                Operador::String("DATE".to_string()),
+                Operador::String("DEFAULT".to_string()),
+                Operador::String("CURRENT_DATE".to_string()),
+            ]),
+        ];
+        let mut id = ColumnaDef::new("id", Some(TipoDato::Integer));
+        id.autoincrement = true;
+        let mut producto = ColumnaDef::new("producto", Some(TipoDato::String));
+        producto.not_null = true;
+        producto.default = Some(Valor::Literal("ninguno".to_string()));
+        let mut fecha = ColumnaDef::new("fecha", Some(TipoDato::String));
+        fecha.default = Some(Valor::Funcion {
+            nombre: "CURRENT_DATE".to_string(),
+            argumentos: vec![],
+        });
+        let mut esquema = Esquema::new("ordenes");
+        esquema.columnas = vec![id, producto, fecha];
+        let esperado = SQLQuery::Create(CreateQuery { esquema });
+        probar_parser_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_parser_create_table_check() {
+        let input = vec![
+            Operador::String("CREATE".to_string()),
+            Operador::String("TABLE".to_string()),
+            Operador::String("ordenes".to_string()),
+            Operador::Lista(vec![
+                Operador::String("cantidad".to_string()),
+                Operador::String("INT".to_string()),
+                Operador::String("CHECK".to_string()),
+                Operador::Lista(vec![
+                    Operador::String("cantidad".to_string()),
+                    Operador::Comparador(">".to_string()),
+                    Operador::String("0".to_string()),
+                ]),
+                Operador::String("CONSTRAINT".to_string()),
+                Operador::String("cantidad_maxima".to_string()),
+                Operador::String("CHECK".to_string()),
+                Operador::Lista(vec![
+                    Operador::String("NOT".to_string()),
+                    Operador::String("cantidad".to_string()),
+                    Operador::Comparador(">".to_string()),
+                    Operador::String("10".to_string()),
+                ]),
+            ]),
+        ];
+        let mut esquema = Esquema::new("ordenes");
+        esquema.columnas = vec![ColumnaDef::new("cantidad", Some(TipoDato::Integer))];
+        esquema.checks = vec![
+            Check::new(
+                "ordenes_check1",
+                ExpresionBooleana::Comparacion {
+                    izq: Valor::String("cantidad".to_string()),
+                    operador: OperadorComparacion::Mayor,
+                    der: Valor::String("0".to_string()),
+                },
+            ),
+            Check::new(
+                "cantidad_maxima",
+                ExpresionBooleana::Not(Box::new(ExpresionBooleana::Comparacion {
+                    izq: Valor::String("cantidad".to_string()),
+                    operador: OperadorComparacion::Mayor,
+                    der: Valor::String("10".to_string()),
+                })),
+            ),
+        ];
+        let esperado = SQLQuery::Create(CreateQuery { esquema });
+        probar_parser_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_parser_insert_select_sin_columnas() {
+        let input = vec![
+            Operador::String("INSERT".to_string()),
+            Operador::String("INTO".to_string()),
+            Operador::String("archivo".to_string()),
+            Operador::String("SELECT".to_string()),
+            Operador::String("*".to_string()),
+            Operador::String("FROM".to_string()),
+            Operador::String("users".to_string()),
+        ];
+        let select = SelectQuery::new(vec!["*".to_string()], "users".to_string(), None, None);
+        let esperado = SQLQuery::Insert(InsertQuery::new_select("archivo", vec![], select));
+        probar_parser_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_parser_delete_returning() {
+        let input = vec![
+            Operador::String("DELETE".to_string()),
+            Operador::String("FROM".to_string()),
+            Operador::String("users".to_string()),
+            Operador::String("WHERE".to_string()),
+            Operador::String("id".to_string()),
+            Operador::Comparador("=".to_string()),
+            Operador::String("1".to_string()),
+            Operador::String("RETURNING".to_string()),
+            Operador::String("id".to_string()),
+            Operador::String("name".to_string()),
+        ];
+        let esperado = SQLQuery::Delete(DeleteQuery {
+            table: "users".to_string(),
+            where_clause: Some(ExpresionBooleana::Comparacion {
+                izq: Valor::String("id".to_string()),
+                operador: OperadorComparacion::Igual,
+                der: Valor::String("1".to_string()),
+            }),
+            returning: Some(vec!["id".to_string(), "name".to_string()]),
+        });
+        probar_parser_exitoso(&input, esperado);
+
+        let input = vec![
+            Operador::String("DELETE".to_string()),
+            Operador::String("FROM".to_string()),
+            Operador::String("users".to_string()),
+            Operador::String("RETURNING".to_string()),
+        ];
+        probar_parser_error(&input, "Faltan columnas despues del RETURNING");
+    }
+
+    #[test]
+    fn test_parser_insert_on_conflict() {
+        let input = vec![
+            Operador::String("INSERT".to_string()),
+            Operador::String("INTO".to_string()),
+            Operador::String("users".to_string()),
+            Operador::String("VALUES".to_string()),
+            Operador::Lista(vec![
+                Operador::String("1".to_string()),
+                Operador::Texto("Ana".to_string()),
+            ]),
+            Operador::String("ON".to_string()),
+            Operador::String("CONFLICT".to_string()),
+            Operador::Lista(vec![Operador::String("id".to_string())]),
+            Operador::String("DO".to_string()),
+            Operador::String("UPDATE".to_string()),
+            Operador::String("SET".to_string()),
+            Operador::String("name".to_string()),
+            Operador::Comparador("=".to_string()),
+            Operador::String("excluded.name".to_string()),
+        ];
+        let mut insert_query = InsertQuery::new(
+            "users",
+            vec![],
+            vec![vec![Datos::Integer(1), Datos::String("Ana".to_string())]],
+        );
+        insert_query.on_conflict = Some(OnConflict::new(
+            vec!["id".to_string()],
+            AccionConflicto::Actualizar(HashMap::from([(
+                "name".to_string(),
+                Valor::String("excluded.name".to_string()),
+            )])),
+        ));
+        probar_parser_exitoso(&input, SQLQuery::Insert(insert_query));
+
+        let input = vec![
+            Operador::String("INSERT".to_string()),
+            Operador::String("INTO".to_string()),
+            Operador::String("users".to_string()),
+            Operador::String("VALUES".to_string()),
+            Operador::Lista(vec![Operador::String("1".to_string())]),
+            Operador::String("ON".to_string()),
+            Operador::String("CONFLICT".to_string()),
+            Operador::String("DO".to_string()),
+            Operador::String("NOTHING".to_string()),
+        ];
+        probar_parser_error(
+            &input,
+            "Sintaxis invalida en ON CONFLICT, se esperaba '(columnas) DO NOTHING' o '(columnas) DO UPDATE SET ...'",
+        );
+    }
+
+    #[test]
+    fn test_parser_select_subconsultas() {
+        let input = vec![
+            Operador::String("SELECT".to_string()),
+            Operador::String("name".to_string()),
+            Operador::String("FROM".to_string()),
+            Operador::String("users".to_string()),
+            Operador::String("WHERE".to_string()),
+            Operador::String("id".to_string()),
+            Operador::String("IN".to_string()),
+            Operador::Lista(vec![
+                Operador::String("SELECT".to_string()),
+                Operador::String("user_id".to_string()),
+                Operador::String("FROM".to_string()),
+                Operador::String("orders".to_string()),
+                Operador::String("WHERE".to_string()),
+                Operador::String("total".to_string()),
+                Operador::Comparador(">".to_string()),
+                Operador::String("10".to_string()),
+                Operador::String("OR".to_string()),
+                Operador::String("total".to_string()),
+                Operador::Comparador("=".to_string()),
+                Operador::String("0".to_string()),
+            ]),
+            Operador::String("AND".to_string()),
+            Operador::String("NOT".to_string()),
+            Operador::String("EXISTS".to_string()),
+            Operador::Lista(vec![
+                Operador::String("SELECT".to_string()),
+                Operador::String("*".to_string()),
+                Operador::String("FROM".to_string()),
+                Operador::String("bans".to_string()),
+            ]),
+        ];
+        let ordenes = SelectQuery::new(
+            vec!["user_id".to_string()],
+            "orders".to_string(),
+            Some(ExpresionBooleana::Or(
+                Box::new(ExpresionBooleana::Comparacion {
+                    izq: Valor::String("total".to_string()),
+                    operador: OperadorComparacion::Mayor,
+                    der: Valor::String("10".to_string()),
+                }),
+                Box::new(ExpresionBooleana::Comparacion {
+                    izq: Valor::String("total".to_string()),
+                    operador: OperadorComparacion::Igual,
+                    der: Valor::String("0".to_string()),
+                }),
+            )),
+            None,
+        );
+        let bans = SelectQuery::new(vec!["*".to_string()], "bans".to_string(), None, None);
+        let esperado = SQLQuery::Select(SelectQuery::new(
+            vec!["name".to_string()],
+            "users".to_string(),
+            Some(ExpresionBooleana::And(
+                Box::new(ExpresionBooleana::In {
+                    valor: Valor::String("id".to_string()),
+                    subconsulta: Subconsulta::Consulta(Box::new(ordenes)),
+                }),
+                Box::new(ExpresionBooleana::Not(Box::new(ExpresionBooleana::Exists(
+                    Subconsulta::Consulta(Box::new(bans)),
+                )))),
+            )),
+            None,
+        ));
+        probar_parser_exitoso(&input, esperado);
+    }
+
+    #[test]
+    fn test_parser_union_intersect() {
+        let select = |tabla: &str| {
+            vec![
+                Operador::String("SELECT".to_string()),
+                Operador::String("id".to_string()),
+                Operador::String("FROM".to_string()),
+                Operador::String(tabla.to_string()),
+            ]
+        };
+        let mut input = select("a");
+        input.push(Operador::String("UNION".to_string()));
+        input.push(Operador::String("ALL".to_string()));
+        input.extend(select("b"));
+        input.push(Operador::String("INTERSECT".to_string()));
+        input.extend(select("c"));
+        input.extend(vec![
+            Operador::String("ORDER".to_string()),
+            Operador::String("BY".to_string()),
+            Operador::String("id".to_string()),
+            Operador::String("LIMIT".to_string()),
+            Operador::String("5".to_string()),
+        ]);
+        let conjunto = |tabla: &str| {
+            Box::new(Conjunto::Select(Box::new(SelectQuery::new(
+                vec!["id".to_string()],
+                tabla.to_string(),
+                None,
+                None,
+            ))))
+        };
+        let esperado = SQLQuery::Compound(CompoundQuery::new(
+            Conjunto::Operacion {
+                izq: conjunto("a"),
+                operador: OperadorConjunto::UnionAll,
+                der: Box::new(Conjunto::Operacion {
+                    izq: conjunto("b"),
+                    operador: OperadorConjunto::Intersect,
+                    der: conjunto("c"),
+                }),
+            },
+            Some(vec![OrderClause {
+                column: "id".to_string(),
+                direccion: OrderDirection::Asc,
+                expresion: None,
+                nulos: None,
+            }]),
+            Some(5),
+        ));
+        probar_parser_exitoso(&input, esperado);
+
+        let mut input = select("a");
+        input.push(Operador::String("EXCEPT".to_string()));
+        probar_parser_error(&input, "Se esperaba una consulta SELECT en la combinación");
+    }
+
+    #[test]
+    fn test_parser_select_distinct_count() {
+        let input = vec![
+            Operador::String("SELECT".to_string()),
+            Operador::String("DISTINCT".to_string()),
+            Operador::String("id_cliente".to_string()),
+            Operador::String("FROM".to_string()),
+            Operador::String("ordenes".to_string()),
+        ];
+        let mut select = SelectQuery::new(
+            vec!["id_cliente".to_string()],
+            "ordenes".to_string(),
+            None,
+            None,
+        );
+        select.distinct = true;
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![
+            Operador::String("SELECT".to_string()),
+            Operador::String("COUNT".to_string()),
+            Operador::Lista(vec![Operador::String("*".to_string())]),
+            Operador::String("COUNT".to_string()),
+            Operador::Lista(vec![
+                Operador::String("DISTINCT".to_string()),
+                Operador::String("id_cliente".to_string()),
+            ]),
+            Operador::String("FROM".to_string()),
+            Operador::String("ordenes".to_string()),
+        ];
+        let mut select = SelectQuery::new(Vec::new(), "ordenes".to_string(), None, None);
+        select.conteos = vec![
+            Conteo::new(None, false),
+            Conteo::new(Some("id_cliente".to_string()), true),
+        ];
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![
+            Operador::String("SELECT".to_string()),
+            Operador::String("id".to_string()),
+            Operador::String("COUNT".to_string()),
+            Operador::Lista(vec![Operador::String("*".to_string())]),
+            Operador::String("FROM".to_string()),
+            Operador::String("ordenes".to_string()),
+        ];
+        probar_parser_error(&input, "No se pueden seleccionar columnas junto con COUNT");
+
+        let input = vec![
+            Operador::String("SELECT".to_string()),
+            Operador::String("COUNT".to_string()),
+            Operador::Lista(vec![Operador::String("DISTINCT".to_string())]),
+            Operador::String("FROM".to_string()),
+            Operador::String("ordenes".to_string()),
+        ];
+        probar_parser_error(&input, "Argumentos invalidos en COUNT");
+    }
+
+    #[test]
+    fn test_parser_case() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let t = |s: &str| Operador::Texto(s.to_string());
+        let case_buscado = vec![
+            s("CASE"),
+            s("WHEN"),
+            s("cantidad"),
+            Operador::Comparador(">".to_string()),
+            s("10"),
+            s("THEN"),
+            t("bulk"),
+            s("ELSE"),
+            t("retail"),
+            s("END"),
+        ];
+        let mut input = vec![s("SELECT"), s("id")];
+        input.extend(case_buscado.clone());
+        input.extend(vec![s("FROM"), s("ordenes"), s("WHERE")]);
+        input.extend(case_buscado);
+        input.extend(vec![Operador::Comparador("=".to_string()), t("bulk")]);
+        input.extend(vec![s("ORDER"), s("BY"), s("CASE"), s("id_cliente")]);
+        input.extend(vec![
+            s("WHEN"),
+            s("1"),
+            s("THEN"),
+            s("0"),
+            s("END"),
+            s("DESC"),
+        ]);
+
+        let buscado = Valor::Case(Box::new(Case::new(
+            vec![(
+                ExpresionBooleana::Comparacion {
+                    izq: Valor::String("cantidad".to_string()),
+                    operador: OperadorComparacion::Mayor,
+                    der: Valor::String("10".to_string()),
+                },
+                Valor::Literal("bulk".to_string()),
+            )],
+            Some(Valor::Literal("retail".to_string())),
+        )));
+        let simple = Valor::Case(Box::new(Case::new(
+            vec![(
+                ExpresionBooleana::Comparacion {
+                    izq: Valor::String("id_cliente".to_string()),
+                    operador: OperadorComparacion::Igual,
+                    der: Valor::String("1".to_string()),
+                },
+                Valor::String("0".to_string()),
+            )],
+            None,
+        )));
+        let nombre = "CASE WHEN cantidad > 10 THEN 'bulk' ELSE 'retail' END".to_string();
+        assert_eq!(buscado.to_string(), nombre);
+        let mut select = SelectQuery::new(
+            vec!["id".to_string(), nombre],
+            "ordenes".to_string(),
+            Some(ExpresionBooleana::Comparacion {
+                izq: buscado.clone(),
+                operador: OperadorComparacion::Igual,
+                der: Valor::Literal("bulk".to_string()),
+            }),
+            Some(vec![OrderClause {
+                column: simple.to_string(),
+                direccion: OrderDirection::Desc,
+                expresion: Some(simple),
+                nulos: None,
+            }]),
+        );
+        select.calculadas = vec![buscado];
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![
+            s("UPDATE"),
+            s("ordenes"),
+            s("SET"),
+            s("producto"),
+            Operador::Comparador("=".to_string()),
+            s("CASE"),
+            s("WHEN"),
+            s("cantidad"),
+        ];
+        probar_parser_error(&input, "Falta el END de la expresión CASE");
+
+        let input = vec![s("SELECT"), s("CASE"), s("ELSE"), t("x"), s("END")];
+        let mut input = input;
+        input.extend(vec![s("FROM"), s("ordenes")]);
+        probar_parser_error(&input, "Sintaxis invalida en CASE");
+    }
+
+    #[test]
+    fn test_parser_with_join() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let igual = || Operador::Comparador("=".to_string());
+        let input = vec![
+            s("WITH"),
+            s("a"),
+            s("AS"),
+            Operador::Lista(vec![s("SELECT"), s("id"), s("FROM"), s("clientes")]),
+            s("b"),
+            Operador::Lista(vec![s("cliente")]),
+            s("AS"),
+            Operador::Lista(vec![s("SELECT"), s("id_cliente"), s("FROM"), s("a")]),
+            s("SELECT"),
+            s("*"),
+            s("FROM"),
+            s("a"),
+            s("INNER"),
+            s("JOIN"),
+            s("b"),
+            s("ON"),
+            s("a.id"),
+            igual(),
+            s("cliente"),
+            s("WHERE"),
+            s("cliente"),
+            igual(),
+            s("1"),
+        ];
+
+        let comparacion = |izq: &str, der: &str| ExpresionBooleana::Comparacion {
+            izq: Valor::String(izq.to_string()),
+            operador: OperadorComparacion::Igual,
+            der: Valor::String(der.to_string()),
+        };
+        let select = |columna: &str, tabla: &str| {
+            SelectQuery::new(vec![columna.to_string()], tabla.to_string(), None, None)
+        };
+        let mut principal = SelectQuery::new(
+            vec!["*".to_string()],
+            "a".to_string(),
+            Some(comparacion("cliente", "1")),
+            None,
+        );
+        principal.joins = vec![Join::new("b", comparacion("a.id", "cliente"))];
+        let esperado = SQLQuery::With(WithQuery::new(
+            vec![
+                Cte::new(
+                    "a",
+                    None,
+                    Consulta::Select(Box::new(select("id", "clientes"))),
+                ),
+                Cte::new(
+                    "b",
+                    Some(vec!["cliente".to_string()]),
+                    Consulta::Select(Box::new(select("id_cliente", "a"))),
+                ),
+            ],
+            Consulta::Select(Box::new(principal)),
+        ));
+        probar_parser_exitoso(&input, esperado);
+
+        let mut input = input[..8].to_vec();
+        input.extend(vec![s("DELETE"), s("FROM"), s("a")]);
+        probar_parser_error(
+            &input,
+            "[INVALID_SYNTAX]: Después del WITH se esperaba una consulta SELECT",
+        );
+        let input = vec![
+            s("SELECT"),
+            s("id"),
+            s("FROM"),
+            s("a"),
+            s("JOIN"),
+            s("b"),
+            s("WHERE"),
+            s("id"),
+            igual(),
+            s("1"),
+        ];
+        probar_parser_error(
+            &input,
+            "[INVALID_SYNTAX]: Se esperaba 'JOIN tabla ON condicion' en SELECT",
+        );
+    }
+
+    #[test]
+    fn test_parser_with_recursive() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let cuerpo = vec![
+            s("SELECT"),
+            s("id"),
+            s("FROM"),
+            s("empleados"),
+            s("UNION"),
+            s("SELECT"),
+            s("id"),
+            s("FROM"),
+            s("r"),
+        ];
+        let mut input = vec![s("WITH"), s("RECURSIVE"), s("r"), s("AS")];
+        input.push(Operador::Lista(cuerpo));
+        input.extend(vec![s("SELECT"), s("id"), s("FROM"), s("r"), s("OPTION")]);
+        input.push(Operador::Lista(vec![s("MAXRECURSION"), s("5")]));
+
+        let select = |tabla: &str| {
+            Box::new(SelectQuery::new(
+                vec!["id".to_string()],
+                tabla.to_string(),
+                None,
+                None,
+            ))
+        };
+        let conjunto = Conjunto::Operacion {
+            izq: Box::new(Conjunto::Select(select("empleados"))),
+            operador: OperadorConjunto::Union,
+            der: Box::new(Conjunto::Select(select("r"))),
+        };
+        let cte = Cte::new(
+            "r",
+            None,
+            Consulta::Compound(CompoundQuery::new(conjunto, None, None)),
+        );
+        let mut with_query = WithQuery::new(vec![cte], Consulta::Select(select("r")));
+        with_query.recursiva = true;
+        with_query.max_iteraciones = 5;
+        probar_parser_exitoso(&input, SQLQuery::With(with_query));
+
+        let n = input.len();
+        input[n - 1] = Operador::Lista(vec![s("MAXRECURSION"), s("-1")]);
+        probar_parser_error(
+            &input,
+            "[INVALID_SYNTAX]: MAXRECURSION debe ser un entero no negativo",
+        );
+    }
+
+    #[test]
+    fn test_parser_create_drop_view() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = vec![
+            s("CREATE"),
+            s("VIEW"),
+            s("grandes"),
+            s("AS"),
+            s("SELECT"),
+            s("id"),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        let select = SelectQuery::new(vec!["id".to_string()], "ordenes".to_string(), None, None);
+        let esperado = SQLQuery::CreateView(CreateViewQuery::new(
+            "grandes",
+            Consulta::Select(Box::new(select)),
+        ));
+        probar_parser_exitoso(&input, esperado);
+
+        let input = vec![s("DROP"), s("VIEW"), s("grandes")];
+        probar_parser_exitoso(
+            &input,
+            SQLQuery::DropView(DropViewQuery::new("grandes", false)),
+        );
+
+        let input = vec![
+            s("CREATE"),
+            s("VIEW"),
+            s("grandes"),
+            s("AS"),
+            s("DELETE"),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        probar_parser_error(
+            &input,
+            "[INVALID_SYNTAX]: Se esperaba 'CREATE VIEW nombre AS SELECT ...'",
+        );
+    }
+
+    #[test]
+    fn test_parser_materialized_view() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let mut input = vec![
+            s("CREATE"),
+            s("MATERIALIZED"),
+            s("VIEW"),
+            s("resumen"),
+            s("AUTO"),
+            s("REFRESH"),
+            s("AS"),
+            s("SELECT"),
+            s("id"),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        let select = SelectQuery::new(vec!["id".to_string()], "ordenes".to_string(), None, None);
+        let consulta = Consulta::Select(Box::new(select));
+        probar_parser_exitoso(
+            &input,
+            SQLQuery::CreateMaterializedView(CreateMaterializedViewQuery::new(
+                "resumen",
+                consulta.clone(),
+                true,
+            )),
+        );
+
+        input.drain(4..6);
+        probar_parser_exitoso(
+            &input,
+            SQLQuery::CreateMaterializedView(CreateMaterializedViewQuery::new(
+                "resumen", consulta, false,
+            )),
+        );
+
+        let input = vec![s("REFRESH"), s("MATERIALIZED"), s("VIEW"), s("resumen")];
+        probar_parser_exitoso(&input, SQLQuery::Refresh(RefreshQuery::new("resumen")));
+
+        let input = vec![s("DROP"), s("MATERIALIZED"), s("VIEW"), s("resumen")];
+        probar_parser_exitoso(
+            &input,
+            SQLQuery::DropView(DropViewQuery::new("resumen", true)),
+        );
+
+        let input = vec![
+            s("CREATE"),
+            s("MATERIALIZED"),
+            s("VIEW"),
+            s("resumen"),
+            s("REFRESH"),
+            s("AS"),
+            s("SELECT"),
+            s("id"),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        probar_parser_error(
+            &input,
+            "[INVALID_SYNTAX]: Se esperaba 'CREATE MATERIALIZED VIEW nombre [AUTO REFRESH] AS SELECT ...'",
+        );
+    }
+
+    #[test]
+    fn test_parser_select_ventanas() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = vec![
+            s("SELECT"),
+            s("id"),
+            s("ROW_NUMBER"),
+            Operador::Lista(Vec::new()),
+            s("OVER"),
+            Operador::Lista(vec![
+                s("PARTITION"),
+                s("BY"),
+                s("id_cliente"),
+                s("ORDER"),
+                s("BY"),
+                s("id"),
+                s("DESC"),
+            ]),
+            s("LAG"),
+            Operador::Lista(vec![s("cantidad"), s("2"), s("0")]),
+            s("OVER"),
+            Operador::Lista(vec![s("ORDER"), s("BY"), s("id")]),
+            s("SUM"),
+            Operador::Lista(vec![s("cantidad")]),
+            s("OVER"),
+            Operador::Lista(vec![
+                s("ORDER"),
+                s("BY"),
+                s("id"),
+                s("ROWS"),
+                s("BETWEEN"),
+                s("1"),
+                s("PRECEDING"),
+                s("AND"),
+                s("UNBOUNDED"),
+                s("FOLLOWING"),
+            ]),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        let por_id = |direccion| OrderClause {
+            column: "id".to_string(),
+            direccion,
+            expresion: None,
+            nulos: None,
+        };
+        let mut numero = Ventana::new(FuncionVentana::RowNumber, None);
+        numero.particion = vec!["id_cliente".to_string()];
+        numero.orden = vec![por_id(OrderDirection::Desc)];
+        let mut anterior = Ventana::new(FuncionVentana::Lag, Some("cantidad".to_string()));
+        anterior.desplazamiento = 2;
+        anterior.defecto = Some(Datos::Integer(0));
+        anterior.orden = vec![por_id(OrderDirection::Asc)];
+        let mut suma = Ventana::new(FuncionVentana::Sum, Some("cantidad".to_string()));
+        suma.orden = vec![por_id(OrderDirection::Asc)];
+        suma.marco = Some((LimiteMarco::Anterior(Some(1)), LimiteMarco::Siguiente(None)));
+        let ventanas = vec![numero, anterior, suma];
+        let mut columnas = vec!["id".to_string()];
+        columnas.extend(ventanas.iter().map(|v| v.nombre_columna()));
+        assert_eq!(columnas[2], "LAG(cantidad 2 0) OVER (ORDER BY id)");
+        let mut select = SelectQuery::new(columnas, "ordenes".to_string(), None, None);
+        select.ventanas = ventanas;
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let mut input = vec![
+            s("SELECT"),
+            s("RANK"),
+            Operador::Lista(Vec::new()),
+            s("OVER"),
+            Operador::Lista(Vec::new()),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        probar_parser_error(&input, "RANK requiere ORDER BY en su ventana");
+        input[1] = s("ROW_NUMBER");
+        input[4] = Operador::Lista(vec![s("ROWS"), s("CURRENT"), s("ROW")]);
+        probar_parser_error(&input, "ROW_NUMBER no admite un marco ROWS");
+        input[1] = s("AVG");
+        probar_parser_error(&input, "'AVG' no es una función de ventana");
+    }
+
+    #[test]
+    fn test_parser_select_alias() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = vec![
+            s("SELECT"),
+            s("o.id"),
+            s("AS"),
+            s("orden"),
+            s("nombre"),
+            s("FROM"),
+            s("ordenes"),
+            s("AS"),
+            s("o"),
+            s("JOIN"),
+            s("clientes"),
+            s("c"),
+            s("ON"),
+            s("o.id_cliente"),
+            Operador::Comparador("=".to_string()),
+            s("c.id"),
+            s("ORDER"),
+            s("BY"),
+            s("orden"),
+        ];
+        let orden = OrderClause {
+            column: "orden".to_string(),
+            direccion: OrderDirection::Asc,
+            expresion: None,
+            nulos: None,
+        };
+        let mut select = SelectQuery::new(
+            vec!["o.id".to_string(), "nombre".to_string()],
+            "ordenes".to_string(),
+            None,
+            Some(vec![orden]),
+        );
+        select.alias_columnas = vec![Some("orden".to_string()), None];
+        select.alias = Some("o".to_string());
+        let condicion = ExpresionBooleana::Comparacion {
+            izq: Valor::String("o.id_cliente".to_string()),
+            operador: OperadorComparacion::Igual,
+            der: Valor::String("c.id".to_string()),
+        };
+        let mut join = Join::new("clientes", condicion);
+        join.alias = Some("c".to_string());
+        select.joins = vec![join];
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![
+            s("SELECT"),
+            s("COUNT"),
+            Operador::Lista(vec![s("*")]),
+            s("AS"),
+            s("total"),
+            s("FROM"),
+            s("ordenes"),
+        ];
+        let mut select = SelectQuery::new(Vec::new(), "ordenes".to_string(), None, None);
+        let mut conteo = Conteo::new(None, false);
+        conteo.alias = Some("total".to_string());
+        select.conteos = vec![conteo];
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![s("SELECT"), s("id"), s("AS"), s("FROM"), s("ordenes")];
+        probar_parser_error(&input, "Se esperaba un alias despues de AS");
+    }
+
+    #[test]
+    fn test_parser_select_sin_from_y_values() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = vec![
+            s("SELECT"),
+            s("1"),
+            s("+"),
+            s("2"),
+            s("*"),
+            s("precio"),
+            s("UPPER"),
+            Operador::Lista(vec![Operador::Texto("x".to_string())]),
+            s("AS"),
+            s("u"),
+        ];
+        let operacion = Valor::Operacion {
+            izq: Box::new(Valor::String("1".to_string())),
+            operador: OperadorAritmetico::Suma,
+            der: Box::new(Valor::Operacion {
+                izq: Box::new(Valor::String("2".to_string())),
+                operador: OperadorAritmetico::Multiplicacion,
+                der: Box::new(Valor::String("precio".to_string())),
+            }),
+        };
+        let funcion = Valor::Funcion {
+            nombre: "UPPER".to_string(),
+            argumentos: vec![Valor::Literal("x".to_string())],
+        };
+        let mut select = SelectQuery::new(
+            vec!["1 + 2 * precio".to_string(), "UPPER('x')".to_string()],
+            String::new(),
+            None,
+            None,
+        );
+        select.alias_columnas = vec![None, Some("u".to_string())];
+        select.calculadas = vec![operacion, funcion];
+        select.valores = Some(Valores::sin_tabla());
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![
+            s("SELECT"),
+            s("*"),
+            s("FROM"),
+            Operador::Lista(vec![
+                s("VALUES"),
+                Operador::Lista(vec![s("1"), Operador::Texto("a".to_string())]),
+                Operador::Lista(vec![s("2"), s("NULL")]),
+            ]),
+            s("AS"),
+            s("v"),
+            Operador::Lista(vec![s("id"), s("nombre")]),
+        ];
+        let mut select = SelectQuery::new(vec!["*".to_string()], "v".to_string(), None, None);
+        select.valores = Some(Valores::new(
+            vec!["id".to_string(), "nombre".to_string()],
+            vec![
+                vec![Datos::Integer(1), Datos::String("a".to_string())],
+                vec![Datos::Integer(2), Datos::String(String::new())],
+            ],
+        ));
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![s("SELECT"), s("*")];
+        probar_parser_error(&input, "No se puede seleccionar '*' en un SELECT sin FROM");
+        let input = vec![
+            s("SELECT"),
+            s("*"),
+            s("FROM"),
+            Operador::Lista(vec![s("VALUES"), Operador::Lista(vec![s("1")])]),
+        ];
+        probar_parser_error(&input, "Se esperaba un nombre para la tabla de VALUES");
+        let input = vec![
+            s("SELECT"),
+            s("*"),
+            s("FROM"),
+            Operador::Lista(vec![
+                s("VALUES"),
+                Operador::Lista(vec![s("1"), s("2")]),
+                Operador::Lista(vec![s("3")]),
+            ]),
+            s("v"),
+        ];
+        probar_parser_error(
+            &input,
+            "Todas las filas de VALUES deben tener la misma cantidad de valores",
+        );
+    }
+
+    #[test]
+    fn test_parser_order_by_posiciones_expresiones_y_nulos() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = vec![
+            s("SELECT"),
+            s("nombre"),
+            s("edad"),
+            s("FROM"),
+            s("personas"),
+            s("ORDER"),
+            s("BY"),
+            s("2"),
+            s("DESC"),
+            s("NULLS"),
+            s("LAST"),
+            s("LOWER"),
+            Operador::Lista(vec![s("nombre")]),
+            s("id"),
+            s("NULLS"),
+            s("FIRST"),
+        ];
+        let lower = Valor::Funcion {
+            nombre: "LOWER".to_string(),
+            argumentos: vec![Valor::String("nombre".to_string())],
+        };
+        let orden = vec![
+            OrderClause {
+                column: "2".to_string(),
+                direccion: OrderDirection::Desc,
+                expresion: None,
+                nulos: Some(OrdenNulos::Ultimo),
+            },
+            OrderClause {
+                column: "LOWER(nombre)".to_string(),
+                direccion: OrderDirection::Asc,
+                expresion: Some(lower),
+                nulos: None,
+            },
+            OrderClause {
+                column: "id".to_string(),
+                direccion: OrderDirection::Asc,
+                expresion: None,
+                nulos: Some(OrdenNulos::Primero),
+            },
+        ];
+        assert_eq!(orden[0].posicion(), Some(2));
+        assert_eq!(orden[1].posicion(), None);
+        let select = SelectQuery::new(
+            vec!["nombre".to_string(), "edad".to_string()],
+            "personas".to_string(),
+            None,
+            Some(orden),
+        );
+        probar_parser_exitoso(&input, SQLQuery::Select(select));
+
+        let input = vec![
+            s("SELECT"),
+            s("nombre"),
+            s("FROM"),
+            s("personas"),
+            s("ORDER"),
+            s("BY"),
+            s("0"),
+        ];
+        probar_parser_error(&input, "La posición 0 del ORDER BY debe ser mayor a cero");
+        let input = vec![
+            s("SELECT"),
+            s("nombre"),
+            s("FROM"),
+            s("personas"),
+            s("ORDER"),
+            s("BY"),
+            s("nombre"),
+            s("NULLS"),
+        ];
+        probar_parser_error(
+            &input,
+            "Se esperaba 'NULLS FIRST' o 'NULLS LAST' en el ORDER BY",
+        );
+    }
+
+    #[test]
+    fn test_parser_parametros() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let igual = || Operador::Comparador("=".to_string());
+        let input = vec![
+            s("INSERT"),
+            s("INTO"),
+            s("users"),
+            s("VALUES"),
+            Operador::Lista(vec![
+                Operador::Parametro(1),
+                Operador::Texto("Ivan".to_string()),
+            ]),
+            Operador::Lista(vec![s("2"), Operador::Parametro(2)]),
+        ];
+        let mut insert = InsertQuery::new(
+            "users",
+            Vec::new(),
+            vec![
+                vec![
+                    Datos::String(String::new()),
+                    Datos::String("Ivan".to_string()),
+                ],
+                vec![Datos::Integer(2), Datos::String(String::new())],
+            ],
+        );
+        insert.parametros = vec![
+            ParametroValor {
+                fila: 0,
+                columna: 0,
+                numero: 1,
+            },
+            ParametroValor {
+                fila: 1,
+                columna: 1,
+                numero: 2,
+            },
+        ];
+        probar_parser_exitoso(&input, SQLQuery::Insert(insert));
+
+        let input = vec![
+            s("UPDATE"),
+            s("users"),
+            s("SET"),
+            s("name"),
+            igual(),
+            Operador::Parametro(1),
+            s("WHERE"),
+            s("id"),
+            igual(),
+            Operador::Parametro(2),
+        ];
+        let condicion = ExpresionBooleana::Comparacion {
+            izq: Valor::String("id".to_string()),
+            operador: OperadorComparacion::Igual,
+            der: Valor::Parametro(2),
+        };
+        let mut update = UpdateQuery::new("users".to_string(), HashMap::new(), Some(condicion));
+        update
+            .expresiones
+            .insert("name".to_string(), Valor::Parametro(1));
+        probar_parser_exitoso(&input, SQLQuery::Update(update));
+
+        let input = vec![
+            s("INSERT"),
+            s("INTO"),
+            s("users"),
+            s("VALUES"),
+            Operador::Lista(vec![Operador::Lista(vec![Operador::Parametro(1)])]),
+        ];
+        probar_parser_error(&input, "No se puede usar el parámetro $1 en este lugar");
+    }
+
+    #[test]
+    fn test_parser_explain() {
+        let s = |s: &str| Operador::String(s.to_string());
+        let input = vec![s("EXPLAIN"), s("SELECT"), s("id"), s("FROM"), s("users")];
+        let select = SelectQuery::new(vec!["id".to_string()], "users".to_string(), None, None);
+        let esperado = ExplainQuery::new(SQLQuery::Select(select.clone()), false);
+        probar_parser_exitoso(&input, SQLQuery::Explain(esperado));
+
+        let input = vec![
+            s("EXPLAIN"),
+            s("ANALYZE"),
+            s("DELETE"),
+            s("FROM"),
+            s("users"),
+        ];
+        let esperado = ExplainQuery::new(SQLQuery::Delete(DeleteQuery::new("users", None)), true);
+        probar_parser_exitoso(&input, SQLQuery::Explain(esperado));
+
+        let input = vec![s("EXPLAIN"), s("DROP"), s("VIEW"), s("activos")];
+        probar_parser_error(&input, "Solo se puede usar EXPLAIN con consultas");
+    }
+
+    #[test]
+    fn test_parser_analyze() {
+        let s = |s: &str| Operador::String(s.to_string());
+        probar_parser_exitoso(
+            &vec![s("ANALYZE")],
+            SQLQuery::Analyze(AnalyzeQuery::new(None)),
+        );
+        probar_parser_exitoso(
+            &vec![s("ANALYZE"), s("users")],
+            SQLQuery::Analyze(AnalyzeQuery::new(Some("users"))),
+        );
+        probar_parser_error(&vec![s("ANALYZE"), s("users"), s("id")], "Query invalida");
+    }
 }
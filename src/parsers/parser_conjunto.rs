@@ -0,0 +1,169 @@
+use crate::{
+    errores::error::ErrorType,
+    lexers::operador::Operador,
+    parsers::parser::{es_subconsulta, limit, order_by, parser_select},
+    queries::{
+        compound_query::{CompoundQuery, Conjunto, OperadorConjunto},
+        order_clause::OrderClause,
+        sql_query::SQLQuery,
+        with_query::Consulta,
+    },
+};
+
+/// Devuelve el operador de conjuntos del principio y lo que le sigue
+/// [UNION, ...], [UNION, ALL, ...], [INTERSECT, ...] o [EXCEPT, ...]
+fn operador_conjunto(rest: &[Operador]) -> Option<(OperadorConjunto, &[Operador])> {
+    match rest {
+        [Operador::String(union), Operador::String(all), rest @ ..]
+            if union == "UNION" && all == "ALL" =>
+        {
+            Some((OperadorConjunto::UnionAll, rest))
+        }
+        [Operador::String(union), rest @ ..] if union == "UNION" => {
+            Some((OperadorConjunto::Union, rest))
+        }
+        [Operador::String(intersect), rest @ ..] if intersect == "INTERSECT" => {
+            Some((OperadorConjunto::Intersect, rest))
+        }
+        [Operador::String(except), rest @ ..] if except == "EXCEPT" => {
+            Some((OperadorConjunto::Except, rest))
+        }
+        _ => None,
+    }
+}
+
+/// Separa la consulta en las consultas que se combinan y los operadores de conjuntos entre ellas
+fn separar_conjuntos(query: &[Operador]) -> (Vec<&[Operador]>, Vec<OperadorConjunto>) {
+    let mut partes = Vec::new();
+    let mut operadores = Vec::new();
+    let mut inicio = 0;
+    let mut actual = 0;
+    while actual < query.len() {
+        match operador_conjunto(&query[actual..]) {
+            Some((operador, rest)) => {
+                partes.push(&query[inicio..actual]);
+                operadores.push(operador);
+                inicio = query.len() - rest.len();
+                actual = inicio;
+            }
+            None => actual += 1,
+        }
+    }
+    partes.push(&query[inicio..]);
+    (partes, operadores)
+}
+
+/// Arma el árbol de la combinación: INTERSECT se aplica antes que UNION y EXCEPT, y los demás de izquierda a derecha
+fn armar_conjunto(mut cabeza: Conjunto, resto: Vec<(OperadorConjunto, Conjunto)>) -> Conjunto {
+    let mut cola: Vec<(OperadorConjunto, Conjunto)> = Vec::new();
+    for (operador, der) in resto {
+        if operador != OperadorConjunto::Intersect {
+            cola.push((operador, der));
+            continue;
+        }
+        let izq = match cola.last_mut() {
+            Some((_, ultimo)) => ultimo,
+            None => &mut cabeza,
+        };
+        *izq = Conjunto::Operacion {
+            izq: Box::new(izq.clone()),
+            operador,
+            der: Box::new(der),
+        };
+    }
+    cola.into_iter()
+        .fold(cabeza, |izq, (operador, der)| Conjunto::Operacion {
+            izq: Box::new(izq),
+            operador,
+            der: Box::new(der),
+        })
+}
+
+/// Procesa una de las consultas que se combinan: [SELECT, ...] o [(SELECT, ...)]
+/// El ORDER BY y el LIMIT de un SELECT solo se permiten si está entre paréntesis.
+fn parse_parte_conjunto(parte: &[Operador]) -> Result<Conjunto, ErrorType> {
+    match parte {
+        [Operador::String(select), rest @ ..] if select == "SELECT" => {
+            let select_query = parser_select(rest)?;
+            if select_query.order_by.is_some() || select_query.limit.is_some() {
+                return Err(ErrorType::InvalidSyntax(
+                    "El ORDER BY y el LIMIT de una consulta combinada deben ir al final"
+                        .to_string(),
+                ));
+            }
+            Ok(Conjunto::Select(Box::new(select_query)))
+        }
+        [Operador::Lista(lista)] if es_subconsulta(lista) => match parse_conjunto(lista)? {
+            (conjunto, None, None) => Ok(conjunto),
+            _ => Err(ErrorType::InvalidSyntax(
+                "Una consulta combinada entre paréntesis no puede tener ORDER BY ni LIMIT"
+                    .to_string(),
+            )),
+        },
+        _ => Err(ErrorType::InvalidSyntax(
+            "Se esperaba una consulta SELECT en la combinación".to_string(),
+        )),
+    }
+}
+
+/// Combinación de consultas junto con el ORDER BY y el LIMIT del resultado combinado.
+type ConjuntoOrdenado = (Conjunto, Option<Vec<OrderClause>>, Option<usize>);
+
+/// Procesa una consulta que puede combinar varios SELECT con operadores de conjuntos,
+/// devolviendo la combinación y el ORDER BY y el LIMIT del resultado combinado.
+/// Si no combina consultas, el ORDER BY y el LIMIT quedan en el único SELECT.
+fn parse_conjunto(query: &[Operador]) -> Result<ConjuntoOrdenado, ErrorType> {
+    let (mut partes, operadores) = separar_conjuntos(query);
+    if operadores.is_empty() {
+        if let [Operador::String(select), rest @ ..] = query {
+            if select == "SELECT" {
+                return Ok((Conjunto::Select(Box::new(parser_select(rest)?)), None, None));
+            }
+        }
+    }
+    let ultima = partes.pop().unwrap_or_default();
+    let posicion = ultima
+        .iter()
+        .position(
+            |operador| matches!(operador, Operador::String(s) if s == "ORDER" || s == "LIMIT"),
+        )
+        .unwrap_or(ultima.len());
+    partes.push(&ultima[..posicion]);
+    let (order_by, rest) = order_by(&ultima[posicion..])?;
+    let (limit, rest) = limit(rest)?;
+    if !rest.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Sintaxis invalida en SELECT".to_string(),
+        ));
+    }
+    let mut conjuntos = Vec::new();
+    for parte in partes {
+        conjuntos.push(parse_parte_conjunto(parte)?);
+    }
+    let mut conjuntos = conjuntos.into_iter();
+    let Some(cabeza) = conjuntos.next() else {
+        return Err(ErrorType::InvalidSyntax(
+            "Se esperaba una consulta SELECT en la combinación".to_string(),
+        ));
+    };
+    let resto = operadores.into_iter().zip(conjuntos).collect();
+    Ok((armar_conjunto(cabeza, resto), order_by, limit))
+}
+
+/// Procesa una consulta que devuelve filas: un SELECT o una combinación de SELECT con operadores de conjuntos
+pub fn parse_consulta(query: &[Operador]) -> Result<Consulta, ErrorType> {
+    match parse_conjunto(query)? {
+        (Conjunto::Select(select_query), None, None) => Ok(Consulta::Select(select_query)),
+        (conjunto, order_by, limit) => Ok(Consulta::Compound(CompoundQuery::new(
+            conjunto, order_by, limit,
+        ))),
+    }
+}
+
+/// Procesar SELECT, que puede combinar varios SELECT con UNION, UNION ALL, INTERSECT o EXCEPT
+pub fn parse_select_query(query: &[Operador]) -> Result<SQLQuery, ErrorType> {
+    match parse_consulta(query)? {
+        Consulta::Select(select_query) => Ok(SQLQuery::Select(*select_query)),
+        Consulta::Compound(compound_query) => Ok(SQLQuery::Compound(compound_query)),
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{
+    errores::error::ErrorType,
+    esquema::{
+        check::Check,
+        columna::{ColumnaDef, TipoDato},
+        esquema_tabla::Esquema,
+        foreign_key::{AccionReferencial, ForeignKey},
+    },
+    lexers::operador::Operador,
+    parsers::parser::parse_condicion,
+    queries::{create_query::CreateQuery, sql_query::SQLQuery},
+    utils::operador_to_single_valor,
+};
+
+/// Transforma la lista de columnas de una restricción de tabla, como `PRIMARY KEY (a, b)`, en una lista de Strings.
+fn columnas_restriccion(lista: &[Operador]) -> Result<Vec<String>, ErrorType> {
+    let mut columnas = Vec::new();
+    for operador in lista {
+        match operador {
+            Operador::String(s) | Operador::Texto(s) => columnas.push(s.to_string()),
+            _ => {
+                return Err(ErrorType::InvalidSyntax(
+                    "Columnas invalidas en la restricción de CREATE TABLE".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(columnas)
+}
+
+/// Asigna la clave primaria al esquema, verificando que no se declare más de una.
+fn agregar_primary_key(esquema: &mut Esquema, columnas: Vec<String>) -> Result<(), ErrorType> {
+    if !esquema.primary_key.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Se declaró más de una PRIMARY KEY en CREATE TABLE".to_string(),
+        ));
+    }
+    esquema.primary_key = columnas;
+    Ok(())
+}
+
+/// Procesa el tipo opcional de una columna, ignorando el tamaño si lo tiene, como en `VARCHAR(20)`.
+fn tipo_columna(rest: &[Operador]) -> (Option<TipoDato>, &[Operador]) {
+    match rest {
+        [Operador::String(tipo), Operador::Lista(_), rest @ ..]
+            if TipoDato::desde_nombre(tipo).is_some() =>
+        {
+            (TipoDato::desde_nombre(tipo), rest)
+        }
+        [Operador::String(tipo), rest @ ..] if TipoDato::desde_nombre(tipo).is_some() => {
+            (TipoDato::desde_nombre(tipo), rest)
+        }
+        _ => (None, rest),
+    }
+}
+
+/// Procesa la acción opcional ON DELETE de una foreign key. Por defecto es RESTRICT.
+fn accion_on_delete(rest: &[Operador]) -> Result<(AccionReferencial, &[Operador]), ErrorType> {
+    match rest {
+        [Operador::String(on), Operador::String(delete), rest @ ..]
+            if on == "ON" && delete == "DELETE" =>
+        {
+            match rest {
+                [Operador::String(set), Operador::String(null), rest @ ..]
+                    if set == "SET" && null == "NULL" =>
+                {
+                    Ok((AccionReferencial::SetNull, rest))
+                }
+                [Operador::String(no), Operador::String(action), rest @ ..]
+                    if no == "NO" && action == "ACTION" =>
+                {
+                    Ok((AccionReferencial::Restrict, rest))
+                }
+                [Operador::String(accion), rest @ ..] if accion == "RESTRICT" => {
+                    Ok((AccionReferencial::Restrict, rest))
+                }
+                [Operador::String(accion), rest @ ..] if accion == "CASCADE" => {
+                    Ok((AccionReferencial::Cascade, rest))
+                }
+                _ => Err(ErrorType::InvalidSyntax(
+                    "Acción invalida en ON DELETE, se esperaba RESTRICT, CASCADE o SET NULL"
+                        .to_string(),
+                )),
+            }
+        }
+        _ => Ok((AccionReferencial::Restrict, rest)),
+    }
+}
+
+/// Procesa [tabla, (columnas), ON, DELETE, accion] a continuación de REFERENCES.
+fn referencia(
+    columnas: Vec<String>,
+    rest: &[Operador],
+) -> Result<(ForeignKey, &[Operador]), ErrorType> {
+    match rest {
+        [Operador::String(tabla) | Operador::Texto(tabla), Operador::Lista(columnas_ref), rest @ ..] =>
+        {
+            let (on_delete, rest) = accion_on_delete(rest)?;
+            let fk = ForeignKey::new(
+                columnas,
+                tabla,
+                columnas_restriccion(columnas_ref)?,
+                on_delete,
+            );
+            Ok((fk, rest))
+        }
+        _ => Err(ErrorType::InvalidSyntax(
+            "Se esperaba 'REFERENCES tabla (columnas)' en la FOREIGN KEY".to_string(),
+        )),
+    }
+}
+
+/// Agrega una restricción CHECK al esquema. Si no tiene nombre se le asigna `<tabla>_check<n>`.
+fn agregar_check(
+    esquema: &mut Esquema,
+    nombre: Option<&str>,
+    condicion: &[Operador],
+) -> Result<(), ErrorType> {
+    let nombre = match nombre {
+        Some(nombre) => nombre.to_string(),
+        None => format!("{}_check{}", esquema.tabla, esquema.checks.len() + 1),
+    };
+    let expresion = parse_condicion(condicion)?;
+    if expresion.tiene_subconsultas() {
+        return Err(ErrorType::InvalidSyntax(format!(
+            "La restricción CHECK '{}' no puede contener subconsultas",
+            nombre
+        )));
+    }
+    let check = Check::new(&nombre, expresion);
+    esquema.checks.push(check);
+    Ok(())
+}
+
+/// Funcion recursiva que procesa las restricciones declaradas junto a una columna
+/// y devuelve el resto a partir de la siguiente definición.
+fn restricciones_columna_rec<'a>(
+    columna: &mut ColumnaDef,
+    rest: &'a [Operador],
+    esquema: &mut Esquema,
+) -> Result<&'a [Operador], ErrorType> {
+    match rest {
+        // [PRIMARY, KEY, (columnas), ...] y [UNIQUE, (columnas), ...] son restricciones de tabla
+        [Operador::String(primary), Operador::String(key), Operador::Lista(_), ..]
+            if primary == "PRIMARY" && key == "KEY" =>
+        {
+            Ok(rest)
+        }
+        [Operador::String(unique), Operador::Lista(_), ..] if unique == "UNIQUE" => Ok(rest),
+        // [CHECK, (condicion), ...] y [CONSTRAINT, nombre, ...] también se procesan como restricciones de tabla
+        [Operador::String(check), ..] if check == "CHECK" || check == "CONSTRAINT" => Ok(rest),
+        [Operador::String(primary), Operador::String(key), rest @ ..]
+            if primary == "PRIMARY" && key == "KEY" =>
+        {
+            agregar_primary_key(esquema, vec![columna.nombre.to_string()])?;
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        [Operador::String(unique), rest @ ..] if unique == "UNIQUE" => {
+            esquema.unique.push(vec![columna.nombre.to_string()]);
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        [Operador::String(references), rest @ ..] if references == "REFERENCES" => {
+            let (fk, rest) = referencia(vec![columna.nombre.to_string()], rest)?;
+            esquema.foreign_keys.push(fk);
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        [Operador::String(not), Operador::String(null), rest @ ..]
+            if not == "NOT" && null == "NULL" =>
+        {
+            columna.not_null = true;
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        [Operador::String(null), rest @ ..] if null == "NULL" => {
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        [Operador::String(default), valor, rest @ ..] if default == "DEFAULT" => {
+            columna.default = Some(operador_to_single_valor(valor)?);
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        [Operador::String(autoincrement), rest @ ..]
+            if autoincrement == "AUTOINCREMENT" || autoincrement == "AUTO_INCREMENT" =>
+        {
+            columna.autoincrement = true;
+            restricciones_columna_rec(columna, rest, esquema)
+        }
+        _ => Ok(rest),
+    }
+}
+
+/// Funcion recursiva que procesa las definiciones de columnas y restricciones de tabla de CREATE TABLE.
+fn definiciones_rec(rest: &[Operador], mut esquema: Esquema) -> Result<Esquema, ErrorType> {
+    match rest {
+        [] => Ok(esquema),
+        // [PRIMARY, KEY, (columnas), ...]
+        [Operador::String(primary), Operador::String(key), Operador::Lista(columnas), rest @ ..]
+            if primary == "PRIMARY" && key == "KEY" =>
+        {
+            agregar_primary_key(&mut esquema, columnas_restriccion(columnas)?)?;
+            definiciones_rec(rest, esquema)
+        }
+        // [UNIQUE, (columnas), ...]
+        [Operador::String(unique), Operador::Lista(columnas), rest @ ..] if unique == "UNIQUE" => {
+            esquema.unique.push(columnas_restriccion(columnas)?);
+            definiciones_rec(rest, esquema)
+        }
+        // [CHECK, (condicion), ...]
+        [Operador::String(check), Operador::Lista(condicion), rest @ ..] if check == "CHECK" => {
+            agregar_check(&mut esquema, None, condicion)?;
+            definiciones_rec(rest, esquema)
+        }
+        // [CONSTRAINT, nombre, CHECK, (condicion), ...]
+        [Operador::String(constraint), Operador::String(nombre), Operador::String(check), Operador::Lista(condicion), rest @ ..]
+            if constraint == "CONSTRAINT" && check == "CHECK" =>
+        {
+            agregar_check(&mut esquema, Some(nombre), condicion)?;
+            definiciones_rec(rest, esquema)
+        }
+        // [FOREIGN, KEY, (columnas), REFERENCES, tabla, (columnas), ...]
+        [Operador::String(foreign), Operador::String(key), Operador::Lista(columnas), Operador::String(references), rest @ ..]
+            if foreign == "FOREIGN" && key == "KEY" && references == "REFERENCES" =>
+        {
+            let (fk, rest) = referencia(columnas_restriccion(columnas)?, rest)?;
+            esquema.foreign_keys.push(fk);
+            definiciones_rec(rest, esquema)
+        }
+        // [columna, tipo, restricciones..., ...]
+        [Operador::String(nombre) | Operador::Texto(nombre), rest @ ..] => {
+            let (tipo, rest) = tipo_columna(rest);
+            let mut columna = ColumnaDef::new(nombre, tipo);
+            let rest = restricciones_columna_rec(&mut columna, rest, &mut esquema)?;
+            esquema.columnas.push(columna);
+            definiciones_rec(rest, esquema)
+        }
+        _ => Err(ErrorType::InvalidSyntax(
+            "Definición de columna invalida en CREATE TABLE".to_string(),
+        )),
+    }
+}
+
+/// Procesar CREATE TABLE
+/// Tabla y [columna1, tipo1, restricciones..., columna2, ...] a CreateQuery
+pub fn parse_create_query(
+    table: &str,
+    definiciones: &[Operador],
+    rest: &[Operador],
+) -> Result<SQLQuery, ErrorType> {
+    if !rest.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Sintaxis invalida en CREATE TABLE".to_string(),
+        ));
+    }
+    let esquema = definiciones_rec(definiciones, Esquema::new(table))?;
+    if esquema.columnas.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Faltan columnas en CREATE TABLE".to_string(),
+        ));
+    }
+    Ok(SQLQuery::Create(CreateQuery::new(esquema)))
+}
@@ -0,0 +1,25 @@
+use crate::{
+    errores::error::ErrorType,
+    lexers::operador::Operador,
+    parsers::parser::parser,
+    queries::{explain_query::ExplainQuery, sql_query::SQLQuery},
+};
+
+/// Parsea la consulta de un EXPLAIN [ANALYZE], que tiene que devolver filas o modificar una tabla.
+pub fn parse_explain_query(rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
+    let (analyze, rest) = match rest {
+        [Operador::String(analyze), rest @ ..] if analyze == "ANALYZE" => (true, rest),
+        _ => (false, rest),
+    };
+    match parser(rest)? {
+        consulta @ (SQLQuery::Select(_)
+        | SQLQuery::Compound(_)
+        | SQLQuery::With(_)
+        | SQLQuery::Insert(_)
+        | SQLQuery::Update(_)
+        | SQLQuery::Delete(_)) => Ok(SQLQuery::Explain(ExplainQuery::new(consulta, analyze))),
+        _ => Err(ErrorType::InvalidSyntax(
+            "Solo se puede usar EXPLAIN con consultas SELECT, INSERT, UPDATE y DELETE".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    lexers::operador::Operador,
+    parsers::parser::{columnas_repetidas, columns_to_string, parser_select, returning},
+    queries::{
+        insert_query::{InsertQuery, ParametroValor},
+        on_conflict::{AccionConflicto, OnConflict},
+        sql_query::SQLQuery,
+        where_clause::valor::Valor,
+    },
+    utils::{operador_to_single_dato, operador_to_single_valor},
+};
+
+/// Transforma una lista de Operadores en una lista de Datos para value
+/// ["value1", "value2", "..."] a Vec<Datos>
+/// Cada parámetro `$n` ocupa un valor vacío y se agrega a `parametros` con su posición en la fila `fila`.
+fn operador_to_value(
+    lista: &[Operador],
+    columnas: &[String],
+    fila: usize,
+    parametros: &mut Vec<ParametroValor>,
+) -> Result<Vec<Datos>, ErrorType> {
+    if !columnas.is_empty() && lista.len() != columnas.len() {
+        return Err(ErrorType::InvalidSyntax(
+            "El número de columnas y valores no coincide.".to_string(),
+        ));
+    }
+    let mut datos: Vec<Datos> = Vec::new();
+    for (columna, operador) in lista.iter().enumerate() {
+        match operador {
+            Operador::Parametro(numero) => {
+                parametros.push(ParametroValor {
+                    fila,
+                    columna,
+                    numero: *numero,
+                });
+                datos.push(Datos::String(String::new()));
+            }
+            _ => datos.push(operador_to_single_dato(operador)?),
+        }
+    }
+    Ok(datos)
+}
+
+/// Para pasar los values a INSERTAR
+/// ["value1", "value2", "..."] ["value1", "value2", "..."] ... a Vec<Vec<Datos>>, en el orden de las columnas,
+/// junto con los parámetros `$n` que aparecen en ellos.
+fn operador_to_values(
+    lista: &[Operador],
+    columnas: &[String],
+) -> Result<(Vec<Vec<Datos>>, Vec<ParametroValor>), ErrorType> {
+    let mut values: Vec<Vec<Datos>> = Vec::new();
+    let mut parametros = Vec::new();
+    for operador in lista {
+        match operador {
+            Operador::Lista(lista) => values.push(operador_to_value(
+                lista,
+                columnas,
+                values.len(),
+                &mut parametros,
+            )?),
+            _ => {
+                return Err(ErrorType::InvalidSyntax(
+                    "Faltan valores en la consulta INSERT.".to_string(),
+                ))
+            }
+        }
+    }
+    Ok((values, parametros))
+}
+
+/// Funcion recursiva para guardar las asignaciones de DO UPDATE SET en ON CONFLICT
+fn asignaciones_rec(
+    mut asignaciones: HashMap<String, Valor>,
+    rest: &[Operador],
+) -> Result<HashMap<String, Valor>, ErrorType> {
+    match rest {
+        [] if !asignaciones.is_empty() => Ok(asignaciones),
+        [Operador::String(columna) | Operador::Texto(columna), Operador::Comparador(igual), valor, rest @ ..]
+            if igual == "=" =>
+        {
+            asignaciones.insert(columna.to_string(), operador_to_single_valor(valor)?);
+            asignaciones_rec(asignaciones, rest)
+        }
+        _ => Err(ErrorType::InvalidSyntax(
+            "Error de sintaxis en el seteo de columnas = value en ON CONFLICT DO UPDATE"
+                .to_string(),
+        )),
+    }
+}
+
+/// Separa la cláusula ON CONFLICT del final de un INSERT.
+/// [..., ON, CONFLICT, (columnas), DO, NOTHING] o [..., ON, CONFLICT, (columnas), DO, UPDATE, SET, column1, =, valor1, ...]
+fn on_conflict(rest: &[Operador]) -> Result<(&[Operador], Option<OnConflict>), ErrorType> {
+    let posicion = rest.windows(2).position(|par| {
+        matches!(par, [Operador::String(on), Operador::String(conflict)] if on == "ON" && conflict == "CONFLICT")
+    });
+    let Some(posicion) = posicion else {
+        return Ok((rest, None));
+    };
+    let on_conflict = match &rest[posicion + 2..] {
+        [Operador::Lista(columnas), Operador::String(hacer), Operador::String(nothing)]
+            if hacer == "DO" && nothing == "NOTHING" =>
+        {
+            OnConflict::new(columns_to_string(columnas)?, AccionConflicto::Nada)
+        }
+        [Operador::Lista(columnas), Operador::String(hacer), Operador::String(update), Operador::String(set), rest @ ..]
+            if hacer == "DO" && update == "UPDATE" && set == "SET" =>
+        {
+            let asignaciones = asignaciones_rec(HashMap::new(), rest)?;
+            OnConflict::new(
+                columns_to_string(columnas)?,
+                AccionConflicto::Actualizar(asignaciones),
+            )
+        }
+        _ => {
+            return Err(ErrorType::InvalidSyntax(
+                "Sintaxis invalida en ON CONFLICT, se esperaba '(columnas) DO NOTHING' o '(columnas) DO UPDATE SET ...'".to_string(),
+            ))
+        }
+    };
+    if on_conflict.columnas.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Faltan las columnas del ON CONFLICT".to_string(),
+        ));
+    }
+    Ok((&rest[..posicion], Some(on_conflict)))
+}
+
+/// Table, [ "colum1", "column2,", "..."] y [VALUES, ["value1", "value2", "..."] ...] o [SELECT, ...] a InsertQuery
+/// Si no se indican columnas, los valores se asignan en el orden del encabezado de la tabla.
+fn parser_insert(
+    table: &str,
+    columns: &[Operador],
+    rest: &[Operador],
+) -> Result<InsertQuery, ErrorType> {
+    let columns_parsed: Vec<String> = columns_to_string(columns)?;
+    if columnas_repetidas(&columns_parsed) {
+        return Err(ErrorType::InvalidSyntax(
+            "Columnas repetidas en INSERT".to_string(),
+        ));
+    }
+    match rest {
+        [Operador::String(values), rest @ ..] if values == "VALUES" => {
+            let (values_parsed, parametros) = operador_to_values(rest, &columns_parsed)?;
+            let mut insert_query = InsertQuery::new(table, columns_parsed, values_parsed);
+            insert_query.parametros = parametros;
+            Ok(insert_query)
+        }
+        [Operador::String(select), rest @ ..] if select == "SELECT" => {
+            let select_query = parser_select(rest)?;
+            Ok(InsertQuery::new_select(table, columns_parsed, select_query))
+        }
+        _ => Err(ErrorType::InvalidSyntax(
+            "Falta 'VALUES' en la consulta INSERT.".to_string(),
+        )),
+    }
+}
+
+/// Procesar INSERT
+pub fn parse_insert_query(table: &str, rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
+    let (rest, returning) = returning(rest)?;
+    let (rest, on_conflict) = on_conflict(rest)?;
+    let mut insert_query = match rest {
+        [Operador::Lista(columns), rest @ ..] => parser_insert(table, columns, rest)?,
+        _ => parser_insert(table, &[], rest)?,
+    };
+    insert_query.on_conflict = on_conflict;
+    insert_query.returning = returning;
+    Ok(SQLQuery::Insert(insert_query))
+}
@@ -0,0 +1,187 @@
+use crate::{
+    errores::error::ErrorType,
+    lexers::operador::Operador,
+    parsers::parser::order_by_rec,
+    queries::{
+        order_clause::OrderClause,
+        ventana::{FuncionVentana, LimiteMarco, Ventana},
+    },
+    utils::operador_to_dato,
+};
+
+/// [funcion, (argumentos), OVER, (definicion)] a Ventana
+pub fn parse_ventana(
+    funcion: &str,
+    argumentos: &[Operador],
+    definicion: &[Operador],
+) -> Result<Ventana, ErrorType> {
+    let Some(funcion) = FuncionVentana::desde(funcion) else {
+        return Err(ErrorType::InvalidSyntax(format!(
+            "'{}' no es una función de ventana",
+            funcion
+        )));
+    };
+    let mut ventana = argumentos_ventana(funcion, argumentos)?;
+    let error = || {
+        ErrorType::InvalidSyntax(
+            "Se esperaba 'OVER (PARTITION BY ... ORDER BY ... ROWS BETWEEN ...)'".to_string(),
+        )
+    };
+    let es_palabra = |operador: &Operador, palabras: &[&str]| matches!(operador, Operador::String(palabra) if palabras.contains(&palabra.as_str()));
+    // [PARTITION, BY, columnas, rest]
+    let rest = match definicion {
+        [Operador::String(partition), Operador::String(by), rest @ ..]
+            if partition == "PARTITION" && by == "BY" =>
+        {
+            let fin = rest
+                .iter()
+                .position(|operador| es_palabra(operador, &["ORDER", "ROWS"]))
+                .unwrap_or(rest.len());
+            for operador in &rest[..fin] {
+                match operador {
+                    Operador::String(columna) | Operador::Texto(columna) => {
+                        ventana.particion.push(columna.to_string())
+                    }
+                    _ => return Err(error()),
+                }
+            }
+            if ventana.particion.is_empty() {
+                return Err(error());
+            }
+            &rest[fin..]
+        }
+        _ => definicion,
+    };
+    // [ORDER, BY, columnas, rest]
+    let fin = rest
+        .iter()
+        .position(|operador| es_palabra(operador, &["ROWS"]))
+        .unwrap_or(rest.len());
+    match &rest[..fin] {
+        [] => {}
+        [Operador::String(order), Operador::String(by), columnas @ ..]
+            if order == "ORDER" && by == "BY" =>
+        {
+            let (orden, _) = order_by_rec(columnas, Vec::new())?;
+            let es_columna =
+                |clause: &OrderClause| clause.expresion.is_none() && clause.posicion().is_none();
+            if orden.is_empty() || !orden.iter().all(es_columna) {
+                return Err(error());
+            }
+            ventana.orden = orden;
+        }
+        _ => return Err(error()),
+    }
+    // [ROWS, BETWEEN, inicio, AND, fin]
+    match &rest[fin..] {
+        [] => {}
+        [_, marco @ ..] if ventana.funcion.es_agregado() => {
+            ventana.marco = Some(marco_ventana(marco)?)
+        }
+        _ => {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "{} no admite un marco ROWS",
+                ventana.funcion.nombre()
+            )))
+        }
+    }
+    let requiere_orden = matches!(
+        ventana.funcion,
+        FuncionVentana::Rank
+            | FuncionVentana::DenseRank
+            | FuncionVentana::Lag
+            | FuncionVentana::Lead
+    );
+    if requiere_orden && ventana.orden.is_empty() {
+        return Err(ErrorType::InvalidSyntax(format!(
+            "{} requiere ORDER BY en su ventana",
+            ventana.funcion.nombre()
+        )));
+    }
+    Ok(ventana)
+}
+
+/// Procesa los argumentos de una función de ventana: ninguno en ROW_NUMBER, RANK y DENSE_RANK,
+/// `columna [, desplazamiento [, defecto]]` en LAG y LEAD, y una columna (o `*` en COUNT) en los agregados.
+fn argumentos_ventana(
+    funcion: FuncionVentana,
+    argumentos: &[Operador],
+) -> Result<Ventana, ErrorType> {
+    let error = ErrorType::InvalidSyntax(format!("Argumentos invalidos en {}", funcion.nombre()));
+    match (&funcion, argumentos) {
+        (FuncionVentana::RowNumber | FuncionVentana::Rank | FuncionVentana::DenseRank, []) => {
+            Ok(Ventana::new(funcion, None))
+        }
+        (FuncionVentana::Count, [Operador::String(asterisco)]) if asterisco == "*" => {
+            Ok(Ventana::new(funcion, None))
+        }
+        (FuncionVentana::Lag | FuncionVentana::Lead, [Operador::String(columna), extra @ ..])
+            if extra.len() <= 2 =>
+        {
+            let mut ventana = Ventana::new(funcion, Some(columna.to_string()));
+            if let Some(operador) = extra.first() {
+                let Operador::String(desplazamiento) = operador else {
+                    return Err(error);
+                };
+                let Ok(desplazamiento) = desplazamiento.parse::<usize>() else {
+                    return Err(error);
+                };
+                ventana.desplazamiento = desplazamiento;
+            }
+            if let Some(defecto) = extra.get(1) {
+                ventana.defecto = Some(operador_to_dato(defecto)?);
+            }
+            Ok(ventana)
+        }
+        (
+            FuncionVentana::Sum | FuncionVentana::Count | FuncionVentana::Min | FuncionVentana::Max,
+            [Operador::String(columna) | Operador::Texto(columna)],
+        ) if columna != "*" => Ok(Ventana::new(funcion, Some(columna.to_string()))),
+        _ => Err(error),
+    }
+}
+
+/// [BETWEEN, inicio, AND, fin] o [inicio], que termina en la fila actual, al marco de una ventana
+fn marco_ventana(rest: &[Operador]) -> Result<(LimiteMarco, LimiteMarco), ErrorType> {
+    let error = || {
+        ErrorType::InvalidSyntax(
+            "Se esperaba 'ROWS BETWEEN inicio AND fin' en la ventana".to_string(),
+        )
+    };
+    let (inicio, fin) = match rest {
+        [Operador::String(between), rest @ ..] if between == "BETWEEN" => {
+            let Some(and) = rest
+                .iter()
+                .position(|op| matches!(op, Operador::String(and) if and == "AND"))
+            else {
+                return Err(error());
+            };
+            (limite_marco(&rest[..and]), limite_marco(&rest[and + 1..]))
+        }
+        _ => (limite_marco(rest), Some(LimiteMarco::FilaActual)),
+    };
+    match (inicio, fin) {
+        (Some(LimiteMarco::Siguiente(None)), _) | (_, Some(LimiteMarco::Anterior(None))) => {
+            Err(error())
+        }
+        (Some(inicio), Some(fin)) => Ok((inicio, fin)),
+        _ => Err(error()),
+    }
+}
+
+/// [UNBOUNDED | n, PRECEDING | FOLLOWING] o [CURRENT, ROW] a LimiteMarco
+fn limite_marco(rest: &[Operador]) -> Option<LimiteMarco> {
+    let [Operador::String(cantidad), Operador::String(direccion)] = rest else {
+        return None;
+    };
+    let filas = match cantidad.as_str() {
+        "CURRENT" if direccion == "ROW" => return Some(LimiteMarco::FilaActual),
+        "UNBOUNDED" => None,
+        cantidad => Some(cantidad.parse::<usize>().ok()?),
+    };
+    match direccion.as_str() {
+        "PRECEDING" => Some(LimiteMarco::Anterior(filas)),
+        "FOLLOWING" => Some(LimiteMarco::Siguiente(filas)),
+        _ => None,
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    errores::error::ErrorType,
+    lexers::operador::Operador,
+    parsers::{parser::es_subconsulta, parser_conjunto::parse_consulta},
+    queries::{
+        create_materialized_view_query::CreateMaterializedViewQuery,
+        create_view_query::CreateViewQuery, sql_query::SQLQuery, with_query::Consulta,
+    },
+};
+
+/// Procesar la consulta de una vista, que debe ser un SELECT. Si no lo es, devuelve `error`
+fn parse_consulta_vista(rest: &[Operador], error: &str) -> Result<Consulta, ErrorType> {
+    match rest {
+        [Operador::String(select), ..] if select == "SELECT" => parse_consulta(rest),
+        [Operador::Lista(lista), ..] if es_subconsulta(lista) => parse_consulta(rest),
+        _ => Err(ErrorType::InvalidSyntax(error.to_string())),
+    }
+}
+
+/// Procesar CREATE VIEW, cuya consulta debe ser un SELECT
+pub fn parse_create_view_query(nombre: &str, rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
+    let consulta = parse_consulta_vista(rest, "Se esperaba 'CREATE VIEW nombre AS SELECT ...'")?;
+    Ok(SQLQuery::CreateView(CreateViewQuery::new(nombre, consulta)))
+}
+
+/// Procesar CREATE MATERIALIZED VIEW, con `AUTO REFRESH` opcional antes del AS
+pub fn parse_create_materialized_view_query(
+    nombre: &str,
+    rest: &[Operador],
+) -> Result<SQLQuery, ErrorType> {
+    let error = "Se esperaba 'CREATE MATERIALIZED VIEW nombre [AUTO REFRESH] AS SELECT ...'";
+    let (automatica, rest) = match rest {
+        [Operador::String(auto), Operador::String(refresh), Operador::String(as_str), rest @ ..]
+            if auto == "AUTO" && refresh == "REFRESH" && as_str == "AS" =>
+        {
+            (true, rest)
+        }
+        [Operador::String(as_str), rest @ ..] if as_str == "AS" => (false, rest),
+        _ => return Err(ErrorType::InvalidSyntax(error.to_string())),
+    };
+    let consulta = parse_consulta_vista(rest, error)?;
+    Ok(SQLQuery::CreateMaterializedView(
+        CreateMaterializedViewQuery::new(nombre, consulta, automatica),
+    ))
+}
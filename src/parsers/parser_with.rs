@@ -0,0 +1,86 @@
+use crate::{
+    errores::error::ErrorType,
+    lexers::operador::Operador,
+    parsers::{
+        parser::{columns_to_string, es_subconsulta},
+        parser_conjunto::parse_consulta,
+    },
+    queries::{
+        sql_query::SQLQuery,
+        with_query::{Cte, WithQuery},
+    },
+};
+
+/// [nombre, AS, (SELECT, ...), nombre, (columnas), AS, (SELECT, ...), ...]
+/// devuelve las expresiones de un WITH hasta la consulta principal
+fn ctes_rec(rest: &[Operador], mut ctes: Vec<Cte>) -> Result<(Vec<Cte>, &[Operador]), ErrorType> {
+    match rest {
+        [Operador::String(nombre) | Operador::Texto(nombre), Operador::String(as_str), Operador::Lista(consulta), rest @ ..]
+            if as_str == "AS" =>
+        {
+            ctes.push(Cte::new(nombre, None, parse_consulta(consulta)?));
+            ctes_rec(rest, ctes)
+        }
+        [Operador::String(nombre) | Operador::Texto(nombre), Operador::Lista(columnas), Operador::String(as_str), Operador::Lista(consulta), rest @ ..]
+            if as_str == "AS" =>
+        {
+            let columnas = columns_to_string(columnas)?;
+            ctes.push(Cte::new(nombre, Some(columnas), parse_consulta(consulta)?));
+            ctes_rec(rest, ctes)
+        }
+        _ => Ok((ctes, rest)),
+    }
+}
+
+/// [..., OPTION, (MAXRECURSION, n)] devuelve la cantidad máxima de iteraciones indicada al final de un WITH
+fn max_iteraciones(rest: &[Operador]) -> Result<(Option<usize>, &[Operador]), ErrorType> {
+    match rest {
+        [rest @ .., Operador::String(option), Operador::Lista(opcion)] if option == "OPTION" => {
+            match opcion.as_slice() {
+                [Operador::String(max), Operador::String(n)] if max == "MAXRECURSION" => {
+                    match n.parse::<usize>() {
+                        Ok(n) => Ok((Some(n), rest)),
+                        Err(_) => Err(ErrorType::InvalidSyntax(
+                            "MAXRECURSION debe ser un entero no negativo".to_string(),
+                        )),
+                    }
+                }
+                _ => Err(ErrorType::InvalidSyntax(
+                    "Se esperaba 'OPTION (MAXRECURSION n)' al final del WITH".to_string(),
+                )),
+            }
+        }
+        _ => Ok((None, rest)),
+    }
+}
+
+/// Procesar WITH, con sus expresiones seguidas de la consulta principal.
+/// Con RECURSIVE, las expresiones se pueden referenciar a sí mismas.
+pub fn parse_with_query(rest: &[Operador]) -> Result<SQLQuery, ErrorType> {
+    let (recursiva, rest) = match rest {
+        [Operador::String(recursive), rest @ ..] if recursive == "RECURSIVE" => (true, rest),
+        _ => (false, rest),
+    };
+    let (max_iteraciones, rest) = max_iteraciones(rest)?;
+    let (ctes, rest) = ctes_rec(rest, Vec::new())?;
+    if ctes.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Se esperaba 'nombre AS (SELECT ...)' despues del WITH".to_string(),
+        ));
+    }
+    match rest {
+        [Operador::String(select), ..] if select == "SELECT" => {}
+        [Operador::Lista(lista), ..] if es_subconsulta(lista) => {}
+        _ => {
+            return Err(ErrorType::InvalidSyntax(
+                "Después del WITH se esperaba una consulta SELECT".to_string(),
+            ))
+        }
+    }
+    let mut with_query = WithQuery::new(ctes, parse_consulta(rest)?);
+    with_query.recursiva = recursiva;
+    if let Some(max_iteraciones) = max_iteraciones {
+        with_query.max_iteraciones = max_iteraciones;
+    }
+    Ok(SQLQuery::With(with_query))
+}
@@ -0,0 +1,263 @@
+use crate::{
+    dato::Datos,
+    queries::where_clause::{
+        expresion_booleana::ExpresionBooleana, operador_comparacion::OperadorComparacion,
+        valor::Valor,
+    },
+};
+
+use super::{
+    estadisticas::{EstadisticasColumna, EstadisticasTabla},
+    optimizador::{duenio, scans},
+    plan_logico::{PlanLogico, Scan},
+};
+
+/// Fracción de las filas que se supone que cumplen una condición cuyas estadísticas no se conocen.
+const SELECTIVIDAD_DESCONOCIDA: f64 = 1.0 / 3.0;
+
+/// Devuelve la cantidad de filas de la tabla según su último ANALYZE, si se analizó.
+pub fn filas_scan(scan: &Scan) -> Option<f64> {
+    scan.estadisticas.as_ref().map(|e| e.filas as f64)
+}
+
+/// Devuelve las estadísticas de la tabla a la que pertenece la columna y las de la columna, si se conocen.
+fn estadisticas_columna<'a>(
+    columna: &str,
+    scans: &'a [Scan],
+) -> Option<(&'a EstadisticasTabla, &'a EstadisticasColumna)> {
+    let tabla = scans[duenio(columna, scans)?].estadisticas.as_ref()?;
+    let nombre = columna.split_once('.').map_or(columna, |(_, c)| c);
+    Some((tabla, tabla.columna(nombre)?))
+}
+
+/// Devuelve el dato de un valor escrito en la consulta, si lo es.
+fn constante(valor: &Valor) -> Option<Datos> {
+    match valor {
+        Valor::String(s) => s.parse::<i64>().ok().map(Datos::Integer),
+        Valor::Literal(texto) => Some(Datos::String(texto.to_string())),
+        _ => None,
+    }
+}
+
+/// Devuelve la fracción de las filas de la tabla en las que la columna no es nula.
+fn no_nulos(tabla: &EstadisticasTabla, columna: &EstadisticasColumna) -> f64 {
+    if tabla.filas == 0 {
+        return 0.0;
+    }
+    1.0 - columna.nulos as f64 / tabla.filas as f64
+}
+
+/// Devuelve la fracción de las filas de una columna numérica con un valor menor al dado,
+/// suponiendo que los valores de cada cubeta del histograma se reparten uniformemente.
+fn fraccion_menor(
+    tabla: &EstadisticasTabla,
+    columna: &EstadisticasColumna,
+    valor: i64,
+) -> Option<f64> {
+    if columna.histograma.is_empty() || tabla.filas == 0 {
+        return None;
+    }
+    let mut menores = 0.0;
+    for cubeta in &columna.histograma {
+        if valor > cubeta.hasta {
+            menores += cubeta.filas as f64;
+        } else if valor > cubeta.desde {
+            let ancho = (cubeta.hasta - cubeta.desde + 1) as f64;
+            menores += cubeta.filas as f64 * (valor - cubeta.desde) as f64 / ancho;
+        }
+    }
+    Some(menores / tabla.filas as f64)
+}
+
+/// Estima la fracción de las filas en las que la columna cumple la comparación con la constante.
+fn selectividad_constante(
+    tabla: &EstadisticasTabla,
+    columna: &EstadisticasColumna,
+    operador: &OperadorComparacion,
+    dato: &Datos,
+) -> f64 {
+    let fuera_de_rango = match (&columna.minimo, &columna.maximo, dato) {
+        (Some(Datos::Integer(minimo)), Some(Datos::Integer(maximo)), Datos::Integer(valor)) => {
+            valor < minimo || valor > maximo
+        }
+        (None, None, _) => true,
+        _ => false,
+    };
+    let igual = if fuera_de_rango {
+        0.0
+    } else {
+        no_nulos(tabla, columna) / columna.distintos.max(1) as f64
+    };
+    let Datos::Integer(valor) = dato else {
+        return match operador {
+            OperadorComparacion::Igual => igual,
+            _ => SELECTIVIDAD_DESCONOCIDA,
+        };
+    };
+    let menor = |valor: i64| fraccion_menor(tabla, columna, valor);
+    let estimada = match operador {
+        OperadorComparacion::Igual => Some(igual),
+        OperadorComparacion::Menor => menor(*valor),
+        OperadorComparacion::MenorIgual => menor(valor.saturating_add(1)),
+        OperadorComparacion::Mayor => {
+            menor(valor.saturating_add(1)).map(|m| no_nulos(tabla, columna) - m)
+        }
+        OperadorComparacion::MayorIgual => menor(*valor).map(|m| no_nulos(tabla, columna) - m),
+    };
+    estimada.unwrap_or(SELECTIVIDAD_DESCONOCIDA)
+}
+
+/// Devuelve el operador que compara los valores en el orden inverso: `a < b` es `b > a`.
+fn invertir(operador: &OperadorComparacion) -> OperadorComparacion {
+    match operador {
+        OperadorComparacion::Igual => OperadorComparacion::Igual,
+        OperadorComparacion::Menor => OperadorComparacion::Mayor,
+        OperadorComparacion::MenorIgual => OperadorComparacion::MayorIgual,
+        OperadorComparacion::Mayor => OperadorComparacion::Menor,
+        OperadorComparacion::MayorIgual => OperadorComparacion::MenorIgual,
+    }
+}
+
+/// Estima la fracción de las filas de las tablas combinadas que cumplen la condición, con las estadísticas
+/// de cada tabla: una igualdad con una constante la cumple una de cada tantas filas como valores distintos
+/// tenga la columna, un rango según el histograma, y una igualdad entre columnas según la que tenga más
+/// valores distintos. Las partes de un AND o un OR se suponen independientes.
+pub fn selectividad(condicion: &ExpresionBooleana, scans: &[Scan]) -> f64 {
+    let estimada = match condicion {
+        ExpresionBooleana::Comparacion { izq, operador, der } => {
+            let columna = |valor: &Valor| match valor {
+                Valor::String(nombre) if constante(valor).is_none() => {
+                    estadisticas_columna(nombre, scans)
+                }
+                _ => None,
+            };
+            match (columna(izq), columna(der), constante(izq), constante(der)) {
+                (Some((tabla, columna)), None, None, Some(dato)) => {
+                    selectividad_constante(tabla, columna, operador, &dato)
+                }
+                (None, Some((tabla, columna)), Some(dato), None) => {
+                    selectividad_constante(tabla, columna, &invertir(operador), &dato)
+                }
+                (Some((tabla1, columna1)), Some((tabla2, columna2)), _, _)
+                    if *operador == OperadorComparacion::Igual =>
+                {
+                    let distintos = columna1.distintos.max(columna2.distintos).max(1);
+                    no_nulos(tabla1, columna1) * no_nulos(tabla2, columna2) / distintos as f64
+                }
+                _ => SELECTIVIDAD_DESCONOCIDA,
+            }
+        }
+        ExpresionBooleana::And(expr1, expr2) => {
+            selectividad(expr1, scans) * selectividad(expr2, scans)
+        }
+        ExpresionBooleana::Or(expr1, expr2) => {
+            let (s1, s2) = (selectividad(expr1, scans), selectividad(expr2, scans));
+            s1 + s2 - s1 * s2
+        }
+        ExpresionBooleana::Not(expr) => 1.0 - selectividad(expr, scans),
+        ExpresionBooleana::In { .. } | ExpresionBooleana::Exists(_) => SELECTIVIDAD_DESCONOCIDA,
+    };
+    estimada.clamp(0.0, 1.0)
+}
+
+/// Estima la cantidad de filas que devuelve el plan, si se analizaron todas las tablas que lee.
+/// Una combinación de SELECT no se estima.
+pub fn filas_estimadas(plan: &PlanLogico) -> Option<f64> {
+    match plan {
+        PlanLogico::Scan(scan) => filas_scan(scan),
+        PlanLogico::Filter { condicion, entrada } => {
+            Some(filas_estimadas(entrada)? * selectividad(condicion, &scans(entrada)))
+        }
+        PlanLogico::Join {
+            condicion,
+            izq,
+            der,
+        } => {
+            let combinadas = filas_estimadas(izq)? * filas_estimadas(der)?;
+            Some(combinadas * selectividad(condicion, &scans(plan)))
+        }
+        PlanLogico::Limit { cantidad, entrada } => {
+            Some(filas_estimadas(entrada)?.min(*cantidad as f64))
+        }
+        PlanLogico::Aggregate { .. } => Some(1.0),
+        PlanLogico::Conjunto { .. } => None,
+        plan => plan.entrada().and_then(filas_estimadas),
+    }
+}
+
+/// Estima las filas que devuelve cada JOIN del plan de un SELECT, en el orden en que se combinan las tablas.
+pub fn filas_por_join(plan: &PlanLogico) -> Vec<Option<f64>> {
+    match plan {
+        PlanLogico::Join { izq, .. } => {
+            let mut filas = filas_por_join(izq);
+            filas.push(filas_estimadas(plan));
+            filas
+        }
+        PlanLogico::Scan(_) | PlanLogico::Conjunto { .. } => Vec::new(),
+        plan => plan.entrada().map(filas_por_join).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::selectividad;
+    use crate::{
+        dato::Datos,
+        lexers::lexer::lexer,
+        parsers::parser::parse_condicion,
+        plan::{
+            estadisticas::{Cubeta, EstadisticasColumna, EstadisticasTabla},
+            plan_logico::Scan,
+        },
+    };
+
+    #[test]
+    fn test_selectividad() {
+        let cubeta = |desde, hasta, filas| Cubeta {
+            desde,
+            hasta,
+            filas,
+        };
+        let scan = Scan {
+            tabla: "ordenes".to_string(),
+            alias: None,
+            columnas: Some(vec!["cantidad".to_string()]),
+            leidas: None,
+            estadisticas: Some(EstadisticasTabla {
+                tabla: "ordenes".to_string(),
+                filas: 100,
+                columnas: vec![EstadisticasColumna {
+                    columna: "cantidad".to_string(),
+                    distintos: 20,
+                    nulos: 20,
+                    minimo: Some(Datos::Integer(1)),
+                    maximo: Some(Datos::Integer(20)),
+                    histograma: vec![cubeta(1, 10, 60), cubeta(11, 20, 20)],
+                }],
+            }),
+        };
+        let casos = [
+            ("cantidad = 5", 0.04),
+            ("cantidad = 50", 0.0),
+            ("cantidad < 6", 0.3),
+            ("cantidad > 10", 0.2),
+            ("5 >= cantidad", 0.3),
+            ("NOT cantidad > 10", 0.8),
+            ("cantidad < 6 AND cantidad > 10", 0.06),
+            ("nombre = 'Ana'", 1.0 / 3.0),
+        ];
+        for (texto, esperada) in casos {
+            let condicion = match lexer(&texto.to_string()).and_then(|t| parse_condicion(&t)) {
+                Ok(condicion) => condicion,
+                Err(e) => panic!("Condición inválida: {}", e),
+            };
+            let estimada = selectividad(&condicion, std::slice::from_ref(&scan));
+            assert!(
+                (estimada - esperada).abs() < 1e-9,
+                "{}: {}",
+                texto,
+                estimada
+            );
+        }
+    }
+}
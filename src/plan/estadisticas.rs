@@ -0,0 +1,445 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap},
+    fs,
+    hash::{Hash, Hasher},
+    io::BufRead,
+    path::Path,
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    executer::{
+        conjuntos::{abrir_para_agregar, escribir, terminar},
+        manejo_csv::{
+            agregar_path, crear_archivo, get_reader, leer_encabezado, nombre_auxiliar,
+            string_to_columns,
+        },
+    },
+    utils::dato_to_string,
+};
+
+/// Nombre con el que se consultan las estadísticas de todas las tablas, como si fueran una tabla.
+pub const CATALOGO_ESTADISTICAS: &str = "estadisticas";
+
+/// Cantidad de cubetas del histograma de una columna numérica.
+const CUBETAS_HISTOGRAMA: i128 = 10;
+
+/// Cantidad de hashes más chicos que se guardan para estimar los valores distintos de una columna.
+const HASHES_DISTINTOS: usize = 1024;
+
+/// Encabezado del archivo de estadísticas de una tabla: una fila por columna.
+const ENCABEZADO: &str = "columna,filas,distintos,nulos,minimo,maximo,histograma";
+
+/// Genera la ruta del archivo donde se guardan las estadísticas de una tabla, calculadas por el último ANALYZE.
+pub fn ruta_estadisticas(path: &str, tabla: &str) -> String {
+    format!("{}/{}.estadisticas", path, tabla)
+}
+
+/// Devuelve si el nombre es el del catálogo de estadísticas: no hay una tabla ni una vista que se llame así.
+pub fn es_catalogo(path: &str, nombre: &String) -> bool {
+    nombre == CATALOGO_ESTADISTICAS && !Path::new(&agregar_path(path, nombre)).exists()
+}
+
+/// Rango de valores de un histograma, con la cantidad de filas de la columna que tienen un valor en él.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cubeta {
+    pub desde: i64,
+    pub hasta: i64,
+    pub filas: usize,
+}
+
+/// Estadísticas de una columna. Los nulos son los valores vacíos, que no cuentan para el resto.
+/// Solo las columnas numéricas tienen histograma, con rangos del mismo ancho entre el mínimo y el máximo.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EstadisticasColumna {
+    pub columna: String,
+    pub distintos: usize,
+    pub nulos: usize,
+    pub minimo: Option<Datos>,
+    pub maximo: Option<Datos>,
+    pub histograma: Vec<Cubeta>,
+}
+
+/// Estadísticas de una tabla, que usa el optimizador para estimar cuántas filas devuelve cada operador.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EstadisticasTabla {
+    pub tabla: String,
+    pub filas: usize,
+    pub columnas: Vec<EstadisticasColumna>,
+}
+
+/// Estima la cantidad de valores distintos guardando solo los `HASHES_DISTINTOS` hashes más chicos:
+/// si los valores se reparten uniformemente, el k-ésimo hash más chico indica cuántos hay.
+struct EstimadorDistintos {
+    hashes: BTreeSet<u64>,
+}
+
+impl EstimadorDistintos {
+    fn new() -> Self {
+        EstimadorDistintos {
+            hashes: BTreeSet::new(),
+        }
+    }
+
+    fn agregar(&mut self, valor: &str) {
+        let mut hasher = DefaultHasher::new();
+        valor.hash(&mut hasher);
+        self.hashes.insert(hasher.finish());
+        if self.hashes.len() > HASHES_DISTINTOS {
+            self.hashes.pop_last();
+        }
+    }
+
+    /// Devuelve la cantidad exacta de valores si hay menos que hashes guardados, o su estimación si no.
+    fn estimar(&self) -> usize {
+        match self.hashes.last() {
+            Some(&mayor) if self.hashes.len() == HASHES_DISTINTOS => {
+                let fraccion = (mayor as f64 + 1.0) / (u64::MAX as f64 + 1.0);
+                ((HASHES_DISTINTOS - 1) as f64 / fraccion).round() as usize
+            }
+            _ => self.hashes.len(),
+        }
+    }
+}
+
+/// Devuelve si el primer dato es menor que el segundo: como números si los dos lo son, o como texto.
+fn es_menor(dato1: &Datos, dato2: &Datos) -> bool {
+    match (dato1, dato2) {
+        (Datos::Integer(entero1), Datos::Integer(entero2)) => entero1 < entero2,
+        _ => dato_to_string(dato1) < dato_to_string(dato2),
+    }
+}
+
+/// Valores de una columna vistos al recorrer la tabla.
+struct Acumulador {
+    distintos: EstimadorDistintos,
+    nulos: usize,
+    minimo: Option<Datos>,
+    maximo: Option<Datos>,
+    numerica: bool,
+}
+
+impl Acumulador {
+    fn new() -> Self {
+        Acumulador {
+            distintos: EstimadorDistintos::new(),
+            nulos: 0,
+            minimo: None,
+            maximo: None,
+            numerica: true,
+        }
+    }
+
+    fn agregar(&mut self, dato: Datos) {
+        let texto = dato_to_string(&dato);
+        if texto.is_empty() {
+            self.nulos += 1;
+            return;
+        }
+        self.distintos.agregar(&texto);
+        self.numerica &= matches!(dato, Datos::Integer(_));
+        if self
+            .minimo
+            .as_ref()
+            .is_none_or(|minimo| es_menor(&dato, minimo))
+        {
+            self.minimo = Some(dato.clone());
+        }
+        if self
+            .maximo
+            .as_ref()
+            .is_none_or(|maximo| es_menor(maximo, &dato))
+        {
+            self.maximo = Some(dato);
+        }
+    }
+}
+
+/// Devuelve las cubetas vacías del histograma de una columna numérica con esos valores mínimo y máximo.
+fn cubetas_vacias(minimo: i64, maximo: i64) -> Vec<Cubeta> {
+    let ancho = ((maximo as i128 - minimo as i128) / CUBETAS_HISTOGRAMA) + 1;
+    let mut cubetas = Vec::new();
+    let mut desde = minimo as i128;
+    while desde <= maximo as i128 {
+        let hasta = (desde + ancho - 1).min(maximo as i128);
+        cubetas.push(Cubeta {
+            desde: desde as i64,
+            hasta: hasta as i64,
+            filas: 0,
+        });
+        desde = hasta + 1;
+    }
+    cubetas
+}
+
+/// Muestra el histograma como sus cubetas `desde..hasta:filas` separadas por `;`, para guardarlo en una columna.
+fn mostrar_histograma(histograma: &[Cubeta]) -> String {
+    let cubetas: Vec<String> = histograma
+        .iter()
+        .map(|c| format!("{}..{}:{}", c.desde, c.hasta, c.filas))
+        .collect();
+    cubetas.join(";")
+}
+
+/// Lee un histograma escrito por `mostrar_histograma`.
+fn leer_histograma(texto: &str) -> Option<Vec<Cubeta>> {
+    if texto.is_empty() {
+        return Some(Vec::new());
+    }
+    texto
+        .split(';')
+        .map(|cubeta| {
+            let (rango, filas) = cubeta.split_once(':')?;
+            let (desde, hasta) = rango.split_once("..")?;
+            Some(Cubeta {
+                desde: desde.parse().ok()?,
+                hasta: hasta.parse().ok()?,
+                filas: filas.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Devuelve el error de un archivo de estadísticas que no se puede leer.
+fn error_archivo(tabla: &str) -> ErrorType {
+    ErrorType::InvalidTable(format!(
+        "El archivo de estadísticas de la tabla '{}' es invalido",
+        tabla
+    ))
+}
+
+/// Recorre las filas de la tabla, aplicando `visitar` a cada una con sus datos por columna.
+/// Devuelve las columnas de la tabla.
+fn recorrer_tabla(
+    path: &str,
+    tabla: &String,
+    visitar: &mut dyn FnMut(HashMap<String, Datos>),
+) -> Result<Vec<String>, ErrorType> {
+    let mut lines = get_reader(&agregar_path(path, tabla))?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    for line in lines {
+        let Ok(line) = line else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        visitar(string_to_columns(&line, &columnas)?);
+    }
+    Ok(columnas)
+}
+
+impl EstadisticasTabla {
+    /// Calcula las estadísticas de cada columna de la tabla. La recorre una vez para contar sus filas,
+    /// nulos y valores distintos y encontrar el mínimo y el máximo, y otra para armar los histogramas.
+    pub fn calcular(path: &str, tabla: &String) -> Result<EstadisticasTabla, ErrorType> {
+        let mut filas = 0;
+        let mut acumuladores: HashMap<String, Acumulador> = HashMap::new();
+        let columnas = recorrer_tabla(path, tabla, &mut |fila| {
+            filas += 1;
+            for (columna, dato) in fila {
+                acumuladores
+                    .entry(columna)
+                    .or_insert_with(Acumulador::new)
+                    .agregar(dato);
+            }
+        })?;
+        let mut histogramas: HashMap<String, Vec<Cubeta>> = HashMap::new();
+        for (columna, acumulador) in &acumuladores {
+            if let (true, Some(Datos::Integer(minimo)), Some(Datos::Integer(maximo))) =
+                (acumulador.numerica, &acumulador.minimo, &acumulador.maximo)
+            {
+                histogramas.insert(columna.to_string(), cubetas_vacias(*minimo, *maximo));
+            }
+        }
+        if !histogramas.is_empty() {
+            recorrer_tabla(path, tabla, &mut |fila| {
+                for (columna, cubetas) in histogramas.iter_mut() {
+                    let Some(Datos::Integer(valor)) = fila.get(columna) else {
+                        continue;
+                    };
+                    if let Some(cubeta) = cubetas
+                        .iter_mut()
+                        .find(|c| c.desde <= *valor && *valor <= c.hasta)
+                    {
+                        cubeta.filas += 1;
+                    }
+                }
+            })?;
+        }
+        let columnas = columnas
+            .into_iter()
+            .map(|columna| {
+                let acumulador = acumuladores
+                    .remove(&columna)
+                    .unwrap_or_else(Acumulador::new);
+                EstadisticasColumna {
+                    histograma: histogramas.remove(&columna).unwrap_or_default(),
+                    columna,
+                    distintos: acumulador.distintos.estimar(),
+                    nulos: acumulador.nulos,
+                    minimo: acumulador.minimo,
+                    maximo: acumulador.maximo,
+                }
+            })
+            .collect();
+        Ok(EstadisticasTabla {
+            tabla: tabla.to_string(),
+            filas,
+            columnas,
+        })
+    }
+
+    /// Devuelve las estadísticas de la columna, si se calcularon.
+    pub fn columna(&self, nombre: &str) -> Option<&EstadisticasColumna> {
+        self.columnas.iter().find(|c| c.columna == nombre)
+    }
+
+    /// Escribe las estadísticas en el archivo `.estadisticas` de la tabla, reemplazando las anteriores.
+    pub fn guardar(&self, path: &str) -> Result<(), ErrorType> {
+        let mut contenido = format!("{}\n", ENCABEZADO);
+        for columna in &self.columnas {
+            let mostrar =
+                |dato: &Option<Datos>| dato.as_ref().map(dato_to_string).unwrap_or_default();
+            contenido.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                columna.columna,
+                self.filas,
+                columna.distintos,
+                columna.nulos,
+                mostrar(&columna.minimo),
+                mostrar(&columna.maximo),
+                mostrar_histograma(&columna.histograma)
+            ));
+        }
+        match fs::write(ruta_estadisticas(path, &self.tabla), contenido) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ErrorType::InvalidTable(
+                "Error al guardar las estadísticas de la tabla".to_string(),
+            )),
+        }
+    }
+
+    /// Lee las estadísticas de la tabla desde su archivo `.estadisticas`.
+    /// Devuelve `None` si nunca se analizó la tabla.
+    pub fn cargar(path: &str, tabla: &str) -> Result<Option<EstadisticasTabla>, ErrorType> {
+        let ruta = ruta_estadisticas(path, tabla);
+        if !Path::new(&ruta).exists() {
+            return Ok(None);
+        }
+        let Ok(contenido) = fs::read_to_string(&ruta) else {
+            return Err(error_archivo(tabla));
+        };
+        let columnas: Vec<String> = ENCABEZADO.split(',').map(|c| c.to_string()).collect();
+        let mut estadisticas = EstadisticasTabla {
+            tabla: tabla.to_string(),
+            filas: 0,
+            columnas: Vec::new(),
+        };
+        for linea in contenido.lines().skip(1).filter(|l| !l.is_empty()) {
+            let mut fila = string_to_columns(linea, &columnas).map_err(|_| error_archivo(tabla))?;
+            let mut texto = |columna: &str| fila.remove(columna).map(|d| dato_to_string(&d));
+            let numero = |texto: Option<String>| texto.and_then(|t| t.parse::<usize>().ok());
+            let (Some(columna), Some(filas), Some(distintos), Some(nulos), Some(histograma)) = (
+                texto("columna"),
+                numero(texto("filas")),
+                numero(texto("distintos")),
+                numero(texto("nulos")),
+                texto("histograma").and_then(|h| leer_histograma(&h)),
+            ) else {
+                return Err(error_archivo(tabla));
+            };
+            let mut dato = |columna: &str| {
+                fila.remove(columna)
+                    .filter(|d| !dato_to_string(d).is_empty())
+            };
+            estadisticas.filas = filas;
+            estadisticas.columnas.push(EstadisticasColumna {
+                columna,
+                distintos,
+                nulos,
+                minimo: dato("minimo"),
+                maximo: dato("maximo"),
+                histograma,
+            });
+        }
+        Ok(Some(estadisticas))
+    }
+}
+
+/// Escribe en un archivo auxiliar el catálogo de estadísticas: las de cada columna de cada tabla analizada,
+/// ordenadas por tabla. Devuelve el nombre del archivo, que se lee como una tabla.
+pub fn escribir_catalogo(path: &str) -> Result<String, ErrorType> {
+    let Ok(entradas) = fs::read_dir(path) else {
+        return Err(ErrorType::InvalidTable(
+            "Error al leer la carpeta de las tablas".to_string(),
+        ));
+    };
+    let mut tablas: Vec<String> = entradas
+        .filter_map(|entrada| entrada.ok())
+        .filter_map(|entrada| {
+            let nombre = entrada.file_name().to_string_lossy().to_string();
+            nombre.strip_suffix(".estadisticas").map(|t| t.to_string())
+        })
+        .collect();
+    tablas.sort();
+    let nombre_aux = nombre_auxiliar("auxiliar");
+    let salida = agregar_path(path, &nombre_aux);
+    crear_archivo(&salida)?;
+    let mut escritor = abrir_para_agregar(&salida)?;
+    escribir(&mut escritor, &format!("tabla,{}", ENCABEZADO))?;
+    for tabla in tablas {
+        let Ok(contenido) = fs::read_to_string(ruta_estadisticas(path, &tabla)) else {
+            return Err(error_archivo(&tabla));
+        };
+        for linea in contenido.lines().skip(1).filter(|l| !l.is_empty()) {
+            escribir(&mut escritor, &format!("{},{}", tabla, linea))?;
+        }
+    }
+    terminar(escritor)?;
+    Ok(nombre_aux)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cubetas_vacias, leer_histograma, mostrar_histograma, EstimadorDistintos};
+
+    #[test]
+    fn test_estimador_distintos() {
+        let mut estimador = EstimadorDistintos::new();
+        for i in 0..500 {
+            estimador.agregar(&(i % 100).to_string());
+        }
+        assert_eq!(estimador.estimar(), 100);
+
+        let mut estimador = EstimadorDistintos::new();
+        for i in 0..20_000 {
+            estimador.agregar(&i.to_string());
+        }
+        let estimados = estimador.estimar();
+        assert!((16_000..24_000).contains(&estimados), "{}", estimados);
+    }
+
+    #[test]
+    fn test_histograma() {
+        let cubetas = cubetas_vacias(1, 25);
+        assert_eq!(cubetas.len(), 9);
+        assert_eq!((cubetas[0].desde, cubetas[0].hasta), (1, 3));
+        assert_eq!((cubetas[8].desde, cubetas[8].hasta), (25, 25));
+
+        let cubetas = cubetas_vacias(7, 7);
+        assert_eq!(cubetas.len(), 1);
+        assert_eq!(
+            leer_histograma(&mostrar_histograma(&cubetas)),
+            Some(cubetas)
+        );
+
+        let cubetas = cubetas_vacias(-20, 0);
+        assert_eq!(mostrar_histograma(&cubetas[..2]), "-20..-18:0;-17..-15:0");
+        assert_eq!(
+            leer_histograma(&mostrar_histograma(&cubetas)),
+            Some(cubetas)
+        );
+        assert_eq!(leer_histograma("1..x:2"), None);
+    }
+}
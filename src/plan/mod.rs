@@ -0,0 +1,4 @@
+pub mod costos;
+pub mod estadisticas;
+pub mod optimizador;
+pub mod plan_logico;
@@ -0,0 +1,974 @@
+use std::collections::HashMap;
+
+use crate::{
+    dato::Datos,
+    queries::{
+        order_clause::OrderClause,
+        where_clause::{
+            comparadores::evaluar_valor, evaluar::Evaluar, expresion_booleana::ExpresionBooleana,
+            funciones::es_funcion_escalar, valor::Valor,
+        },
+    },
+};
+
+use super::{
+    costos::{filas_scan, selectividad},
+    plan_logico::{PlanLogico, Scan},
+};
+
+/// Resultado de simplificar una expresión booleana: si vale lo mismo en todas las filas, se indica cuál.
+/// Una expresión siempre falsa se conserva para poder evaluarla.
+#[derive(Debug, PartialEq, Clone)]
+enum Simplificada {
+    Verdadera(ExpresionBooleana),
+    Falsa(ExpresionBooleana),
+    Variable(ExpresionBooleana),
+}
+
+impl Simplificada {
+    /// Devuelve la expresión simplificada, o `None` si es verdadera en todas las filas.
+    fn condicion(self) -> Option<ExpresionBooleana> {
+        match self {
+            Simplificada::Verdadera(_) => None,
+            Simplificada::Falsa(expresion) | Simplificada::Variable(expresion) => Some(expresion),
+        }
+    }
+
+    /// Devuelve la expresión simplificada, aunque sea verdadera en todas las filas.
+    fn expresion(self) -> ExpresionBooleana {
+        match self {
+            Simplificada::Verdadera(expresion)
+            | Simplificada::Falsa(expresion)
+            | Simplificada::Variable(expresion) => expresion,
+        }
+    }
+}
+
+/// Optimiza el plan sin cambiar su resultado: calcula las operaciones entre constantes, simplifica
+/// las condiciones, combina las tablas de los JOIN en el orden más barato según sus estadísticas,
+/// filtra las filas de cada tabla antes de combinarlas y descarta las columnas que la consulta no usa.
+pub fn optimizar(plan: PlanLogico) -> PlanLogico {
+    let plan = ordenar_joins(simplificar_condiciones(plan));
+    podar_columnas(empujar_predicados(plan))
+}
+
+/// Devuelve si el valor es el mismo en todas las filas: un número o un texto escrito en la consulta.
+fn es_constante(valor: &Valor) -> bool {
+    match valor {
+        Valor::String(s) => s.parse::<i64>().is_ok(),
+        Valor::Literal(_) => true,
+        _ => false,
+    }
+}
+
+/// Devuelve el valor escrito en una consulta que representa al dato.
+fn valor_constante(dato: Datos) -> Valor {
+    match dato {
+        Datos::Integer(entero) => Valor::String(entero.to_string()),
+        Datos::String(texto) => Valor::Literal(texto),
+    }
+}
+
+/// Reemplaza cada operación aritmética y cada función escalar cuyos operandos son constantes por su resultado.
+/// Si calcularla da un error, como al dividir por cero, se deja sin calcular para que el error
+/// aparezca al ejecutar la consulta.
+pub fn plegar_constantes(valor: Valor) -> Valor {
+    let plegado = match valor {
+        Valor::Operacion { izq, operador, der } => Valor::Operacion {
+            izq: Box::new(plegar_constantes(*izq)),
+            operador,
+            der: Box::new(plegar_constantes(*der)),
+        },
+        Valor::Funcion { nombre, argumentos } => Valor::Funcion {
+            nombre,
+            argumentos: argumentos.into_iter().map(plegar_constantes).collect(),
+        },
+        valor => return valor,
+    };
+    let constante = match &plegado {
+        Valor::Operacion { izq, der, .. } => es_constante(izq) && es_constante(der),
+        Valor::Funcion { nombre, argumentos } => {
+            es_funcion_escalar(nombre) && argumentos.iter().all(es_constante)
+        }
+        _ => false,
+    };
+    if !constante {
+        return plegado;
+    }
+    match evaluar_valor(&plegado, &HashMap::new()) {
+        Ok(dato) => valor_constante(dato),
+        Err(_) => plegado,
+    }
+}
+
+/// Simplifica la expresión: calcula sus operaciones entre constantes, evalúa las comparaciones entre constantes
+/// y elimina las partes de un AND o un OR que no cambian su resultado, las repetidas y las dobles negaciones.
+fn simplificar(expresion: ExpresionBooleana) -> Simplificada {
+    match expresion {
+        ExpresionBooleana::Comparacion { izq, operador, der } => {
+            let izq = plegar_constantes(izq);
+            let der = plegar_constantes(der);
+            let constante = es_constante(&izq) && es_constante(&der);
+            let comparacion = ExpresionBooleana::Comparacion { izq, operador, der };
+            if !constante {
+                return Simplificada::Variable(comparacion);
+            }
+            match comparacion.evaluar(&HashMap::new()) {
+                Ok(true) => Simplificada::Verdadera(comparacion),
+                Ok(false) => Simplificada::Falsa(comparacion),
+                Err(_) => Simplificada::Variable(comparacion),
+            }
+        }
+        ExpresionBooleana::In { valor, subconsulta } => {
+            Simplificada::Variable(ExpresionBooleana::In {
+                valor: plegar_constantes(valor),
+                subconsulta,
+            })
+        }
+        ExpresionBooleana::Exists(_) => Simplificada::Variable(expresion),
+        ExpresionBooleana::And(expr1, expr2) => match (simplificar(*expr1), simplificar(*expr2)) {
+            (Simplificada::Verdadera(_), otra) | (otra, Simplificada::Verdadera(_)) => otra,
+            (Simplificada::Falsa(falsa), _) | (_, Simplificada::Falsa(falsa)) => {
+                Simplificada::Falsa(falsa)
+            }
+            (Simplificada::Variable(expr1), Simplificada::Variable(expr2)) if expr1 == expr2 => {
+                Simplificada::Variable(expr1)
+            }
+            (expr1, expr2) => Simplificada::Variable(ExpresionBooleana::And(
+                Box::new(expr1.expresion()),
+                Box::new(expr2.expresion()),
+            )),
+        },
+        ExpresionBooleana::Or(expr1, expr2) => match (simplificar(*expr1), simplificar(*expr2)) {
+            (Simplificada::Verdadera(verdadera), _) | (_, Simplificada::Verdadera(verdadera)) => {
+                Simplificada::Verdadera(verdadera)
+            }
+            (Simplificada::Falsa(_), otra) | (otra, Simplificada::Falsa(_)) => otra,
+            (Simplificada::Variable(expr1), Simplificada::Variable(expr2)) if expr1 == expr2 => {
+                Simplificada::Variable(expr1)
+            }
+            (expr1, expr2) => Simplificada::Variable(ExpresionBooleana::Or(
+                Box::new(expr1.expresion()),
+                Box::new(expr2.expresion()),
+            )),
+        },
+        ExpresionBooleana::Not(expr) => match simplificar(*expr) {
+            Simplificada::Verdadera(expr) => {
+                Simplificada::Falsa(ExpresionBooleana::Not(Box::new(expr)))
+            }
+            Simplificada::Falsa(expr) => {
+                Simplificada::Verdadera(ExpresionBooleana::Not(Box::new(expr)))
+            }
+            Simplificada::Variable(ExpresionBooleana::Not(expr)) => Simplificada::Variable(*expr),
+            Simplificada::Variable(expr) => {
+                Simplificada::Variable(ExpresionBooleana::Not(Box::new(expr)))
+            }
+        },
+    }
+}
+
+/// Simplifica la condición, o devuelve `None` si es verdadera en todas las filas.
+pub fn simplificar_condicion(condicion: ExpresionBooleana) -> Option<ExpresionBooleana> {
+    simplificar(condicion).condicion()
+}
+
+/// Aplica `optimizar` a cada operador del que el plan recibe filas.
+fn optimizar_entradas(
+    plan: PlanLogico,
+    optimizar: &dyn Fn(PlanLogico) -> PlanLogico,
+) -> PlanLogico {
+    let optimizar_box = |plan: Box<PlanLogico>| Box::new(optimizar(*plan));
+    match plan {
+        PlanLogico::Scan(scan) => PlanLogico::Scan(scan),
+        PlanLogico::Filter { condicion, entrada } => PlanLogico::Filter {
+            condicion,
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Join {
+            condicion,
+            izq,
+            der,
+        } => PlanLogico::Join {
+            condicion,
+            izq: optimizar_box(izq),
+            der: optimizar_box(der),
+        },
+        PlanLogico::Project {
+            columnas,
+            calculadas,
+            entrada,
+        } => PlanLogico::Project {
+            columnas,
+            calculadas,
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Sort { orden, entrada } => PlanLogico::Sort {
+            orden,
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Limit { cantidad, entrada } => PlanLogico::Limit {
+            cantidad,
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Aggregate { conteos, entrada } => PlanLogico::Aggregate {
+            conteos,
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Distinct { entrada } => PlanLogico::Distinct {
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Window { ventanas, entrada } => PlanLogico::Window {
+            ventanas,
+            entrada: optimizar_box(entrada),
+        },
+        PlanLogico::Conjunto { operador, izq, der } => PlanLogico::Conjunto {
+            operador,
+            izq: optimizar_box(izq),
+            der: optimizar_box(der),
+        },
+    }
+}
+
+/// Simplifica la condición de cada filtro y cada JOIN. Un filtro siempre verdadero se elimina;
+/// un JOIN conserva su condición aunque sea siempre verdadera.
+fn simplificar_condiciones(plan: PlanLogico) -> PlanLogico {
+    match optimizar_entradas(plan, &simplificar_condiciones) {
+        PlanLogico::Filter { condicion, entrada } => match simplificar_condicion(condicion) {
+            Some(condicion) => PlanLogico::Filter { condicion, entrada },
+            None => *entrada,
+        },
+        PlanLogico::Join {
+            condicion,
+            izq,
+            der,
+        } => PlanLogico::Join {
+            condicion: simplificar(condicion).expresion(),
+            izq,
+            der,
+        },
+        plan => plan,
+    }
+}
+
+/// Separa la condición en las partes unidas por AND, que se pueden evaluar por separado.
+fn separar_and(condicion: ExpresionBooleana, partes: &mut Vec<ExpresionBooleana>) {
+    match condicion {
+        ExpresionBooleana::And(expr1, expr2) => {
+            separar_and(*expr1, partes);
+            separar_and(*expr2, partes);
+        }
+        condicion => partes.push(condicion),
+    }
+}
+
+/// Une las condiciones con AND, o devuelve `None` si no hay ninguna.
+fn unir_and(condiciones: Vec<ExpresionBooleana>) -> Option<ExpresionBooleana> {
+    condiciones
+        .into_iter()
+        .reduce(|expr1, expr2| ExpresionBooleana::And(Box::new(expr1), Box::new(expr2)))
+}
+
+/// Devuelve las lecturas de tablas que combinan los JOIN del plan, en orden.
+pub fn scans(plan: &PlanLogico) -> Vec<Scan> {
+    match plan {
+        PlanLogico::Scan(scan) => vec![scan.clone()],
+        PlanLogico::Filter { entrada, .. } => scans(entrada),
+        PlanLogico::Join { izq, der, .. } => {
+            let mut scans = scans(izq);
+            scans.extend(self::scans(der));
+            scans
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Devuelve la posición de la única tabla a la que puede pertenecer la columna, si se puede saber:
+/// la que se referencia con el nombre que la califica o, sin calificar, la única que tiene una columna
+/// con ese nombre, si se conocen las columnas de todas.
+pub fn duenio(columna: &str, scans: &[Scan]) -> Option<usize> {
+    if let Some((referencia, _)) = columna.split_once('.') {
+        return scans
+            .iter()
+            .position(|scan| scan.referencia() == referencia);
+    }
+    let mut duenios = Vec::new();
+    for (i, scan) in scans.iter().enumerate() {
+        if scan.columnas.as_ref()?.iter().any(|c| c == columna) {
+            duenios.push(i);
+        }
+    }
+    match duenios.as_slice() {
+        [i] => Some(*i),
+        _ => None,
+    }
+}
+
+/// Devuelve la primera y la última posición de las tablas que usa la condición, si usa alguna columna,
+/// no tiene subconsultas y se sabe a qué tabla pertenece cada columna.
+fn tablas_usadas(condicion: &ExpresionBooleana, scans: &[Scan]) -> Option<(usize, usize)> {
+    if condicion.tiene_subconsultas() {
+        return None;
+    }
+    let mut posiciones = Vec::new();
+    for columna in condicion.columnas_referenciadas() {
+        posiciones.push(duenio(&columna, scans)?);
+    }
+    Some((*posiciones.iter().min()?, *posiciones.iter().max()?))
+}
+
+/// Devuelve las posiciones de las tablas que usa la condición, sin repetir, si no tiene subconsultas
+/// y se sabe a qué tabla pertenece cada columna.
+fn tablas_de(condicion: &ExpresionBooleana, scans: &[Scan]) -> Option<Vec<usize>> {
+    if condicion.tiene_subconsultas() {
+        return None;
+    }
+    let mut tablas = Vec::new();
+    for columna in condicion.columnas_referenciadas() {
+        let tabla = duenio(&columna, scans)?;
+        if !tablas.contains(&tabla) {
+            tablas.push(tabla);
+        }
+    }
+    Some(tablas)
+}
+
+/// Agrega a `condiciones` las partes unidas por AND de las condiciones de los JOIN del plan.
+fn condiciones_de_joins(plan: &PlanLogico, condiciones: &mut Vec<ExpresionBooleana>) {
+    if let PlanLogico::Join {
+        condicion,
+        izq,
+        der,
+    } = plan
+    {
+        condiciones_de_joins(izq, condiciones);
+        condiciones_de_joins(der, condiciones);
+        separar_and(condicion.clone(), condiciones);
+    }
+}
+
+/// Condiciones de los JOIN y del WHERE de un SELECT, con las tablas que combinan, para elegir el orden de los JOIN.
+struct Combinacion<'a> {
+    scans: &'a [Scan],
+    /// Filas de cada tabla que cumplen las condiciones que usan solo sus columnas.
+    filas: Vec<f64>,
+    /// Condiciones que usan columnas de más de una tabla, con sus posiciones.
+    condiciones: Vec<(&'a ExpresionBooleana, Vec<usize>)>,
+}
+
+impl Combinacion<'_> {
+    /// Devuelve las condiciones que se pueden evaluar al agregar la tabla a las ya combinadas:
+    /// las que la usan junto con otras ya combinadas.
+    fn al_agregar(&self, tabla: usize, combinadas: &[usize]) -> Vec<&ExpresionBooleana> {
+        self.condiciones
+            .iter()
+            .filter(|(_, tablas)| {
+                tablas.contains(&tabla)
+                    && tablas.iter().all(|t| *t == tabla || combinadas.contains(t))
+            })
+            .map(|(condicion, _)| *condicion)
+            .collect()
+    }
+
+    /// Estima las filas que se obtienen al agregar la tabla a las filas ya combinadas, si alguna condición
+    /// la relaciona con ellas. Sin condiciones, la combinación tendría todas las filas de ambas.
+    fn agregar(&self, filas: f64, tabla: usize, combinadas: &[usize]) -> Option<f64> {
+        let condiciones = self.al_agregar(tabla, combinadas);
+        if condiciones.is_empty() {
+            return None;
+        }
+        let selectividad: f64 = condiciones
+            .iter()
+            .map(|condicion| selectividad(condicion, self.scans))
+            .product();
+        Some(filas * self.filas[tabla] * selectividad)
+    }
+
+    /// Estima el costo de combinar las tablas en ese orden como la suma de las filas de cada combinación.
+    /// Devuelve `None` si alguna tabla no se relaciona con las anteriores por ninguna condición.
+    fn costo(&self, orden: &[usize]) -> Option<f64> {
+        let mut filas = self.filas[orden[0]];
+        let mut costo = 0.0;
+        for i in 1..orden.len() {
+            filas = self.agregar(filas, orden[i], &orden[..i])?;
+            costo += filas;
+        }
+        Some(costo)
+    }
+
+    /// Devuelve el orden que se obtiene empezando por la tabla y agregando en cada paso
+    /// la que produce menos filas, si siempre hay alguna relacionada con las anteriores.
+    fn orden_desde(&self, primera: usize) -> Option<Vec<usize>> {
+        let mut orden = vec![primera];
+        let mut filas = self.filas[primera];
+        while orden.len() < self.scans.len() {
+            let (tabla, nuevas) = (0..self.scans.len())
+                .filter(|tabla| !orden.contains(tabla))
+                .filter_map(|tabla| Some((tabla, self.agregar(filas, tabla, &orden)?)))
+                .min_by(|(_, filas1), (_, filas2)| filas1.total_cmp(filas2))?;
+            orden.push(tabla);
+            filas = nuevas;
+        }
+        Some(orden)
+    }
+}
+
+/// Devuelve el plan de las tablas combinadas por JOIN en el orden de menor costo estimado, con las condiciones
+/// que relacionan cada tabla con las anteriores en su JOIN y el resto en un filtro sobre el plan.
+/// Si no se analizaron todas las tablas, o el orden escrito no es más caro, lo conserva.
+fn reordenar(join: PlanLogico, mut condiciones: Vec<ExpresionBooleana>) -> PlanLogico {
+    let scans = scans(&join);
+    let Some(filas) = scans.iter().map(filas_scan).collect::<Option<Vec<f64>>>() else {
+        return con_filtro(join, condiciones);
+    };
+    let mut todas = condiciones.clone();
+    condiciones_de_joins(&join, &mut todas);
+    let mut combinacion = Combinacion {
+        scans: &scans,
+        filas,
+        condiciones: Vec::new(),
+    };
+    for condicion in &todas {
+        match tablas_de(condicion, &scans) {
+            Some(tablas) if tablas.len() == 1 => {
+                combinacion.filas[tablas[0]] *= selectividad(condicion, &scans);
+            }
+            Some(tablas) if tablas.len() > 1 => combinacion.condiciones.push((condicion, tablas)),
+            _ => {}
+        }
+    }
+    let escrito: Vec<usize> = (0..scans.len()).collect();
+    let Some(costo_escrito) = combinacion.costo(&escrito) else {
+        return con_filtro(join, condiciones);
+    };
+    let mejor = (0..scans.len())
+        .filter_map(|primera| combinacion.orden_desde(primera))
+        .filter_map(|orden| Some((combinacion.costo(&orden)?, orden)))
+        .min_by(|(costo1, _), (costo2, _)| costo1.total_cmp(costo2));
+    let orden = match mejor {
+        Some((costo, orden)) if costo < costo_escrito => orden,
+        _ => return con_filtro(join, condiciones),
+    };
+    let mut usadas = Vec::new();
+    let mut plan = PlanLogico::Scan(scans[orden[0]].clone());
+    for i in 1..orden.len() {
+        let en_join: Vec<ExpresionBooleana> = combinacion
+            .al_agregar(orden[i], &orden[..i])
+            .into_iter()
+            .cloned()
+            .collect();
+        usadas.extend(en_join.iter().cloned());
+        let Some(condicion) = unir_and(en_join) else {
+            return con_filtro(join, condiciones);
+        };
+        plan = PlanLogico::Join {
+            condicion,
+            izq: Box::new(plan),
+            der: Box::new(PlanLogico::Scan(scans[orden[i]].clone())),
+        };
+    }
+    condiciones = todas.into_iter().filter(|c| !usadas.contains(c)).collect();
+    con_filtro(plan, condiciones)
+}
+
+/// Devuelve el plan con un filtro de las condiciones, si hay alguna.
+fn con_filtro(plan: PlanLogico, condiciones: Vec<ExpresionBooleana>) -> PlanLogico {
+    match unir_and(condiciones) {
+        Some(condicion) => PlanLogico::Filter {
+            condicion,
+            entrada: Box::new(plan),
+        },
+        None => plan,
+    }
+}
+
+/// Cambia el orden en que se combinan las tablas de los JOIN de cada SELECT por el de menor costo estimado.
+/// Con `SELECT *` se conserva el orden, que determina el de las columnas del resultado.
+fn ordenar_joins(plan: PlanLogico) -> PlanLogico {
+    match plan {
+        PlanLogico::Project { ref columnas, .. } if columnas.iter().any(|c| c == "*") => plan,
+        PlanLogico::Filter { condicion, entrada }
+            if matches!(*entrada, PlanLogico::Join { .. }) =>
+        {
+            let mut condiciones = Vec::new();
+            separar_and(condicion, &mut condiciones);
+            reordenar(*entrada, condiciones)
+        }
+        PlanLogico::Join { .. } => reordenar(plan, Vec::new()),
+        plan => optimizar_entradas(plan, &ordenar_joins),
+    }
+}
+
+/// Ubica cada condición lo más abajo posible en el plan de las tablas `desde..` combinadas por JOIN:
+/// sobre la tabla si usa solo columnas de una, o en el primer JOIN que combina todas las que usa.
+/// Las que no se pueden ubicar quedan en un filtro sobre el plan. Un JOIN conserva al menos una condición.
+fn empujar(
+    plan: PlanLogico,
+    condiciones: Vec<ExpresionBooleana>,
+    scans: &[Scan],
+    desde: usize,
+) -> PlanLogico {
+    match plan {
+        PlanLogico::Join {
+            condicion,
+            izq,
+            der,
+        } => {
+            let medio = desde + self::scans(&izq).len();
+            let hasta = medio + self::scans(&der).len();
+            let mut propias = Vec::new();
+            separar_and(condicion.clone(), &mut propias);
+            let (mut a_izq, mut a_der, mut en_join, mut encima) =
+                (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+            let ubicaciones = propias
+                .into_iter()
+                .map(|condicion| (condicion, true))
+                .chain(condiciones.into_iter().map(|condicion| (condicion, false)));
+            for (condicion, propia) in ubicaciones {
+                match tablas_usadas(&condicion, scans) {
+                    Some((primera, ultima)) if primera >= desde && ultima < medio => {
+                        a_izq.push((condicion, propia))
+                    }
+                    Some((primera, ultima)) if primera >= medio && ultima < hasta => {
+                        a_der.push((condicion, propia))
+                    }
+                    Some((primera, ultima)) if primera >= desde && ultima < hasta => {
+                        en_join.push(condicion)
+                    }
+                    _ if propia => en_join.push(condicion),
+                    _ => encima.push(condicion),
+                }
+            }
+            if en_join.is_empty() {
+                let lado = if a_der.iter().any(|(_, propia)| *propia) {
+                    &mut a_der
+                } else {
+                    &mut a_izq
+                };
+                if let Some(posicion) = lado.iter().rposition(|(_, propia)| *propia) {
+                    en_join.push(lado.remove(posicion).0);
+                }
+            }
+            let sin_marca = |condiciones: Vec<(ExpresionBooleana, bool)>| {
+                condiciones
+                    .into_iter()
+                    .map(|(condicion, _)| condicion)
+                    .collect()
+            };
+            let join = match unir_and(en_join) {
+                Some(en_join) => PlanLogico::Join {
+                    condicion: en_join,
+                    izq: Box::new(empujar(*izq, sin_marca(a_izq), scans, desde)),
+                    der: Box::new(empujar(*der, sin_marca(a_der), scans, medio)),
+                },
+                None => PlanLogico::Join {
+                    condicion,
+                    izq,
+                    der,
+                },
+            };
+            con_filtro(join, encima)
+        }
+        PlanLogico::Filter { condicion, entrada } => {
+            let mut condiciones = condiciones;
+            separar_and(condicion, &mut condiciones);
+            empujar(*entrada, condiciones, scans, desde)
+        }
+        plan => con_filtro(plan, condiciones),
+    }
+}
+
+/// Filtra las filas de cada tabla antes de combinarlas en los JOIN con las condiciones del WHERE y de los JOIN
+/// que usan solo sus columnas, y evalúa en cada JOIN las que combinan sus tablas.
+fn empujar_predicados(plan: PlanLogico) -> PlanLogico {
+    match plan {
+        PlanLogico::Filter { condicion, entrada }
+            if matches!(*entrada, PlanLogico::Join { .. }) =>
+        {
+            let scans = scans(&entrada);
+            let mut condiciones = Vec::new();
+            separar_and(condicion, &mut condiciones);
+            empujar(*entrada, condiciones, &scans, 0)
+        }
+        PlanLogico::Join { .. } => {
+            let scans = scans(&plan);
+            empujar(plan, Vec::new(), &scans, 0)
+        }
+        plan => optimizar_entradas(plan, &empujar_predicados),
+    }
+}
+
+/// Agrega a `columnas` las que usa cada operador del plan de un SELECT, sin incluir las de la lectura
+/// de las tablas. Devuelve `false` si no se pueden descartar columnas: si se selecciona `*`,
+/// o si hay subconsultas, que pueden usar columnas de las filas de la consulta.
+fn columnas_usadas(plan: &PlanLogico, columnas: &mut Vec<String>) -> bool {
+    let mut agregar = |nombres: Vec<String>| {
+        for nombre in nombres {
+            if !columnas.contains(&nombre) {
+                columnas.push(nombre);
+            }
+        }
+    };
+    let orden = |orden: &[OrderClause]| -> Vec<String> {
+        let mut nombres = Vec::new();
+        for clause in orden {
+            nombres.push(clause.column.to_string());
+            if let Some(expresion) = &clause.expresion {
+                nombres.extend(expresion.columnas_referenciadas());
+            }
+        }
+        nombres
+    };
+    match plan {
+        PlanLogico::Scan(_) => return true,
+        PlanLogico::Filter { condicion, .. } | PlanLogico::Join { condicion, .. } => {
+            if condicion.tiene_subconsultas() {
+                return false;
+            }
+            agregar(condicion.columnas_referenciadas());
+        }
+        PlanLogico::Project {
+            columnas: seleccionadas,
+            calculadas,
+            ..
+        } => {
+            if seleccionadas.iter().any(|c| c == "*")
+                || calculadas.iter().any(Valor::tiene_subconsultas)
+            {
+                return false;
+            }
+            agregar(seleccionadas.clone());
+            for calculada in calculadas {
+                agregar(calculada.columnas_referenciadas());
+            }
+        }
+        PlanLogico::Sort { orden: clauses, .. } => {
+            if clauses
+                .iter()
+                .any(|c| c.expresion.as_ref().is_some_and(Valor::tiene_subconsultas))
+            {
+                return false;
+            }
+            agregar(orden(clauses));
+        }
+        PlanLogico::Aggregate { conteos, .. } => {
+            agregar(conteos.iter().filter_map(|c| c.columna.clone()).collect());
+        }
+        PlanLogico::Window { ventanas, .. } => {
+            for ventana in ventanas {
+                agregar(ventana.columna.iter().cloned().collect());
+                agregar(ventana.particion.clone());
+                agregar(orden(&ventana.orden));
+            }
+        }
+        PlanLogico::Limit { .. } | PlanLogico::Distinct { .. } | PlanLogico::Conjunto { .. } => {}
+    }
+    match plan {
+        PlanLogico::Join { izq, der, .. } | PlanLogico::Conjunto { izq, der, .. } => {
+            columnas_usadas(izq, columnas) && columnas_usadas(der, columnas)
+        }
+        plan => plan
+            .entrada()
+            .is_none_or(|entrada| columnas_usadas(entrada, columnas)),
+    }
+}
+
+/// Indica en cada lectura del plan de un SELECT cuáles de sus columnas usa la consulta. Si no usa ninguna,
+/// se lee igual la primera para conservar la cantidad de filas.
+fn podar_select(plan: PlanLogico, usadas: &[String]) -> PlanLogico {
+    match optimizar_entradas(plan, &|plan| podar_select(plan, usadas)) {
+        PlanLogico::Scan(mut scan) => {
+            if let Some(columnas) = &scan.columnas {
+                let usada = |columna: &String| {
+                    usadas.iter().any(|nombre| match nombre.split_once('.') {
+                        Some((tabla, nombre)) => {
+                            nombre == columna && (tabla == scan.referencia() || tabla == scan.tabla)
+                        }
+                        None => nombre == columna,
+                    })
+                };
+                let mut leidas: Vec<String> =
+                    columnas.iter().filter(|c| usada(c)).cloned().collect();
+                if leidas.is_empty() {
+                    leidas.extend(columnas.first().cloned());
+                }
+                scan.leidas = Some(leidas);
+            }
+            PlanLogico::Scan(scan)
+        }
+        plan => plan,
+    }
+}
+
+/// Descarta de la lectura de cada tabla las columnas que no usa su SELECT.
+fn podar_columnas(plan: PlanLogico) -> PlanLogico {
+    match plan {
+        PlanLogico::Project { .. } => {
+            let mut usadas = Vec::new();
+            if columnas_usadas(&plan, &mut usadas) {
+                podar_select(plan, &usadas)
+            } else {
+                plan
+            }
+        }
+        plan => optimizar_entradas(plan, &podar_columnas),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{optimizar, plegar_constantes, simplificar_condicion};
+    use crate::{
+        dato::Datos,
+        lexers::lexer::lexer,
+        parsers::parser::{parse_condicion, parser},
+        plan::{
+            estadisticas::{EstadisticasColumna, EstadisticasTabla},
+            plan_logico::{PlanLogico, Scan},
+        },
+        queries::{
+            sql_query::SQLQuery,
+            where_clause::{
+                expresion_booleana::ExpresionBooleana,
+                valor::{OperadorAritmetico, Valor},
+            },
+        },
+    };
+
+    fn condicion(texto: &str) -> ExpresionBooleana {
+        match lexer(&texto.to_string()).and_then(|tokens| parse_condicion(&tokens)) {
+            Ok(condicion) => condicion,
+            Err(e) => panic!("Condición inválida: {}", e),
+        }
+    }
+
+    fn plan_select(consulta: &str) -> PlanLogico {
+        match lexer(&consulta.to_string()).and_then(|tokens| parser(&tokens)) {
+            Ok(SQLQuery::Select(select)) => PlanLogico::desde_select(&select, ""),
+            _ => panic!("Consulta inválida: {}", consulta),
+        }
+    }
+
+    /// Devuelve el plan optimizado del SELECT, con las columnas de cada tabla en el orden del FROM y los JOIN.
+    fn plan_optimizado(consulta: &str, columnas: &[&[&str]]) -> PlanLogico {
+        let mut plan = plan_select(consulta);
+        let mut columnas = columnas.iter();
+        modificar_scans(&mut plan, &mut |scan| {
+            scan.columnas = columnas
+                .next()
+                .map(|c| c.iter().map(|c| c.to_string()).collect());
+        });
+        optimizar(plan)
+    }
+
+    /// Devuelve el plan optimizado del SELECT con las estadísticas de cada tabla en el orden del FROM
+    /// y los JOIN: su cantidad de filas y la de valores distintos de cada columna, que no tienen nulos.
+    fn plan_analizado(consulta: &str, tablas: &[(usize, &[(&str, usize)])]) -> PlanLogico {
+        let mut plan = plan_select(consulta);
+        let mut tablas = tablas.iter();
+        modificar_scans(&mut plan, &mut |scan| {
+            let Some((filas, columnas)) = tablas.next() else {
+                return;
+            };
+            scan.columnas = Some(columnas.iter().map(|(c, _)| c.to_string()).collect());
+            scan.estadisticas = Some(EstadisticasTabla {
+                tabla: scan.tabla.to_string(),
+                filas: *filas,
+                columnas: columnas
+                    .iter()
+                    .map(|(columna, distintos)| EstadisticasColumna {
+                        columna: columna.to_string(),
+                        distintos: *distintos,
+                        nulos: 0,
+                        minimo: Some(Datos::Integer(1)),
+                        maximo: Some(Datos::Integer(*distintos as i64)),
+                        histograma: Vec::new(),
+                    })
+                    .collect(),
+            });
+        });
+        optimizar(plan)
+    }
+
+    fn modificar_scans(plan: &mut PlanLogico, modificar: &mut dyn FnMut(&mut Scan)) {
+        match plan {
+            PlanLogico::Scan(scan) => modificar(scan),
+            PlanLogico::Join { izq, der, .. } => {
+                modificar_scans(izq, modificar);
+                modificar_scans(der, modificar);
+            }
+            PlanLogico::Filter { entrada, .. }
+            | PlanLogico::Project { entrada, .. }
+            | PlanLogico::Sort { entrada, .. }
+            | PlanLogico::Limit { entrada, .. }
+            | PlanLogico::Aggregate { entrada, .. } => modificar_scans(entrada, modificar),
+            _ => {}
+        }
+    }
+
+    fn tablas_leidas(plan: &PlanLogico) -> Vec<&str> {
+        plan.lecturas()
+            .into_iter()
+            .map(|(scan, _)| scan.tabla.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_plegar_constantes() {
+        let operacion = |izq: Valor, operador, der: Valor| Valor::Operacion {
+            izq: Box::new(izq),
+            operador,
+            der: Box::new(der),
+        };
+        let numero = |n: &str| Valor::String(n.to_string());
+        let suma = operacion(
+            numero("2"),
+            OperadorAritmetico::Suma,
+            operacion(numero("3"), OperadorAritmetico::Multiplicacion, numero("4")),
+        );
+        assert_eq!(plegar_constantes(suma), numero("14"));
+
+        let con_columna = operacion(numero("cantidad"), OperadorAritmetico::Suma, numero("1"));
+        assert_eq!(plegar_constantes(con_columna.clone()), con_columna);
+
+        let division = operacion(numero("1"), OperadorAritmetico::Division, numero("0"));
+        assert_eq!(plegar_constantes(division.clone()), division);
+
+        let upper = Valor::Funcion {
+            nombre: "UPPER".to_string(),
+            argumentos: vec![Valor::Literal("ana".to_string())],
+        };
+        assert_eq!(plegar_constantes(upper), Valor::Literal("ANA".to_string()));
+    }
+
+    #[test]
+    fn test_simplificar_condicion() {
+        let casos = [
+            ("cantidad = 1 AND 1 = 1", Some("cantidad = 1")),
+            ("1 = 1 OR cantidad = 1", None),
+            ("cantidad = 1 OR 2 < 1", Some("cantidad = 1")),
+            ("NOT NOT cantidad = 1", Some("cantidad = 1")),
+            ("cantidad = 1 AND cantidad = 1", Some("cantidad = 1")),
+            ("cantidad = 1 AND 1 = 2", Some("1 = 2")),
+            ("NOT 1 = 2", None),
+        ];
+        for (texto, esperado) in casos {
+            let simplificada = simplificar_condicion(condicion(texto));
+            assert_eq!(simplificada, esperado.map(condicion), "{}", texto);
+        }
+    }
+
+    #[test]
+    fn test_empujar_predicados() {
+        let plan = plan_optimizado(
+            "SELECT c.nombre, o.producto FROM ordenes o JOIN clientes c ON o.id_cliente = c.id AND c.id > 1 WHERE o.cantidad = 1 AND (o.cantidad = 2 OR c.nombre = 'Ana')",
+            &[&["id", "id_cliente", "producto", "cantidad"], &["id", "nombre"]],
+        );
+        let filtros: Vec<Option<ExpresionBooleana>> = plan
+            .lecturas()
+            .into_iter()
+            .map(|(_, filtro)| filtro.cloned())
+            .collect();
+        assert_eq!(
+            filtros,
+            vec![
+                Some(condicion("o.cantidad = 1")),
+                Some(condicion("c.id > 1"))
+            ]
+        );
+        assert_eq!(
+            plan.condiciones_join(),
+            vec![&condicion(
+                "o.id_cliente = c.id AND (o.cantidad = 2 OR c.nombre = 'Ana')"
+            )]
+        );
+        assert_eq!(plan.filtro_combinadas(), None);
+    }
+
+    #[test]
+    fn test_empujar_predicados_sin_ubicar() {
+        let plan = plan_optimizado(
+            "SELECT * FROM ordenes o JOIN clientes c ON o.id_cliente = c.id WHERE id > 1",
+            &[
+                &["id", "id_cliente", "producto", "cantidad"],
+                &["id", "nombre"],
+            ],
+        );
+        assert!(plan.lecturas().iter().all(|(_, filtro)| filtro.is_none()));
+        assert_eq!(plan.filtro_combinadas(), Some(&condicion("id > 1")));
+    }
+
+    #[test]
+    fn test_podar_columnas() {
+        let plan = plan_optimizado(
+            "SELECT nombre FROM ordenes o JOIN clientes c ON o.id_cliente = c.id ORDER BY o.producto",
+            &[&["id", "id_cliente", "producto", "cantidad"], &["id", "nombre"]],
+        );
+        let leidas: Vec<Option<Vec<String>>> = plan
+            .lecturas()
+            .into_iter()
+            .map(|(scan, _)| scan.leidas.clone())
+            .collect();
+        let columnas = |columnas: &[&str]| Some(columnas.iter().map(|c| c.to_string()).collect());
+        assert_eq!(
+            leidas,
+            vec![
+                columnas(&["id_cliente", "producto"]),
+                columnas(&["id", "nombre"])
+            ]
+        );
+
+        let plan = plan_optimizado("SELECT * FROM ordenes", &[&["id", "producto"]]);
+        assert_eq!(plan.lecturas()[0].0.leidas, None);
+
+        let plan = plan_optimizado("SELECT COUNT(*) FROM ordenes", &[&["id", "producto"]]);
+        assert_eq!(plan.lecturas()[0].0.leidas, columnas(&["id"]));
+    }
+
+    #[test]
+    fn test_ordenar_joins() {
+        let consulta = "SELECT c.nombre, p.nombre FROM productos p JOIN ordenes o ON o.id_producto = p.id JOIN clientes c ON o.id_cliente = c.id WHERE c.nombre = 'Ana'";
+        let productos: (usize, &[(&str, usize)]) = (100, &[("id", 100), ("nombre", 100)]);
+        let ordenes: (usize, &[(&str, usize)]) = (
+            1000,
+            &[("id", 1000), ("id_cliente", 10), ("id_producto", 100)],
+        );
+        let clientes: (usize, &[(&str, usize)]) = (10, &[("id", 10), ("nombre", 10)]);
+
+        let plan = plan_analizado(consulta, &[productos, ordenes, clientes]);
+        assert_eq!(
+            tablas_leidas(&plan),
+            vec!["ordenes", "clientes", "productos"]
+        );
+        assert_eq!(
+            plan.condiciones_join(),
+            vec![
+                &condicion("o.id_cliente = c.id"),
+                &condicion("o.id_producto = p.id")
+            ]
+        );
+        assert_eq!(plan.lecturas()[1].1, Some(&condicion("c.nombre = 'Ana'")));
+        assert_eq!(plan.filtro_combinadas(), None);
+
+        let sin_analizar = plan_optimizado(
+            consulta,
+            &[
+                &["id", "nombre"],
+                &["id", "id_cliente", "id_producto"],
+                &["id", "nombre"],
+            ],
+        );
+        assert_eq!(
+            tablas_leidas(&sin_analizar),
+            vec!["productos", "ordenes", "clientes"]
+        );
+
+        let todas = plan_analizado(
+            &consulta.replace("c.nombre, p.nombre", "*"),
+            &[productos, ordenes, clientes],
+        );
+        assert_eq!(
+            tablas_leidas(&todas),
+            vec!["productos", "ordenes", "clientes"]
+        );
+    }
+}
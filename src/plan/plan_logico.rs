@@ -0,0 +1,277 @@
+use std::io::BufRead;
+
+use crate::{
+    errores::error::ErrorType,
+    executer::manejo_csv::{agregar_path, get_reader, leer_encabezado},
+    queries::{
+        compound_query::{CompoundQuery, Conjunto, OperadorConjunto},
+        conteo::Conteo,
+        order_clause::OrderClause,
+        select_query::SelectQuery,
+        sql_query::SQLQuery,
+        ventana::Ventana,
+        where_clause::{expresion_booleana::ExpresionBooleana, valor::Valor},
+        with_query::Consulta,
+    },
+};
+
+use super::estadisticas::EstadisticasTabla;
+
+/// Lectura de las filas de una tabla o de los `VALUES` escritos en una consulta.
+/// `columnas` son las de la tabla, si se conocen antes de ejecutar la consulta, y `leidas`
+/// las únicas que usa la consulta, si el optimizador pudo descartar las demás.
+/// `estadisticas` son las que guardó el último ANALYZE de la tabla, si se analizó.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Scan {
+    pub tabla: String,
+    pub alias: Option<String>,
+    pub columnas: Option<Vec<String>>,
+    pub leidas: Option<Vec<String>>,
+    pub estadisticas: Option<EstadisticasTabla>,
+}
+
+impl Scan {
+    /// Devuelve el nombre con el que se referencian las columnas de la tabla.
+    pub fn referencia(&self) -> &String {
+        self.alias.as_ref().unwrap_or(&self.tabla)
+    }
+}
+
+/// Plan lógico de una consulta que devuelve filas: el árbol de operadores que la calculan, desde la lectura
+/// de las tablas hasta las columnas del resultado. Cada operador recibe las filas de los que tiene debajo.
+/// El optimizador lo transforma sin cambiar el resultado, y un SELECT lo usa para decidir cómo leer sus tablas.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PlanLogico {
+    Scan(Scan),
+    Filter {
+        condicion: ExpresionBooleana,
+        entrada: Box<PlanLogico>,
+    },
+    Join {
+        condicion: ExpresionBooleana,
+        izq: Box<PlanLogico>,
+        der: Box<PlanLogico>,
+    },
+    /// Calcula las expresiones de cada fila y se queda con las columnas seleccionadas.
+    Project {
+        columnas: Vec<String>,
+        calculadas: Vec<Valor>,
+        entrada: Box<PlanLogico>,
+    },
+    Sort {
+        orden: Vec<OrderClause>,
+        entrada: Box<PlanLogico>,
+    },
+    Limit {
+        cantidad: usize,
+        entrada: Box<PlanLogico>,
+    },
+    /// Reduce las filas a una única fila con el resultado de cada COUNT.
+    Aggregate {
+        conteos: Vec<Conteo>,
+        entrada: Box<PlanLogico>,
+    },
+    Distinct {
+        entrada: Box<PlanLogico>,
+    },
+    Window {
+        ventanas: Vec<Ventana>,
+        entrada: Box<PlanLogico>,
+    },
+    Conjunto {
+        operador: OperadorConjunto,
+        izq: Box<PlanLogico>,
+        der: Box<PlanLogico>,
+    },
+}
+
+/// Lee las columnas del encabezado de la tabla, si es un archivo que existe.
+/// Las vistas y las expresiones de un WITH no tienen archivo hasta que se ejecuta la consulta.
+fn columnas_tabla(path: &str, tabla: &String) -> Option<Vec<String>> {
+    let reader = get_reader(&agregar_path(path, tabla)).ok()?;
+    leer_encabezado(&mut reader.lines()).ok()
+}
+
+/// Lee las estadísticas de la tabla, si es una tabla que se analizó. Los `VALUES` de una consulta no tienen.
+fn estadisticas_tabla(path: &str, tabla: &str, es_tabla: bool) -> Option<EstadisticasTabla> {
+    if !es_tabla {
+        return None;
+    }
+    EstadisticasTabla::cargar(path, tabla).ok().flatten()
+}
+
+impl PlanLogico {
+    /// Devuelve el plan de una consulta que devuelve filas. Las columnas de cada tabla se leen de `path`.
+    /// En un WITH, es el plan de la consulta principal, que lee cada expresión como una tabla.
+    pub fn desde(consulta: &SQLQuery, path: &str) -> Result<PlanLogico, ErrorType> {
+        match consulta {
+            SQLQuery::Select(select) => Ok(PlanLogico::desde_select(select, path)),
+            SQLQuery::Compound(compound) => Ok(PlanLogico::desde_compound(compound, path)),
+            SQLQuery::With(with) => Ok(match &with.consulta {
+                Consulta::Select(select) => PlanLogico::desde_select(select, path),
+                Consulta::Compound(compound) => PlanLogico::desde_compound(compound, path),
+            }),
+            _ => Err(ErrorType::InvalidSyntax(
+                "Solo se puede planificar una consulta SELECT".to_string(),
+            )),
+        }
+    }
+
+    /// Devuelve el plan de un SELECT: la lectura de su tabla combinada con las de cada JOIN, el WHERE,
+    /// las funciones de ventana, el COUNT o el DISTINCT, el orden, el LIMIT y las columnas seleccionadas.
+    pub fn desde_select(select: &SelectQuery, path: &str) -> PlanLogico {
+        let columnas = match &select.valores {
+            Some(valores) => Some(valores.columnas.clone()),
+            None => columnas_tabla(path, &select.table),
+        };
+        let mut plan = PlanLogico::Scan(Scan {
+            tabla: select.table.to_string(),
+            alias: select.alias.clone(),
+            columnas,
+            leidas: None,
+            estadisticas: estadisticas_tabla(path, &select.table, select.valores.is_none()),
+        });
+        for join in &select.joins {
+            let der = PlanLogico::Scan(Scan {
+                tabla: join.tabla.to_string(),
+                alias: join.alias.clone(),
+                columnas: columnas_tabla(path, &join.tabla),
+                leidas: None,
+                estadisticas: estadisticas_tabla(path, &join.tabla, true),
+            });
+            plan = PlanLogico::Join {
+                condicion: join.condicion.clone(),
+                izq: Box::new(plan),
+                der: Box::new(der),
+            };
+        }
+        if let Some(where_clause) = &select.where_clause {
+            plan = PlanLogico::Filter {
+                condicion: where_clause.clone(),
+                entrada: Box::new(plan),
+            };
+        }
+        if !select.ventanas.is_empty() {
+            plan = PlanLogico::Window {
+                ventanas: select.ventanas.clone(),
+                entrada: Box::new(plan),
+            };
+        }
+        if !select.conteos.is_empty() {
+            plan = PlanLogico::Aggregate {
+                conteos: select.conteos.clone(),
+                entrada: Box::new(plan),
+            };
+        } else if select.distinct {
+            plan = PlanLogico::Distinct {
+                entrada: Box::new(plan),
+            };
+        }
+        if let (Some(orden), true) = (&select.order_by, select.conteos.is_empty()) {
+            plan = PlanLogico::Sort {
+                orden: orden.clone(),
+                entrada: Box::new(plan),
+            };
+        }
+        if let Some(cantidad) = select.limit {
+            plan = PlanLogico::Limit {
+                cantidad,
+                entrada: Box::new(plan),
+            };
+        }
+        PlanLogico::Project {
+            columnas: select.columns_select.clone(),
+            calculadas: select.calculadas.clone(),
+            entrada: Box::new(plan),
+        }
+    }
+
+    /// Devuelve el plan de una combinación de SELECT con operadores de conjuntos, su orden y su LIMIT.
+    fn desde_compound(compound: &CompoundQuery, path: &str) -> PlanLogico {
+        let mut plan = PlanLogico::desde_conjunto(&compound.conjunto, path);
+        if let Some(orden) = &compound.order_by {
+            plan = PlanLogico::Sort {
+                orden: orden.clone(),
+                entrada: Box::new(plan),
+            };
+        }
+        if let Some(cantidad) = compound.limit {
+            plan = PlanLogico::Limit {
+                cantidad,
+                entrada: Box::new(plan),
+            };
+        }
+        plan
+    }
+
+    /// Devuelve el plan de los SELECT combinados.
+    fn desde_conjunto(conjunto: &Conjunto, path: &str) -> PlanLogico {
+        match conjunto {
+            Conjunto::Select(select) => PlanLogico::desde_select(select, path),
+            Conjunto::Operacion { izq, operador, der } => PlanLogico::Conjunto {
+                operador: operador.clone(),
+                izq: Box::new(PlanLogico::desde_conjunto(izq, path)),
+                der: Box::new(PlanLogico::desde_conjunto(der, path)),
+            },
+        }
+    }
+
+    /// Devuelve el operador del que recibe las filas, si recibe las de uno solo.
+    pub fn entrada(&self) -> Option<&PlanLogico> {
+        match self {
+            PlanLogico::Filter { entrada, .. }
+            | PlanLogico::Project { entrada, .. }
+            | PlanLogico::Sort { entrada, .. }
+            | PlanLogico::Limit { entrada, .. }
+            | PlanLogico::Aggregate { entrada, .. }
+            | PlanLogico::Distinct { entrada }
+            | PlanLogico::Window { entrada, .. } => Some(entrada),
+            PlanLogico::Scan(_) | PlanLogico::Join { .. } | PlanLogico::Conjunto { .. } => None,
+        }
+    }
+
+    /// Devuelve la lectura de cada tabla del plan de un SELECT, en el orden del FROM y los JOIN,
+    /// junto con la condición que filtra sus filas antes de combinarlas, si hay alguna.
+    pub fn lecturas(&self) -> Vec<(&Scan, Option<&ExpresionBooleana>)> {
+        match self {
+            PlanLogico::Scan(scan) => vec![(scan, None)],
+            PlanLogico::Filter { condicion, entrada } => match entrada.as_ref() {
+                PlanLogico::Scan(scan) => vec![(scan, Some(condicion))],
+                entrada => entrada.lecturas(),
+            },
+            PlanLogico::Join { izq, der, .. } => {
+                let mut lecturas = izq.lecturas();
+                lecturas.extend(der.lecturas());
+                lecturas
+            }
+            _ => self.entrada().map(PlanLogico::lecturas).unwrap_or_default(),
+        }
+    }
+
+    /// Devuelve la condición de cada JOIN del plan de un SELECT, en el orden en que se escribieron.
+    pub fn condiciones_join(&self) -> Vec<&ExpresionBooleana> {
+        match self {
+            PlanLogico::Join { condicion, izq, .. } => {
+                let mut condiciones = izq.condiciones_join();
+                condiciones.push(condicion);
+                condiciones
+            }
+            PlanLogico::Scan(_) | PlanLogico::Conjunto { .. } => Vec::new(),
+            _ => self
+                .entrada()
+                .map(PlanLogico::condiciones_join)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Devuelve la condición que filtra las filas ya combinadas por los JOIN del plan de un SELECT, si hay alguna.
+    pub fn filtro_combinadas(&self) -> Option<&ExpresionBooleana> {
+        match self {
+            PlanLogico::Filter { condicion, entrada } => match entrada.as_ref() {
+                PlanLogico::Join { .. } => Some(condicion),
+                entrada => entrada.filtro_combinadas(),
+            },
+            _ => self.entrada().and_then(PlanLogico::filtro_combinadas),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::{fs, path::Path};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{execute::Execute, manejo_csv::agregar_path},
+    plan::estadisticas::EstadisticasTabla,
+};
+
+use super::vista::es_vista;
+
+/// Representa una consulta SQL `ANALYZE [tabla]`, que calcula las estadísticas de la tabla
+/// o, sin tabla, las de todas las tablas de la carpeta.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnalyzeQuery {
+    pub tabla: Option<String>,
+}
+
+impl AnalyzeQuery {
+    /// Crea una nueva instancia de `AnalyzeQuery`.
+    pub fn new(tabla: Option<&str>) -> Self {
+        AnalyzeQuery {
+            tabla: tabla.map(|t| t.to_string()),
+        }
+    }
+}
+
+/// Devuelve las tablas de la carpeta, ordenadas por nombre: cada archivo `.csv`.
+fn tablas(path: &str) -> Result<Vec<String>, ErrorType> {
+    let Ok(entradas) = fs::read_dir(path) else {
+        return Err(ErrorType::InvalidTable(
+            "Error al leer la carpeta de las tablas".to_string(),
+        ));
+    };
+    let mut tablas: Vec<String> = entradas
+        .filter_map(|entrada| entrada.ok())
+        .filter_map(|entrada| {
+            let nombre = entrada.file_name().to_string_lossy().to_string();
+            nombre.strip_suffix(".csv").map(|t| t.to_string())
+        })
+        .collect();
+    tablas.sort();
+    Ok(tablas)
+}
+
+impl Execute for AnalyzeQuery {
+    /// Ejecuta la consulta ANALYZE guardando las estadísticas de cada tabla en su archivo `.estadisticas`,
+    /// que reemplaza las del ANALYZE anterior. Las vistas no se pueden analizar.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let tablas = match &self.tabla {
+            Some(tabla) if es_vista(path, tabla) => {
+                return Err(ErrorType::InvalidTable(format!(
+                    "No se puede analizar la vista '{}'",
+                    tabla
+                )))
+            }
+            Some(tabla) if !Path::new(&agregar_path(path, tabla)).exists() => {
+                return Err(ErrorType::InvalidTable(format!(
+                    "La tabla '{}' no existe",
+                    tabla
+                )))
+            }
+            Some(tabla) => vec![tabla.to_string()],
+            None => tablas(path)?,
+        };
+        for tabla in tablas {
+            EstadisticasTabla::calcular(path, &tabla)?.guardar(path)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,321 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{
+        conjuntos::combinar,
+        execute::Execute,
+        manejo_csv::{
+            agregar_path, crear_archivo, eliminar_archivo, imprimir_archivo, limitar_archivo,
+            nombre_auxiliar, ordenar_archivo, proyectar_archivo,
+        },
+        mediciones::{clave, Mediciones},
+        primeras_filas::primeras_filas_archivo,
+    },
+};
+
+use super::{
+    order_clause::OrderClause,
+    select_query::{RenombrarTabla, SelectQuery},
+    where_clause::valor::VisitarValor,
+};
+
+/// Operadores que combinan los resultados de dos consultas.
+#[derive(Debug, PartialEq, Clone)]
+pub enum OperadorConjunto {
+    Union,
+    UnionAll,
+    Intersect,
+    Except,
+}
+
+/// Consulta que se combina con otras: un SELECT o la combinación de otras dos con un operador de conjuntos.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Conjunto {
+    Select(Box<SelectQuery>),
+    Operacion {
+        izq: Box<Conjunto>,
+        operador: OperadorConjunto,
+        der: Box<Conjunto>,
+    },
+}
+
+/// Representa una consulta que combina varios SELECT con UNION, UNION ALL, INTERSECT o EXCEPT,
+/// con un orden y una cantidad máxima de filas opcionales para el resultado combinado.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompoundQuery {
+    pub conjunto: Conjunto,
+    pub order_by: Option<Vec<OrderClause>>,
+    pub limit: Option<usize>,
+}
+
+/// Escribe en el archivo de salida las columnas seleccionadas de cada fila del resultado de un SELECT,
+/// sin encabezado, y devuelve los nombres de las columnas seleccionadas.
+fn escribir_select(
+    select: &SelectQuery,
+    path: &str,
+    salida: &String,
+    mediciones: &mut Mediciones,
+    prefijo: &str,
+) -> Result<Vec<String>, ErrorType> {
+    let (path_aux, columnas_filtradas, posiciones) =
+        select.materializar_midiendo(path, mediciones, prefijo)?;
+    let resultado = crear_archivo(salida).and_then(|_| {
+        mediciones.medir(
+            &clave(prefijo, "proyeccion"),
+            &[&path_aux],
+            &path_aux,
+            || proyectar_archivo(&path_aux, &posiciones, salida),
+        )
+    });
+    eliminar_archivo(&path_aux)?;
+    resultado?;
+    Ok(columnas_filtradas
+        .split(',')
+        .map(|c| c.to_string())
+        .collect())
+}
+
+impl Conjunto {
+    /// Aplica `renombrar` a cada tabla referenciada por los SELECT combinados.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        match self {
+            Conjunto::Select(select) => select.renombrar_tablas(renombrar),
+            Conjunto::Operacion { izq, der, .. } => {
+                izq.renombrar_tablas(renombrar);
+                der.renombrar_tablas(renombrar);
+            }
+        }
+    }
+
+    /// Aplica `visitar` a cada valor de las consultas que se combinan.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        match self {
+            Conjunto::Select(select) => select.visitar_valores(visitar),
+            Conjunto::Operacion { izq, der, .. } => {
+                izq.visitar_valores(visitar);
+                der.visitar_valores(visitar);
+            }
+        }
+    }
+
+    /// Escribe en el archivo de salida, sin encabezado, las filas del resultado y devuelve los nombres de sus columnas,
+    /// que son las del primer SELECT.
+    pub fn materializar(&self, path: &str, salida: &String) -> Result<Vec<String>, ErrorType> {
+        self.materializar_midiendo(path, salida, &mut Mediciones::default(), "")
+    }
+
+    /// Igual que `materializar`, registrando en `mediciones` las filas y el tiempo de cada etapa
+    /// con claves que empiezan con `prefijo`. Las de cada consulta combinada agregan `izq.` o `der.` al prefijo.
+    pub fn materializar_midiendo(
+        &self,
+        path: &str,
+        salida: &String,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<Vec<String>, ErrorType> {
+        let (izq, operador, der) = match self {
+            Conjunto::Select(select) => {
+                return escribir_select(select, path, salida, mediciones, prefijo)
+            }
+            Conjunto::Operacion { izq, operador, der } => (izq, operador, der),
+        };
+        let path_izq = agregar_path(path, &nombre_auxiliar("auxiliar"));
+        let path_der = agregar_path(path, &nombre_auxiliar("auxiliar"));
+        let resultado = izq
+            .materializar_midiendo(path, &path_izq, mediciones, &clave(prefijo, "izq."))
+            .and_then(|columnas| {
+                let columnas_der = der.materializar_midiendo(
+                    path,
+                    &path_der,
+                    mediciones,
+                    &clave(prefijo, "der."),
+                )?;
+                if columnas.len() != columnas_der.len() {
+                    return Err(ErrorType::InvalidSyntax(format!(
+                        "Las consultas combinadas con {} deben tener la misma cantidad de columnas ({} y {})",
+                        operador,
+                        columnas.len(),
+                        columnas_der.len()
+                    )));
+                }
+                crear_archivo(salida)?;
+                let clave = clave(prefijo, "conjunto");
+                mediciones.medir_sin_encabezado(&clave, &[&path_izq, &path_der], salida, || {
+                    combinar(path, &path_izq, &path_der, salida, operador)
+                })?;
+                Ok(columnas)
+            });
+        let _ = eliminar_archivo(&path_izq);
+        let _ = eliminar_archivo(&path_der);
+        resultado
+    }
+}
+
+impl CompoundQuery {
+    /// Crea una nueva instancia de `CompoundQuery`.
+    pub fn new(
+        conjunto: Conjunto,
+        order_by: Option<Vec<OrderClause>>,
+        limit: Option<usize>,
+    ) -> Self {
+        CompoundQuery {
+            conjunto,
+            order_by,
+            limit,
+        }
+    }
+
+    /// Escribe en un archivo auxiliar el resultado combinado con el encabezado de las columnas,
+    /// ordenado según el ORDER BY y con las primeras filas según el LIMIT.
+    /// Devuelve el nombre del archivo auxiliar y las columnas del resultado.
+    pub fn materializar(&self, path: &str) -> Result<(String, Vec<String>), ErrorType> {
+        self.materializar_midiendo(path, &mut Mediciones::default(), "")
+    }
+
+    /// Igual que `materializar`, registrando en `mediciones` las filas y el tiempo de cada etapa
+    /// con claves que empiezan con `prefijo`.
+    pub fn materializar_midiendo(
+        &self,
+        path: &str,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<(String, Vec<String>), ErrorType> {
+        let nombre_filas = nombre_auxiliar("auxiliar");
+        let path_filas = agregar_path(path, &nombre_filas);
+        let nombre_resultado = nombre_auxiliar("auxiliar");
+        let path_resultado = agregar_path(path, &nombre_resultado);
+        let resultado = self
+            .conjunto
+            .materializar_midiendo(path, &path_filas, mediciones, prefijo)
+            .and_then(|columnas| {
+                let order_by = self.orden_resuelto(&columnas)?;
+                let mut resultado = crear_archivo(&path_resultado)?;
+                let copiado = writeln!(resultado, "{}", columnas.join(",")).and_then(|_| {
+                    std::io::copy(&mut std::fs::File::open(&path_filas)?, &mut resultado)
+                });
+                if copiado.is_err() {
+                    return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+                }
+                let entrada = [&path_resultado];
+                let orden = clave(prefijo, "orden");
+                match (&order_by, self.limit) {
+                    (Some(order_by), Some(limite)) => {
+                        mediciones.medir(&orden, &entrada, &path_resultado, || {
+                            primeras_filas_archivo(path, &nombre_resultado, order_by, limite)
+                        })?
+                    }
+                    (_, Some(limite)) => {
+                        let limite_clave = clave(prefijo, "limite");
+                        mediciones.medir(&limite_clave, &entrada, &path_resultado, || {
+                            limitar_archivo(&path_resultado, limite)
+                        })?
+                    }
+                    (Some(_), None) => {
+                        mediciones.medir(&orden, &entrada, &path_resultado, || {
+                            ordenar_archivo(path, &nombre_resultado, &order_by)
+                        })?
+                    }
+                    (None, None) => {}
+                }
+                Ok(columnas)
+            });
+        let _ = eliminar_archivo(&path_filas);
+        match resultado {
+            Ok(columnas) => Ok((path_resultado, columnas)),
+            Err(e) => {
+                let _ = eliminar_archivo(&path_resultado);
+                Err(e)
+            }
+        }
+    }
+
+    /// Devuelve el ORDER BY con cada posición reemplazada por la columna del resultado combinado
+    /// en esa posición, y verifica que todas sus columnas sean columnas del resultado.
+    fn orden_resuelto(&self, columnas: &[String]) -> Result<Option<Vec<OrderClause>>, ErrorType> {
+        let mut order_by = self.order_by.clone();
+        for clause in order_by.iter_mut().flatten() {
+            if let Some(posicion) = clause.posicion() {
+                match posicion.checked_sub(1).and_then(|i| columnas.get(i)) {
+                    Some(columna) => clause.column = columna.to_string(),
+                    None => {
+                        return Err(ErrorType::InvalidColumn(format!(
+                            "La posición {} del ORDER BY no corresponde a ninguna columna del resultado combinado",
+                            posicion
+                        )))
+                    }
+                }
+            }
+            if !columnas.contains(&clause.column) {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "La columna '{}' del ORDER BY no está en el resultado combinado",
+                    clause.column
+                )));
+            }
+        }
+        Ok(order_by)
+    }
+}
+
+impl Execute for CompoundQuery {
+    /// Ejecuta cada SELECT, combina sus resultados e imprime por pantalla el resultado ordenado.
+    /// Para descartar las filas repetidas, si no entran en memoria usa archivos auxiliares.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let (path_resultado, columnas) = self.materializar(path)?;
+        let posiciones = (0..columnas.len()).collect();
+        let resultado = imprimir_archivo(&path_resultado, columnas.join(","), posiciones);
+        eliminar_archivo(&path_resultado)?;
+        resultado
+    }
+}
+
+/// Muestra una de las consultas combinadas, entre paréntesis si combina otras.
+fn fmt_operando(conjunto: &Conjunto, f: &mut Formatter) -> fmt::Result {
+    match conjunto {
+        Conjunto::Select(select) => write!(f, "{}", select),
+        Conjunto::Operacion { .. } => write!(f, "({})", conjunto),
+    }
+}
+
+impl Display for Conjunto {
+    /// Muestra la combinación con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Conjunto::Select(select) => write!(f, "{}", select),
+            Conjunto::Operacion { izq, operador, der } => {
+                fmt_operando(izq, f)?;
+                write!(f, " {} ", operador)?;
+                fmt_operando(der, f)
+            }
+        }
+    }
+}
+
+impl Display for CompoundQuery {
+    /// Muestra la consulta con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.conjunto)?;
+        if let Some(order_by) = &self.order_by {
+            let order_by: Vec<String> = order_by.iter().map(|o| o.to_string()).collect();
+            write!(f, " ORDER BY {}", order_by.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OperadorConjunto {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            OperadorConjunto::Union => write!(f, "UNION"),
+            OperadorConjunto::UnionAll => write!(f, "UNION ALL"),
+            OperadorConjunto::Intersect => write!(f, "INTERSECT"),
+            OperadorConjunto::Except => write!(f, "EXCEPT"),
+        }
+    }
+}
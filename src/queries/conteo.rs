@@ -0,0 +1,163 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::BufRead,
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    executer::{
+        conjuntos::{abrir_para_agregar, distintas, escribir, terminar},
+        manejo_csv::{
+            agregar_path, crear_archivo, eliminar_archivo, get_reader, leer_encabezado,
+            nombre_auxiliar, string_to_columns,
+        },
+    },
+    utils::dato_to_string,
+};
+
+/// Representa un COUNT de la lista de columnas de un SELECT: `COUNT(*)`, `COUNT(columna)` o `COUNT(DISTINCT columna)`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Conteo {
+    /// La columna cuyos valores no nulos se cuentan, o `None` para contar todas las filas.
+    pub columna: Option<String>,
+    pub distinct: bool,
+    /// Nombre de la columna del COUNT en el resultado, indicado con `AS`.
+    pub alias: Option<String>,
+}
+
+impl Conteo {
+    /// Crea una nueva instancia de `Conteo`.
+    pub fn new(columna: Option<String>, distinct: bool) -> Self {
+        Conteo {
+            columna,
+            distinct,
+            alias: None,
+        }
+    }
+
+    /// Devuelve el nombre de la columna del COUNT en el resultado: su alias, o su texto si no tiene.
+    pub fn nombre_columna(&self) -> String {
+        match &self.alias {
+            Some(alias) => alias.to_string(),
+            None => self.to_string(),
+        }
+    }
+
+    /// Devuelve el valor de la fila que cuenta el COUNT, o `None` si la fila no se cuenta por tener la columna vacía.
+    /// En un `COUNT(*)` todas las filas se cuentan.
+    pub fn valor(&self, fila: &HashMap<String, Datos>) -> Option<Datos> {
+        match &self.columna {
+            None => Some(Datos::Integer(1)),
+            Some(columna) => fila
+                .get(columna)
+                .filter(|dato| !dato_to_string(dato).is_empty())
+                .cloned(),
+        }
+    }
+
+    /// Cuenta los valores que se guardaron en memoria, descartando los repetidos si es un `COUNT(DISTINCT ...)`.
+    pub fn contar_valores(&self, mut valores: Vec<Datos>) -> usize {
+        if self.distinct {
+            valores.sort();
+            valores.dedup();
+        }
+        valores.len()
+    }
+}
+
+/// Verifica que las columnas de los COUNT sean columnas de la tabla.
+pub fn verificar_columnas(conteos: &[Conteo], columnas: &[String]) -> Result<(), ErrorType> {
+    for columna in conteos.iter().filter_map(|c| c.columna.as_ref()) {
+        if !columnas.contains(columna) {
+            return Err(ErrorType::InvalidColumn(
+                "Esa columna no pertenece a la tabla".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Cuenta las filas del archivo con encabezado según cada COUNT.
+/// Los valores de los `COUNT(DISTINCT ...)` se escriben en archivos auxiliares en `path`
+/// y se descartan los repetidos por hash, sin necesidad de que entren todos en memoria.
+pub fn contar(conteos: &[Conteo], path: &str, origen: &String) -> Result<Vec<usize>, ErrorType> {
+    let mut valores = Vec::new();
+    for _ in conteos.iter().filter(|c| c.distinct) {
+        let path_valores = agregar_path(path, &nombre_auxiliar("distintos"));
+        if let Err(e) = crear_archivo(&path_valores) {
+            for path_valores in &valores {
+                eliminar_archivo(path_valores)?;
+            }
+            return Err(e);
+        }
+        valores.push(path_valores);
+    }
+    let resultado = contar_en_archivos(conteos, path, origen, &valores);
+    for path_valores in &valores {
+        eliminar_archivo(path_valores)?;
+    }
+    resultado
+}
+
+/// Recorre el archivo contando las filas de cada COUNT y escribiendo los valores de los `COUNT(DISTINCT ...)`,
+/// en orden, en los archivos de `valores`. Luego cuenta los valores distintos de cada uno.
+fn contar_en_archivos(
+    conteos: &[Conteo],
+    path: &str,
+    origen: &String,
+    valores: &[String],
+) -> Result<Vec<usize>, ErrorType> {
+    let mut lines = get_reader(origen)?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    verificar_columnas(conteos, &columnas)?;
+    let mut escritores = valores
+        .iter()
+        .map(abrir_para_agregar)
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut cantidades = vec![0; conteos.len()];
+    for line in lines {
+        let Ok(line) = line else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        let fila = string_to_columns(&line, &columnas)?;
+        for (conteo, cantidad) in conteos.iter().zip(cantidades.iter_mut()) {
+            if !conteo.distinct && conteo.valor(&fila).is_some() {
+                *cantidad += 1;
+            }
+        }
+        let distinct = conteos.iter().filter(|c| c.distinct);
+        for (conteo, escritor) in distinct.zip(escritores.iter_mut()) {
+            if let Some(valor) = conteo.valor(&fila) {
+                escribir(escritor, &dato_to_string(&valor))?;
+            }
+        }
+    }
+    escritores.into_iter().try_for_each(terminar)?;
+    let distinct = cantidades
+        .iter_mut()
+        .zip(conteos)
+        .filter(|(_, c)| c.distinct);
+    for ((cantidad, _), path_valores) in distinct.zip(valores) {
+        let path_distintos = agregar_path(path, &nombre_auxiliar("distintos"));
+        crear_archivo(&path_distintos)?;
+        let distintos = distintas(path, path_valores, &path_distintos);
+        eliminar_archivo(&path_distintos)?;
+        *cantidad = distintos?;
+    }
+    Ok(cantidades)
+}
+
+impl Display for Conteo {
+    /// Muestra el COUNT como se escribe en la consulta, sin su alias.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let distinct = if self.distinct { "DISTINCT " } else { "" };
+        match &self.columna {
+            Some(columna) => write!(f, "COUNT({}{})", distinct, columna),
+            None => write!(f, "COUNT(*)"),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{
+        execute::Execute,
+        manejo_csv::{agregar_path, eliminar_archivo},
+    },
+};
+
+use super::{
+    vista::{ruta_vista, validar_tablas},
+    vista_materializada::refrescar,
+    with_query::Consulta,
+};
+
+/// Representa una consulta SQL `CREATE MATERIALIZED VIEW nombre [AUTO REFRESH] AS SELECT ...`.
+/// El resultado de la consulta se guarda en su propio CSV y se actualiza con `REFRESH MATERIALIZED VIEW`,
+/// o al usarla si es `AUTO REFRESH` y sus tablas cambiaron.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CreateMaterializedViewQuery {
+    pub nombre: String,
+    pub consulta: Consulta,
+    pub automatica: bool,
+}
+
+impl CreateMaterializedViewQuery {
+    /// Crea una nueva instancia de `CreateMaterializedViewQuery`.
+    pub fn new(nombre: &str, consulta: Consulta, automatica: bool) -> Self {
+        CreateMaterializedViewQuery {
+            nombre: nombre.to_string(),
+            consulta,
+            automatica,
+        }
+    }
+}
+
+impl Execute for CreateMaterializedViewQuery {
+    /// Ejecuta la consulta CREATE MATERIALIZED VIEW guardando su texto en el archivo `.vista`
+    /// y su resultado en el CSV de la vista. Si la consulta falla no se crea ningún archivo.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let ruta_csv = agregar_path(path, &self.nombre);
+        if Path::new(&ruta_csv).exists() {
+            return Err(ErrorType::InvalidTable(
+                "Ya existe una tabla con ese nombre".to_string(),
+            ));
+        }
+        let ruta = ruta_vista(path, &self.nombre);
+        if Path::new(&ruta).exists() {
+            return Err(ErrorType::InvalidTable(
+                "Ya existe una vista con ese nombre".to_string(),
+            ));
+        }
+        validar_tablas(path, &self.nombre, &self.consulta)?;
+        if fs::write(&ruta, format!("{}\n", self)).is_err() {
+            return Err(ErrorType::InvalidTable(
+                "Error al guardar la vista".to_string(),
+            ));
+        }
+        if let Err(error) = refrescar(path, self) {
+            eliminar_archivo(&ruta)?;
+            if Path::new(&ruta_csv).exists() {
+                eliminar_archivo(&ruta_csv)?;
+            }
+            return Err(error);
+        }
+        Ok(())
+    }
+}
+
+impl Display for CreateMaterializedViewQuery {
+    /// Muestra la sentencia CREATE MATERIALIZED VIEW con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CREATE MATERIALIZED VIEW {} ", self.nombre)?;
+        if self.automatica {
+            write!(f, "AUTO REFRESH ")?;
+        }
+        write!(f, "AS {}", self.consulta)
+    }
+}
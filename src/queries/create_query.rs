@@ -0,0 +1,91 @@
+use std::{io::BufRead, path::Path};
+
+use crate::{
+    errores::error::ErrorType,
+    esquema::{
+        esquema_tabla::{ruta_esquema, Esquema},
+        restricciones::{VerificadorClaves, VerificadorReferencias},
+    },
+    executer::{
+        execute::Execute,
+        manejo_csv::{
+            agregar_linea, agregar_path, crear_archivo, eliminar_archivo, get_reader,
+            leer_encabezado,
+        },
+    },
+};
+
+use super::vista::ruta_vista;
+
+/// Representa una consulta SQL CREATE TABLE con el esquema de la tabla a crear.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CreateQuery {
+    pub esquema: Esquema,
+}
+
+impl CreateQuery {
+    /// Crea una nueva instancia de `CreateQuery`.
+    pub fn new(esquema: Esquema) -> Self {
+        CreateQuery { esquema }
+    }
+
+    /// Verifica que la tabla tenga las columnas del esquema y que sus filas cumplan todas las restricciones.
+    fn validar_tabla(&self, path: &str, path_tabla: &String) -> Result<(), ErrorType> {
+        let mut lines = get_reader(path_tabla)?.lines();
+        let columnas = leer_encabezado(&mut lines)?;
+        if columnas != self.esquema.nombres_columnas() {
+            return Err(ErrorType::InvalidColumn(
+                "Las columnas del esquema no coinciden con las de la tabla existente".to_string(),
+            ));
+        }
+        let esquema = Some(self.esquema.clone());
+        let mut verificador = VerificadorClaves::new(&esquema, &columnas)?;
+        let referencias = VerificadorReferencias::new(path, &esquema, &columnas, path_tabla)?;
+        for line in lines {
+            match line {
+                Ok(line) => {
+                    self.esquema.verificar_fila(&line, &columnas)?;
+                    verificador.registrar(&line)?;
+                    referencias.verificar(&line)?;
+                }
+                Err(_) => {
+                    return Err(ErrorType::InvalidTable(
+                        "Error al leer una linea".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Execute for CreateQuery {
+    /// Ejecuta la consulta CREATE TABLE guardando el esquema junto a la tabla.
+    /// Si el archivo de la tabla no existe lo crea con el encabezado; si existe, valida que sus datos respeten el esquema.
+    /// En ambos casos verifica que las tablas referenciadas por las foreign keys existan.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        self.esquema.validar()?;
+        if Path::new(&ruta_esquema(path, &self.esquema.tabla)).exists() {
+            return Err(ErrorType::InvalidTable(
+                "La tabla ya tiene un esquema definido".to_string(),
+            ));
+        }
+        let path_tabla = agregar_path(path, &self.esquema.tabla);
+        if Path::new(&ruta_vista(path, &self.esquema.tabla)).exists() {
+            return Err(ErrorType::InvalidTable(
+                "Ya existe una vista con ese nombre".to_string(),
+            ));
+        }
+        if Path::new(&path_tabla).exists() {
+            self.validar_tabla(path, &path_tabla)?;
+        } else {
+            crear_archivo(&path_tabla)?;
+            agregar_linea(&path_tabla, &self.esquema.nombres_columnas().join(","))?;
+            if let Err(e) = self.validar_tabla(path, &path_tabla) {
+                eliminar_archivo(&path_tabla)?;
+                return Err(e);
+            }
+        }
+        self.esquema.guardar(path)
+    }
+}
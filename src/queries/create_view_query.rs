@@ -0,0 +1,64 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{execute::Execute, manejo_csv::agregar_path},
+};
+
+use super::{
+    vista::{ruta_vista, validar_tablas},
+    with_query::Consulta,
+};
+
+/// Representa una consulta SQL `CREATE VIEW nombre AS SELECT ...`.
+/// La vista se guarda como texto SQL y su consulta se ejecuta cada vez que se usa como tabla.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CreateViewQuery {
+    pub nombre: String,
+    pub consulta: Consulta,
+}
+
+impl CreateViewQuery {
+    /// Crea una nueva instancia de `CreateViewQuery`.
+    pub fn new(nombre: &str, consulta: Consulta) -> Self {
+        CreateViewQuery {
+            nombre: nombre.to_string(),
+            consulta,
+        }
+    }
+}
+
+impl Execute for CreateViewQuery {
+    /// Ejecuta la consulta CREATE VIEW guardando su texto en el archivo `.vista`,
+    /// si no existe otra tabla o vista con el mismo nombre.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        if Path::new(&agregar_path(path, &self.nombre)).exists() {
+            return Err(ErrorType::InvalidTable(
+                "Ya existe una tabla con ese nombre".to_string(),
+            ));
+        }
+        if Path::new(&ruta_vista(path, &self.nombre)).exists() {
+            return Err(ErrorType::InvalidTable(
+                "Ya existe una vista con ese nombre".to_string(),
+            ));
+        }
+        validar_tablas(path, &self.nombre, &self.consulta)?;
+        match fs::write(ruta_vista(path, &self.nombre), format!("{}\n", self)) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ErrorType::InvalidTable(
+                "Error al guardar la vista".to_string(),
+            )),
+        }
+    }
+}
+
+impl Display for CreateViewQuery {
+    /// Muestra la sentencia CREATE VIEW con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CREATE VIEW {} AS {}", self.nombre, self.consulta)
+    }
+}
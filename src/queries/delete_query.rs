@@ -1,23 +1,35 @@
-use std::io::BufRead;
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufRead,
+};
 
 use crate::{
+    dato::Datos,
     errores::error::ErrorType,
+    esquema::{
+        esquema_tabla::Esquema,
+        foreign_key::{referencias_a, AccionReferencial, ForeignKey},
+        restricciones::{verificar_no_referenciadas, ClavesQuitadas},
+    },
     executer::{
         execute::Execute,
         manejo_csv::{
-            agregar_linea, eliminar_archivo, listar_columnas, preparar_archivos,
-            reemplazar_archivo, string_to_columns, where_condition,
+            agregar_linea, agregar_path, filtrar_columnas, get_reader, imprimir_filas,
+            leer_encabezado, listar_columnas, modificar_linea, string_to_columns, where_condition,
         },
+        transaccion::Transaccion,
     },
+    utils::dato_to_string,
 };
 
-use super::where_clause::expresion_booleana::ExpresionBooleana;
+use super::where_clause::{expresion_booleana::ExpresionBooleana, subconsulta::preparar_condicion};
 
 /// Representa una consulta SQL DELETE con una tabla y una cláusula WHERE opcional.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct DeleteQuery {
     pub table: String,
     pub where_clause: Option<ExpresionBooleana>,
+    pub returning: Option<Vec<String>>,
 }
 
 impl DeleteQuery {
@@ -26,34 +38,172 @@ impl DeleteQuery {
         DeleteQuery {
             table: table.to_string(),
             where_clause,
+            returning: None,
+        }
+    }
+
+    /// Prepara las subconsultas del WHERE para evaluarlo sobre las filas de la tabla.
+    fn preparar_where(&self, path: &str) -> Result<Option<ExpresionBooleana>, ErrorType> {
+        let mut lines = get_reader(&agregar_path(path, &self.table))?.lines();
+        let columnas = leer_encabezado(&mut lines)?;
+        preparar_condicion(&self.where_clause, path, &self.table, &columnas)
+    }
+}
+
+/// Criterio que decide si una fila se elimina o se modifica.
+type Criterio<'a> = dyn Fn(&HashMap<String, Datos>) -> Result<bool, ErrorType> + 'a;
+
+/// Devuelve un criterio que se cumple si las columnas de la foreign key de la fila están entre las claves dadas.
+fn referencia_alguna<'a>(
+    fk: &'a ForeignKey,
+    claves: &'a HashSet<Vec<String>>,
+) -> impl Fn(&HashMap<String, Datos>) -> Result<bool, ErrorType> + 'a {
+    move |fila| {
+        let mut clave = Vec::new();
+        for columna in &fk.columnas {
+            match fila.get(columna).map(dato_to_string) {
+                Some(valor) if !valor.is_empty() => clave.push(valor),
+                _ => return Ok(false),
+            }
+        }
+        Ok(claves.contains(&clave))
+    }
+}
+
+/// Pone en nulo las columnas de la foreign key en las filas de la tabla hija que referencian alguna de las claves.
+fn anular_referencias(
+    transaccion: &mut Transaccion,
+    hija: &str,
+    fk: &ForeignKey,
+    claves: &HashSet<Vec<String>>,
+) -> Result<(), ErrorType> {
+    let criterio = referencia_alguna(fk, claves);
+    let cambios: HashMap<String, Datos> = fk
+        .columnas
+        .iter()
+        .map(|c| (c.to_string(), Datos::String("".to_string())))
+        .collect();
+    let esquema = Esquema::cargar(transaccion.path(), hija)?;
+    let path_aux = transaccion.nuevo_auxiliar()?;
+    let reader = get_reader(&transaccion.origen(hija))?;
+    let (lines, columnas) = listar_columnas(&path_aux, reader.lines())?;
+    for line in lines {
+        match line {
+            Ok(mut line) => {
+                if criterio(&string_to_columns(&line, &columnas)?)? {
+                    line = modificar_linea(&line, &cambios, &columnas)?;
+                    if let Some(esquema) = &esquema {
+                        esquema.verificar_fila(&line, &columnas)?;
+                    }
+                }
+                agregar_linea(&path_aux, &line)?;
+            }
+            Err(_) => {
+                return Err(ErrorType::InvalidTable(
+                    "Error al escribir una linea".to_string(),
+                ))
+            }
         }
     }
+    transaccion.registrar(hija, &path_aux);
+    Ok(())
+}
+
+/// Aplica la acción ON DELETE de cada foreign key que referencia a las claves eliminadas de la tabla.
+fn aplicar_on_delete(
+    transaccion: &mut Transaccion,
+    tabla: &str,
+    referencias: &[(String, ForeignKey)],
+    claves_eliminadas: &ClavesQuitadas,
+) -> Result<(), ErrorType> {
+    for (hija, fk) in referencias {
+        let Some(claves) = claves_eliminadas.claves().get(&fk.columnas_ref) else {
+            continue;
+        };
+        if claves.is_empty() {
+            continue;
+        }
+        match fk.on_delete {
+            AccionReferencial::Restrict => {
+                verificar_no_referenciadas(tabla, hija, fk, claves, &transaccion.origen(hija))?
+            }
+            AccionReferencial::Cascade => {
+                eliminar_filas(transaccion, hija, &referencia_alguna(fk, claves), false)?;
+            }
+            AccionReferencial::SetNull => anular_referencias(transaccion, hija, fk, claves)?,
+        }
+    }
+    Ok(())
+}
+
+/// Escribe en un archivo auxiliar las filas de la tabla que no cumplen el criterio
+/// y aplica las acciones ON DELETE de las foreign keys que referencian a las filas eliminadas.
+/// Devuelve las columnas de la tabla y, si se pide con `devolver`, las filas eliminadas.
+fn eliminar_filas(
+    transaccion: &mut Transaccion,
+    tabla: &str,
+    criterio: &Criterio,
+    devolver: bool,
+) -> Result<(Vec<String>, Vec<String>), ErrorType> {
+    let referencias = referencias_a(transaccion.path(), tabla)?;
+    let path_aux = transaccion.nuevo_auxiliar()?;
+    let reader = get_reader(&transaccion.origen(tabla))?;
+    let (lines, columnas) = listar_columnas(&path_aux, reader.lines())?;
+    let mut claves_eliminadas = ClavesQuitadas::new(&referencias, &columnas)?;
+    let mut eliminadas = Vec::new();
+    for line in lines {
+        match line {
+            Ok(line) => {
+                let fila = string_to_columns(&line, &columnas)?;
+                if criterio(&fila)? {
+                    claves_eliminadas.registrar(&line);
+                    if devolver {
+                        eliminadas.push(line);
+                    }
+                } else {
+                    agregar_linea(&path_aux, &line)?;
+                }
+            }
+            Err(_) => {
+                return Err(ErrorType::InvalidTable(
+                    "Error al escribir una linea".to_string(),
+                ))
+            }
+        }
+    }
+    transaccion.registrar(tabla, &path_aux);
+    aplicar_on_delete(transaccion, tabla, &referencias, &claves_eliminadas)?;
+    Ok((columnas, eliminadas))
 }
 
 impl Execute for DeleteQuery {
     /// Ejecuta la consulta DELETE en el archivo especificado, considerando la cláusula WHERE.
+    /// Las filas de otras tablas que referencian a las eliminadas se tratan según su acción ON DELETE;
+    /// si alguna restricción lo impide, ninguna tabla se modifica.
+    /// Con RETURNING, imprime las filas eliminadas como el resultado de un SELECT.
     fn execute(&self, path: &str) -> Result<(), ErrorType> {
-        let (path_delete, reader, path_aux) =
-            preparar_archivos(path, &self.table, &"auxiliar".to_string())?;
-        let lines = reader.lines();
-        let (lines, columnas) = listar_columnas(&path_aux, lines)?;
-        for line in lines {
-            match line {
-                Ok(line) => {
-                    let fila = string_to_columns(&line, &columnas)?;
-                    if !where_condition(&self.where_clause, &fila)? {
-                        agregar_linea(&path_aux, &line)?;
-                    }
-                }
-                Err(_) => {
-                    return Err(ErrorType::InvalidTable(
-                        "Error al escribir una linea".to_string(),
-                    ))
+        let where_clause = self.preparar_where(path)?;
+        let mut transaccion = Transaccion::new(path);
+        let criterio = |fila: &HashMap<String, Datos>| where_condition(&where_clause, fila);
+        let devolver = self.returning.is_some();
+        let resultado = eliminar_filas(&mut transaccion, &self.table, &criterio, devolver)
+            .and_then(|(columnas, eliminadas)| match &self.returning {
+                Some(returning) => {
+                    filtrar_columnas(returning, &columnas).map(|_| (columnas, eliminadas))
                 }
+                None => Ok((columnas, eliminadas)),
+            });
+        let (columnas, eliminadas) = match resultado {
+            Ok(resultado) => resultado,
+            Err(e) => {
+                transaccion.descartar();
+                return Err(e);
             }
+        };
+        transaccion.confirmar()?;
+        match &self.returning {
+            Some(returning) => imprimir_filas(path, &columnas, &eliminadas, returning),
+            None => Ok(()),
         }
-        reemplazar_archivo(&path_aux, &path_delete)?;
-        eliminar_archivo(&path_aux)?;
-        Ok(())
     }
 }
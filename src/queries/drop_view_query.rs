@@ -0,0 +1,77 @@
+use std::{fs, path::Path};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{
+        execute::Execute,
+        manejo_csv::{agregar_path, eliminar_archivo},
+    },
+};
+
+use super::{
+    vista::{ruta_vista, vistas_dependientes},
+    vista_materializada::{es_materializada, ruta_fuentes},
+};
+
+/// Representa una consulta SQL `DROP VIEW nombre` o `DROP MATERIALIZED VIEW nombre`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DropViewQuery {
+    pub nombre: String,
+    pub materializada: bool,
+}
+
+impl DropViewQuery {
+    /// Crea una nueva instancia de `DropViewQuery`.
+    pub fn new(nombre: &str, materializada: bool) -> Self {
+        DropViewQuery {
+            nombre: nombre.to_string(),
+            materializada,
+        }
+    }
+}
+
+impl Execute for DropViewQuery {
+    /// Ejecuta la consulta DROP VIEW eliminando el archivo `.vista`. Las tablas de la vista no se modifican.
+    /// Para una vista materializada también se eliminan su CSV y su archivo `.fuentes`.
+    /// No se puede eliminar una vista mientras otra vista la use.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let ruta = ruta_vista(path, &self.nombre);
+        if !Path::new(&ruta).exists() {
+            return Err(ErrorType::InvalidTable(format!(
+                "La vista '{}' no existe",
+                self.nombre
+            )));
+        }
+        match (self.materializada, es_materializada(path, &self.nombre)) {
+            (false, true) => {
+                return Err(ErrorType::InvalidTable(format!(
+                    "'{}' es una vista materializada, se debe usar DROP MATERIALIZED VIEW",
+                    self.nombre
+                )))
+            }
+            (true, false) => {
+                return Err(ErrorType::InvalidTable(format!(
+                    "La vista materializada '{}' no existe",
+                    self.nombre
+                )))
+            }
+            _ => {}
+        }
+        if let Some(dependiente) = vistas_dependientes(path, &self.nombre)?.first() {
+            return Err(ErrorType::InvalidTable(format!(
+                "No se puede eliminar la vista '{}' porque la usa la vista '{}'",
+                self.nombre, dependiente
+            )));
+        }
+        if self.materializada {
+            eliminar_archivo(&agregar_path(path, &self.nombre))?;
+            eliminar_archivo(&ruta_fuentes(path, &self.nombre))?;
+        }
+        match fs::remove_file(ruta) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(ErrorType::InvalidTable(
+                "Error al eliminar la vista".to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,543 @@
+use std::{io::Write, slice, time::Instant};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    executer::{
+        execute::Execute,
+        manejo_csv::{
+            agregar_path, crear_archivo, eliminar_archivo, nombre_auxiliar, proyectar_archivo,
+        },
+        mediciones::{clave, clave_lectura, contar_filas, Medicion, Mediciones},
+    },
+    plan::{
+        costos::{filas_por_join, filas_scan, selectividad},
+        optimizador::optimizar,
+        plan_logico::{PlanLogico, Scan},
+    },
+};
+
+use super::{
+    compound_query::{CompoundQuery, Conjunto},
+    order_clause::OrderClause,
+    select_query::SelectQuery,
+    sql_query::SQLQuery,
+    where_clause::expresion_booleana::ExpresionBooleana,
+    with_query::{Consulta, WithQuery},
+};
+
+/// Representa una consulta `EXPLAIN [ANALYZE] consulta`, que muestra el árbol de operadores con el que
+/// se ejecuta la consulta. Con `analyze`, además la ejecuta sin imprimir su resultado y muestra en cada
+/// operador las filas que recibió y devolvió y cuánto tardó.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExplainQuery {
+    pub consulta: Box<SQLQuery>,
+    pub analyze: bool,
+}
+
+/// Operador del plan de una consulta, con los detalles que muestra debajo de su nombre y los operadores
+/// de los que recibe sus filas. La clave identifica su medición en `EXPLAIN ANALYZE`.
+#[derive(Debug, PartialEq, Clone)]
+struct NodoPlan {
+    operador: String,
+    detalles: Vec<String>,
+    clave: Option<String>,
+    hijos: Vec<NodoPlan>,
+}
+
+impl NodoPlan {
+    /// Crea un operador sin detalles ni hijos.
+    fn new(operador: &str, clave: Option<String>) -> Self {
+        NodoPlan {
+            operador: operador.to_string(),
+            detalles: Vec::new(),
+            clave,
+            hijos: Vec::new(),
+        }
+    }
+
+    /// Crea un operador que recibe las filas de `hijo`.
+    fn sobre(operador: &str, clave: Option<String>, hijo: NodoPlan) -> Self {
+        let mut nodo = NodoPlan::new(operador, clave);
+        nodo.hijos.push(hijo);
+        nodo
+    }
+
+    /// Quita las claves del operador y de sus hijos, que se ejecutan sin medir cada etapa.
+    fn sin_mediciones(mut self) -> Self {
+        self.clave = None;
+        self.hijos = self
+            .hijos
+            .into_iter()
+            .map(NodoPlan::sin_mediciones)
+            .collect();
+        self
+    }
+
+    /// Agrega las líneas del operador y de sus hijos, con la sangría indicada.
+    /// Los hijos se muestran debajo de los detalles, precedidos por `->`.
+    fn escribir(
+        &self,
+        sangria: &str,
+        es_hijo: bool,
+        mediciones: Option<&Mediciones>,
+        lineas: &mut Vec<String>,
+    ) {
+        let flecha = if es_hijo { "-> " } else { "" };
+        let mut linea = format!("{}{}{}", sangria, flecha, self.operador);
+        if let (Some(mediciones), Some(clave)) = (mediciones, &self.clave) {
+            match mediciones.medicion(clave) {
+                Some(medicion) => linea.push_str(&format!(" ({})", mostrar_medicion(medicion))),
+                None => linea.push_str(" (no ejecutado)"),
+            }
+        }
+        lineas.push(linea);
+        let sangria = format!("{}{}", sangria, if es_hijo { "     " } else { "  " });
+        for detalle in &self.detalles {
+            lineas.push(format!("{}{}", sangria, detalle));
+        }
+        for hijo in &self.hijos {
+            hijo.escribir(&sangria, true, mediciones, lineas);
+        }
+    }
+}
+
+/// Muestra las filas y el tiempo de una medición.
+fn mostrar_medicion(medicion: &Medicion) -> String {
+    let filas = match medicion.filas_entrada {
+        Some(entrada) => format!("{} -> {}", entrada, medicion.filas_salida),
+        None => medicion.filas_salida.to_string(),
+    };
+    format!(
+        "filas: {}, tiempo: {:.3} ms",
+        filas,
+        medicion.tiempo.as_secs_f64() * 1000.0
+    )
+}
+
+/// Muestra las columnas de un ORDER BY separadas por comas.
+fn mostrar_orden(order_by: &[OrderClause]) -> String {
+    let orden: Vec<String> = order_by.iter().map(|o| o.to_string()).collect();
+    orden.join(", ")
+}
+
+/// Devuelve el operador que lee las filas de la tabla del FROM, de sus VALUES o, sin FROM, la única fila.
+fn operador_lectura(select: &SelectQuery) -> String {
+    let operador = match &select.valores {
+        Some(_) if select.table.is_empty() => return "Result".to_string(),
+        Some(_) => format!("Values Scan en {}", select.table),
+        None => format!("Seq Scan en {}", select.table),
+    };
+    match &select.alias {
+        Some(alias) => format!("{} {}", operador, alias),
+        None => operador,
+    }
+}
+
+/// Devuelve el operador que lee las filas de una tabla combinada por JOIN.
+fn operador_scan(scan: &Scan) -> String {
+    match &scan.alias {
+        Some(alias) => format!("Seq Scan en {} {}", scan.tabla, alias),
+        None => format!("Seq Scan en {}", scan.tabla),
+    }
+}
+
+/// Devuelve el detalle con la cantidad estimada de filas, si se analizaron las tablas que la determinan.
+fn detalle_estimacion(filas: Option<f64>) -> Option<String> {
+    filas.map(|filas| format!("Filas estimadas: {:.0}", filas))
+}
+
+/// Devuelve los detalles de la lectura de una tabla: el filtro que se aplica a sus filas, las columnas
+/// que se leen de ella si no son todas y, si se analizó, cuántas filas se estima que devuelve.
+fn detalles_lectura(scan: &Scan, filtro: Option<&ExpresionBooleana>) -> Vec<String> {
+    let mut detalles = Vec::new();
+    if let Some(filtro) = filtro {
+        detalles.push(format!("Filtro: {}", filtro));
+    }
+    if let (Some(columnas), Some(leidas)) = (&scan.columnas, &scan.leidas) {
+        if leidas.len() < columnas.len() {
+            detalles.push(format!("Columnas leídas: {}", leidas.join(", ")));
+        }
+    }
+    let selectividad = filtro.map_or(1.0, |filtro| selectividad(filtro, slice::from_ref(scan)));
+    detalles.extend(detalle_estimacion(
+        filas_scan(scan).map(|filas| filas * selectividad),
+    ));
+    detalles
+}
+
+/// Devuelve el operador que lee las filas de la consulta y escribe las que cumplen el WHERE, junto con
+/// las expresiones calculadas. Con JOIN, lee las filas combinadas de los operadores de cada JOIN.
+/// Las condiciones y el orden de los JOIN son los del plan optimizado: con JOIN, cada tabla muestra
+/// las condiciones que filtran sus filas antes de combinarlas. Cada tabla muestra las columnas
+/// que se leen de ella, si no son todas.
+/// Si se analizaron las tablas, cada operador muestra cuántas filas se estima que devuelve.
+fn plan_filas(select: &SelectQuery, path: &str, prefijo: &str) -> NodoPlan {
+    let optimizado = optimizar(PlanLogico::desde_select(select, path));
+    let lecturas = optimizado.lecturas();
+    let mut nodo = if select.joins.is_empty() {
+        let mut nodo = NodoPlan::new(&operador_lectura(select), Some(clave(prefijo, "filas")));
+        if let Some((scan, filtro)) = lecturas.first() {
+            nodo.detalles = detalles_lectura(scan, *filtro);
+        }
+        nodo
+    } else {
+        let scans: Vec<Scan> = lecturas.iter().map(|(scan, _)| (*scan).clone()).collect();
+        let nodo_lectura = |posicion: usize| {
+            let (scan, filtro) = lecturas[posicion];
+            let clave = clave_lectura(prefijo, posicion);
+            let mut nodo = NodoPlan::new(&operador_scan(scan), Some(clave));
+            nodo.detalles = detalles_lectura(scan, filtro);
+            nodo
+        };
+        let mut actual = nodo_lectura(0);
+        if select.valores.is_some() {
+            actual.operador = operador_lectura(select);
+        }
+        let filas_joins = filas_por_join(&optimizado);
+        let condiciones = optimizado.condiciones_join();
+        for (i, condicion) in condiciones.into_iter().enumerate() {
+            let clave_join = clave(prefijo, &format!("join{}", i));
+            let mut nodo_join = NodoPlan::sobre("Block Nested Loop Join", Some(clave_join), actual);
+            nodo_join.detalles.push(format!("Condición: {}", condicion));
+            nodo_join
+                .detalles
+                .extend(detalle_estimacion(filas_joins.get(i).copied().flatten()));
+            nodo_join.hijos.push(nodo_lectura(i + 1));
+            actual = nodo_join;
+        }
+        let filtro = optimizado.filtro_combinadas();
+        let operador = if filtro.is_some() { "Filter" } else { "Result" };
+        let mut nodo = NodoPlan::sobre(operador, Some(clave(prefijo, "filas")), actual);
+        if let Some(filtro) = filtro {
+            nodo.detalles.push(format!("Filtro: {}", filtro));
+            let combinadas = filas_joins.last().copied().flatten();
+            nodo.detalles.extend(detalle_estimacion(
+                combinadas.map(|filas| filas * selectividad(filtro, &scans)),
+            ));
+        }
+        nodo
+    };
+    if !select.calculadas.is_empty() {
+        let calculadas: Vec<String> = select.calculadas.iter().map(|c| c.to_string()).collect();
+        nodo.detalles
+            .push(format!("Calcula: {}", calculadas.join(", ")));
+    }
+    if let (true, Some(order_by), Some(limite)) =
+        (select.usa_primeras_filas(), &select.order_by, select.limit)
+    {
+        nodo.detalles.push(format!(
+            "Primeras {} filas según: {}",
+            limite,
+            mostrar_orden(order_by)
+        ));
+    }
+    nodo
+}
+
+/// Devuelve el plan de un SELECT: la lectura de sus filas, las funciones de ventana, el COUNT, el
+/// DISTINCT o el orden, el LIMIT y la proyección de las columnas seleccionadas.
+fn plan_select(select: &SelectQuery, path: &str, prefijo: &str) -> NodoPlan {
+    let mut nodo = plan_filas(select, path, prefijo);
+    if !select.ventanas.is_empty() {
+        let ventanas: Vec<String> = select.ventanas.iter().map(|v| v.to_string()).collect();
+        nodo = NodoPlan::sobre("WindowAgg", Some(clave(prefijo, "ventanas")), nodo);
+        nodo.detalles
+            .push(format!("Calcula: {}", ventanas.join(", ")));
+    }
+    if !select.conteos.is_empty() {
+        let conteos: Vec<String> = select.conteos.iter().map(|c| c.to_string()).collect();
+        nodo = NodoPlan::sobre("Aggregate", Some(clave(prefijo, "conteo")), nodo);
+        nodo.detalles
+            .push(format!("Calcula: {}", conteos.join(", ")));
+    } else if select.distinct {
+        nodo = NodoPlan::sobre("Unique", Some(clave(prefijo, "distinct")), nodo);
+    } else if let (Some(order_by), false) = (&select.order_by, select.usa_primeras_filas()) {
+        nodo = NodoPlan::sobre("Sort", Some(clave(prefijo, "orden")), nodo);
+        nodo.detalles
+            .push(format!("Orden: {}", mostrar_orden(order_by)));
+    }
+    if let Some(limite) = select.limit {
+        nodo = NodoPlan::sobre("Limit", Some(clave(prefijo, "limite")), nodo);
+        nodo.detalles.push(format!("Filas: {}", limite));
+    }
+    let columnas: Vec<String> = if select.conteos.is_empty() {
+        select
+            .columns_select
+            .iter()
+            .zip(&select.alias_columnas)
+            .map(|(columna, alias)| match alias {
+                Some(alias) => format!("{} AS {}", columna, alias),
+                None => columna.to_string(),
+            })
+            .collect()
+    } else {
+        select.conteos.iter().map(|c| c.to_string()).collect()
+    };
+    nodo = NodoPlan::sobre("Project", Some(clave(prefijo, "proyeccion")), nodo);
+    nodo.detalles
+        .push(format!("Columnas: {}", columnas.join(", ")));
+    nodo
+}
+
+/// Devuelve el plan de los SELECT combinados, con las claves de cada lado precedidas por `izq.` o `der.`.
+fn plan_conjunto(conjunto: &Conjunto, path: &str, prefijo: &str) -> NodoPlan {
+    match conjunto {
+        Conjunto::Select(select) => plan_select(select, path, prefijo),
+        Conjunto::Operacion { izq, operador, der } => {
+            let mut nodo = NodoPlan::new(&operador.to_string(), Some(clave(prefijo, "conjunto")));
+            nodo.hijos
+                .push(plan_conjunto(izq, path, &clave(prefijo, "izq.")));
+            nodo.hijos
+                .push(plan_conjunto(der, path, &clave(prefijo, "der.")));
+            nodo
+        }
+    }
+}
+
+/// Devuelve el plan de una combinación de SELECT, con su orden y su LIMIT.
+fn plan_compound(compound: &CompoundQuery, path: &str, prefijo: &str) -> NodoPlan {
+    let nodo = plan_conjunto(&compound.conjunto, path, prefijo);
+    match (&compound.order_by, compound.limit) {
+        (Some(order_by), Some(limite)) => {
+            let mut nodo = NodoPlan::sobre("Sort", Some(clave(prefijo, "orden")), nodo);
+            nodo.detalles.push(format!(
+                "Primeras {} filas según: {}",
+                limite,
+                mostrar_orden(order_by)
+            ));
+            nodo
+        }
+        (None, Some(limite)) => {
+            let mut nodo = NodoPlan::sobre("Limit", Some(clave(prefijo, "limite")), nodo);
+            nodo.detalles.push(format!("Filas: {}", limite));
+            nodo
+        }
+        (Some(order_by), None) => {
+            let mut nodo = NodoPlan::sobre("Sort", Some(clave(prefijo, "orden")), nodo);
+            nodo.detalles
+                .push(format!("Orden: {}", mostrar_orden(order_by)));
+            nodo
+        }
+        (None, None) => nodo,
+    }
+}
+
+/// Devuelve el plan de un SELECT o de una combinación de SELECT.
+fn plan_consulta(consulta: &Consulta, path: &str, prefijo: &str) -> NodoPlan {
+    match consulta {
+        Consulta::Select(select) => plan_select(select, path, prefijo),
+        Consulta::Compound(compound) => plan_compound(compound, path, prefijo),
+    }
+}
+
+/// Devuelve el plan de un WITH: el de cada expresión, que se ejecuta una vez si se usa, y el de la consulta principal.
+/// Cada expresión se mide como un único operador, con las filas de su resultado.
+fn plan_with(with: &WithQuery, path: &str) -> NodoPlan {
+    let mut nodo = NodoPlan::new("With", None);
+    for (i, (cte, usada)) in with.ctes.iter().zip(with.usadas()).enumerate() {
+        let recursiva = if with.recursiva && cte.es_recursiva() {
+            " (recursiva)"
+        } else {
+            ""
+        };
+        let operador = format!("CTE {}{}", cte.nombre, recursiva);
+        let consulta = plan_consulta(&cte.consulta, path, "").sin_mediciones();
+        let mut nodo_cte = NodoPlan::sobre(&operador, Some(format!("cte{}", i)), consulta);
+        if !usada {
+            nodo_cte.detalles.push("Sin usar".to_string());
+        }
+        nodo.hijos.push(nodo_cte);
+    }
+    nodo.hijos.push(plan_consulta(&with.consulta, path, ""));
+    nodo
+}
+
+/// Devuelve el plan de una consulta. Las que modifican una tabla tienen un único operador medido,
+/// con las filas de la tabla antes y después de ejecutarla. Las columnas de cada tabla se leen de `path`.
+fn plan(consulta: &SQLQuery, path: &str) -> Result<NodoPlan, ErrorType> {
+    let consulta_clave = Some("consulta".to_string());
+    let nodo = match consulta {
+        SQLQuery::Select(select) => plan_select(select, path, ""),
+        SQLQuery::Compound(compound) => plan_compound(compound, path, ""),
+        SQLQuery::With(with) => plan_with(with, path),
+        SQLQuery::Insert(insert) => {
+            let operador = format!("Insert en {}", insert.table);
+            match &insert.select {
+                Some(select) => NodoPlan::sobre(
+                    &operador,
+                    consulta_clave,
+                    plan_select(select, path, "select."),
+                ),
+                None => {
+                    let mut valores = NodoPlan::new("Values Scan", None);
+                    valores
+                        .detalles
+                        .push(format!("Filas: {}", insert.values.len()));
+                    NodoPlan::sobre(&operador, consulta_clave, valores)
+                }
+            }
+        }
+        SQLQuery::Update(update) => {
+            let mut asignaciones: Vec<String> = update
+                .changes
+                .iter()
+                .map(|(columna, dato)| match dato {
+                    Datos::Integer(entero) => format!("{} = {}", columna, entero),
+                    Datos::String(texto) => format!("{} = '{}'", columna, texto),
+                })
+                .chain(
+                    update
+                        .expresiones
+                        .iter()
+                        .map(|(columna, valor)| format!("{} = {}", columna, valor)),
+                )
+                .collect();
+            asignaciones.sort();
+            let mut scan = NodoPlan::new(&format!("Seq Scan en {}", update.table), None);
+            if let Some(where_condition) = &update.where_condition {
+                scan.detalles.push(format!("Filtro: {}", where_condition));
+            }
+            let operador = format!("Update en {}", update.table);
+            let mut nodo = NodoPlan::sobre(&operador, consulta_clave, scan);
+            nodo.detalles
+                .push(format!("Asigna: {}", asignaciones.join(", ")));
+            nodo
+        }
+        SQLQuery::Delete(delete) => {
+            let mut scan = NodoPlan::new(&format!("Seq Scan en {}", delete.table), None);
+            if let Some(where_clause) = &delete.where_clause {
+                scan.detalles.push(format!("Filtro: {}", where_clause));
+            }
+            NodoPlan::sobre(&format!("Delete en {}", delete.table), consulta_clave, scan)
+        }
+        _ => {
+            return Err(ErrorType::InvalidSyntax(
+                "Solo se puede usar EXPLAIN con consultas SELECT, INSERT, UPDATE y DELETE"
+                    .to_string(),
+            ))
+        }
+    };
+    Ok(nodo)
+}
+
+/// Proyecta las columnas seleccionadas en un archivo auxiliar que se descarta, como al imprimir el resultado.
+fn proyectar(
+    path: &str,
+    origen: &String,
+    posiciones: &Vec<usize>,
+    mediciones: &mut Mediciones,
+) -> Result<(), ErrorType> {
+    let salida = agregar_path(path, &nombre_auxiliar("auxiliar"));
+    let resultado = crear_archivo(&salida).and_then(|mut archivo| {
+        if writeln!(archivo).is_err() {
+            return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+        }
+        mediciones.medir("proyeccion", &[origen], &salida, || {
+            proyectar_archivo(origen, posiciones, &salida)
+        })
+    });
+    let _ = eliminar_archivo(&salida);
+    resultado
+}
+
+/// Ejecuta la consulta principal sin imprimir su resultado, registrando las mediciones de cada etapa.
+fn analizar_consulta(
+    path: &str,
+    consulta: &Consulta,
+    mediciones: &mut Mediciones,
+) -> Result<(), ErrorType> {
+    let (origen, _, posiciones) = consulta.materializar_midiendo(path, mediciones, "")?;
+    let resultado = match consulta {
+        Consulta::Select(_) => proyectar(path, &origen, &posiciones, mediciones),
+        Consulta::Compound(_) => Ok(()),
+    };
+    eliminar_archivo(&origen)?;
+    resultado
+}
+
+impl ExplainQuery {
+    /// Crea una nueva instancia de `ExplainQuery`.
+    pub fn new(consulta: SQLQuery, analyze: bool) -> Self {
+        ExplainQuery {
+            consulta: Box::new(consulta),
+            analyze,
+        }
+    }
+
+    /// Ejecuta la consulta registrando en `mediciones` las filas y el tiempo de cada operador.
+    /// Las que devuelven filas no imprimen su resultado; las que modifican una tabla se ejecutan normalmente.
+    fn analizar(&self, path: &str, mediciones: &mut Mediciones) -> Result<(), ErrorType> {
+        let tabla = match self.consulta.as_ref() {
+            SQLQuery::Insert(query) => &query.table,
+            SQLQuery::Update(query) => &query.table,
+            SQLQuery::Delete(query) => &query.table,
+            _ => {
+                return self
+                    .consulta
+                    .con_vistas_materializadas(path, |consulta| match consulta {
+                        SQLQuery::Select(select) => analizar_consulta(
+                            path,
+                            &Consulta::Select(select.clone().into()),
+                            mediciones,
+                        ),
+                        SQLQuery::Compound(compound) => analizar_consulta(
+                            path,
+                            &Consulta::Compound(compound.clone()),
+                            mediciones,
+                        ),
+                        SQLQuery::With(with) => {
+                            let mut auxiliares = Vec::new();
+                            let resultado = with
+                                .materializar_expresiones(path, &mut auxiliares, mediciones)
+                                .and_then(|consulta| {
+                                    analizar_consulta(path, &consulta, mediciones)
+                                });
+                            for nombre_aux in &auxiliares {
+                                eliminar_archivo(&agregar_path(path, nombre_aux))?;
+                            }
+                            resultado
+                        }
+                        _ => Ok(()),
+                    })
+            }
+        };
+        let path_tabla = agregar_path(path, tabla);
+        let antes = contar_filas(&path_tabla);
+        let inicio = Instant::now();
+        self.consulta.execute(path)?;
+        let medicion = Medicion {
+            filas_entrada: Some(antes?),
+            filas_salida: contar_filas(&path_tabla)?,
+            tiempo: inicio.elapsed(),
+        };
+        mediciones.registrar("consulta", medicion);
+        Ok(())
+    }
+}
+
+impl Execute for ExplainQuery {
+    /// Imprime el plan de la consulta, un operador por línea con sus hijos debajo.
+    /// Con ANALYZE, primero ejecuta la consulta y agrega a cada operador su medición y al final el tiempo total.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let plan = plan(&self.consulta, path)?;
+        let mut lineas = Vec::new();
+        if self.analyze {
+            let mut mediciones = Mediciones::para_analizar();
+            let inicio = Instant::now();
+            self.analizar(path, &mut mediciones)?;
+            let total = inicio.elapsed();
+            plan.escribir("", false, Some(&mediciones), &mut lineas);
+            lineas.push(format!(
+                "Tiempo total: {:.3} ms",
+                total.as_secs_f64() * 1000.0
+            ));
+        } else {
+            plan.escribir("", false, None, &mut lineas);
+        }
+        for linea in lineas {
+            println!("{}", linea);
+        }
+        Ok(())
+    }
+}
@@ -1,61 +1,324 @@
-use std::{collections::HashMap, io::BufRead};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+};
 
 use crate::{
     dato::Datos,
     errores::error::ErrorType,
+    esquema::{
+        esquema_tabla::Esquema,
+        restricciones::{VerificadorClaves, VerificadorReferencias},
+        secuencia::Secuencia,
+    },
     executer::{
         execute::Execute,
+        insercion::Insercion,
         manejo_csv::{
-            agregar_linea, datos_to_row, eliminar_archivo, listar_columnas, preparar_archivos,
-            reemplazar_archivo,
+            agregar_path, datos_to_row, eliminar_archivo, filtrar_columnas, get_reader,
+            imprimir_filas, leer_encabezado, ordenar_linea, string_to_columns,
         },
     },
 };
 
-/// Representa una consulta SQL INSERT con una tabla, columnas y valores.
-#[derive(Debug, PartialEq)]
+use super::{on_conflict::OnConflict, select_query::SelectQuery};
+
+/// Representa una consulta SQL INSERT con una tabla, columnas y los valores a insertar,
+/// que pueden ser una lista de filas o el resultado de un SELECT.
+/// Si no se indican columnas, los valores se asignan en el orden del encabezado de la tabla.
+#[derive(Debug, PartialEq, Clone)]
 pub struct InsertQuery {
     pub table: String,
     pub columns: Vec<String>,
-    pub values: Vec<HashMap<String, Datos>>,
+    pub values: Vec<Vec<Datos>>,
+    pub select: Option<Box<SelectQuery>>,
+    pub on_conflict: Option<OnConflict>,
+    pub returning: Option<Vec<String>>,
+    /// Los parámetros `$n` escritos en VALUES, que se reemplazan por su valor antes de ejecutar la consulta.
+    pub parametros: Vec<ParametroValor>,
+}
+
+/// Parámetro `$n` de una consulta preparada escrito en VALUES, con la fila y la columna del valor que ocupa.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParametroValor {
+    pub fila: usize,
+    pub columna: usize,
+    pub numero: usize,
+}
+
+/// Función que recibe cada fila a insertar, asociada con las columnas de destino.
+type AgregarFila<'a> = dyn FnMut(&HashMap<String, Datos>) -> Result<(), ErrorType> + 'a;
+
+/// Función que recibe cada fila a insertar, ya completa y verificada, como línea de CSV.
+type GuardarFila<'a> = dyn FnMut(String) -> Result<(), ErrorType> + 'a;
+
+/// Asocia los valores de una fila con las columnas en las que se insertan.
+fn fila_a_datos(
+    valores: &[Datos],
+    columnas: &[String],
+) -> Result<HashMap<String, Datos>, ErrorType> {
+    if valores.len() != columnas.len() {
+        return Err(ErrorType::InvalidSyntax(
+            "El número de columnas y valores no coincide.".to_string(),
+        ));
+    }
+    Ok(columnas
+        .iter()
+        .cloned()
+        .zip(valores.iter().cloned())
+        .collect())
+}
+
+/// Convierte los datos a insertar en una fila de CSV, completando los valores por defecto y el AUTOINCREMENT,
+/// y verificando los tipos y las columnas NOT NULL del esquema.
+pub fn completar_fila(
+    datos: &HashMap<String, Datos>,
+    columnas: &[String],
+    esquema: &Option<Esquema>,
+    secuencia: &mut Option<Secuencia>,
+) -> Result<String, ErrorType> {
+    let Some(esquema) = esquema else {
+        return datos_to_row(datos, columnas);
+    };
+    esquema.validar_tipos(datos)?;
+    let mut datos = datos.clone();
+    esquema.completar_defaults(&mut datos)?;
+    if let Some(secuencia) = secuencia {
+        match datos.get(secuencia.columna()) {
+            Some(Datos::Integer(valor)) => secuencia.observar_valor(*valor),
+            Some(Datos::String(s)) if !s.is_empty() => {}
+            _ => {
+                let valor = secuencia.siguiente()?;
+                datos.insert(secuencia.columna().to_string(), Datos::Integer(valor));
+            }
+        }
+    }
+    let fila = datos_to_row(&datos, columnas)?;
+    esquema.verificar_fila(&fila, columnas)?;
+    Ok(fila)
+}
+
+/// Recorre el archivo con el resultado de un SELECT tomando las columnas seleccionadas de cada fila.
+fn recorrer_resultado(
+    path_resultado: &String,
+    posiciones: &Vec<usize>,
+    columnas: &[String],
+    agregar: &mut AgregarFila,
+) -> Result<(), ErrorType> {
+    if posiciones.len() != columnas.len() {
+        return Err(ErrorType::InvalidSyntax(
+            "El número de columnas y valores no coincide.".to_string(),
+        ));
+    }
+    for line in get_reader(path_resultado)?.lines().skip(1) {
+        match line {
+            Ok(line) => {
+                let fila = string_to_columns(&ordenar_linea(&line, posiciones)?, columnas)?;
+                agregar(&fila)?;
+            }
+            Err(_) => {
+                return Err(ErrorType::InvalidTable(
+                    "Error al leer una linea".to_string(),
+                ))
+            }
+        }
+    }
+    Ok(())
 }
 
 impl InsertQuery {
-    /// Crea una nueva instancia de `InsertQuery`.
-    pub fn new(table: &String, columns: Vec<String>, values: Vec<HashMap<String, Datos>>) -> Self {
+    /// Crea una nueva instancia de `InsertQuery` con una lista de filas.
+    pub fn new(table: &str, columns: Vec<String>, values: Vec<Vec<Datos>>) -> Self {
         InsertQuery {
             table: table.to_string(),
             columns,
             values,
+            select: None,
+            on_conflict: None,
+            returning: None,
+            parametros: Vec::new(),
         }
     }
-}
 
-impl Execute for InsertQuery {
-    /// Ejecuta la consulta INSERT en el archivo especificado, añadiendo nuevas filas.
-    fn execute(&self, path: &str) -> Result<(), ErrorType> {
-        let (path_insert, reader, path_aux) =
-            preparar_archivos(path, &self.table, &"auxiliar".to_string())?;
-        let lines = reader.lines();
-        let (lines, columns) = listar_columnas(&path_aux, lines)?;
-        for line in lines {
-            match line {
-                Ok(line) => agregar_linea(&path_aux, &line)?,
-                Err(_) => {
-                    return Err(ErrorType::InvalidTable(
-                        "Error al escribir una linea".to_string(),
-                    ))
+    /// Crea una nueva instancia de `InsertQuery` que inserta el resultado de un SELECT.
+    pub fn new_select(table: &str, columns: Vec<String>, select: SelectQuery) -> Self {
+        InsertQuery {
+            table: table.to_string(),
+            columns,
+            values: Vec::new(),
+            select: Some(Box::new(select)),
+            on_conflict: None,
+            returning: None,
+            parametros: Vec::new(),
+        }
+    }
+
+    /// Devuelve las columnas en las que se insertan los valores: las indicadas o, si no hay, todas las de la tabla.
+    fn columnas_destino(&self, columnas_tabla: &[String]) -> Vec<String> {
+        if self.columns.is_empty() {
+            columnas_tabla.to_vec()
+        } else {
+            self.columns.clone()
+        }
+    }
+
+    /// Recorre las filas del resultado del SELECT y las pasa a `agregar`, asociadas con las columnas de destino.
+    fn recorrer_select(
+        select: &SelectQuery,
+        path: &str,
+        columnas: &[String],
+        agregar: &mut AgregarFila,
+    ) -> Result<(), ErrorType> {
+        let (path_resultado, _, posiciones) = select.materializar(path)?;
+        let resultado = recorrer_resultado(&path_resultado, &posiciones, columnas, agregar);
+        eliminar_archivo(&path_resultado)?;
+        resultado
+    }
+
+    /// Recorre las filas a insertar, ya sean las de VALUES o las del resultado del SELECT,
+    /// y las pasa a `agregar` asociadas con las columnas de destino.
+    fn recorrer_filas(
+        &self,
+        path: &str,
+        columnas: &[String],
+        agregar: &mut AgregarFila,
+    ) -> Result<(), ErrorType> {
+        match &self.select {
+            Some(select) => Self::recorrer_select(select, path, columnas, agregar),
+            None => {
+                for valores in &self.values {
+                    agregar(&fila_a_datos(valores, columnas)?)?;
                 }
+                Ok(())
             }
         }
+    }
 
-        for value in &self.values {
-            let value = datos_to_row(value, &columns)?;
-            agregar_linea(&path_aux, &value)?;
+    /// Genera las filas a agregar, verificando las restricciones del esquema, y pasa cada una a `guardar`.
+    /// Las filas existentes solo se leen si hace falta compararlas con las claves o inicializar el AUTOINCREMENT.
+    /// Con ON CONFLICT las claves y foreign keys se verifican después, al resolver los conflictos.
+    /// Devuelve el contador del AUTOINCREMENT, que se guarda recién cuando las filas se escribieron.
+    fn insertar(
+        &self,
+        path: &str,
+        lines: Lines<BufReader<File>>,
+        columns: &[String],
+        esquema: &Option<Esquema>,
+        guardar: &mut GuardarFila,
+    ) -> Result<Option<Secuencia>, ErrorType> {
+        let verificar_claves = self.on_conflict.is_none();
+        let mut verificador = VerificadorClaves::new(esquema, columns)?;
+        let mut referencias =
+            VerificadorReferencias::new(path, esquema, columns, &agregar_path(path, &self.table))?;
+        let mut secuencia = Secuencia::cargar(path, esquema, columns)?;
+        let inicializar = secuencia.as_ref().is_some_and(|s| s.requiere_inicializar());
+        if (verificar_claves && !verificador.vacio()) || inicializar {
+            for line in lines {
+                match line {
+                    Ok(line) => {
+                        if verificar_claves {
+                            verificador.registrar(&line)?;
+                        }
+                        if let Some(secuencia) = secuencia.as_mut().filter(|_| inicializar) {
+                            secuencia.observar(&line);
+                        }
+                    }
+                    Err(_) => {
+                        return Err(ErrorType::InvalidTable(
+                            "Error al leer una linea".to_string(),
+                        ))
+                    }
+                }
+            }
         }
 
-        reemplazar_archivo(&path_aux, &path_insert)?;
-        eliminar_archivo(&path_aux)?;
-        Ok(())
+        let columnas_destino = self.columnas_destino(columns);
+        self.recorrer_filas(path, &columnas_destino, &mut |datos| {
+            let value = completar_fila(datos, columns, esquema, &mut secuencia)?;
+            if verificar_claves {
+                verificador.registrar(&value)?;
+                referencias.registrar(&value)?;
+            }
+            guardar(value)
+        })?;
+        Ok(secuencia)
+    }
+
+    /// Agrega las filas al final de la tabla a medida que se generan, sin acumularlas en memoria.
+    /// Si alguna fila viola una restricción o la escritura falla, descarta las filas ya agregadas.
+    /// Con RETURNING, guarda además en `filas` las filas insertadas.
+    fn agregar_al_final(
+        &self,
+        path: &str,
+        lines: Lines<BufReader<File>>,
+        columns: &[String],
+        esquema: &Option<Esquema>,
+        filas: &mut Vec<String>,
+    ) -> Result<Option<Secuencia>, ErrorType> {
+        let path_insert = agregar_path(path, &self.table);
+        let mut insercion: Option<Insercion> = None;
+        let resultado = self.insertar(path, lines, columns, esquema, &mut |fila| {
+            let insercion = match &mut insercion {
+                Some(insercion) => insercion,
+                None => insercion.insert(Insercion::iniciar(&path_insert)?),
+            };
+            insercion.agregar(&fila)?;
+            if self.returning.is_some() {
+                filas.push(fila);
+            }
+            Ok(())
+        });
+        match (resultado, insercion) {
+            (Ok(secuencia), Some(insercion)) => insercion.terminar().map(|_| secuencia),
+            (Err(e), Some(insercion)) => {
+                insercion.deshacer()?;
+                Err(e)
+            }
+            (resultado, None) => resultado,
+        }
+    }
+}
+
+impl Execute for InsertQuery {
+    /// Ejecuta la consulta INSERT en el archivo especificado, agregando las nuevas filas al final de la tabla
+    /// por lotes, a medida que se generan.
+    /// Con ON CONFLICT, en cambio, reescribe la tabla resolviendo las filas con claves repetidas.
+    /// Si alguna fila viola una restricción del esquema, la tabla no se modifica.
+    /// Con RETURNING, imprime las filas insertadas como el resultado de un SELECT.
+    /// Si tiene parámetros en VALUES, no se puede ejecutar hasta reemplazarlos por sus valores.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        if let Some(parametro) = self.parametros.first() {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "Falta el valor del parámetro ${}",
+                parametro.numero
+            )));
+        }
+        let esquema = Esquema::cargar(path, &self.table)?;
+        let path_insert = agregar_path(path, &self.table);
+        let mut lines = get_reader(&path_insert)?.lines();
+        let columns = leer_encabezado(&mut lines)?;
+        if let Some(returning) = &self.returning {
+            filtrar_columnas(returning, &columns)?;
+        }
+        let mut filas = Vec::new();
+        let secuencia = match &self.on_conflict {
+            Some(on_conflict) => {
+                let secuencia = self.insertar(path, lines, &columns, &esquema, &mut |fila| {
+                    filas.push(fila);
+                    Ok(())
+                })?;
+                filas = on_conflict.aplicar(path, &self.table, filas, &esquema)?;
+                secuencia
+            }
+            None => self.agregar_al_final(path, lines, &columns, &esquema, &mut filas)?,
+        };
+        if let Some(secuencia) = &secuencia {
+            secuencia.guardar(path, &self.table)?;
+        }
+        match &self.returning {
+            Some(returning) => imprimir_filas(path, &columns, &filas, returning),
+            None => Ok(()),
+        }
     }
 }
@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::BufRead,
+    time::Instant,
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    executer::{
+        conjuntos::{abrir_para_agregar, escribir, terminar},
+        manejo_csv::{
+            agregar_path, get_reader, leer_encabezado, posiciones_usadas,
+            string_to_columns_podadas, where_condition,
+        },
+        mediciones::Medicion,
+    },
+};
+
+use super::where_clause::{
+    expresion_booleana::ExpresionBooleana, subconsulta::preparar_condicion, valor::Valor,
+};
+
+/// Cantidad de filas de la izquierda que se guardan en memoria mientras se recorre una vez la tabla de la derecha.
+const FILAS_POR_BLOQUE: usize = 10_000;
+
+/// Representa un `JOIN tabla ON condición` de un SELECT. Solo se combinan las filas que cumplen la condición.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Join {
+    pub tabla: String,
+    /// Nombre con el que se referencian las columnas de la tabla, si no es el de la tabla.
+    pub alias: Option<String>,
+    pub condicion: ExpresionBooleana,
+}
+
+impl Join {
+    /// Crea una nueva instancia de `Join`.
+    pub fn new(tabla: &str, condicion: ExpresionBooleana) -> Self {
+        Join {
+            tabla: tabla.to_string(),
+            alias: None,
+            condicion,
+        }
+    }
+
+    /// Devuelve el nombre con el que se referencian las columnas de la tabla.
+    pub fn referencia(&self) -> &String {
+        self.alias.as_ref().unwrap_or(&self.tabla)
+    }
+
+    /// Combina las filas del archivo de la izquierda, con su encabezado de columnas ya calificadas,
+    /// con las filas de la tabla que cumplen la condición, y escribe el resultado con su encabezado en la salida.
+    /// Devuelve las columnas calificadas del resultado.
+    /// Las filas de la izquierda se leen por bloques, de modo que la tabla se recorre una vez por bloque.
+    /// Suma a `lectura` el tiempo de leer la tabla y cuenta en ella sus filas, leídas en la primera pasada.
+    pub fn unir(
+        &self,
+        path: &str,
+        izquierda: &String,
+        salida: &String,
+        lectura: &mut Medicion,
+    ) -> Result<Vec<String>, ErrorType> {
+        let mut lines_izq = get_reader(izquierda)?.lines();
+        let columnas_izq = leer_encabezado(&mut lines_izq)?;
+        let path_tabla = agregar_path(path, &self.tabla);
+        let columnas_der = calificar_encabezado(self.referencia(), &columnas_tabla(&path_tabla)?);
+        let mut columnas = columnas_izq.clone();
+        columnas.extend(columnas_der.iter().cloned());
+        let condicion = preparar_condicion(
+            &Some(self.condicion.clone()),
+            path,
+            self.referencia(),
+            &columnas,
+        )?;
+        let sin_calificar = sin_calificar(&columnas);
+        let usadas_izq = posiciones_referenciadas(&columnas_izq, &condicion, &[]);
+        let usadas_der = posiciones_referenciadas(&columnas_der, &condicion, &[]);
+        let mut escritor = abrir_para_agregar(salida)?;
+        escribir(&mut escritor, &columnas.join(","))?;
+        let mut bloque: Vec<(String, HashMap<String, Datos>)> = Vec::new();
+        let mut terminado = false;
+        let mut primera_pasada = true;
+        while !terminado {
+            bloque.clear();
+            for line in lines_izq.by_ref().take(FILAS_POR_BLOQUE) {
+                let Ok(line) = line else {
+                    return Err(error_lectura());
+                };
+                let fila = string_to_columns_podadas(&line, &columnas_izq, &usadas_izq)?;
+                bloque.push((line, fila));
+            }
+            terminado = bloque.len() < FILAS_POR_BLOQUE;
+            if bloque.is_empty() {
+                break;
+            }
+            let inicio = Instant::now();
+            let mut lines_der = get_reader(&path_tabla)?.lines();
+            leer_encabezado(&mut lines_der)?;
+            lectura.tiempo += inicio.elapsed();
+            loop {
+                let inicio = Instant::now();
+                let Some(line_der) = lines_der.next() else {
+                    lectura.tiempo += inicio.elapsed();
+                    break;
+                };
+                let Ok(line_der) = line_der else {
+                    return Err(error_lectura());
+                };
+                let fila_der = string_to_columns_podadas(&line_der, &columnas_der, &usadas_der)?;
+                lectura.tiempo += inicio.elapsed();
+                if primera_pasada {
+                    lectura.filas_salida += 1;
+                }
+                for (line_izq, fila_izq) in &bloque {
+                    let mut fila = fila_izq.clone();
+                    fila.extend(fila_der.iter().map(|(c, d)| (c.to_string(), d.clone())));
+                    agregar_sin_calificar(&mut fila, &sin_calificar);
+                    if where_condition(&condicion, &fila)? {
+                        escribir(&mut escritor, &format!("{},{}", line_izq, line_der))?;
+                    }
+                }
+            }
+            primera_pasada = false;
+        }
+        terminar(escritor)?;
+        Ok(columnas)
+    }
+}
+
+/// Devuelve las posiciones de las columnas que pueden usar la condición y los valores, que son las únicas
+/// que hace falta leer de cada fila para evaluarlos. Si alguno tiene subconsultas, devuelve todas.
+pub fn posiciones_referenciadas(
+    columnas: &[String],
+    condicion: &Option<ExpresionBooleana>,
+    valores: &[Valor],
+) -> Vec<usize> {
+    let con_subconsultas = condicion
+        .as_ref()
+        .is_some_and(ExpresionBooleana::tiene_subconsultas)
+        || valores.iter().any(Valor::tiene_subconsultas);
+    if con_subconsultas {
+        return (0..columnas.len()).collect();
+    }
+    let mut nombres: Vec<String> = condicion
+        .iter()
+        .flat_map(ExpresionBooleana::columnas_referenciadas)
+        .collect();
+    for valor in valores {
+        nombres.extend(valor.columnas_referenciadas());
+    }
+    posiciones_usadas(columnas, &nombres)
+}
+
+/// Devuelve el error de lectura de una línea.
+fn error_lectura() -> ErrorType {
+    ErrorType::InvalidTable("Error al leer una linea".to_string())
+}
+
+/// Lee las columnas del encabezado del archivo de una tabla.
+fn columnas_tabla(path_tabla: &String) -> Result<Vec<String>, ErrorType> {
+    let mut lines = get_reader(path_tabla)?.lines();
+    leer_encabezado(&mut lines)
+}
+
+/// Antepone a cada columna el nombre con el que se referencia su tabla, como `tabla.columna`.
+pub fn calificar_encabezado(referencia: &str, columnas: &[String]) -> Vec<String> {
+    columnas
+        .iter()
+        .map(|columna| format!("{}.{}", referencia, columna))
+        .collect()
+}
+
+/// Devuelve, para cada columna calificada cuyo nombre no se repite en otra tabla,
+/// el nombre sin calificar junto con el calificado.
+pub fn sin_calificar(columnas: &[String]) -> Vec<(String, String)> {
+    let nombre = |columna: &String| {
+        columna
+            .split_once('.')
+            .map_or(columna.to_string(), |(_, c)| c.to_string())
+    };
+    columnas
+        .iter()
+        .filter(|columna| {
+            columnas
+                .iter()
+                .filter(|otra| nombre(otra) == nombre(columna))
+                .count()
+                == 1
+        })
+        .map(|columna| (nombre(columna), columna.to_string()))
+        .collect()
+}
+
+/// Agrega a la fila los valores de las columnas que no se repiten también con su nombre sin calificar.
+pub fn agregar_sin_calificar(
+    fila: &mut HashMap<String, Datos>,
+    sin_calificar: &[(String, String)],
+) {
+    for (nombre, calificada) in sin_calificar {
+        if let Some(dato) = fila.get(calificada).cloned() {
+            fila.insert(nombre.to_string(), dato);
+        }
+    }
+}
+
+/// Devuelve la columna del encabezado a la que se refiere el nombre: la que se llama igual o,
+/// si el nombre no está calificado, la única columna calificada con ese nombre.
+pub fn resolver_columna(nombre: &str, columnas: &[String]) -> Result<String, ErrorType> {
+    if nombre == "*" || columnas.iter().any(|c| c == nombre) || nombre.contains('.') {
+        return Ok(nombre.to_string());
+    }
+    let sufijo = format!(".{}", nombre);
+    let mut candidatas = columnas.iter().filter(|c| c.ends_with(&sufijo));
+    match (candidatas.next(), candidatas.next()) {
+        (Some(columna), None) => Ok(columna.to_string()),
+        (Some(_), Some(_)) => Err(ErrorType::InvalidColumn(format!(
+            "La columna '{}' es ambigua, se debe indicar su tabla",
+            nombre
+        ))),
+        (None, _) => Ok(nombre.to_string()),
+    }
+}
+
+impl Display for Join {
+    /// Muestra el JOIN como se escribe en un SELECT.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "JOIN {}", self.tabla)?;
+        if let Some(alias) = &self.alias {
+            write!(f, " {}", alias)?;
+        }
+        write!(f, " ON {}", self.condicion)
+    }
+}
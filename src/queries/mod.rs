@@ -1,7 +1,24 @@
+pub mod analyze_query;
+pub mod compound_query;
+pub mod conteo;
+pub mod create_materialized_view_query;
+pub mod create_query;
+pub mod create_view_query;
 pub mod delete_query;
+pub mod drop_view_query;
+pub mod explain_query;
 pub mod insert_query;
+pub mod join;
+pub mod on_conflict;
 pub mod order_clause;
+pub mod prepared_statement;
+pub mod refresh_query;
 pub mod select_query;
 pub mod sql_query;
 pub mod update_query;
+pub mod valores;
+pub mod ventana;
+pub mod vista;
+pub mod vista_materializada;
 pub mod where_clause;
+pub mod with_query;
@@ -0,0 +1,200 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    esquema::{
+        esquema_tabla::Esquema,
+        foreign_key::{posiciones_columnas, referencias_a, valores_clave},
+        restricciones::{
+            verificar_sin_referencias, ClavesQuitadas, VerificadorClaves, VerificadorReferencias,
+        },
+    },
+    executer::manejo_csv::{
+        agregar_linea, eliminar_archivo, listar_columnas, modificar_linea, nombre_auxiliar,
+        preparar_archivos, reemplazar_archivo, string_to_columns,
+    },
+};
+
+use super::where_clause::{comparadores::evaluar_valor, valor::Valor};
+
+/// Acción a realizar cuando una fila a insertar tiene la misma clave que una fila existente.
+#[derive(Debug, PartialEq, Clone)]
+pub enum AccionConflicto {
+    Nada,
+    Actualizar(HashMap<String, Valor>),
+}
+
+/// Representa la cláusula `ON CONFLICT (columnas) DO NOTHING | DO UPDATE SET ...` de un INSERT.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OnConflict {
+    pub columnas: Vec<String>,
+    pub accion: AccionConflicto,
+}
+
+impl OnConflict {
+    /// Crea una nueva instancia de `OnConflict`.
+    pub fn new(columnas: Vec<String>, accion: AccionConflicto) -> Self {
+        OnConflict { columnas, accion }
+    }
+
+    /// Verifica que las columnas del conflicto sean la PRIMARY KEY o un UNIQUE del esquema, si la tabla lo tiene.
+    fn validar(&self, esquema: &Option<Esquema>) -> Result<(), ErrorType> {
+        let Some(esquema) = esquema else {
+            return Ok(());
+        };
+        let columnas: HashSet<&String> = self.columnas.iter().collect();
+        let es_clave = std::iter::once(&esquema.primary_key)
+            .chain(esquema.unique.iter())
+            .any(|clave| clave.iter().collect::<HashSet<&String>>() == columnas);
+        if es_clave {
+            return Ok(());
+        }
+        Err(ErrorType::InvalidColumn(format!(
+            "ON CONFLICT ({}) no corresponde a ninguna PRIMARY KEY o UNIQUE de la tabla",
+            self.columnas.join(", ")
+        )))
+    }
+
+    /// Aplica los cambios de DO UPDATE SET a la fila existente. Los valores de la fila que se intentó
+    /// insertar se referencian como `excluded.columna`.
+    fn actualizar(
+        &self,
+        existente: &str,
+        nueva: &str,
+        cambios: &HashMap<String, Valor>,
+        columnas: &[String],
+        esquema: &Option<Esquema>,
+    ) -> Result<String, ErrorType> {
+        let mut fila = string_to_columns(existente, columnas)?;
+        for (columna, dato) in string_to_columns(nueva, columnas)? {
+            fila.insert(format!("excluded.{}", columna), dato.clone());
+            fila.insert(format!("EXCLUDED.{}", columna), dato);
+        }
+        let mut datos: HashMap<String, Datos> = HashMap::new();
+        for (columna, valor) in cambios {
+            datos.insert(columna.to_string(), evaluar_valor(valor, &fila)?);
+        }
+        if let Some(esquema) = esquema {
+            esquema.validar_tipos(&datos)?;
+        }
+        let linea = modificar_linea(existente, &datos, columnas)?;
+        if let Some(esquema) = esquema {
+            esquema.verificar_fila(&linea, columnas)?;
+        }
+        Ok(linea)
+    }
+
+    /// Escribe en el archivo auxiliar las filas existentes y las nuevas, resolviendo los conflictos de clave
+    /// con la acción indicada y verificando las claves del resultado.
+    /// Devuelve las filas insertadas o actualizadas, en el orden de las filas a insertar.
+    fn reescribir(
+        &self,
+        lines: Lines<BufReader<File>>,
+        columnas: &[String],
+        nuevas: Vec<String>,
+        path_aux: &String,
+        esquema: &Option<Esquema>,
+        claves_modificadas: &mut ClavesQuitadas,
+    ) -> Result<Vec<String>, ErrorType> {
+        let posiciones = posiciones_columnas(&self.columnas, columnas)?;
+        let mut pendientes: Vec<Option<String>> = Vec::new();
+        let mut por_clave: HashMap<Vec<String>, usize> = HashMap::new();
+        for nueva in nuevas {
+            if let Some(clave) = valores_clave(&nueva, &posiciones) {
+                if por_clave.contains_key(&clave) {
+                    match self.accion {
+                        AccionConflicto::Nada => continue,
+                        AccionConflicto::Actualizar(_) => {
+                            return Err(ErrorType::ConstraintViolation(format!(
+                                "ON CONFLICT DO UPDATE no puede modificar dos veces la fila ({})",
+                                clave.join(", ")
+                            )))
+                        }
+                    }
+                }
+                por_clave.insert(clave, pendientes.len());
+            }
+            pendientes.push(Some(nueva));
+        }
+
+        let mut verificador = VerificadorClaves::new(esquema, columnas)?;
+        let mut afectadas: Vec<Option<String>> = vec![None; pendientes.len()];
+        for line in lines {
+            let Ok(mut line) = line else {
+                return Err(ErrorType::InvalidTable(
+                    "Error al leer una linea".to_string(),
+                ));
+            };
+            let conflicto = valores_clave(&line, &posiciones).and_then(|c| por_clave.get(&c));
+            if let Some(&i) = conflicto {
+                if let Some(nueva) = pendientes[i].take() {
+                    if let AccionConflicto::Actualizar(cambios) = &self.accion {
+                        let anterior = line;
+                        line = self.actualizar(&anterior, &nueva, cambios, columnas, esquema)?;
+                        claves_modificadas.registrar_cambio(&anterior, &line);
+                        afectadas[i] = Some(line.to_string());
+                    }
+                }
+            }
+            verificador.registrar(&line)?;
+            agregar_linea(path_aux, &line)?;
+        }
+        for (i, pendiente) in pendientes.into_iter().enumerate() {
+            if let Some(nueva) = pendiente {
+                verificador.registrar(&nueva)?;
+                agregar_linea(path_aux, &nueva)?;
+                afectadas[i] = Some(nueva);
+            }
+        }
+        Ok(afectadas.into_iter().flatten().collect())
+    }
+
+    /// Inserta las filas nuevas en la tabla reescribiéndola una única vez, resolviendo los conflictos de clave
+    /// con la acción indicada. Si el resultado viola una restricción del esquema, la tabla no se modifica.
+    /// Devuelve las filas insertadas o actualizadas.
+    pub fn aplicar(
+        &self,
+        path: &str,
+        tabla: &str,
+        nuevas: Vec<String>,
+        esquema: &Option<Esquema>,
+    ) -> Result<Vec<String>, ErrorType> {
+        self.validar(esquema)?;
+        let (path_tabla, reader, path_aux) =
+            preparar_archivos(path, &tabla.to_string(), &nombre_auxiliar("auxiliar"))?;
+        let resultado = listar_columnas(&path_aux, reader.lines()).and_then(|(lines, columnas)| {
+            let referencias_hijas = referencias_a(path, tabla)?;
+            let mut claves_modificadas = ClavesQuitadas::new(&referencias_hijas, &columnas)?;
+            let afectadas = self.reescribir(
+                lines,
+                &columnas,
+                nuevas,
+                &path_aux,
+                esquema,
+                &mut claves_modificadas,
+            )?;
+            let referencias = VerificadorReferencias::new(path, esquema, &columnas, &path_aux)?;
+            for line in &afectadas {
+                referencias.verificar(line)?;
+            }
+            verificar_sin_referencias(
+                path,
+                tabla,
+                &referencias_hijas,
+                claves_modificadas.claves(),
+                &path_aux,
+            )?;
+            Ok(afectadas)
+        });
+        if resultado.is_ok() {
+            reemplazar_archivo(&path_aux, &path_tabla)?;
+        }
+        eliminar_archivo(&path_aux)?;
+        resultado
+    }
+}
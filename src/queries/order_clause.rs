@@ -1,11 +1,109 @@
-#[derive(Debug, PartialEq)]
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::dato::Datos;
+
+use super::where_clause::valor::Valor;
+
+/// Columna por la que se ordena y su dirección. Si se ordena por una expresión, como un CASE,
+/// `column` es el texto de la expresión y se calcula en cada fila como una columna más.
+/// Si `column` es un número, como en `ORDER BY 2`, es la posición de una de las columnas seleccionadas.
+/// Con `nulos` se indica si los valores vacíos van al principio o al final; si no, se ordenan
+/// como el menor valor posible.
+#[derive(Debug, PartialEq, Clone)]
 pub struct OrderClause {
     pub column: String,
     pub direccion: OrderDirection,
+    pub expresion: Option<Valor>,
+    pub nulos: Option<OrdenNulos>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum OrderDirection {
     Asc,
     Desc,
 }
+
+/// Posición de los valores vacíos en el orden: `NULLS FIRST` o `NULLS LAST`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum OrdenNulos {
+    Primero,
+    Ultimo,
+}
+
+/// Compara dos valores no vacíos: los enteros por su valor, los textos alfabéticamente,
+/// y todos los enteros van antes que todos los textos.
+fn comparar_datos(dato_1: &Datos, dato_2: &Datos) -> Ordering {
+    match (dato_1, dato_2) {
+        (Datos::Integer(numero_1), Datos::Integer(numero_2)) => numero_1.cmp(numero_2),
+        (Datos::String(texto_1), Datos::String(texto_2)) => texto_1.cmp(texto_2),
+        (Datos::Integer(_), Datos::String(_)) => Ordering::Less,
+        (Datos::String(_), Datos::Integer(_)) => Ordering::Greater,
+    }
+}
+
+impl OrderClause {
+    /// Devuelve la posición de la columna seleccionada por la que se ordena, contando desde 1, si es un `ORDER BY n`.
+    pub fn posicion(&self) -> Option<usize> {
+        match self.expresion {
+            None => self.column.parse::<usize>().ok(),
+            Some(_) => None,
+        }
+    }
+
+    /// Compara dos filas según el valor de la columna, respetando la dirección y la posición de los vacíos.
+    pub fn comparar(
+        &self,
+        fila_1: &HashMap<String, Datos>,
+        fila_2: &HashMap<String, Datos>,
+    ) -> Ordering {
+        let valor = |fila: &HashMap<String, Datos>| {
+            fila.get(&self.column)
+                .filter(|dato| **dato != Datos::String(String::new()))
+                .cloned()
+        };
+        let nulos_primero = match self.nulos {
+            Some(OrdenNulos::Primero) => true,
+            Some(OrdenNulos::Ultimo) => false,
+            None => self.direccion == OrderDirection::Asc,
+        };
+        match (valor(fila_1), valor(fila_2)) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) if nulos_primero => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) if nulos_primero => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(dato_1), Some(dato_2)) => match self.direccion {
+                OrderDirection::Asc => comparar_datos(&dato_1, &dato_2),
+                OrderDirection::Desc => comparar_datos(&dato_2, &dato_1),
+            },
+        }
+    }
+}
+
+impl Display for OrdenNulos {
+    /// Muestra la posición de los vacíos como se escribe en un ORDER BY.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            OrdenNulos::Primero => write!(f, "NULLS FIRST"),
+            OrdenNulos::Ultimo => write!(f, "NULLS LAST"),
+        }
+    }
+}
+
+impl Display for OrderClause {
+    /// Muestra la columna seguida de su dirección, como se escribe en un ORDER BY.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.direccion {
+            OrderDirection::Asc => write!(f, "{} ASC", self.column)?,
+            OrderDirection::Desc => write!(f, "{} DESC", self.column)?,
+        }
+        if let Some(nulos) = &self.nulos {
+            write!(f, " {}", nulos)?;
+        }
+        Ok(())
+    }
+}
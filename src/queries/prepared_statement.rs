@@ -0,0 +1,328 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    esquema::{columna::TipoDato, esquema_tabla::Esquema},
+    executer::execute::Execute,
+    lexers::lexer::lexer,
+    parsers::parser::parser,
+};
+
+use super::{
+    compound_query::Conjunto,
+    on_conflict::AccionConflicto,
+    select_query::SelectQuery,
+    sql_query::SQLQuery,
+    where_clause::{expresion_booleana::ExpresionBooleana, valor::Valor},
+    with_query::Consulta,
+};
+
+/// Consulta con parámetros `?` o `$n` que se analiza una única vez y se ejecuta con distintos valores,
+/// sin volver a pasar por el lexer ni el parser. Los valores reemplazan a los parámetros en la consulta
+/// ya analizada, por lo que nunca se interpretan como parte del SQL.
+/// El tipo de cada parámetro se deduce del esquema de la tabla cuando se compara con una columna,
+/// se asigna a una columna en un UPDATE o se inserta en una columna con VALUES.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PreparedStatement {
+    path: String,
+    consulta: SQLQuery,
+    tipos: Vec<Option<TipoDato>>,
+}
+
+/// Devuelve el nombre de la columna sin la tabla que la califica, como `id` en `tabla.id`.
+fn sin_calificar(columna: &str) -> &str {
+    columna.rsplit('.').next().unwrap_or(columna)
+}
+
+/// Devuelve el tipo declarado de la columna en el primero de los esquemas que la tiene.
+fn tipo_columna(esquemas: &[Esquema], columna: &str) -> Option<TipoDato> {
+    esquemas
+        .iter()
+        .find_map(|esquema| esquema.columna(sin_calificar(columna)))
+        .and_then(|columna| columna.tipo.clone())
+}
+
+/// Asigna un tipo al parámetro, si todavía no tiene uno.
+fn asignar_tipo(tipos: &mut [Option<TipoDato>], numero: usize, tipo: Option<TipoDato>) {
+    if let Some(actual) = tipos.get_mut(numero - 1).filter(|actual| actual.is_none()) {
+        *actual = tipo;
+    }
+}
+
+/// Deduce el tipo de los parámetros que se comparan con una columna en la condición.
+fn tipos_condicion(
+    condicion: &ExpresionBooleana,
+    esquemas: &[Esquema],
+    tipos: &mut [Option<TipoDato>],
+) {
+    match condicion {
+        ExpresionBooleana::Comparacion { izq, der, .. } => match (izq, der) {
+            (Valor::String(columna), Valor::Parametro(numero))
+            | (Valor::Parametro(numero), Valor::String(columna)) => {
+                asignar_tipo(tipos, *numero, tipo_columna(esquemas, columna))
+            }
+            _ => {}
+        },
+        ExpresionBooleana::And(expr1, expr2) | ExpresionBooleana::Or(expr1, expr2) => {
+            tipos_condicion(expr1, esquemas, tipos);
+            tipos_condicion(expr2, esquemas, tipos);
+        }
+        ExpresionBooleana::Not(expr) => tipos_condicion(expr, esquemas, tipos),
+        ExpresionBooleana::In { .. } | ExpresionBooleana::Exists(_) => {}
+    }
+}
+
+/// Carga los esquemas de las tablas que se indican, ignorando las que no tienen.
+fn cargar_esquemas<'a>(
+    path: &str,
+    tablas: impl Iterator<Item = &'a String>,
+) -> Result<Vec<Esquema>, ErrorType> {
+    let mut esquemas = Vec::new();
+    for tabla in tablas.filter(|tabla| !tabla.is_empty()) {
+        esquemas.extend(Esquema::cargar(path, tabla)?);
+    }
+    Ok(esquemas)
+}
+
+/// Deduce el tipo de los parámetros de las condiciones de un SELECT, con los esquemas de su tabla y las de sus JOIN.
+fn tipos_select(
+    path: &str,
+    select: &SelectQuery,
+    tipos: &mut [Option<TipoDato>],
+) -> Result<(), ErrorType> {
+    let tabla = select.valores.is_none().then_some(&select.table);
+    let tablas = tabla
+        .into_iter()
+        .chain(select.joins.iter().map(|join| &join.tabla));
+    let esquemas = cargar_esquemas(path, tablas)?;
+    for join in &select.joins {
+        tipos_condicion(&join.condicion, &esquemas, tipos);
+    }
+    if let Some(where_clause) = &select.where_clause {
+        tipos_condicion(where_clause, &esquemas, tipos);
+    }
+    Ok(())
+}
+
+/// Deduce el tipo de los parámetros de cada SELECT que se combina.
+fn tipos_conjunto(
+    path: &str,
+    conjunto: &Conjunto,
+    tipos: &mut [Option<TipoDato>],
+) -> Result<(), ErrorType> {
+    match conjunto {
+        Conjunto::Select(select) => tipos_select(path, select, tipos),
+        Conjunto::Operacion { izq, der, .. } => {
+            tipos_conjunto(path, izq, tipos)?;
+            tipos_conjunto(path, der, tipos)
+        }
+    }
+}
+
+/// Deduce el tipo de los parámetros de una consulta que devuelve filas.
+fn tipos_consulta(
+    path: &str,
+    consulta: &Consulta,
+    tipos: &mut [Option<TipoDato>],
+) -> Result<(), ErrorType> {
+    match consulta {
+        Consulta::Select(select) => tipos_select(path, select, tipos),
+        Consulta::Compound(compound) => tipos_conjunto(path, &compound.conjunto, tipos),
+    }
+}
+
+/// Deduce el tipo de los parámetros de la consulta a partir de los esquemas de las tablas que usa.
+/// Los parámetros cuyo tipo no se puede deducir quedan sin tipo y admiten cualquier valor.
+fn deducir_tipos(
+    path: &str,
+    consulta: &SQLQuery,
+    tipos: &mut [Option<TipoDato>],
+) -> Result<(), ErrorType> {
+    match consulta {
+        SQLQuery::Select(select) => tipos_select(path, select, tipos),
+        SQLQuery::Compound(compound) => tipos_conjunto(path, &compound.conjunto, tipos),
+        SQLQuery::With(with) => {
+            for cte in &with.ctes {
+                tipos_consulta(path, &cte.consulta, tipos)?;
+            }
+            tipos_consulta(path, &with.consulta, tipos)
+        }
+        SQLQuery::Insert(insert) => {
+            let esquemas = cargar_esquemas(path, [&insert.table].into_iter())?;
+            if let Some(esquema) = esquemas.first() {
+                let columnas = if insert.columns.is_empty() {
+                    esquema.nombres_columnas()
+                } else {
+                    insert.columns.clone()
+                };
+                for parametro in &insert.parametros {
+                    let tipo = columnas
+                        .get(parametro.columna)
+                        .and_then(|columna| tipo_columna(&esquemas, columna));
+                    asignar_tipo(tipos, parametro.numero, tipo);
+                }
+            }
+            if let Some(AccionConflicto::Actualizar(valores)) = insert
+                .on_conflict
+                .as_ref()
+                .map(|on_conflict| &on_conflict.accion)
+            {
+                for (columna, valor) in valores {
+                    if let Valor::Parametro(numero) = valor {
+                        asignar_tipo(tipos, *numero, tipo_columna(&esquemas, columna));
+                    }
+                }
+            }
+            if let Some(select) = &insert.select {
+                tipos_select(path, select, tipos)?;
+            }
+            Ok(())
+        }
+        SQLQuery::Update(update) => {
+            let esquemas = cargar_esquemas(path, [&update.table].into_iter())?;
+            for (columna, valor) in &update.expresiones {
+                if let Valor::Parametro(numero) = valor {
+                    asignar_tipo(tipos, *numero, tipo_columna(&esquemas, columna));
+                }
+            }
+            if let Some(where_condition) = &update.where_condition {
+                tipos_condicion(where_condition, &esquemas, tipos);
+            }
+            Ok(())
+        }
+        SQLQuery::Delete(delete) => {
+            let esquemas = cargar_esquemas(path, [&delete.table].into_iter())?;
+            if let Some(where_clause) = &delete.where_clause {
+                tipos_condicion(where_clause, &esquemas, tipos);
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Verifica que el valor tenga el tipo del parámetro. Los valores vacíos (nulos) admiten cualquier tipo.
+/// Los textos no pueden tener comas ni saltos de línea, porque se guardarían como otras celdas o filas de la tabla.
+fn validar_parametro(
+    numero: usize,
+    tipo: &Option<TipoDato>,
+    dato: &Datos,
+) -> Result<(), ErrorType> {
+    if let Datos::String(texto) = dato {
+        if texto.contains([',', '\n', '\r']) {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "El parámetro ${} no puede contener comas ni saltos de línea",
+                numero
+            )));
+        }
+    }
+    let valido = match (tipo, dato) {
+        (Some(TipoDato::Integer), Datos::String(s)) => s.is_empty(),
+        (Some(TipoDato::String), Datos::Integer(_)) => false,
+        _ => true,
+    };
+    if valido {
+        return Ok(());
+    }
+    Err(ErrorType::InvalidColumn(format!(
+        "Tipo invalido para el parámetro ${}, se esperaba {}",
+        numero,
+        tipo.as_ref().map(TipoDato::to_string).unwrap_or_default()
+    )))
+}
+
+/// Convierte el valor de un parámetro en el valor que lo reemplaza en la consulta.
+fn dato_to_valor(dato: &Datos) -> Valor {
+    match dato {
+        Datos::Integer(numero) => Valor::String(numero.to_string()),
+        Datos::String(texto) => Valor::Literal(texto.to_string()),
+    }
+}
+
+impl PreparedStatement {
+    /// Analiza la consulta una única vez para ejecutarla sobre las tablas de `path`,
+    /// y deduce el tipo de sus parámetros. Solo se pueden preparar SELECT, INSERT, UPDATE y DELETE.
+    pub fn preparar(path: &str, consulta: &String) -> Result<Self, ErrorType> {
+        let mut consulta = parser(&lexer(consulta)?)?;
+        if !matches!(
+            consulta,
+            SQLQuery::Select(_)
+                | SQLQuery::Compound(_)
+                | SQLQuery::With(_)
+                | SQLQuery::Insert(_)
+                | SQLQuery::Update(_)
+                | SQLQuery::Delete(_)
+        ) {
+            return Err(ErrorType::InvalidSyntax(
+                "Solo se pueden preparar consultas SELECT, INSERT, UPDATE y DELETE".to_string(),
+            ));
+        }
+        let mut numeros = BTreeSet::new();
+        consulta.visitar_valores(&mut |valor| {
+            if let Valor::Parametro(numero) = valor {
+                numeros.insert(*numero);
+            }
+        });
+        if let SQLQuery::Insert(insert) = &consulta {
+            numeros.extend(insert.parametros.iter().map(|parametro| parametro.numero));
+        }
+        let cantidad = numeros.last().copied().unwrap_or(0);
+        if let Some(faltante) = (1..=cantidad).find(|numero| !numeros.contains(numero)) {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "Falta el parámetro ${} en la consulta",
+                faltante
+            )));
+        }
+        let mut tipos = vec![None; cantidad];
+        deducir_tipos(path, &consulta, &mut tipos)?;
+        Ok(PreparedStatement {
+            path: path.to_string(),
+            consulta,
+            tipos,
+        })
+    }
+
+    /// Devuelve la cantidad de parámetros de la consulta.
+    pub fn cantidad_parametros(&self) -> usize {
+        self.tipos.len()
+    }
+
+    /// Devuelve el tipo deducido del parámetro `$numero`, si se pudo deducir.
+    pub fn tipo_parametro(&self, numero: usize) -> Option<&TipoDato> {
+        self.tipos.get(numero.checked_sub(1)?)?.as_ref()
+    }
+
+    /// Devuelve la consulta con cada parámetro `$n` reemplazado por el valor `n` de `parametros`,
+    /// después de verificar que haya un valor por parámetro y que cada uno tenga el tipo del parámetro.
+    pub fn vincular(&self, parametros: &[Datos]) -> Result<SQLQuery, ErrorType> {
+        if parametros.len() != self.tipos.len() {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "Se esperaban {} parámetros, pero se recibieron {}",
+                self.tipos.len(),
+                parametros.len()
+            )));
+        }
+        for (i, (tipo, dato)) in self.tipos.iter().zip(parametros).enumerate() {
+            validar_parametro(i + 1, tipo, dato)?;
+        }
+        let mut consulta = self.consulta.clone();
+        consulta.visitar_valores(&mut |valor| {
+            if let Valor::Parametro(numero) = valor {
+                *valor = dato_to_valor(&parametros[*numero - 1]);
+            }
+        });
+        if let SQLQuery::Insert(insert) = &mut consulta {
+            for parametro in std::mem::take(&mut insert.parametros) {
+                insert.values[parametro.fila][parametro.columna] =
+                    parametros[parametro.numero - 1].clone();
+            }
+        }
+        Ok(consulta)
+    }
+
+    /// Ejecuta la consulta con los valores de sus parámetros.
+    pub fn ejecutar(&self, parametros: &[Datos]) -> Result<(), ErrorType> {
+        self.vincular(parametros)?.execute(&self.path)
+    }
+}
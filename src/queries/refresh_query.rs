@@ -0,0 +1,31 @@
+use crate::{errores::error::ErrorType, executer::execute::Execute};
+
+use super::vista_materializada::{cargar_materializada, es_materializada, refrescar};
+
+/// Representa una consulta SQL `REFRESH MATERIALIZED VIEW nombre`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RefreshQuery {
+    pub nombre: String,
+}
+
+impl RefreshQuery {
+    /// Crea una nueva instancia de `RefreshQuery`.
+    pub fn new(nombre: &str) -> Self {
+        RefreshQuery {
+            nombre: nombre.to_string(),
+        }
+    }
+}
+
+impl Execute for RefreshQuery {
+    /// Ejecuta la consulta REFRESH volviendo a calcular el resultado de la vista materializada.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        if !es_materializada(path, &self.nombre) {
+            return Err(ErrorType::InvalidTable(format!(
+                "La vista materializada '{}' no existe",
+                self.nombre
+            )));
+        }
+        refrescar(path, &cargar_materializada(path, &self.nombre)?)
+    }
+}
@@ -1,25 +1,80 @@
-use std::io::BufRead;
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    time::Duration,
+};
 
 use crate::{
     errores::error::ErrorType,
     executer::{
+        conjuntos::{abrir_para_agregar, distintas, escribir, terminar},
         execute::Execute,
         manejo_csv::{
-            agregar_linea, eliminar_archivo, filtrar_columnas, imprimir_archivo, listar_columnas,
-            ordenar_archivo, preparar_archivos, string_to_columns, where_condition,
+            agregar_linea, agregar_path, crear_archivo, eliminar_archivo, filtrar_columnas,
+            get_reader, imprimir_archivo, leer_encabezado, limitar_archivo, nombre_auxiliar,
+            ordenar_archivo, ordenar_linea, particionar_archivo, posiciones_usadas,
+            proyectar_archivo, reemplazar_archivo, string_to_columns, string_to_columns_podadas,
+            where_condition,
         },
+        mediciones::{clave, clave_lectura, Medicion, Mediciones},
+        primeras_filas::PrimerasFilas,
+    },
+    plan::{
+        optimizador::optimizar,
+        plan_logico::{PlanLogico, Scan},
+    },
+    utils::dato_to_string,
+};
+
+use super::{
+    conteo::{contar, Conteo},
+    join::{
+        agregar_sin_calificar, calificar_encabezado, posiciones_referenciadas, resolver_columna,
+        sin_calificar, Join,
+    },
+    order_clause::OrderClause,
+    valores::Valores,
+    ventana::Ventana,
+    where_clause::{
+        comparadores::evaluar_valor,
+        evaluar::Evaluar,
+        expresion_booleana::ExpresionBooleana,
+        subconsulta::{calificar_columnas, preparar_condicion, preparar_valores},
+        valor::{Valor, VisitarValor},
     },
 };
 
-use super::{order_clause::OrderClause, where_clause::expresion_booleana::ExpresionBooleana};
+/// Función que recibe el nombre de una tabla referenciada en una consulta junto con su alias, y puede reemplazarlos.
+pub type RenombrarTabla<'a> = dyn FnMut(&mut String, &mut Option<String>) + 'a;
 
-/// Representa una consulta SQL SELECT con columnas seleccionadas, tabla, cláusula WHERE, orden y columnas para ordenar.
-#[derive(Debug, PartialEq)]
+/// Representa una consulta SQL SELECT con columnas seleccionadas, tabla, cláusula WHERE, orden y columnas para ordenar,
+/// y la cantidad máxima de filas a devolver. Con `distinct` se descartan las filas repetidas del resultado,
+/// y si tiene conteos el resultado es una única fila con un COUNT en cada columna.
+/// Las expresiones CASE seleccionadas se calculan en cada fila y su texto es el nombre de su columna en `columns_select`.
+/// Con JOIN, las columnas se pueden referenciar calificadas con el nombre de su tabla, como `tabla.columna`.
+/// Las funciones de ventana se calculan luego del WHERE, y el nombre de su columna también está en `columns_select`.
+/// Cada columna seleccionada puede tener un alias con `AS`, que es su nombre en el encabezado del resultado
+/// y se puede usar en el ORDER BY.
+/// Con `valores` las filas se leen de los `VALUES` escritos en la consulta en lugar de un archivo, y `table`
+/// es el nombre con el que se referencian; sin FROM, `table` es vacío y se lee una única fila sin columnas.
+#[derive(Debug, PartialEq, Clone)]
 pub struct SelectQuery {
+    pub distinct: bool,
     pub columns_select: Vec<String>,
+    /// El alias de cada columna de `columns_select`, en el mismo orden.
+    pub alias_columnas: Vec<Option<String>>,
+    pub conteos: Vec<Conteo>,
+    pub calculadas: Vec<Valor>,
+    pub ventanas: Vec<Ventana>,
     pub table: String,
+    pub valores: Option<Valores>,
+    /// Nombre con el que se referencia la tabla, si no es el de su archivo.
+    pub alias: Option<String>,
+    pub joins: Vec<Join>,
     pub where_clause: Option<ExpresionBooleana>,
     pub order_by: Option<Vec<OrderClause>>,
+    pub limit: Option<usize>,
 }
 
 impl SelectQuery {
@@ -31,27 +86,625 @@ impl SelectQuery {
         order_by: Option<Vec<OrderClause>>,
     ) -> Self {
         SelectQuery {
+            distinct: false,
+            alias_columnas: vec![None; columns_select.len()],
             columns_select,
+            conteos: Vec::new(),
+            calculadas: Vec::new(),
+            ventanas: Vec::new(),
             table,
+            valores: None,
+            alias: None,
+            joins: Vec::new(),
             where_clause,
             order_by,
+            limit: None,
         }
     }
 }
 
-impl Execute for SelectQuery {
-    /// Ejecuta la consulta SELECT en el archivo especificado, filtrando, seleccionando columnas y ordenando los resultados.
-    /// Filtra en auxiliar.csv las filas que cumplen el where clause select y luego las ordena e imprime por pantalla.
-    fn execute(&self, path: &str) -> Result<(), ErrorType> {
-        let (_, reader, path_aux) = preparar_archivos(path, &self.table, &"auxiliar".to_string())?;
-        let lines = reader.lines();
-        let (lines, columnas) = listar_columnas(&path_aux, lines)?;
+impl SelectQuery {
+    /// Filtra en un archivo auxiliar las filas que cumplen el where clause, las ordena y se queda con las primeras según el LIMIT.
+    /// Devuelve la ruta del archivo, que conserva las columnas de la tabla que usa la consulta,
+    /// junto con el encabezado de las columnas seleccionadas y sus posiciones.
+    /// Con DISTINCT o COUNT, el archivo tiene solo las columnas del resultado.
+    pub fn materializar(&self, path: &str) -> Result<(String, String, Vec<usize>), ErrorType> {
+        self.materializar_midiendo(path, &mut Mediciones::default(), "")
+    }
+
+    /// Igual que `materializar`, registrando en `mediciones` las filas y el tiempo de cada etapa
+    /// con claves que empiezan con `prefijo`.
+    pub fn materializar_midiendo(
+        &self,
+        path: &str,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<(String, String, Vec<usize>), ErrorType> {
+        let mut auxiliares = Vec::new();
+        let resultado = self
+            .optimizada(path, &mut auxiliares, mediciones, prefijo)
+            .and_then(|(select, leidas)| {
+                select.ejecutar_etapas(path, leidas.as_deref(), mediciones, prefijo)
+            });
+        for path_aux in &auxiliares {
+            eliminar_archivo(path_aux)?;
+        }
+        resultado
+    }
+
+    /// Devuelve la consulta que se ejecuta según su plan optimizado, con el WHERE y las condiciones de los JOIN
+    /// simplificados y las tablas de los JOIN en el orden elegido por el optimizador. Con JOIN, cada tabla que se puede filtrar o de la que no se usan todas las columnas
+    /// se reemplaza por un archivo auxiliar con solo las filas que cumplen las condiciones sobre sus columnas
+    /// y las columnas que usa la consulta. Guarda en `auxiliares` las rutas de esos archivos, y en `mediciones`
+    /// el tiempo de leer y filtrar cada tabla.
+    /// Sin JOIN, devuelve además las únicas columnas de la tabla que se leen, si no son todas.
+    pub fn optimizada(
+        &self,
+        path: &str,
+        auxiliares: &mut Vec<String>,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<(SelectQuery, Option<Vec<String>>), ErrorType> {
+        let plan = optimizar(PlanLogico::desde_select(self, path));
+        let mut select = self.clone();
+        let lecturas = plan.lecturas();
+        if self.joins.is_empty() {
+            let Some((scan, filtro)) = lecturas.first() else {
+                select.where_clause = None;
+                return Ok((select, None));
+            };
+            select.where_clause = filtro.cloned();
+            let leidas = match (&scan.columnas, &scan.leidas) {
+                (Some(columnas), Some(leidas)) if leidas.len() < columnas.len() => {
+                    Some(leidas.clone())
+                }
+                _ => None,
+            };
+            return Ok((select, leidas));
+        }
+        select.where_clause = plan.filtro_combinadas().cloned();
+        let (primera, _) = lecturas[0];
+        select.table = primera.tabla.to_string();
+        select.alias = primera.alias.clone();
+        select.joins = lecturas[1..]
+            .iter()
+            .zip(plan.condiciones_join())
+            .map(|((scan, _), condicion)| Join {
+                tabla: scan.tabla.to_string(),
+                alias: scan.alias.clone(),
+                condicion: condicion.clone(),
+            })
+            .collect();
+        for (i, (scan, filtro)) in lecturas.into_iter().enumerate() {
+            let podada = matches!(
+                (&scan.columnas, &scan.leidas),
+                (Some(columnas), Some(leidas)) if leidas.len() < columnas.len()
+            );
+            if filtro.is_none() && !podada {
+                continue;
+            }
+            let valores = if i == 0 { select.valores.take() } else { None };
+            let origen = match valores {
+                Some(valores) => {
+                    let origen = valores.escribir(path)?;
+                    auxiliares.push(origen.clone());
+                    origen
+                }
+                None => agregar_path(path, &scan.tabla),
+            };
+            let nombre_aux = nombre_auxiliar("auxiliar");
+            let salida = agregar_path(path, &nombre_aux);
+            auxiliares.push(salida.clone());
+            mediciones.medir_sumando(&clave_lectura(prefijo, i), &salida, || {
+                escribir_lectura(&origen, scan, filtro, &salida)
+            })?;
+            let (tabla, alias) = match i.checked_sub(1) {
+                Some(j) => {
+                    let join = &mut select.joins[j];
+                    (&mut join.tabla, &mut join.alias)
+                }
+                None => (&mut select.table, &mut select.alias),
+            };
+            if alias.is_none() {
+                *alias = Some(tabla.to_string());
+            }
+            *tabla = nombre_aux;
+        }
+        Ok((select, None))
+    }
+
+    /// Ejecuta cada etapa de la consulta, registrando en `mediciones` sus filas y su tiempo.
+    /// Si se indican `leidas`, de cada fila de la tabla solo se leen y se conservan esas columnas.
+    fn ejecutar_etapas(
+        &self,
+        path: &str,
+        leidas: Option<&[String]>,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<(String, String, Vec<usize>), ErrorType> {
+        let nombre_aux = nombre_auxiliar("auxiliar");
+        let path_aux = agregar_path(path, &nombre_aux);
+        let fuente = self.fuente(path, mediciones, prefijo)?;
+        let columnas_filtradas = get_reader(&fuente)
+            .and_then(|reader| {
+                crear_archivo(&path_aux)?;
+                let columnas =
+                    mediciones.medir(&clave(prefijo, "filas"), &[&fuente], &path_aux, || {
+                        self.filtrar(path, reader, leidas, &path_aux)
+                    })?;
+                if self.ventanas.is_empty() {
+                    return Ok(columnas);
+                }
+                mediciones.medir(&clave(prefijo, "ventanas"), &[&path_aux], &path_aux, || {
+                    self.calcular_ventanas(path, &nombre_aux, columnas)
+                })
+            })
+            .and_then(|columnas| {
+                if !self.conteos.is_empty() {
+                    mediciones.medir(&clave(prefijo, "conteo"), &[&path_aux], &path_aux, || {
+                        self.contar(path, &path_aux)
+                    })
+                } else if self.distinct {
+                    mediciones.medir(&clave(prefijo, "distinct"), &[&path_aux], &path_aux, || {
+                        self.distinguir(path, &path_aux, &nombre_aux, &columnas)
+                    })
+                } else {
+                    let filtradas = self.seleccion(&columnas)?;
+                    if self.order_by.is_some() && !self.usa_primeras_filas() {
+                        let orden = self.orden_resuelto(&columnas)?;
+                        mediciones.medir(
+                            &clave(prefijo, "orden"),
+                            &[&path_aux],
+                            &path_aux,
+                            || ordenar_archivo(path, &nombre_aux, &orden),
+                        )?;
+                    }
+                    Ok(filtradas)
+                }
+            })
+            .and_then(|filtradas| match self.limit {
+                Some(limite) => mediciones
+                    .medir(&clave(prefijo, "limite"), &[&path_aux], &path_aux, || {
+                        limitar_archivo(&path_aux, limite)
+                    })
+                    .map(|_| filtradas),
+                None => Ok(filtradas),
+            });
+        if !self.joins.is_empty() || self.valores.is_some() {
+            eliminar_archivo(&fuente)?;
+        }
+        match columnas_filtradas {
+            Ok((columnas_filtradas, posiciones)) => Ok((path_aux, columnas_filtradas, posiciones)),
+            Err(e) => {
+                let _ = eliminar_archivo(&path_aux);
+                Err(e)
+            }
+        }
+    }
+
+    /// Aplica `renombrar` a cada tabla referenciada por la consulta: la del FROM, las de los JOIN
+    /// y las de sus subconsultas.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        if self.valores.is_none() {
+            renombrar(&mut self.table, &mut self.alias);
+        }
+        for join in &mut self.joins {
+            renombrar(&mut join.tabla, &mut join.alias);
+            join.condicion.renombrar_tablas(renombrar);
+        }
+        if let Some(where_clause) = &mut self.where_clause {
+            where_clause.renombrar_tablas(renombrar);
+        }
+        for calculada in &mut self.calculadas {
+            calculada.renombrar_tablas(renombrar);
+        }
+        let expresiones = self.order_by.iter_mut().flatten();
+        for expresion in expresiones.filter_map(|clause| clause.expresion.as_mut()) {
+            expresion.renombrar_tablas(renombrar);
+        }
+    }
+
+    /// Aplica `visitar` a cada valor de la consulta: los de las condiciones, las expresiones calculadas
+    /// y las del ORDER BY. Si una expresión cambia, también cambia el nombre de la columna que la selecciona.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        for join in &mut self.joins {
+            join.condicion.visitar_valores(visitar);
+        }
+        if let Some(where_clause) = &mut self.where_clause {
+            where_clause.visitar_valores(visitar);
+        }
+        for calculada in &mut self.calculadas {
+            let nombre = calculada.to_string();
+            calculada.visitar_valores(visitar);
+            for columna in self.columns_select.iter_mut().filter(|c| **c == nombre) {
+                *columna = calculada.to_string();
+            }
+        }
+        for clause in self.order_by.iter_mut().flatten() {
+            if let Some(expresion) = &mut clause.expresion {
+                expresion.visitar_valores(visitar);
+                clause.column = expresion.to_string();
+            }
+        }
+    }
+
+    /// Devuelve el nombre con el que se referencian las columnas de la tabla.
+    pub fn referencia(&self) -> &String {
+        self.alias.as_ref().unwrap_or(&self.table)
+    }
+
+    /// Devuelve la ruta del archivo con las filas sobre las que se ejecuta la consulta: el de la tabla,
+    /// uno auxiliar con las filas de los `VALUES` o, con JOIN, uno auxiliar con las filas combinadas
+    /// y las columnas calificadas con el nombre de su tabla.
+    fn fuente(
+        &self,
+        path: &str,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<String, ErrorType> {
+        let path_tabla = match &self.valores {
+            Some(valores) => valores.escribir(path)?,
+            None => agregar_path(path, &self.table),
+        };
+        if self.joins.is_empty() {
+            return Ok(path_tabla);
+        }
+        let mut actual = agregar_path(path, &nombre_auxiliar("auxiliar"));
+        let copiado = crear_archivo(&actual).and_then(|mut archivo| {
+            mediciones.medir_sumando(&clave_lectura(prefijo, 0), &actual, || {
+                let mut lines = get_reader(&path_tabla)?.lines();
+                let columnas =
+                    calificar_encabezado(self.referencia(), &leer_encabezado(&mut lines)?);
+                if writeln!(archivo, "{}", columnas.join(",")).is_err() {
+                    return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+                }
+                proyectar_archivo(&path_tabla, &(0..columnas.len()).collect(), &actual)
+            })
+        });
+        if self.valores.is_some() {
+            eliminar_archivo(&path_tabla)?;
+        }
+        if let Err(e) = copiado {
+            let _ = eliminar_archivo(&actual);
+            return Err(e);
+        }
+        for (i, join) in self.joins.iter().enumerate() {
+            let siguiente = agregar_path(path, &nombre_auxiliar("auxiliar"));
+            let mut lectura = Medicion {
+                filas_entrada: None,
+                filas_salida: 0,
+                tiempo: Duration::ZERO,
+            };
+            let unido = crear_archivo(&siguiente).and_then(|_| {
+                let clave = clave(prefijo, &format!("join{}", i));
+                mediciones.medir(&clave, &[&actual], &siguiente, || {
+                    join.unir(path, &actual, &siguiente, &mut lectura)
+                })
+            });
+            mediciones.sumar(&clave_lectura(prefijo, i + 1), lectura);
+            eliminar_archivo(&actual)?;
+            actual = siguiente;
+            if let Err(e) = unido {
+                let _ = eliminar_archivo(&actual);
+                return Err(e);
+            }
+        }
+        Ok(actual)
+    }
+
+    /// Devuelve las columnas de las filas sobre las que se ejecuta la consulta, sin leerlas:
+    /// las de la tabla o los `VALUES` o, con JOIN, las de todas las tablas calificadas con el nombre de su tabla.
+    pub fn columnas_fuente(&self, path: &str) -> Result<Vec<String>, ErrorType> {
+        let encabezado = |tabla: &String| {
+            let mut lines = get_reader(&agregar_path(path, tabla))?.lines();
+            leer_encabezado(&mut lines)
+        };
+        let columnas_tabla = match &self.valores {
+            Some(valores) => valores.columnas.clone(),
+            None => encabezado(&self.table)?,
+        };
+        if self.joins.is_empty() {
+            return Ok(columnas_tabla);
+        }
+        let mut columnas = calificar_encabezado(self.referencia(), &columnas_tabla);
+        for join in &self.joins {
+            columnas.extend(calificar_encabezado(
+                join.referencia(),
+                &encabezado(&join.tabla)?,
+            ));
+        }
+        Ok(columnas)
+    }
+
+    /// Devuelve las columnas de la fuente seguidas de los nombres de las expresiones calculadas en cada fila
+    /// y de las columnas de las funciones de ventana.
+    pub fn columnas_disponibles(&self, columnas_fuente: &[String]) -> Vec<String> {
+        let mut disponibles = columnas_fuente.to_vec();
+        disponibles.extend(self.expresiones().iter().map(|e| e.to_string()));
+        for ventana in &self.ventanas {
+            let nombre = ventana.nombre_columna();
+            if !disponibles.contains(&nombre) {
+                disponibles.push(nombre);
+            }
+        }
+        disponibles
+    }
+
+    /// Agrega al archivo auxiliar una columna con el resultado de cada función de ventana, ordenando sus filas
+    /// por partición. Devuelve las columnas recibidas seguidas de las de las ventanas.
+    fn calcular_ventanas(
+        &self,
+        path: &str,
+        nombre_aux: &String,
+        mut columnas: Vec<String>,
+    ) -> Result<Vec<String>, ErrorType> {
+        for ventana in &self.ventanas {
+            let nombre = ventana.nombre_columna();
+            if columnas.contains(&nombre) {
+                continue;
+            }
+            let resuelta =
+                ventana.resuelta(&columnas, &|nombre| self.resolver(nombre, &columnas))?;
+            particionar_archivo(
+                path,
+                nombre_aux,
+                &resuelta.particion,
+                &resuelta.orden,
+                &nombre,
+                &mut |filas| resuelta.calcular(filas),
+            )?;
+            columnas.push(nombre);
+        }
+        Ok(columnas)
+    }
+
+    /// Devuelve el encabezado de las columnas seleccionadas, con su alias o como se escribieron en la consulta,
+    /// y sus posiciones entre las columnas disponibles. Con `*` se seleccionan las columnas de la fuente.
+    pub fn seleccion(&self, columnas: &[String]) -> Result<(String, Vec<usize>), ErrorType> {
+        if self.columns_select.len() == 1 && self.columns_select[0] == "*" {
+            let calculadas = self.columnas_disponibles(&[]).len();
+            let cantidad = columnas.len() - calculadas;
+            return Ok((columnas[..cantidad].join(","), (0..cantidad).collect()));
+        }
+        let resueltas = self
+            .columns_select
+            .iter()
+            .map(|columna| self.resolver(columna, columnas))
+            .collect::<Result<Vec<String>, ErrorType>>()?;
+        let (_, posiciones) = filtrar_columnas(&resueltas, columnas)?;
+        Ok((self.encabezado().join(","), posiciones))
+    }
+
+    /// Devuelve el nombre de cada columna seleccionada en el resultado: su alias, o su texto si no tiene.
+    pub fn encabezado(&self) -> Vec<String> {
+        self.columns_select
+            .iter()
+            .zip(&self.alias_columnas)
+            .map(|(columna, alias)| alias.as_ref().unwrap_or(columna).to_string())
+            .collect()
+    }
+
+    /// Devuelve la columna seleccionada que tiene ese alias, si hay alguna.
+    fn columna_con_alias(&self, alias: &str) -> Option<&String> {
+        self.alias_columnas
+            .iter()
+            .position(|a| a.as_deref() == Some(alias))
+            .map(|i| &self.columns_select[i])
+    }
+
+    /// Devuelve la columna del encabezado a la que se refiere el nombre, que puede estar calificado con el nombre
+    /// o el alias de la tabla. Con JOIN, un nombre sin calificar se refiere a la única columna calificada con ese nombre.
+    pub fn resolver(&self, nombre: &str, columnas: &[String]) -> Result<String, ErrorType> {
+        if self.joins.is_empty() {
+            let prefijo = format!("{}.", self.referencia());
+            if let Some(columna) = nombre.strip_prefix(&prefijo) {
+                if columnas.iter().any(|c| c == columna) {
+                    return Ok(columna.to_string());
+                }
+            }
+        }
+        resolver_columna(nombre, columnas)
+    }
+
+    /// Devuelve la columna seleccionada en la posición de un `ORDER BY n`, contando desde 1.
+    /// Con `*`, es la columna de la fuente en esa posición.
+    fn columna_en_posicion(
+        &self,
+        posicion: usize,
+        columnas: &[String],
+    ) -> Result<String, ErrorType> {
+        let seleccionadas = if self.columns_select.len() == 1 && self.columns_select[0] == "*" {
+            let calculadas = self.columnas_disponibles(&[]).len();
+            &columnas[..columnas.len() - calculadas]
+        } else {
+            &self.columns_select[..]
+        };
+        match posicion.checked_sub(1).and_then(|i| seleccionadas.get(i)) {
+            Some(columna) => Ok(columna.to_string()),
+            None => Err(ErrorType::InvalidColumn(format!(
+                "La posición {} del ORDER BY no corresponde a ninguna columna seleccionada",
+                posicion
+            ))),
+        }
+    }
+
+    /// Devuelve el order by con cada posición y cada alias reemplazados por su columna, y cada columna
+    /// sin calificar reemplazada por la columna calificada a la que se refiere.
+    fn orden_resuelto(&self, columnas: &[String]) -> Result<Option<Vec<OrderClause>>, ErrorType> {
+        let Some(order_by) = &self.order_by else {
+            return Ok(None);
+        };
+        let mut resuelto = Vec::new();
+        for clause in order_by {
+            let mut clause = clause.clone();
+            if let Some(posicion) = clause.posicion() {
+                clause.column = self.columna_en_posicion(posicion, columnas)?;
+            } else if let Some(columna) = self.columna_con_alias(&clause.column) {
+                clause.column = columna.to_string();
+            }
+            clause.column = self.resolver(&clause.column, columnas)?;
+            resuelto.push(clause);
+        }
+        Ok(Some(resuelto))
+    }
+
+    /// Reemplaza las filas filtradas del archivo auxiliar por las columnas seleccionadas sin filas repetidas,
+    /// ordenadas según el order by. Si no entran en memoria, las filas se reparten por hash en archivos auxiliares.
+    fn distinguir(
+        &self,
+        path: &str,
+        path_aux: &String,
+        nombre_aux: &String,
+        columnas: &[String],
+    ) -> Result<(String, Vec<usize>), ErrorType> {
+        let (columnas_filtradas, posiciones) = self.seleccion(columnas)?;
+        let seleccionadas: Vec<&str> = columnas_filtradas.split(',').collect();
+        let mut order_by = self.order_by.clone();
+        for clause in order_by.iter_mut().flatten() {
+            if let Some(posicion) = clause.posicion() {
+                clause.column = match posicion.checked_sub(1).and_then(|i| seleccionadas.get(i)) {
+                    Some(columna) => columna.to_string(),
+                    None => {
+                        return Err(ErrorType::InvalidColumn(format!(
+                        "La posición {} del ORDER BY no corresponde a ninguna columna seleccionada",
+                        posicion
+                    )))
+                    }
+                };
+                continue;
+            }
+            let seleccionada = self.columns_select.iter().position(|c| *c == clause.column);
+            if let Some(Some(alias)) = seleccionada.map(|i| &self.alias_columnas[i]) {
+                clause.column = alias.to_string();
+            }
+            if !seleccionadas.contains(&clause.column.as_str()) {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "Con SELECT DISTINCT, la columna '{}' del ORDER BY debe estar seleccionada",
+                    clause.column
+                )));
+            }
+        }
+        let path_filas = agregar_path(path, &nombre_auxiliar("auxiliar"));
+        let resultado = crear_archivo(&path_filas)
+            .and_then(|_| proyectar_archivo(path_aux, &posiciones, &path_filas))
+            .and_then(|_| {
+                let mut archivo = crear_archivo(path_aux)?;
+                if writeln!(archivo, "{}", columnas_filtradas).is_err() {
+                    return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+                }
+                distintas(path, &path_filas, path_aux)
+            });
+        eliminar_archivo(&path_filas)?;
+        resultado?;
+        ordenar_archivo(path, nombre_aux, &order_by)?;
+        let posiciones = (0..posiciones.len()).collect();
+        Ok((columnas_filtradas, posiciones))
+    }
+
+    /// Reemplaza las filas filtradas del archivo auxiliar por una única fila con el resultado de cada COUNT.
+    fn contar(&self, path: &str, path_aux: &String) -> Result<(String, Vec<usize>), ErrorType> {
+        let cantidades = contar(&self.conteos, path, path_aux)?;
+        let encabezado: Vec<String> = self.conteos.iter().map(|c| c.nombre_columna()).collect();
+        let fila: Vec<String> = cantidades.iter().map(|c| c.to_string()).collect();
+        let path_resultado = agregar_path(path, &nombre_auxiliar("auxiliar"));
+        let mut resultado = crear_archivo(&path_resultado)?;
+        let escrito = writeln!(resultado, "{}\n{}", encabezado.join(","), fila.join(","));
+        let reemplazado = match escrito {
+            Ok(_) => reemplazar_archivo(&path_resultado, path_aux),
+            Err(_) => Err(ErrorType::Error("Error al escribir en archivo".to_string())),
+        };
+        eliminar_archivo(&path_resultado)?;
+        reemplazado?;
+        Ok((encabezado.join(","), (0..encabezado.len()).collect()))
+    }
+
+    /// Devuelve las expresiones que se calculan en cada fila: las seleccionadas y las del order by, sin repetir.
+    pub fn expresiones(&self) -> Vec<&Valor> {
+        let mut expresiones: Vec<&Valor> = self.calculadas.iter().collect();
+        let del_orden = self.order_by.iter().flatten();
+        for expresion in del_orden.filter_map(|clause| clause.expresion.as_ref()) {
+            if !expresiones.contains(&expresion) {
+                expresiones.push(expresion);
+            }
+        }
+        expresiones
+    }
+
+    /// Indica si las filas del resultado se pueden elegir con un heap de las primeras según el ORDER BY
+    /// mientras se filtran, en lugar de ordenarlas todas: con ORDER BY y LIMIT, y sin DISTINCT, COUNT
+    /// ni funciones de ventana, que necesitan todas las filas.
+    pub fn usa_primeras_filas(&self) -> bool {
+        self.order_by.is_some()
+            && self.limit.is_some()
+            && !self.distinct
+            && self.conteos.is_empty()
+            && self.ventanas.is_empty()
+    }
+
+    /// Escribe en el archivo auxiliar las filas que cumplen el where clause, agregando al final de cada una
+    /// los valores de las expresiones calculadas, y devuelve las columnas de la tabla seguidas de las calculadas.
+    /// Con ORDER BY y LIMIT, escribe solo las primeras filas según el orden, ya ordenadas.
+    /// Con `leidas`, cada fila conserva solo esas columnas de la tabla, que son las únicas que se devuelven.
+    fn filtrar(
+        &self,
+        path: &str,
+        reader: BufReader<File>,
+        leidas: Option<&[String]>,
+        path_aux: &String,
+    ) -> Result<Vec<String>, ErrorType> {
+        let mut lines = reader.lines();
+        let mut columnas = leer_encabezado(&mut lines)?;
+        let tabla = self.referencia();
+        let where_clause = preparar_condicion(&self.where_clause, path, tabla, &columnas)?;
+        let expresiones: Vec<Valor> = self.expresiones().into_iter().cloned().collect();
+        let expresiones = preparar_valores(&expresiones, path, tabla, &columnas)?;
+        let sin_calificar = sin_calificar(&columnas);
+        let columnas_tabla = columnas.clone();
+        let usadas = posiciones_referenciadas(&columnas_tabla, &where_clause, &expresiones);
+        let conservadas: Option<Vec<usize>> = leidas.map(|leidas| {
+            (0..columnas_tabla.len())
+                .filter(|&i| leidas.contains(&columnas_tabla[i]))
+                .collect()
+        });
+        if let Some(conservadas) = &conservadas {
+            columnas = conservadas
+                .iter()
+                .map(|&i| columnas_tabla[i].clone())
+                .collect();
+        }
+        columnas.extend(expresiones.iter().map(|e| e.to_string()));
+        agregar_linea(path_aux, &columnas.join(","))?;
+        let orden = match (self.usa_primeras_filas(), self.limit) {
+            (true, Some(limite)) => self.orden_resuelto(&columnas)?.map(|orden| (orden, limite)),
+            _ => None,
+        };
+        let mut primeras = orden
+            .as_ref()
+            .map(|(orden, limite)| PrimerasFilas::new(orden, *limite));
         for line in lines {
             match line {
-                Ok(line) => {
-                    let fila = string_to_columns(&line, &columnas)?;
-                    if where_condition(&self.where_clause, &fila)? {
-                        agregar_linea(&path_aux, &line)?;
+                Ok(mut line) => {
+                    let mut fila = string_to_columns_podadas(&line, &columnas_tabla, &usadas)?;
+                    if self.joins.is_empty() {
+                        calificar_columnas(&mut fila, tabla, &columnas_tabla);
+                    } else {
+                        agregar_sin_calificar(&mut fila, &sin_calificar);
+                    }
+                    if where_condition(&where_clause, &fila)? {
+                        if let Some(conservadas) = &conservadas {
+                            line = ordenar_linea(&line, conservadas)?;
+                        }
+                        for expresion in &expresiones {
+                            line.push(',');
+                            line.push_str(&dato_to_string(&evaluar_valor(expresion, &fila)?));
+                        }
+                        match &mut primeras {
+                            Some(primeras) => {
+                                let fila = string_to_columns(&line, &columnas)?;
+                                primeras.agregar(line, fila);
+                            }
+                            None => agregar_linea(path_aux, &line)?,
+                        }
                     }
                 }
                 Err(_) => {
@@ -61,10 +714,118 @@ impl Execute for SelectQuery {
                 }
             }
         }
-        ordenar_archivo(path, &"auxiliar".to_string(), &self.order_by)?;
-        let (columnas_filtradas, posiciones) = filtrar_columnas(&self.columns_select, &columnas)?;
-        imprimir_archivo(&path_aux, columnas_filtradas, posiciones)?;
+        for linea in primeras.map(PrimerasFilas::lineas).unwrap_or_default() {
+            agregar_linea(path_aux, &linea)?;
+        }
+        Ok(columnas)
+    }
+}
+
+/// Escribe en la salida las filas del archivo de origen leído por `scan` que cumplen el filtro, con solo
+/// las columnas que lee y su encabezado sin calificar. El filtro usa únicamente columnas de esa tabla.
+fn escribir_lectura(
+    origen: &String,
+    scan: &Scan,
+    filtro: Option<&ExpresionBooleana>,
+    salida: &String,
+) -> Result<(), ErrorType> {
+    let mut lines = get_reader(origen)?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    let posiciones: Vec<usize> = match &scan.leidas {
+        Some(leidas) => (0..columnas.len())
+            .filter(|&i| leidas.contains(&columnas[i]))
+            .collect(),
+        None => (0..columnas.len()).collect(),
+    };
+    let usadas = filtro
+        .map(|filtro| posiciones_usadas(&columnas, &filtro.columnas_referenciadas()))
+        .unwrap_or_default();
+    crear_archivo(salida)?;
+    let mut escritor = abrir_para_agregar(salida)?;
+    let encabezado: Vec<&str> = posiciones.iter().map(|&i| columnas[i].as_str()).collect();
+    escribir(&mut escritor, &encabezado.join(","))?;
+    for line in lines {
+        let Ok(line) = line else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        if let Some(filtro) = filtro {
+            let mut fila = string_to_columns_podadas(&line, &columnas, &usadas)?;
+            calificar_columnas(&mut fila, scan.referencia(), &columnas);
+            if !filtro.evaluar(&fila)? {
+                continue;
+            }
+        }
+        escribir(&mut escritor, &ordenar_linea(&line, &posiciones)?)?;
+    }
+    terminar(escritor)
+}
+
+impl Execute for SelectQuery {
+    /// Ejecuta la consulta SELECT en el archivo especificado, filtrando, seleccionando columnas y ordenando los resultados.
+    /// Filtra en un archivo auxiliar las filas que cumplen el where clause select y luego las ordena e imprime por pantalla.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let (path_aux, columnas_filtradas, posiciones) = self.materializar(path)?;
+        let resultado = imprimir_archivo(&path_aux, columnas_filtradas, posiciones);
         eliminar_archivo(&path_aux)?;
+        resultado
+    }
+}
+
+impl Display for SelectQuery {
+    /// Muestra la consulta con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "SELECT ")?;
+        if self.distinct {
+            write!(f, "DISTINCT ")?;
+        }
+        let columnas: Vec<String> = if self.conteos.is_empty() {
+            self.columns_select
+                .iter()
+                .zip(&self.alias_columnas)
+                .map(|(columna, alias)| match alias {
+                    Some(alias) => format!("{} AS {}", columna, alias),
+                    None => columna.to_string(),
+                })
+                .collect()
+        } else {
+            self.conteos
+                .iter()
+                .map(|c| match &c.alias {
+                    Some(alias) => format!("{} AS {}", c, alias),
+                    None => c.to_string(),
+                })
+                .collect()
+        };
+        write!(f, "{}", columnas.join(", "))?;
+        match &self.valores {
+            Some(_) if self.table.is_empty() => {}
+            Some(valores) => write!(
+                f,
+                " FROM {} AS {}({})",
+                valores,
+                self.table,
+                valores.columnas.join(", ")
+            )?,
+            None => write!(f, " FROM {}", self.table)?,
+        }
+        if let Some(alias) = &self.alias {
+            write!(f, " {}", alias)?;
+        }
+        for join in &self.joins {
+            write!(f, " {}", join)?;
+        }
+        if let Some(where_clause) = &self.where_clause {
+            write!(f, " WHERE {}", where_clause)?;
+        }
+        if let Some(order_by) = &self.order_by {
+            let order_by: Vec<String> = order_by.iter().map(|o| o.to_string()).collect();
+            write!(f, " ORDER BY {}", order_by.join(", "))?;
+        }
+        if let Some(limit) = self.limit {
+            write!(f, " LIMIT {}", limit)?;
+        }
         Ok(())
     }
 }
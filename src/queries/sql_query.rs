@@ -1,27 +1,206 @@
-use crate::{errores::error::ErrorType, executer::execute::Execute};
+use std::collections::HashMap;
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{
+        execute::Execute,
+        manejo_csv::{agregar_path, eliminar_archivo},
+    },
+};
 
 use super::{
-    delete_query::DeleteQuery, insert_query::InsertQuery, select_query::SelectQuery,
+    analyze_query::AnalyzeQuery,
+    compound_query::CompoundQuery,
+    create_materialized_view_query::CreateMaterializedViewQuery,
+    create_query::CreateQuery,
+    create_view_query::CreateViewQuery,
+    delete_query::DeleteQuery,
+    drop_view_query::DropViewQuery,
+    explain_query::ExplainQuery,
+    insert_query::InsertQuery,
+    on_conflict::AccionConflicto,
+    refresh_query::RefreshQuery,
+    select_query::{RenombrarTabla, SelectQuery},
     update_query::UpdateQuery,
+    vista::{es_vista, materializar_vistas},
+    vista_materializada::es_materializada,
+    where_clause::valor::VisitarValor,
+    with_query::WithQuery,
 };
 
 /// Enum que representa los diferentes tipos de consultas SQL soportadas.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum SQLQuery {
     Select(SelectQuery),
+    Compound(CompoundQuery),
+    With(WithQuery),
     Insert(InsertQuery),
     Update(UpdateQuery),
     Delete(DeleteQuery),
+    Create(CreateQuery),
+    CreateView(CreateViewQuery),
+    DropView(DropViewQuery),
+    CreateMaterializedView(CreateMaterializedViewQuery),
+    Refresh(RefreshQuery),
+    Explain(ExplainQuery),
+    Analyze(AnalyzeQuery),
 }
 
-impl Execute for SQLQuery {
+impl SQLQuery {
+    /// Aplica `renombrar` a cada tabla de la que la consulta lee filas. No incluye la tabla que modifica.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        match self {
+            SQLQuery::Select(query) => query.renombrar_tablas(renombrar),
+            SQLQuery::Compound(query) => query.conjunto.renombrar_tablas(renombrar),
+            SQLQuery::With(query) => query.renombrar_tablas(renombrar),
+            SQLQuery::Insert(query) => {
+                if let Some(select) = &mut query.select {
+                    select.renombrar_tablas(renombrar);
+                }
+                if let Some(on_conflict) = &mut query.on_conflict {
+                    if let AccionConflicto::Actualizar(valores) = &mut on_conflict.accion {
+                        for valor in valores.values_mut() {
+                            valor.renombrar_tablas(renombrar);
+                        }
+                    }
+                }
+            }
+            SQLQuery::Update(query) => {
+                if let Some(where_condition) = &mut query.where_condition {
+                    where_condition.renombrar_tablas(renombrar);
+                }
+                for valor in query.expresiones.values_mut() {
+                    valor.renombrar_tablas(renombrar);
+                }
+            }
+            SQLQuery::Delete(query) => {
+                if let Some(where_clause) = &mut query.where_clause {
+                    where_clause.renombrar_tablas(renombrar);
+                }
+            }
+            SQLQuery::Create(_)
+            | SQLQuery::CreateView(_)
+            | SQLQuery::DropView(_)
+            | SQLQuery::CreateMaterializedView(_)
+            | SQLQuery::Refresh(_)
+            | SQLQuery::Explain(_)
+            | SQLQuery::Analyze(_) => {}
+        }
+    }
+
+    /// Aplica `visitar` a cada valor de la consulta, también dentro de sus subconsultas.
+    /// No incluye los valores de VALUES en un INSERT, que no son expresiones.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        match self {
+            SQLQuery::Select(query) => query.visitar_valores(visitar),
+            SQLQuery::Compound(query) => query.conjunto.visitar_valores(visitar),
+            SQLQuery::With(query) => query.visitar_valores(visitar),
+            SQLQuery::Insert(query) => {
+                if let Some(select) = &mut query.select {
+                    select.visitar_valores(visitar);
+                }
+                if let Some(on_conflict) = &mut query.on_conflict {
+                    if let AccionConflicto::Actualizar(valores) = &mut on_conflict.accion {
+                        for valor in valores.values_mut() {
+                            valor.visitar_valores(visitar);
+                        }
+                    }
+                }
+            }
+            SQLQuery::Update(query) => {
+                if let Some(where_condition) = &mut query.where_condition {
+                    where_condition.visitar_valores(visitar);
+                }
+                for valor in query.expresiones.values_mut() {
+                    valor.visitar_valores(visitar);
+                }
+            }
+            SQLQuery::Delete(query) => {
+                if let Some(where_clause) = &mut query.where_clause {
+                    where_clause.visitar_valores(visitar);
+                }
+            }
+            SQLQuery::Create(_)
+            | SQLQuery::CreateView(_)
+            | SQLQuery::DropView(_)
+            | SQLQuery::CreateMaterializedView(_)
+            | SQLQuery::Refresh(_)
+            | SQLQuery::Explain(_)
+            | SQLQuery::Analyze(_) => {}
+        }
+    }
+
+    /// Devuelve la tabla cuyas filas modifica la consulta, si modifica alguna.
+    fn tabla_modificada(&self) -> Option<&String> {
+        match self {
+            SQLQuery::Insert(query) => Some(&query.table),
+            SQLQuery::Update(query) => Some(&query.table),
+            SQLQuery::Delete(query) => Some(&query.table),
+            _ => None,
+        }
+    }
+
     /// Ejecuta la consulta SQL según el tipo de consulta.
-    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+    fn ejecutar(&self, path: &str) -> Result<(), ErrorType> {
         match self {
             SQLQuery::Select(query) => query.execute(path),
+            SQLQuery::Compound(query) => query.execute(path),
+            SQLQuery::With(query) => query.execute(path),
             SQLQuery::Insert(query) => query.execute(path),
             SQLQuery::Update(query) => query.execute(path),
             SQLQuery::Delete(query) => query.execute(path),
+            SQLQuery::Create(query) => query.execute(path),
+            SQLQuery::CreateView(query) => query.execute(path),
+            SQLQuery::DropView(query) => query.execute(path),
+            SQLQuery::CreateMaterializedView(query) => query.execute(path),
+            SQLQuery::Refresh(query) => query.execute(path),
+            SQLQuery::Explain(query) => query.execute(path),
+            SQLQuery::Analyze(query) => query.execute(path),
+        }
+    }
+
+    /// Llama a `ejecutar` con una copia de la consulta en la que las vistas que usa se reemplazan
+    /// por archivos auxiliares con su resultado, que se eliminan al terminar.
+    pub fn con_vistas_materializadas<T>(
+        &self,
+        path: &str,
+        ejecutar: impl FnOnce(&SQLQuery) -> Result<T, ErrorType>,
+    ) -> Result<T, ErrorType> {
+        let mut query = self.clone();
+        let mut vistas = HashMap::new();
+        let materializadas = materializar_vistas(
+            path,
+            &mut |renombrar| query.renombrar_tablas(renombrar),
+            &mut vistas,
+        );
+        let resultado = materializadas.and_then(|_| ejecutar(&query));
+        for nombre_aux in vistas.values() {
+            eliminar_archivo(&agregar_path(path, nombre_aux))?;
+        }
+        resultado
+    }
+}
+
+impl Execute for SQLQuery {
+    /// Ejecuta la consulta SQL según el tipo de consulta.
+    /// Antes, ejecuta las vistas que usa y las reemplaza por archivos auxiliares con su resultado,
+    /// que se eliminan al terminar. Las vistas, materializadas o no, no se pueden modificar.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        if let Some(tabla) = self.tabla_modificada().filter(|t| es_vista(path, t)) {
+            return Err(ErrorType::InvalidTable(format!(
+                "No se puede modificar la vista '{}'",
+                tabla
+            )));
+        }
+        if let Some(tabla) = self
+            .tabla_modificada()
+            .filter(|t| es_materializada(path, t))
+        {
+            return Err(ErrorType::InvalidTable(format!(
+                "No se puede modificar la vista materializada '{}'",
+                tabla
+            )));
         }
+        self.con_vistas_materializadas(path, |query| query.ejecutar(path))
     }
 }
@@ -1,25 +1,45 @@
-use std::{collections::HashMap, io::BufRead};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+};
 
 use crate::{
     dato::Datos,
     errores::error::ErrorType,
+    esquema::{
+        esquema_tabla::Esquema,
+        foreign_key::referencias_a,
+        restricciones::{
+            verificar_sin_referencias, ClavesQuitadas, VerificadorClaves, VerificadorReferencias,
+        },
+    },
     executer::{
         execute::Execute,
         manejo_csv::{
-            agregar_linea, eliminar_archivo, listar_columnas, modificar_linea, preparar_archivos,
-            reemplazar_archivo, string_to_columns, where_condition,
+            agregar_linea, eliminar_archivo, filtrar_columnas, imprimir_filas, listar_columnas,
+            modificar_linea, nombre_auxiliar, preparar_archivos, reemplazar_archivo,
+            string_to_columns, where_condition,
         },
     },
 };
 
-use super::where_clause::expresion_booleana::ExpresionBooleana;
+use super::where_clause::{
+    comparadores::evaluar_valor,
+    expresion_booleana::ExpresionBooleana,
+    subconsulta::{preparar_condicion, preparar_valores},
+    valor::Valor,
+};
 
 /// Representa una consulta SQL UPDATE que modifica registros en una tabla.
-#[derive(Debug, PartialEq)]
+/// Las columnas de `expresiones` toman el valor de una expresión CASE calculada con los valores anteriores de cada fila.
+#[derive(Debug, PartialEq, Clone)]
 pub struct UpdateQuery {
     pub table: String,
     pub changes: HashMap<String, Datos>,
+    pub expresiones: HashMap<String, Valor>,
     pub where_condition: Option<ExpresionBooleana>,
+    pub returning: Option<Vec<String>>,
 }
 
 impl UpdateQuery {
@@ -32,26 +52,71 @@ impl UpdateQuery {
         UpdateQuery {
             table,
             changes,
+            expresiones: HashMap::new(),
             where_condition,
+            returning: None,
         }
     }
-}
 
-impl Execute for UpdateQuery {
-    /// Ejecuta la consulta UPDATE en el archivo especificado, aplicando los cambios a las filas que cumplen la condición WHERE.
-    fn execute(&self, path: &str) -> Result<(), ErrorType> {
-        let (path_update, reader, path_aux) =
-            preparar_archivos(path, &self.table, &"auxiliar".to_string())?;
-        let lines = reader.lines();
-        let (lines, columnas) = listar_columnas(&path_aux, lines)?;
+    /// Verifica que las columnas de RETURNING pertenezcan a la tabla y actualiza las filas.
+    fn verificar_y_actualizar(
+        &self,
+        path: &str,
+        lines: Lines<BufReader<File>>,
+        columnas: &[String],
+        path_aux: &String,
+        esquema: &Option<Esquema>,
+    ) -> Result<Vec<String>, ErrorType> {
+        if let Some(returning) = &self.returning {
+            filtrar_columnas(returning, columnas)?;
+        }
+        self.actualizar(path, lines, columnas, path_aux, esquema)
+    }
+
+    /// Escribe en el archivo auxiliar las filas de la tabla, modificando las que cumplen la condición WHERE
+    /// y verificando que el resultado respete las restricciones del esquema y las foreign keys que referencian a la tabla.
+    /// Devuelve las filas modificadas, con sus nuevos valores.
+    fn actualizar(
+        &self,
+        path: &str,
+        lines: Lines<BufReader<File>>,
+        columnas: &[String],
+        path_aux: &String,
+        esquema: &Option<Esquema>,
+    ) -> Result<Vec<String>, ErrorType> {
+        if let Some(esquema) = esquema {
+            esquema.validar_tipos(&self.changes)?;
+        }
+        let referencias_hijas = referencias_a(path, &self.table)?;
+        let mut claves_modificadas = ClavesQuitadas::new(&referencias_hijas, columnas)?;
+        let mut verificador = VerificadorClaves::new(esquema, columnas)?;
+        let where_clause = preparar_condicion(&self.where_condition, path, &self.table, columnas)?;
+        let (columnas_calculadas, expresiones): (Vec<&String>, Vec<Valor>) =
+            self.expresiones.iter().map(|(c, v)| (c, v.clone())).unzip();
+        let expresiones = preparar_valores(&expresiones, path, &self.table, columnas)?;
+        let mut modificadas = Vec::new();
         for line in lines {
             match line {
                 Ok(mut line) => {
-                    let fila = string_to_columns(&line, &columnas)?;
-                    if where_condition(&self.where_condition, &fila)? {
-                        line = modificar_linea(&line, &self.changes, &columnas)?;
+                    let fila = string_to_columns(&line, columnas)?;
+                    if where_condition(&where_clause, &fila)? {
+                        let anterior = line;
+                        let mut cambios = self.changes.clone();
+                        for (columna, expresion) in columnas_calculadas.iter().zip(&expresiones) {
+                            cambios.insert(columna.to_string(), evaluar_valor(expresion, &fila)?);
+                        }
+                        if let (Some(esquema), false) = (esquema, expresiones.is_empty()) {
+                            esquema.validar_tipos(&cambios)?;
+                        }
+                        line = modificar_linea(&anterior, &cambios, columnas)?;
+                        if let Some(esquema) = esquema {
+                            esquema.verificar_fila(&line, columnas)?;
+                        }
+                        claves_modificadas.registrar_cambio(&anterior, &line);
+                        modificadas.push(line.to_string());
                     }
-                    agregar_linea(&path_aux, &line)?;
+                    verificador.registrar(&line)?;
+                    agregar_linea(path_aux, &line)?;
                 }
                 Err(_) => {
                     return Err(ErrorType::InvalidTable(
@@ -60,8 +125,44 @@ impl Execute for UpdateQuery {
                 }
             }
         }
+        let referencias = VerificadorReferencias::new(path, esquema, columnas, path_aux)?;
+        for line in &modificadas {
+            referencias.verificar(line)?;
+        }
+        verificar_sin_referencias(
+            path,
+            &self.table,
+            &referencias_hijas,
+            claves_modificadas.claves(),
+            path_aux,
+        )?;
+        Ok(modificadas)
+    }
+}
+
+impl Execute for UpdateQuery {
+    /// Ejecuta la consulta UPDATE en el archivo especificado, aplicando los cambios a las filas que cumplen la condición WHERE.
+    /// Si el resultado viola una restricción del esquema, la tabla no se modifica.
+    /// Con RETURNING, imprime las filas modificadas como el resultado de un SELECT.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let esquema = Esquema::cargar(path, &self.table)?;
+        let (path_update, reader, path_aux) =
+            preparar_archivos(path, &self.table, &nombre_auxiliar("auxiliar"))?;
+        let lines = reader.lines();
+        let (lines, columnas) = listar_columnas(&path_aux, lines)?;
+        let modificadas =
+            match self.verificar_y_actualizar(path, lines, &columnas, &path_aux, &esquema) {
+                Ok(modificadas) => modificadas,
+                Err(e) => {
+                    eliminar_archivo(&path_aux)?;
+                    return Err(e);
+                }
+            };
         reemplazar_archivo(&path_aux, &path_update)?;
         eliminar_archivo(&path_aux)?;
-        Ok(())
+        match &self.returning {
+            Some(returning) => imprimir_filas(path, &columnas, &modificadas, returning),
+            None => Ok(()),
+        }
     }
 }
@@ -0,0 +1,73 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    executer::manejo_csv::{agregar_path, crear_archivo, eliminar_archivo, nombre_auxiliar},
+    utils::dato_to_string,
+};
+
+/// Representa las filas escritas en la consulta con `(VALUES (...), (...)) AS tabla(columnas)`,
+/// que se leen como si fueran una tabla. Un SELECT sin FROM lee de una única fila con una columna vacía.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Valores {
+    pub columnas: Vec<String>,
+    pub filas: Vec<Vec<Datos>>,
+}
+
+impl Valores {
+    /// Crea una nueva instancia de `Valores`.
+    pub fn new(columnas: Vec<String>, filas: Vec<Vec<Datos>>) -> Self {
+        Valores { columnas, filas }
+    }
+
+    /// Crea los valores de un SELECT sin FROM: una única fila con una columna sin nombre.
+    pub fn sin_tabla() -> Self {
+        Valores::new(
+            vec![String::new()],
+            vec![vec![Datos::String(String::new())]],
+        )
+    }
+
+    /// Escribe las filas en un archivo auxiliar con formato CSV y devuelve su ruta.
+    pub fn escribir(&self, path: &str) -> Result<String, ErrorType> {
+        let path_aux = agregar_path(path, &nombre_auxiliar("valores"));
+        let mut archivo = crear_archivo(&path_aux)?;
+        let mut contenido = format!("{}\n", self.columnas.join(","));
+        for fila in &self.filas {
+            let fila: Vec<String> = fila.iter().map(dato_to_string).collect();
+            contenido.push_str(&format!("{}\n", fila.join(",")));
+        }
+        if write!(archivo, "{}", contenido).is_err() {
+            eliminar_archivo(&path_aux)?;
+            return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+        }
+        Ok(path_aux)
+    }
+}
+
+impl Display for Valores {
+    /// Muestra las filas como se escriben en la consulta, `(VALUES (1, 'a'), (2, 'b'))`.
+    /// Los valores vacíos se muestran como `NULL`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let filas: Vec<String> = self
+            .filas
+            .iter()
+            .map(|fila| {
+                let valores: Vec<String> = fila
+                    .iter()
+                    .map(|dato| match dato {
+                        Datos::String(texto) if texto.is_empty() => "NULL".to_string(),
+                        Datos::String(texto) => format!("'{}'", texto),
+                        Datos::Integer(numero) => numero.to_string(),
+                    })
+                    .collect();
+                format!("({})", valores.join(", "))
+            })
+            .collect();
+        write!(f, "(VALUES {})", filas.join(", "))
+    }
+}
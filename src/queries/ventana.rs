@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{dato::Datos, errores::error::ErrorType, utils::dato_to_string};
+
+use super::order_clause::{OrderClause, OrderDirection};
+
+/// Funciones que se pueden calcular sobre una ventana con `OVER (...)`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum FuncionVentana {
+    RowNumber,
+    Rank,
+    DenseRank,
+    Lag,
+    Lead,
+    Sum,
+    Count,
+    Min,
+    Max,
+}
+
+impl FuncionVentana {
+    /// Devuelve la función con ese nombre, si es una función de ventana.
+    pub fn desde(nombre: &str) -> Option<FuncionVentana> {
+        match nombre {
+            "ROW_NUMBER" => Some(FuncionVentana::RowNumber),
+            "RANK" => Some(FuncionVentana::Rank),
+            "DENSE_RANK" => Some(FuncionVentana::DenseRank),
+            "LAG" => Some(FuncionVentana::Lag),
+            "LEAD" => Some(FuncionVentana::Lead),
+            "SUM" => Some(FuncionVentana::Sum),
+            "COUNT" => Some(FuncionVentana::Count),
+            "MIN" => Some(FuncionVentana::Min),
+            "MAX" => Some(FuncionVentana::Max),
+            _ => None,
+        }
+    }
+
+    /// Devuelve el nombre de la función como se escribe en la consulta.
+    pub fn nombre(&self) -> &str {
+        match self {
+            FuncionVentana::RowNumber => "ROW_NUMBER",
+            FuncionVentana::Rank => "RANK",
+            FuncionVentana::DenseRank => "DENSE_RANK",
+            FuncionVentana::Lag => "LAG",
+            FuncionVentana::Lead => "LEAD",
+            FuncionVentana::Sum => "SUM",
+            FuncionVentana::Count => "COUNT",
+            FuncionVentana::Min => "MIN",
+            FuncionVentana::Max => "MAX",
+        }
+    }
+
+    /// Indica si la función agrega los valores de un marco de filas, como `SUM`.
+    pub fn es_agregado(&self) -> bool {
+        matches!(
+            self,
+            FuncionVentana::Sum | FuncionVentana::Count | FuncionVentana::Min | FuncionVentana::Max
+        )
+    }
+}
+
+/// Límite de un marco `ROWS BETWEEN inicio AND fin`. `None` es `UNBOUNDED`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LimiteMarco {
+    Anterior(Option<usize>),
+    FilaActual,
+    Siguiente(Option<usize>),
+}
+
+impl LimiteMarco {
+    /// Devuelve la posición del límite dentro de una partición de `cantidad` filas, para la fila `actual`.
+    fn posicion(&self, actual: usize, cantidad: usize) -> i64 {
+        match self {
+            LimiteMarco::Anterior(None) => 0,
+            LimiteMarco::Anterior(Some(filas)) => actual as i64 - *filas as i64,
+            LimiteMarco::FilaActual => actual as i64,
+            LimiteMarco::Siguiente(Some(filas)) => (actual + filas) as i64,
+            LimiteMarco::Siguiente(None) => cantidad as i64 - 1,
+        }
+    }
+}
+
+impl Display for LimiteMarco {
+    /// Muestra el límite como se escribe en un `ROWS BETWEEN`.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LimiteMarco::Anterior(None) => write!(f, "UNBOUNDED PRECEDING"),
+            LimiteMarco::Anterior(Some(filas)) => write!(f, "{} PRECEDING", filas),
+            LimiteMarco::FilaActual => write!(f, "CURRENT ROW"),
+            LimiteMarco::Siguiente(Some(filas)) => write!(f, "{} FOLLOWING", filas),
+            LimiteMarco::Siguiente(None) => write!(f, "UNBOUNDED FOLLOWING"),
+        }
+    }
+}
+
+/// Representa una función de ventana del SELECT, como `ROW_NUMBER() OVER (PARTITION BY id_cliente ORDER BY id)`.
+/// Las filas se reparten en particiones según las columnas de `particion` y se ordenan dentro de cada una;
+/// los agregados como `SUM` se calculan sobre el marco de filas `ROWS BETWEEN ...` de cada fila.
+/// Sin marco, el agregado va desde el comienzo de la partición hasta la fila actual si hay ORDER BY,
+/// o abarca toda la partición si no lo hay.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ventana {
+    pub funcion: FuncionVentana,
+    /// La columna sobre la que se calcula la función, o `None` en `ROW_NUMBER()`, `RANK()`, `DENSE_RANK()` y `COUNT(*)`.
+    pub columna: Option<String>,
+    /// Cantidad de filas hacia atrás o hacia adelante de `LAG` y `LEAD`.
+    pub desplazamiento: usize,
+    /// Valor de `LAG` y `LEAD` cuando no existe la fila, vacío si no se indica.
+    pub defecto: Option<Datos>,
+    pub particion: Vec<String>,
+    pub orden: Vec<OrderClause>,
+    pub marco: Option<(LimiteMarco, LimiteMarco)>,
+}
+
+impl Ventana {
+    /// Crea una nueva instancia de `Ventana`, sin partición, orden ni marco.
+    pub fn new(funcion: FuncionVentana, columna: Option<String>) -> Self {
+        Ventana {
+            funcion,
+            columna,
+            desplazamiento: 1,
+            defecto: None,
+            particion: Vec::new(),
+            orden: Vec::new(),
+            marco: None,
+        }
+    }
+
+    /// Devuelve el nombre de la columna con el resultado de la función: su texto sin comas,
+    /// para que pueda ser parte del encabezado de un CSV.
+    pub fn nombre_columna(&self) -> String {
+        self.to_string().replace(',', "")
+    }
+
+    /// Devuelve la ventana con sus columnas reemplazadas por las columnas del encabezado a las que se refieren
+    /// según `resolver`. Verifica que todas existan.
+    pub fn resuelta(
+        &self,
+        columnas: &[String],
+        resolver: &dyn Fn(&str) -> Result<String, ErrorType>,
+    ) -> Result<Ventana, ErrorType> {
+        let resolver = |nombre: &String| {
+            let columna = resolver(nombre)?;
+            if !columnas.contains(&columna) {
+                return Err(ErrorType::InvalidColumn(format!(
+                    "La columna '{}' de la función {} no pertenece a la tabla",
+                    nombre,
+                    self.funcion.nombre()
+                )));
+            }
+            Ok(columna)
+        };
+        let mut resuelta = self.clone();
+        resuelta.columna = self.columna.as_ref().map(resolver).transpose()?;
+        resuelta.particion = self
+            .particion
+            .iter()
+            .map(resolver)
+            .collect::<Result<_, _>>()?;
+        for clause in &mut resuelta.orden {
+            clause.column = resolver(&clause.column)?;
+        }
+        Ok(resuelta)
+    }
+
+    /// Calcula el valor de la función para cada fila de una partición, que ya está ordenada.
+    pub fn calcular(&self, filas: &[HashMap<String, Datos>]) -> Result<Vec<Datos>, ErrorType> {
+        let vacio = Datos::String(String::new());
+        let mut valores = Vec::with_capacity(filas.len());
+        let mut rango = 0;
+        for (actual, fila) in filas.iter().enumerate() {
+            let empata = actual > 0 && self.empatan(&filas[actual - 1], fila);
+            let valor = match self.funcion {
+                FuncionVentana::RowNumber => Datos::Integer(actual as i64 + 1),
+                FuncionVentana::Rank => {
+                    if !empata {
+                        rango = actual + 1;
+                    }
+                    Datos::Integer(rango as i64)
+                }
+                FuncionVentana::DenseRank => {
+                    if !empata {
+                        rango += 1;
+                    }
+                    Datos::Integer(rango as i64)
+                }
+                FuncionVentana::Lag | FuncionVentana::Lead => {
+                    let otra = match self.funcion {
+                        FuncionVentana::Lag => actual.checked_sub(self.desplazamiento),
+                        _ => Some(actual + self.desplazamiento).filter(|&i| i < filas.len()),
+                    };
+                    match otra {
+                        Some(otra) => self.valor(&filas[otra]).unwrap_or_else(|| vacio.clone()),
+                        None => self.defecto.clone().unwrap_or_else(|| vacio.clone()),
+                    }
+                }
+                _ => self.agregar(filas, actual)?,
+            };
+            valores.push(valor);
+        }
+        Ok(valores)
+    }
+
+    /// Devuelve el valor de la columna de la función en la fila, o `None` si está vacío.
+    fn valor(&self, fila: &HashMap<String, Datos>) -> Option<Datos> {
+        match &self.columna {
+            Some(columna) => fila
+                .get(columna)
+                .filter(|dato| !dato_to_string(dato).is_empty())
+                .cloned(),
+            None => Some(Datos::Integer(1)),
+        }
+    }
+
+    /// Indica si dos filas tienen los mismos valores en las columnas del ORDER BY de la ventana.
+    fn empatan(&self, fila_1: &HashMap<String, Datos>, fila_2: &HashMap<String, Datos>) -> bool {
+        self.orden
+            .iter()
+            .all(|clause| fila_1.get(&clause.column) == fila_2.get(&clause.column))
+    }
+
+    /// Calcula el agregado sobre las filas del marco de la fila `actual`. Los valores vacíos no se agregan.
+    fn agregar(&self, filas: &[HashMap<String, Datos>], actual: usize) -> Result<Datos, ErrorType> {
+        let (inicio, fin) = match &self.marco {
+            Some(marco) => marco.clone(),
+            None if self.orden.is_empty() => {
+                (LimiteMarco::Anterior(None), LimiteMarco::Siguiente(None))
+            }
+            None => (LimiteMarco::Anterior(None), LimiteMarco::FilaActual),
+        };
+        let inicio = inicio.posicion(actual, filas.len()).max(0) as usize;
+        let fin = fin
+            .posicion(actual, filas.len())
+            .min(filas.len() as i64 - 1);
+        let marco = if fin < inicio as i64 {
+            &filas[0..0]
+        } else {
+            &filas[inicio..=fin as usize]
+        };
+        let valores = marco.iter().filter_map(|fila| self.valor(fila));
+        let resultado = match self.funcion {
+            FuncionVentana::Count => Some(Datos::Integer(valores.count() as i64)),
+            FuncionVentana::Min => valores.min(),
+            FuncionVentana::Max => valores.max(),
+            _ => {
+                let mut suma = None;
+                for valor in valores {
+                    let Datos::Integer(numero) = valor else {
+                        return Err(ErrorType::InvalidColumn(format!(
+                            "SUM solo se puede calcular sobre valores enteros, y '{}' no lo es",
+                            dato_to_string(&valor)
+                        )));
+                    };
+                    suma = Some(suma.unwrap_or(0) + numero);
+                }
+                suma.map(Datos::Integer)
+            }
+        };
+        Ok(resultado.unwrap_or(Datos::String(String::new())))
+    }
+}
+
+impl Display for Ventana {
+    /// Muestra la función de ventana con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut argumentos: Vec<String> = self.columna.iter().cloned().collect();
+        if self.funcion == FuncionVentana::Count && self.columna.is_none() {
+            argumentos.push("*".to_string());
+        }
+        if self.funcion == FuncionVentana::Lag || self.funcion == FuncionVentana::Lead {
+            if self.desplazamiento != 1 || self.defecto.is_some() {
+                argumentos.push(self.desplazamiento.to_string());
+            }
+            match &self.defecto {
+                Some(Datos::String(texto)) => argumentos.push(format!("'{}'", texto)),
+                Some(Datos::Integer(numero)) => argumentos.push(numero.to_string()),
+                None => {}
+            }
+        }
+        write!(
+            f,
+            "{}({}) OVER (",
+            self.funcion.nombre(),
+            argumentos.join(", ")
+        )?;
+        let mut partes = Vec::new();
+        if !self.particion.is_empty() {
+            partes.push(format!("PARTITION BY {}", self.particion.join(", ")));
+        }
+        if !self.orden.is_empty() {
+            let orden: Vec<String> = self
+                .orden
+                .iter()
+                .map(|clause| {
+                    let columna = match clause.direccion {
+                        OrderDirection::Asc => clause.column.to_string(),
+                        OrderDirection::Desc => format!("{} DESC", clause.column),
+                    };
+                    match &clause.nulos {
+                        Some(nulos) => format!("{} {}", columna, nulos),
+                        None => columna,
+                    }
+                })
+                .collect();
+            partes.push(format!("ORDER BY {}", orden.join(", ")));
+        }
+        if let Some((inicio, fin)) = &self.marco {
+            partes.push(format!("ROWS BETWEEN {} AND {}", inicio, fin));
+        }
+        write!(f, "{})", partes.join(" "))
+    }
+}
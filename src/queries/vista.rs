@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::manejo_csv::agregar_path,
+    lexers::lexer::lexer,
+    parsers::parser::parser,
+    plan::estadisticas::{es_catalogo, escribir_catalogo, CATALOGO_ESTADISTICAS},
+};
+
+use super::{
+    select_query::RenombrarTabla,
+    sql_query::SQLQuery,
+    vista_materializada::{cargar_materializada, es_materializada, verificar_materializada},
+    with_query::{reemplazo, Consulta, Cte},
+};
+
+/// Función que aplica a una consulta la función que recibe, con cada tabla que referencia.
+pub type VisitarTablas<'a> = dyn FnMut(&mut RenombrarTabla) + 'a;
+
+/// Genera la ruta del archivo de una vista, que se guarda en la carpeta de las tablas con extensión `.vista`.
+pub fn ruta_vista(path: &str, nombre: &str) -> String {
+    format!("{}/{}.vista", path, nombre)
+}
+
+/// Devuelve si el nombre es el de una vista: tiene archivo `.vista` y no hay una tabla con ese nombre.
+pub fn es_vista(path: &str, nombre: &String) -> bool {
+    !Path::new(&agregar_path(path, nombre)).exists()
+        && Path::new(&ruta_vista(path, nombre)).exists()
+}
+
+/// Lee la consulta de la vista desde su archivo `.vista`, que contiene la sentencia CREATE VIEW.
+pub fn cargar_vista(path: &str, nombre: &str) -> Result<Consulta, ErrorType> {
+    let Ok(texto) = fs::read_to_string(ruta_vista(path, nombre)) else {
+        return Err(ErrorType::InvalidTable(format!(
+            "Error al leer la vista '{}'",
+            nombre
+        )));
+    };
+    let texto = texto.trim().trim_end_matches(';').replace('\n', " ");
+    match parser(&lexer(&texto)?)? {
+        SQLQuery::CreateView(query) => Ok(query.consulta),
+        _ => Err(ErrorType::InvalidTable(format!(
+            "El archivo de la vista '{}' no contiene un CREATE VIEW",
+            nombre
+        ))),
+    }
+}
+
+/// Verifica que las tablas que usa la consulta de una vista existan, como tablas o como otras vistas,
+/// y que la consulta no use la vista que se está creando.
+pub fn validar_tablas(path: &str, nombre: &str, consulta: &Consulta) -> Result<(), ErrorType> {
+    let mut faltantes = Vec::new();
+    consulta.clone().renombrar_tablas(&mut |tabla, _| {
+        let existe = Path::new(&agregar_path(path, tabla)).exists()
+            || es_vista(path, tabla)
+            || es_catalogo(path, tabla);
+        if *tabla == nombre || !existe {
+            faltantes.push(tabla.to_string());
+        }
+    });
+    match faltantes.first() {
+        Some(tabla) if *tabla == nombre => Err(ErrorType::InvalidTable(format!(
+            "La vista '{}' no puede referenciarse a sí misma",
+            nombre
+        ))),
+        Some(tabla) => Err(ErrorType::InvalidTable(format!(
+            "La tabla '{}' no existe",
+            tabla
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Devuelve las vistas de la carpeta, comunes o materializadas, cuya consulta usa la vista `nombre`,
+/// ordenadas por nombre.
+pub fn vistas_dependientes(path: &str, nombre: &str) -> Result<Vec<String>, ErrorType> {
+    let Ok(entradas) = fs::read_dir(path) else {
+        return Err(ErrorType::InvalidTable(
+            "Error al leer la carpeta de las tablas".to_string(),
+        ));
+    };
+    let mut vistas: Vec<String> = entradas
+        .filter_map(|entrada| entrada.ok())
+        .filter_map(|entrada| {
+            let archivo = entrada.file_name().to_string_lossy().to_string();
+            archivo.strip_suffix(".vista").map(|v| v.to_string())
+        })
+        .filter(|vista| vista != nombre)
+        .collect();
+    vistas.sort();
+    let mut dependientes = Vec::new();
+    for vista in vistas {
+        let consulta = if es_materializada(path, &vista) {
+            cargar_materializada(path, &vista)?.consulta
+        } else {
+            cargar_vista(path, &vista)?
+        };
+        let mut usa = false;
+        consulta
+            .clone()
+            .renombrar_tablas(&mut |tabla, _| usa |= tabla == nombre);
+        if usa {
+            dependientes.push(vista);
+        }
+    }
+    Ok(dependientes)
+}
+
+/// Ejecuta cada vista referenciada por la consulta que recorre `visitar`, una única vez aunque se referencie
+/// varias veces, y reemplaza sus referencias por el archivo auxiliar con su resultado.
+/// Las vistas pueden usar otras vistas. En `tablas` quedan los archivos auxiliares de cada vista ejecutada.
+/// Las vistas materializadas se usan como tablas, verificando antes si sus tablas cambiaron.
+/// El catálogo de estadísticas también se reemplaza por un archivo auxiliar con su contenido.
+pub fn materializar_vistas(
+    path: &str,
+    visitar: &mut VisitarTablas,
+    tablas: &mut HashMap<String, String>,
+) -> Result<(), ErrorType> {
+    materializar_vistas_rec(path, visitar, tablas, &mut Vec::new())
+}
+
+/// Materializa las vistas referenciadas, llevando las vistas que se están ejecutando para detectar ciclos.
+fn materializar_vistas_rec(
+    path: &str,
+    visitar: &mut VisitarTablas,
+    tablas: &mut HashMap<String, String>,
+    en_proceso: &mut Vec<String>,
+) -> Result<(), ErrorType> {
+    let mut vistas: Vec<String> = Vec::new();
+    let mut materializadas: Vec<String> = Vec::new();
+    let mut catalogo = false;
+    visitar(&mut |tabla, _| {
+        if !tablas.contains_key(tabla) && !vistas.contains(tabla) && es_vista(path, tabla) {
+            vistas.push(tabla.to_string());
+        } else if !materializadas.contains(tabla) && es_materializada(path, tabla) {
+            materializadas.push(tabla.to_string());
+        } else if !tablas.contains_key(tabla) && es_catalogo(path, tabla) {
+            catalogo = true;
+        }
+    });
+    for nombre in materializadas {
+        verificar_materializada(path, &nombre)?;
+    }
+    if catalogo {
+        tablas.insert(CATALOGO_ESTADISTICAS.to_string(), escribir_catalogo(path)?);
+    }
+    for nombre in vistas {
+        if en_proceso.contains(&nombre) {
+            return Err(ErrorType::InvalidTable(format!(
+                "La vista '{}' se referencia a sí misma",
+                nombre
+            )));
+        }
+        en_proceso.push(nombre.to_string());
+        let mut consulta = cargar_vista(path, &nombre)?;
+        materializar_vistas_rec(
+            path,
+            &mut |renombrar| consulta.renombrar_tablas(renombrar),
+            tablas,
+            en_proceso,
+        )?;
+        let nombre_aux = Cte::new(&nombre, None, consulta).materializar(path)?;
+        en_proceso.pop();
+        tablas.insert(nombre, nombre_aux);
+    }
+    visitar(&mut reemplazo(tablas));
+    Ok(())
+}
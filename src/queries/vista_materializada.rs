@@ -0,0 +1,159 @@
+use std::{collections::HashMap, fs, path::Path, time::UNIX_EPOCH};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::manejo_csv::{agregar_path, eliminar_archivo, reemplazar_archivo},
+    lexers::lexer::lexer,
+    parsers::parser::parser,
+};
+
+use super::{
+    create_materialized_view_query::CreateMaterializedViewQuery,
+    sql_query::SQLQuery,
+    vista::{cargar_vista, es_vista, materializar_vistas, ruta_vista},
+    with_query::{Consulta, Cte},
+};
+
+/// Genera la ruta del archivo donde se guardan las tablas de las que sale una vista materializada,
+/// con la fecha de modificación que tenían en el último REFRESH.
+pub fn ruta_fuentes(path: &str, nombre: &str) -> String {
+    format!("{}/{}.fuentes", path, nombre)
+}
+
+/// Devuelve si el nombre es el de una vista materializada: tiene archivo `.fuentes`.
+pub fn es_materializada(path: &str, nombre: &str) -> bool {
+    Path::new(&ruta_fuentes(path, nombre)).exists()
+}
+
+/// Lee la sentencia CREATE MATERIALIZED VIEW de la vista desde su archivo `.vista`.
+pub fn cargar_materializada(
+    path: &str,
+    nombre: &str,
+) -> Result<CreateMaterializedViewQuery, ErrorType> {
+    let Ok(texto) = fs::read_to_string(ruta_vista(path, nombre)) else {
+        return Err(ErrorType::InvalidTable(format!(
+            "Error al leer la vista materializada '{}'",
+            nombre
+        )));
+    };
+    let texto = texto.trim().trim_end_matches(';').replace('\n', " ");
+    match parser(&lexer(&texto)?)? {
+        SQLQuery::CreateMaterializedView(query) => Ok(query),
+        _ => Err(ErrorType::InvalidTable(format!(
+            "El archivo de la vista '{}' no contiene un CREATE MATERIALIZED VIEW",
+            nombre
+        ))),
+    }
+}
+
+/// Devuelve las tablas de las que lee la consulta, reemplazando las vistas por las tablas que usan.
+fn tablas_fuente(path: &str, consulta: &Consulta) -> Result<Vec<String>, ErrorType> {
+    let mut tablas = Vec::new();
+    tablas_fuente_rec(path, consulta, &mut tablas, &mut Vec::new())?;
+    Ok(tablas)
+}
+
+/// Agrega a `tablas` las tablas de la consulta, llevando las vistas ya recorridas en `vistas`.
+fn tablas_fuente_rec(
+    path: &str,
+    consulta: &Consulta,
+    tablas: &mut Vec<String>,
+    vistas: &mut Vec<String>,
+) -> Result<(), ErrorType> {
+    let mut referenciadas = Vec::new();
+    consulta
+        .clone()
+        .renombrar_tablas(&mut |tabla, _| referenciadas.push(tabla.to_string()));
+    for tabla in referenciadas {
+        if es_vista(path, &tabla) {
+            if !vistas.contains(&tabla) {
+                vistas.push(tabla.to_string());
+                tablas_fuente_rec(path, &cargar_vista(path, &tabla)?, tablas, vistas)?;
+            }
+        } else if !tablas.contains(&tabla) {
+            tablas.push(tabla);
+        }
+    }
+    Ok(())
+}
+
+/// Devuelve la fecha de modificación del archivo de la tabla en nanosegundos, o vacío si no existe.
+fn modificacion(path: &str, tabla: &String) -> String {
+    fs::metadata(agregar_path(path, tabla))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|fecha| fecha.duration_since(UNIX_EPOCH).ok())
+        .map(|duracion| duracion.as_nanos().to_string())
+        .unwrap_or_default()
+}
+
+/// Indica si alguna de las tablas de la vista materializada se modificó desde el último REFRESH.
+fn cambiaron_fuentes(path: &str, nombre: &str) -> Result<bool, ErrorType> {
+    let Ok(contenido) = fs::read_to_string(ruta_fuentes(path, nombre)) else {
+        return Err(ErrorType::InvalidTable(format!(
+            "Error al leer las tablas de la vista materializada '{}'",
+            nombre
+        )));
+    };
+    for linea in contenido.lines().filter(|l| !l.is_empty()) {
+        let Some((tabla, fecha)) = linea.split_once(',') else {
+            return Err(ErrorType::InvalidTable(format!(
+                "El archivo de tablas de la vista materializada '{}' es invalido",
+                nombre
+            )));
+        };
+        if modificacion(path, &tabla.to_string()) != fecha {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Vuelve a ejecutar la consulta de la vista materializada, reemplaza su archivo con el resultado
+/// y guarda la fecha de modificación que tenían sus tablas antes de ejecutarla.
+pub fn refrescar(path: &str, vista: &CreateMaterializedViewQuery) -> Result<(), ErrorType> {
+    let fuentes: Vec<String> = tablas_fuente(path, &vista.consulta)?
+        .iter()
+        .map(|tabla| format!("{},{}\n", tabla, modificacion(path, tabla)))
+        .collect();
+    let mut consulta = vista.consulta.clone();
+    let mut vistas = HashMap::new();
+    let materializadas = materializar_vistas(
+        path,
+        &mut |renombrar| consulta.renombrar_tablas(renombrar),
+        &mut vistas,
+    );
+    let resultado =
+        materializadas.and_then(|_| Cte::new(&vista.nombre, None, consulta).materializar(path));
+    for nombre_aux in vistas.values() {
+        eliminar_archivo(&agregar_path(path, nombre_aux))?;
+    }
+    let nombre_aux = resultado?;
+    let ruta_aux = agregar_path(path, &nombre_aux);
+    let reemplazado = reemplazar_archivo(&ruta_aux, &agregar_path(path, &vista.nombre));
+    eliminar_archivo(&ruta_aux)?;
+    reemplazado?;
+    match fs::write(ruta_fuentes(path, &vista.nombre), fuentes.concat()) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ErrorType::InvalidTable(
+            "Error al guardar las tablas de la vista materializada".to_string(),
+        )),
+    }
+}
+
+/// Verifica si las tablas de la vista materializada cambiaron desde el último REFRESH. Si la vista es
+/// `AUTO REFRESH` la vuelve a ejecutar; si no, advierte por STDERR que sus datos pueden estar desactualizados.
+pub fn verificar_materializada(path: &str, nombre: &str) -> Result<(), ErrorType> {
+    if !cambiaron_fuentes(path, nombre)? {
+        return Ok(());
+    }
+    let vista = cargar_materializada(path, nombre)?;
+    if vista.automatica {
+        return refrescar(path, &vista);
+    }
+    eprintln!(
+        "Advertencia: las tablas de la vista materializada '{}' cambiaron desde el último REFRESH",
+        nombre
+    );
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::{expresion_booleana::ExpresionBooleana, valor::Valor};
+
+/// Representa una expresión `CASE WHEN condición THEN valor ... ELSE valor END`.
+/// La forma simple `CASE operando WHEN valor THEN ...` se guarda con una comparación de igualdad en cada rama.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Case {
+    pub ramas: Vec<(ExpresionBooleana, Valor)>,
+    pub sino: Option<Valor>,
+}
+
+impl Case {
+    /// Crea una nueva instancia de `Case`.
+    pub fn new(ramas: Vec<(ExpresionBooleana, Valor)>, sino: Option<Valor>) -> Self {
+        Case { ramas, sino }
+    }
+}
+
+impl Display for Case {
+    /// Muestra la expresión con la sintaxis SQL de la forma con condiciones.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CASE")?;
+        for (condicion, valor) in &self.ramas {
+            write!(f, " WHEN {} THEN {}", condicion, valor)?;
+        }
+        if let Some(sino) = &self.sino {
+            write!(f, " ELSE {}", sino)?;
+        }
+        write!(f, " END")
+    }
+}
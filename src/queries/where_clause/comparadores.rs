@@ -3,12 +3,15 @@ use std::collections::HashMap;
 use crate::{dato::Datos, errores::error::ErrorType};
 
 use super::{
-    evaluar::Evaluar, expresion_booleana::ExpresionBooleana,
-    operador_comparacion::OperadorComparacion, valor::Valor,
+    evaluar::Evaluar,
+    expresion_booleana::ExpresionBooleana,
+    funciones::{evaluar_funcion, evaluar_operacion},
+    operador_comparacion::OperadorComparacion,
+    valor::Valor,
 };
 
 /// Evalúa el valor de una expresión booleano.
-fn evaluar_valor(valor: &Valor, fila: &HashMap<String, Datos>) -> Result<Datos, ErrorType> {
+pub fn evaluar_valor(valor: &Valor, fila: &HashMap<String, Datos>) -> Result<Datos, ErrorType> {
     match valor {
         Valor::String(s) => {
             if let Some(dato) = fila.get(s) {
@@ -26,6 +29,34 @@ fn evaluar_valor(valor: &Valor, fila: &HashMap<String, Datos>) -> Result<Datos,
             }
         }
         Valor::Literal(lit) => Ok(Datos::String(lit.to_string())),
+        Valor::Funcion { nombre, argumentos } => {
+            let mut datos = Vec::new();
+            for argumento in argumentos {
+                datos.push(evaluar_valor(argumento, fila)?);
+            }
+            evaluar_funcion(nombre, &datos)
+        }
+        Valor::Subconsulta(subconsulta) => subconsulta.escalar(fila),
+        Valor::Case(case) => {
+            for (condicion, valor) in &case.ramas {
+                if condicion.evaluar(fila)? {
+                    return evaluar_valor(valor, fila);
+                }
+            }
+            match &case.sino {
+                Some(sino) => evaluar_valor(sino, fila),
+                None => Ok(Datos::String("".to_string())),
+            }
+        }
+        Valor::Operacion { izq, operador, der } => evaluar_operacion(
+            &evaluar_valor(izq, fila)?,
+            operador,
+            &evaluar_valor(der, fila)?,
+        ),
+        Valor::Parametro(numero) => Err(ErrorType::InvalidSyntax(format!(
+            "Falta el valor del parámetro ${}",
+            numero
+        ))),
     }
 }
 
@@ -44,6 +75,10 @@ impl Evaluar for ExpresionBooleana {
                     OperadorComparacion::MayorIgual => Ok(valor_izq >= valor_der),
                 }
             }
+            ExpresionBooleana::In { valor, subconsulta } => {
+                subconsulta.contiene(&evaluar_valor(valor, fila)?, fila)
+            }
+            ExpresionBooleana::Exists(subconsulta) => subconsulta.existe(fila),
             ExpresionBooleana::And(expr1, expr2) => {
                 Ok(expr1.evaluar(fila)? && expr2.evaluar(fila)?)
             }
@@ -1,14 +1,128 @@
-use super::{operador_comparacion::OperadorComparacion, valor::Valor};
+use std::fmt::{self, Display, Formatter};
+
+use crate::queries::select_query::RenombrarTabla;
+
+use super::{
+    operador_comparacion::OperadorComparacion,
+    subconsulta::Subconsulta,
+    valor::{agregar_columna, Valor, VisitarValor},
+};
 
 /// Enum para representar diferentes tipos de expresiones booleanas.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum ExpresionBooleana {
     Comparacion {
         izq: Valor,
         operador: OperadorComparacion,
         der: Valor,
     },
+    In {
+        valor: Valor,
+        subconsulta: Subconsulta,
+    },
+    Exists(Subconsulta),
     And(Box<ExpresionBooleana>, Box<ExpresionBooleana>),
     Or(Box<ExpresionBooleana>, Box<ExpresionBooleana>),
     Not(Box<ExpresionBooleana>),
 }
+
+impl ExpresionBooleana {
+    /// Devuelve si la expresión contiene alguna subconsulta.
+    pub fn tiene_subconsultas(&self) -> bool {
+        match self {
+            ExpresionBooleana::Comparacion { izq, der, .. } => {
+                izq.tiene_subconsultas() || der.tiene_subconsultas()
+            }
+            ExpresionBooleana::In { .. } | ExpresionBooleana::Exists(_) => true,
+            ExpresionBooleana::And(expr1, expr2) | ExpresionBooleana::Or(expr1, expr2) => {
+                expr1.tiene_subconsultas() || expr2.tiene_subconsultas()
+            }
+            ExpresionBooleana::Not(expr) => expr.tiene_subconsultas(),
+        }
+    }
+
+    /// Devuelve los nombres de las columnas que usa la expresión, sin repetir, también dentro de sus subconsultas.
+    pub fn columnas_referenciadas(&self) -> Vec<String> {
+        let mut columnas = Vec::new();
+        self.clone()
+            .visitar_valores(&mut |valor| agregar_columna(valor, &mut columnas));
+        columnas
+    }
+
+    /// Aplica `renombrar` a cada tabla referenciada por las subconsultas de la expresión.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        match self {
+            ExpresionBooleana::Comparacion { izq, der, .. } => {
+                izq.renombrar_tablas(renombrar);
+                der.renombrar_tablas(renombrar);
+            }
+            ExpresionBooleana::In { valor, subconsulta } => {
+                valor.renombrar_tablas(renombrar);
+                subconsulta.renombrar_tablas(renombrar);
+            }
+            ExpresionBooleana::Exists(subconsulta) => subconsulta.renombrar_tablas(renombrar),
+            ExpresionBooleana::And(expr1, expr2) | ExpresionBooleana::Or(expr1, expr2) => {
+                expr1.renombrar_tablas(renombrar);
+                expr2.renombrar_tablas(renombrar);
+            }
+            ExpresionBooleana::Not(expr) => expr.renombrar_tablas(renombrar),
+        }
+    }
+
+    /// Aplica `visitar` a cada valor de la expresión, también dentro de sus subconsultas.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        match self {
+            ExpresionBooleana::Comparacion { izq, der, .. } => {
+                izq.visitar_valores(visitar);
+                der.visitar_valores(visitar);
+            }
+            ExpresionBooleana::In { valor, subconsulta } => {
+                valor.visitar_valores(visitar);
+                subconsulta.visitar_valores(visitar);
+            }
+            ExpresionBooleana::Exists(subconsulta) => subconsulta.visitar_valores(visitar),
+            ExpresionBooleana::And(expr1, expr2) | ExpresionBooleana::Or(expr1, expr2) => {
+                expr1.visitar_valores(visitar);
+                expr2.visitar_valores(visitar);
+            }
+            ExpresionBooleana::Not(expr) => expr.visitar_valores(visitar),
+        }
+    }
+}
+
+/// Muestra una subexpresión, entre paréntesis si combina otras con AND u OR.
+fn fmt_operando(expresion: &ExpresionBooleana, f: &mut Formatter) -> fmt::Result {
+    match expresion {
+        ExpresionBooleana::And(_, _) | ExpresionBooleana::Or(_, _) => write!(f, "({})", expresion),
+        _ => write!(f, "{}", expresion),
+    }
+}
+
+impl Display for ExpresionBooleana {
+    /// Muestra la expresión con la sintaxis SQL con la que se escribe en un WHERE.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ExpresionBooleana::Comparacion { izq, operador, der } => {
+                write!(f, "{} {} {}", izq, operador, der)
+            }
+            ExpresionBooleana::In { valor, subconsulta } => {
+                write!(f, "{} IN {}", valor, subconsulta)
+            }
+            ExpresionBooleana::Exists(subconsulta) => write!(f, "EXISTS {}", subconsulta),
+            ExpresionBooleana::And(expr1, expr2) => {
+                fmt_operando(expr1, f)?;
+                write!(f, " AND ")?;
+                fmt_operando(expr2, f)
+            }
+            ExpresionBooleana::Or(expr1, expr2) => {
+                fmt_operando(expr1, f)?;
+                write!(f, " OR ")?;
+                fmt_operando(expr2, f)
+            }
+            ExpresionBooleana::Not(expr) => {
+                write!(f, "NOT ")?;
+                fmt_operando(expr, f)
+            }
+        }
+    }
+}
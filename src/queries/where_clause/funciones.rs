@@ -0,0 +1,123 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{dato::Datos, errores::error::ErrorType, utils::dato_to_string};
+
+use super::valor::OperadorAritmetico;
+
+/// Nombres de las funciones que se escriben sin paréntesis ni argumentos, como `CURRENT_DATE`.
+const FUNCIONES_SIN_ARGUMENTOS: [&str; 2] = ["CURRENT_DATE", "CURRENT_TIMESTAMP"];
+
+/// Nombres de las funciones escalares que reciben un argumento entre paréntesis, como `UPPER(nombre)`.
+const FUNCIONES_ESCALARES: [&str; 3] = ["UPPER", "LOWER", "LENGTH"];
+
+/// Verifica si la palabra es una función que se escribe sin argumentos.
+pub fn es_funcion_sin_argumentos(nombre: &str) -> bool {
+    FUNCIONES_SIN_ARGUMENTOS.contains(&nombre)
+}
+
+/// Verifica si la palabra es una función escalar que recibe sus argumentos entre paréntesis.
+pub fn es_funcion_escalar(nombre: &str) -> bool {
+    FUNCIONES_ESCALARES.contains(&nombre)
+}
+
+/// Convierte una cantidad de días desde 1970-01-01 en una fecha (año, mes, día) del calendario gregoriano.
+fn fecha_desde_dias(dias: i64) -> (i64, i64, i64) {
+    let z = dias + 719468;
+    let era = z.div_euclid(146097);
+    let dia_era = z.rem_euclid(146097);
+    let anio_era = (dia_era - dia_era / 1460 + dia_era / 36524 - dia_era / 146096) / 365;
+    let dia_anio = dia_era - (365 * anio_era + anio_era / 4 - anio_era / 100);
+    let mes_marzo = (5 * dia_anio + 2) / 153;
+    let dia = dia_anio - (153 * mes_marzo + 2) / 5 + 1;
+    let mes = if mes_marzo < 10 {
+        mes_marzo + 3
+    } else {
+        mes_marzo - 9
+    };
+    let anio = anio_era + era * 400 + if mes <= 2 { 1 } else { 0 };
+    (anio, mes, dia)
+}
+
+/// Devuelve los segundos transcurridos desde 1970-01-01 en UTC.
+fn segundos_actuales() -> Result<i64, ErrorType> {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duracion) => Ok(duracion.as_secs() as i64),
+        Err(_) => Err(ErrorType::Error(
+            "Error al obtener la fecha actual".to_string(),
+        )),
+    }
+}
+
+/// Devuelve la fecha actual en UTC con el formato `AAAA-MM-DD`.
+fn fecha_actual() -> Result<String, ErrorType> {
+    let (anio, mes, dia) = fecha_desde_dias(segundos_actuales()?.div_euclid(86400));
+    Ok(format!("{:04}-{:02}-{:02}", anio, mes, dia))
+}
+
+/// Devuelve la fecha y hora actual en UTC con el formato `AAAA-MM-DD HH:MM:SS`.
+fn fecha_hora_actual() -> Result<String, ErrorType> {
+    let segundos = segundos_actuales()?.rem_euclid(86400);
+    Ok(format!(
+        "{} {:02}:{:02}:{:02}",
+        fecha_actual()?,
+        segundos / 3600,
+        segundos % 3600 / 60,
+        segundos % 60
+    ))
+}
+
+/// Evalúa una función con los argumentos ya evaluados.
+pub fn evaluar_funcion(nombre: &str, argumentos: &[Datos]) -> Result<Datos, ErrorType> {
+    match (nombre, argumentos) {
+        ("CURRENT_DATE", []) => Ok(Datos::String(fecha_actual()?)),
+        ("CURRENT_TIMESTAMP", []) => Ok(Datos::String(fecha_hora_actual()?)),
+        ("UPPER", [dato]) => Ok(Datos::String(dato_to_string(dato).to_uppercase())),
+        ("LOWER", [dato]) => Ok(Datos::String(dato_to_string(dato).to_lowercase())),
+        ("LENGTH", [Datos::String(texto)]) if texto.is_empty() => Ok(Datos::String(String::new())),
+        ("LENGTH", [dato]) => Ok(Datos::Integer(dato_to_string(dato).chars().count() as i64)),
+        (funcion, _) if es_funcion_escalar(funcion) => Err(ErrorType::InvalidSyntax(format!(
+            "La función {} recibe un único argumento",
+            nombre
+        ))),
+        _ => Err(ErrorType::InvalidSyntax(format!(
+            "Función invalida: {}",
+            nombre
+        ))),
+    }
+}
+
+/// Calcula una operación aritmética entre dos enteros. Si alguno de los valores es vacío, el resultado es vacío.
+pub fn evaluar_operacion(
+    izq: &Datos,
+    operador: &OperadorAritmetico,
+    der: &Datos,
+) -> Result<Datos, ErrorType> {
+    let (izq, der) = match (izq, der) {
+        (Datos::String(vacio), _) | (_, Datos::String(vacio)) if vacio.is_empty() => {
+            return Ok(Datos::String(String::new()))
+        }
+        (Datos::Integer(izq), Datos::Integer(der)) => (*izq, *der),
+        (Datos::String(texto), _) | (_, Datos::String(texto)) => {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "La operación '{}' solo se puede calcular sobre valores enteros, y '{}' no lo es",
+                operador, texto
+            )))
+        }
+    };
+    let resultado = match operador {
+        OperadorAritmetico::Division if der == 0 => {
+            return Err(ErrorType::Error("División por cero".to_string()))
+        }
+        OperadorAritmetico::Suma => izq.checked_add(der),
+        OperadorAritmetico::Resta => izq.checked_sub(der),
+        OperadorAritmetico::Multiplicacion => izq.checked_mul(der),
+        OperadorAritmetico::Division => izq.checked_div(der),
+    };
+    match resultado {
+        Some(resultado) => Ok(Datos::Integer(resultado)),
+        None => Err(ErrorType::Error(format!(
+            "El resultado de {} {} {} es demasiado grande",
+            izq, operador, der
+        ))),
+    }
+}
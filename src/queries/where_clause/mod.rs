@@ -1,5 +1,8 @@
+pub mod case;
 pub mod comparadores;
 pub mod evaluar;
 pub mod expresion_booleana;
+pub mod funciones;
 pub mod operador_comparacion;
+pub mod subconsulta;
 pub mod valor;
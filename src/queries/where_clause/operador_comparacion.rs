@@ -1,5 +1,7 @@
+use std::fmt::{self, Display, Formatter};
+
 /// Enum para representar operadores de comparación.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum OperadorComparacion {
     Igual,
     Menor,
@@ -7,3 +9,16 @@ pub enum OperadorComparacion {
     Mayor,
     MayorIgual,
 }
+
+impl Display for OperadorComparacion {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let operador = match self {
+            OperadorComparacion::Igual => "=",
+            OperadorComparacion::Menor => "<",
+            OperadorComparacion::MenorIgual => "<=",
+            OperadorComparacion::Mayor => ">",
+            OperadorComparacion::MayorIgual => ">=",
+        };
+        write!(f, "{}", operador)
+    }
+}
@@ -0,0 +1,476 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::BufRead,
+};
+
+use crate::{
+    dato::Datos,
+    errores::error::ErrorType,
+    executer::manejo_csv::{
+        agregar_path, eliminar_archivo, get_reader, leer_encabezado, string_to_columns,
+        where_condition,
+    },
+    queries::{
+        conteo::verificar_columnas,
+        select_query::{RenombrarTabla, SelectQuery},
+    },
+    utils::dato_to_string,
+};
+
+use super::{
+    case::Case,
+    comparadores::evaluar_valor,
+    expresion_booleana::ExpresionBooleana,
+    valor::{Valor, VisitarValor},
+};
+
+/// Representa una subconsulta usada dentro de una condición: `IN (SELECT ...)`, `EXISTS (SELECT ...)`
+/// o un valor escalar en una comparación.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Subconsulta {
+    /// La subconsulta tal como se escribió, antes de preparar la condición.
+    Consulta(Box<SelectQuery>),
+    /// Los valores de la primera columna del resultado de una subconsulta que no referencia a la fila externa,
+    /// ordenados. Se calculan una única vez al preparar la condición.
+    Resuelta(Vec<Datos>),
+    /// Una subconsulta que referencia columnas de la fila externa, por lo que se ejecuta en cada fila.
+    /// Guarda la carpeta de tablas y la tabla externa con sus columnas.
+    Correlacionada {
+        select: Box<SelectQuery>,
+        path: String,
+        tabla: String,
+        columnas: Vec<String>,
+    },
+}
+
+/// Devuelve si el nombre es una columna de la tabla, con o sin el nombre de la tabla adelante.
+/// Con JOIN, las columnas ya están calificadas y el nombre puede referirse a cualquiera de ellas.
+fn es_columna_de(nombre: &str, tabla: &str, columnas: &[String]) -> bool {
+    if columnas.iter().any(|c| c == nombre) {
+        return true;
+    }
+    let columna = match nombre.split_once('.') {
+        Some((prefijo, columna)) if prefijo == tabla => columna,
+        Some(_) => return false,
+        None => nombre,
+    };
+    let sufijo = format!(".{}", columna);
+    columnas
+        .iter()
+        .any(|c| c == columna || c.ends_with(&sufijo))
+}
+
+/// Agrega a la fila cada columna de la tabla también con el nombre de la tabla adelante, como `tabla.columna`.
+pub fn calificar_columnas(fila: &mut HashMap<String, Datos>, tabla: &str, columnas: &[String]) {
+    for columna in columnas {
+        if let Some(dato) = fila.get(columna).cloned() {
+            fila.insert(format!("{}.{}", tabla, columna), dato);
+        }
+    }
+}
+
+/// Ejecuta el SELECT completo de una subconsulta con LIMIT, DISTINCT, COUNT o JOIN, respetando su ORDER BY,
+/// y devuelve el valor de la primera columna del resultado de cada fila.
+fn ejecutar_materializada(select: &SelectQuery, path: &str) -> Result<Vec<Datos>, ErrorType> {
+    let (path_aux, _, posiciones) = select.materializar(path)?;
+    let valores = get_reader(&path_aux).and_then(|reader| {
+        let mut lines = reader.lines();
+        let columnas = leer_encabezado(&mut lines)?;
+        let columna = &columnas[posiciones[0]];
+        let mut valores = Vec::new();
+        for line in lines {
+            let Ok(line) = line else {
+                return Err(ErrorType::InvalidTable(
+                    "Error al leer una linea".to_string(),
+                ));
+            };
+            if let Some(dato) = string_to_columns(&line, &columnas)?.remove(columna) {
+                valores.push(dato);
+            }
+        }
+        Ok(valores)
+    });
+    eliminar_archivo(&path_aux)?;
+    valores
+}
+
+/// Ejecuta el SELECT de una subconsulta y devuelve el valor de la primera columna seleccionada de cada fila.
+/// Las columnas de la fila externa que no están en la tabla de la subconsulta se pueden usar en su WHERE.
+fn ejecutar(
+    select: &SelectQuery,
+    path: &str,
+    externa: &HashMap<String, Datos>,
+) -> Result<Vec<Datos>, ErrorType> {
+    let materializar = select.limit.is_some()
+        || select.distinct
+        || !select.conteos.is_empty()
+        || !select.calculadas.is_empty()
+        || !select.ventanas.is_empty()
+        || !select.joins.is_empty()
+        || select.valores.is_some();
+    if materializar && externa.is_empty() {
+        return ejecutar_materializada(select, path);
+    }
+    if select.limit.is_some() {
+        return Err(ErrorType::InvalidSyntax(
+            "Una subconsulta correlacionada no puede tener LIMIT".to_string(),
+        ));
+    }
+    if !select.joins.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Una subconsulta correlacionada no puede tener JOIN".to_string(),
+        ));
+    }
+    if !select.ventanas.is_empty() {
+        return Err(ErrorType::InvalidSyntax(
+            "Una subconsulta correlacionada no puede tener funciones de ventana".to_string(),
+        ));
+    }
+    if select.valores.is_some() {
+        return Err(ErrorType::InvalidSyntax(
+            "Una subconsulta correlacionada debe leer de una tabla en el FROM".to_string(),
+        ));
+    }
+    let mut lines = get_reader(&agregar_path(path, &select.table))?.lines();
+    let columnas = leer_encabezado(&mut lines)?;
+    verificar_columnas(&select.conteos, &columnas)?;
+    let disponibles = select.columnas_disponibles(&columnas);
+    let (_, posiciones) = select.seleccion(&disponibles)?;
+    let conteo = select.conteos.first();
+    let columna = posiciones.first().map(|&i| &disponibles[i]);
+    let calculada = columna.and_then(|c| select.calculadas.iter().find(|v| v.to_string() == *c));
+    if conteo.is_none() && columna.is_none() {
+        return Err(ErrorType::InvalidSyntax(
+            "La subconsulta no selecciona ninguna columna".to_string(),
+        ));
+    }
+    let mut valores = Vec::new();
+    for line in lines {
+        let Ok(line) = line else {
+            return Err(ErrorType::InvalidTable(
+                "Error al leer una linea".to_string(),
+            ));
+        };
+        let mut fila = string_to_columns(&line, &columnas)?;
+        calificar_columnas(&mut fila, select.referencia(), &columnas);
+        for (nombre, dato) in externa {
+            fila.entry(nombre.to_string())
+                .or_insert_with(|| dato.clone());
+        }
+        if where_condition(&select.where_clause, &fila)? {
+            let valor = match (conteo, columna) {
+                (Some(conteo), _) => conteo.valor(&fila),
+                (None, Some(_)) if calculada.is_some() => {
+                    calculada.map(|v| evaluar_valor(v, &fila)).transpose()?
+                }
+                (None, Some(columna)) => fila.get(columna).cloned(),
+                (None, None) => None,
+            };
+            valores.extend(valor);
+        }
+    }
+    if let Some(conteo) = conteo {
+        return Ok(vec![Datos::Integer(conteo.contar_valores(valores) as i64)]);
+    }
+    if select.distinct {
+        valores.sort();
+        valores.dedup();
+    }
+    Ok(valores)
+}
+
+impl Subconsulta {
+    /// Aplica `renombrar` a cada tabla referenciada por la subconsulta, si todavía no se preparó.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        if let Subconsulta::Consulta(select) = self {
+            select.renombrar_tablas(renombrar);
+        }
+    }
+
+    /// Aplica `visitar` a cada valor de la subconsulta, si todavía no se preparó.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        if let Subconsulta::Consulta(select) = self {
+            select.visitar_valores(visitar);
+        }
+    }
+
+    /// Prepara la subconsulta para evaluarse sobre las tablas de `path`, dentro de una condición sobre `tabla`.
+    /// Si la subconsulta no referencia a la fila externa, la ejecuta una única vez y guarda su resultado.
+    /// Con `una_columna`, verifica que la subconsulta devuelva una sola columna, como en `IN` o en un valor escalar.
+    /// Devuelve también las columnas que usa y no pertenecen a su propia tabla.
+    fn preparar(
+        &self,
+        path: &str,
+        tabla: &str,
+        columnas: &[String],
+        una_columna: bool,
+    ) -> Result<(Subconsulta, Vec<String>), ErrorType> {
+        let Subconsulta::Consulta(select) = self else {
+            return Ok((self.clone(), Vec::new()));
+        };
+        let columnas_select = select.columnas_fuente(path)?;
+        let disponibles = select.columnas_disponibles(&columnas_select);
+        let (_, posiciones) = select.seleccion(&disponibles)?;
+        let cantidad_columnas = match select.conteos.len() {
+            0 => posiciones.len(),
+            conteos => conteos,
+        };
+        if una_columna && cantidad_columnas != 1 {
+            return Err(ErrorType::InvalidSyntax(
+                "La subconsulta debe devolver una única columna".to_string(),
+            ));
+        }
+        let mut select = select.clone();
+        let (where_clause, referencias) = preparar_opcional(
+            &select.where_clause,
+            path,
+            select.referencia(),
+            &columnas_select,
+        )?;
+        select.where_clause = where_clause;
+        let externas: Vec<String> = referencias
+            .into_iter()
+            .filter(|r| !es_columna_de(r, select.referencia(), &columnas_select))
+            .collect();
+        if externas.is_empty() {
+            let mut valores = ejecutar(&select, path, &HashMap::new())?;
+            valores.sort();
+            return Ok((Subconsulta::Resuelta(valores), externas));
+        }
+        let correlacionada = Subconsulta::Correlacionada {
+            select,
+            path: path.to_string(),
+            tabla: tabla.to_string(),
+            columnas: columnas.to_vec(),
+        };
+        Ok((correlacionada, externas))
+    }
+
+    /// Devuelve los valores de la subconsulta para la fila externa dada.
+    fn valores(&self, fila: &HashMap<String, Datos>) -> Result<Cow<'_, [Datos]>, ErrorType> {
+        match self {
+            Subconsulta::Resuelta(valores) => Ok(Cow::Borrowed(valores)),
+            Subconsulta::Correlacionada {
+                select,
+                path,
+                tabla,
+                columnas,
+            } => {
+                let mut externa = fila.clone();
+                calificar_columnas(&mut externa, tabla, columnas);
+                Ok(Cow::Owned(ejecutar(select, path, &externa)?))
+            }
+            Subconsulta::Consulta(_) => Err(ErrorType::Error(
+                "La subconsulta debe prepararse antes de evaluarse".to_string(),
+            )),
+        }
+    }
+
+    /// Devuelve si el dato está entre los valores de la subconsulta. Un dato nulo no está en ninguna subconsulta.
+    pub fn contiene(&self, dato: &Datos, fila: &HashMap<String, Datos>) -> Result<bool, ErrorType> {
+        if dato_to_string(dato).is_empty() {
+            return Ok(false);
+        }
+        match self.valores(fila)? {
+            Cow::Borrowed(valores) => Ok(valores.binary_search(dato).is_ok()),
+            Cow::Owned(valores) => Ok(valores.contains(dato)),
+        }
+    }
+
+    /// Devuelve si la subconsulta tiene al menos una fila.
+    pub fn existe(&self, fila: &HashMap<String, Datos>) -> Result<bool, ErrorType> {
+        Ok(!self.valores(fila)?.is_empty())
+    }
+
+    /// Devuelve el único valor de la subconsulta, o nulo si no tiene filas.
+    pub fn escalar(&self, fila: &HashMap<String, Datos>) -> Result<Datos, ErrorType> {
+        match self.valores(fila)?.as_ref() {
+            [] => Ok(Datos::String("".to_string())),
+            [dato] => Ok(dato.clone()),
+            _ => Err(ErrorType::InvalidSyntax(
+                "La subconsulta escalar devolvió más de una fila".to_string(),
+            )),
+        }
+    }
+}
+
+impl Display for Subconsulta {
+    /// Muestra la subconsulta entre paréntesis, como se escribe en una condición.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Subconsulta::Consulta(select) | Subconsulta::Correlacionada { select, .. } => {
+                write!(f, "({})", select)
+            }
+            Subconsulta::Resuelta(valores) => {
+                let valores: Vec<String> = valores.iter().map(dato_to_string).collect();
+                write!(f, "({})", valores.join(", "))
+            }
+        }
+    }
+}
+
+/// Prepara las subconsultas de un valor y devuelve también las columnas que usa.
+fn preparar_valor(
+    valor: &Valor,
+    path: &str,
+    tabla: &str,
+    columnas: &[String],
+) -> Result<(Valor, Vec<String>), ErrorType> {
+    match valor {
+        Valor::String(s) if s.parse::<i64>().is_err() => Ok((valor.clone(), vec![s.to_string()])),
+        Valor::Funcion { nombre, argumentos } => {
+            let mut preparados = Vec::new();
+            let mut referencias = Vec::new();
+            for argumento in argumentos {
+                let (argumento, usadas) = preparar_valor(argumento, path, tabla, columnas)?;
+                preparados.push(argumento);
+                referencias.extend(usadas);
+            }
+            let funcion = Valor::Funcion {
+                nombre: nombre.to_string(),
+                argumentos: preparados,
+            };
+            Ok((funcion, referencias))
+        }
+        Valor::Subconsulta(subconsulta) => {
+            let (subconsulta, referencias) = subconsulta.preparar(path, tabla, columnas, true)?;
+            Ok((Valor::Subconsulta(subconsulta), referencias))
+        }
+        Valor::Case(case) => {
+            let mut ramas = Vec::new();
+            let mut referencias = Vec::new();
+            for (condicion, valor) in &case.ramas {
+                let (condicion, usadas) = preparar_expresion(condicion, path, tabla, columnas)?;
+                referencias.extend(usadas);
+                let (valor, usadas) = preparar_valor(valor, path, tabla, columnas)?;
+                referencias.extend(usadas);
+                ramas.push((condicion, valor));
+            }
+            let sino = match &case.sino {
+                Some(sino) => {
+                    let (sino, usadas) = preparar_valor(sino, path, tabla, columnas)?;
+                    referencias.extend(usadas);
+                    Some(sino)
+                }
+                None => None,
+            };
+            Ok((Valor::Case(Box::new(Case::new(ramas, sino))), referencias))
+        }
+        Valor::Operacion { izq, operador, der } => {
+            let (izq, mut referencias) = preparar_valor(izq, path, tabla, columnas)?;
+            let (der, usadas) = preparar_valor(der, path, tabla, columnas)?;
+            referencias.extend(usadas);
+            let operacion = Valor::Operacion {
+                izq: Box::new(izq),
+                operador: operador.clone(),
+                der: Box::new(der),
+            };
+            Ok((operacion, referencias))
+        }
+        _ => Ok((valor.clone(), Vec::new())),
+    }
+}
+
+/// Prepara las subconsultas de una expresión y devuelve también las columnas que usa.
+fn preparar_expresion(
+    expresion: &ExpresionBooleana,
+    path: &str,
+    tabla: &str,
+    columnas: &[String],
+) -> Result<(ExpresionBooleana, Vec<String>), ErrorType> {
+    let preparada = match expresion {
+        ExpresionBooleana::Comparacion { izq, operador, der } => {
+            let (izq, mut referencias) = preparar_valor(izq, path, tabla, columnas)?;
+            let (der, usadas) = preparar_valor(der, path, tabla, columnas)?;
+            referencias.extend(usadas);
+            let operador = operador.clone();
+            (
+                ExpresionBooleana::Comparacion { izq, operador, der },
+                referencias,
+            )
+        }
+        ExpresionBooleana::In { valor, subconsulta } => {
+            let (valor, mut referencias) = preparar_valor(valor, path, tabla, columnas)?;
+            let (subconsulta, usadas) = subconsulta.preparar(path, tabla, columnas, true)?;
+            referencias.extend(usadas);
+            (ExpresionBooleana::In { valor, subconsulta }, referencias)
+        }
+        ExpresionBooleana::Exists(subconsulta) => {
+            let (subconsulta, referencias) = subconsulta.preparar(path, tabla, columnas, false)?;
+            (ExpresionBooleana::Exists(subconsulta), referencias)
+        }
+        ExpresionBooleana::And(expr1, expr2) | ExpresionBooleana::Or(expr1, expr2) => {
+            let (expr1, mut referencias) = preparar_expresion(expr1, path, tabla, columnas)?;
+            let (expr2, usadas) = preparar_expresion(expr2, path, tabla, columnas)?;
+            referencias.extend(usadas);
+            let operador_logico = match expresion {
+                ExpresionBooleana::And(_, _) => ExpresionBooleana::And,
+                _ => ExpresionBooleana::Or,
+            };
+            (
+                operador_logico(Box::new(expr1), Box::new(expr2)),
+                referencias,
+            )
+        }
+        ExpresionBooleana::Not(expr) => {
+            let (expr, referencias) = preparar_expresion(expr, path, tabla, columnas)?;
+            (ExpresionBooleana::Not(Box::new(expr)), referencias)
+        }
+    };
+    Ok(preparada)
+}
+
+/// Prepara las subconsultas de una condición opcional y devuelve también las columnas que usa.
+fn preparar_opcional(
+    condicion: &Option<ExpresionBooleana>,
+    path: &str,
+    tabla: &str,
+    columnas: &[String],
+) -> Result<(Option<ExpresionBooleana>, Vec<String>), ErrorType> {
+    match condicion {
+        Some(condicion) => {
+            let (condicion, referencias) = preparar_expresion(condicion, path, tabla, columnas)?;
+            Ok((Some(condicion), referencias))
+        }
+        None => Ok((None, Vec::new())),
+    }
+}
+
+/// Prepara las subconsultas de la condición de una consulta sobre `tabla` para evaluarla en cada fila.
+/// Las subconsultas que no referencian a la fila externa se ejecutan en este momento, una única vez;
+/// las correlacionadas se ejecutan al evaluar cada fila.
+pub fn preparar_condicion(
+    condicion: &Option<ExpresionBooleana>,
+    path: &str,
+    tabla: &str,
+    columnas: &[String],
+) -> Result<Option<ExpresionBooleana>, ErrorType> {
+    match condicion {
+        Some(expresion) if expresion.tiene_subconsultas() => {
+            let (condicion, _) = preparar_opcional(condicion, path, tabla, columnas)?;
+            Ok(condicion)
+        }
+        _ => Ok(condicion.clone()),
+    }
+}
+
+/// Prepara las subconsultas de los valores que se calculan en cada fila de una consulta sobre `tabla`,
+/// como las expresiones CASE de un SELECT o de un UPDATE.
+pub fn preparar_valores(
+    valores: &[Valor],
+    path: &str,
+    tabla: &str,
+    columnas: &[String],
+) -> Result<Vec<Valor>, ErrorType> {
+    let mut preparados = Vec::new();
+    for valor in valores {
+        if valor.tiene_subconsultas() {
+            preparados.push(preparar_valor(valor, path, tabla, columnas)?.0);
+        } else {
+            preparados.push(valor.clone());
+        }
+    }
+    Ok(preparados)
+}
@@ -1,6 +1,198 @@
-/// Enum para representar valores que pueden ser una cadena o un literal.
-#[derive(Debug, PartialEq)]
+use std::fmt::{self, Display, Formatter};
+
+use crate::queries::select_query::RenombrarTabla;
+
+use super::{case::Case, subconsulta::Subconsulta};
+
+/// Operadores de las expresiones aritméticas entre valores enteros.
+#[derive(Debug, PartialEq, Clone)]
+pub enum OperadorAritmetico {
+    Suma,
+    Resta,
+    Multiplicacion,
+    Division,
+}
+
+impl OperadorAritmetico {
+    /// Devuelve el operador con ese símbolo, si es un operador aritmético.
+    pub fn desde(simbolo: &str) -> Option<OperadorAritmetico> {
+        match simbolo {
+            "+" => Some(OperadorAritmetico::Suma),
+            "-" => Some(OperadorAritmetico::Resta),
+            "*" => Some(OperadorAritmetico::Multiplicacion),
+            "/" => Some(OperadorAritmetico::Division),
+            _ => None,
+        }
+    }
+
+    /// Devuelve la precedencia del operador: la multiplicación y la división se calculan antes que la suma y la resta.
+    pub fn precedencia(&self) -> u8 {
+        match self {
+            OperadorAritmetico::Suma | OperadorAritmetico::Resta => 1,
+            OperadorAritmetico::Multiplicacion | OperadorAritmetico::Division => 2,
+        }
+    }
+}
+
+impl Display for OperadorAritmetico {
+    /// Muestra el símbolo del operador.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            OperadorAritmetico::Suma => write!(f, "+"),
+            OperadorAritmetico::Resta => write!(f, "-"),
+            OperadorAritmetico::Multiplicacion => write!(f, "*"),
+            OperadorAritmetico::Division => write!(f, "/"),
+        }
+    }
+}
+
+/// Enum para representar valores que pueden ser una cadena, un literal, el resultado de una función,
+/// el de una subconsulta escalar, el de una expresión CASE, el de una operación aritmética
+/// o un parámetro `$n` de una consulta preparada, que se reemplaza por su valor antes de ejecutarla.
+#[derive(Debug, PartialEq, Clone)]
 pub enum Valor {
     String(String),
     Literal(String),
+    Funcion {
+        nombre: String,
+        argumentos: Vec<Valor>,
+    },
+    Subconsulta(Subconsulta),
+    Case(Box<Case>),
+    Operacion {
+        izq: Box<Valor>,
+        operador: OperadorAritmetico,
+        der: Box<Valor>,
+    },
+    Parametro(usize),
+}
+
+/// Función que recibe cada valor de una consulta y puede reemplazarlo.
+pub type VisitarValor<'a> = dyn FnMut(&mut Valor) + 'a;
+
+impl Valor {
+    /// Devuelve si el valor contiene alguna subconsulta.
+    pub fn tiene_subconsultas(&self) -> bool {
+        match self {
+            Valor::Subconsulta(_) => true,
+            Valor::Funcion { argumentos, .. } => argumentos.iter().any(Valor::tiene_subconsultas),
+            Valor::Case(case) => {
+                case.ramas.iter().any(|(condicion, valor)| {
+                    condicion.tiene_subconsultas() || valor.tiene_subconsultas()
+                }) || case.sino.as_ref().is_some_and(Valor::tiene_subconsultas)
+            }
+            Valor::Operacion { izq, der, .. } => {
+                izq.tiene_subconsultas() || der.tiene_subconsultas()
+            }
+            Valor::String(_) | Valor::Literal(_) | Valor::Parametro(_) => false,
+        }
+    }
+
+    /// Devuelve los nombres de las columnas que usa el valor, sin repetir, también dentro de sus subconsultas.
+    pub fn columnas_referenciadas(&self) -> Vec<String> {
+        let mut columnas = Vec::new();
+        self.clone()
+            .visitar_valores(&mut |valor| agregar_columna(valor, &mut columnas));
+        columnas
+    }
+
+    /// Aplica `renombrar` a cada tabla referenciada por las subconsultas del valor.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        match self {
+            Valor::Subconsulta(subconsulta) => subconsulta.renombrar_tablas(renombrar),
+            Valor::Funcion { argumentos, .. } => argumentos
+                .iter_mut()
+                .for_each(|argumento| argumento.renombrar_tablas(renombrar)),
+            Valor::Case(case) => {
+                for (condicion, valor) in &mut case.ramas {
+                    condicion.renombrar_tablas(renombrar);
+                    valor.renombrar_tablas(renombrar);
+                }
+                if let Some(sino) = &mut case.sino {
+                    sino.renombrar_tablas(renombrar);
+                }
+            }
+            Valor::Operacion { izq, der, .. } => {
+                izq.renombrar_tablas(renombrar);
+                der.renombrar_tablas(renombrar);
+            }
+            Valor::String(_) | Valor::Literal(_) | Valor::Parametro(_) => {}
+        }
+    }
+
+    /// Aplica `visitar` al valor y después a cada valor que contiene, también dentro de sus subconsultas.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        visitar(self);
+        match self {
+            Valor::Subconsulta(subconsulta) => subconsulta.visitar_valores(visitar),
+            Valor::Funcion { argumentos, .. } => argumentos
+                .iter_mut()
+                .for_each(|argumento| argumento.visitar_valores(visitar)),
+            Valor::Case(case) => {
+                for (condicion, valor) in &mut case.ramas {
+                    condicion.visitar_valores(visitar);
+                    valor.visitar_valores(visitar);
+                }
+                if let Some(sino) = &mut case.sino {
+                    sino.visitar_valores(visitar);
+                }
+            }
+            Valor::Operacion { izq, der, .. } => {
+                izq.visitar_valores(visitar);
+                der.visitar_valores(visitar);
+            }
+            Valor::String(_) | Valor::Literal(_) | Valor::Parametro(_) => {}
+        }
+    }
+
+    /// Devuelve la precedencia del valor dentro de una operación aritmética: la de su operador,
+    /// o la máxima si no es una operación.
+    fn precedencia(&self) -> u8 {
+        match self {
+            Valor::Operacion { operador, .. } => operador.precedencia(),
+            _ => u8::MAX,
+        }
+    }
+}
+
+/// Agrega a `columnas` el nombre de la columna si el valor es una columna, y no un número, que todavía no está.
+pub fn agregar_columna(valor: &Valor, columnas: &mut Vec<String>) {
+    if let Valor::String(nombre) = valor {
+        if nombre.parse::<i64>().is_err() && !columnas.contains(nombre) {
+            columnas.push(nombre.to_string());
+        }
+    }
+}
+
+impl Display for Valor {
+    /// Muestra el valor con la sintaxis SQL con la que se escribe en una consulta.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Valor::String(s) => write!(f, "{}", s),
+            Valor::Literal(s) => write!(f, "'{}'", s),
+            Valor::Parametro(numero) => write!(f, "${}", numero),
+            Valor::Funcion { nombre, argumentos } if argumentos.is_empty() => {
+                write!(f, "{}", nombre)
+            }
+            Valor::Funcion { nombre, argumentos } => {
+                let argumentos: Vec<String> = argumentos.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", nombre, argumentos.join(", "))
+            }
+            Valor::Subconsulta(subconsulta) => write!(f, "{}", subconsulta),
+            Valor::Case(case) => write!(f, "{}", case),
+            Valor::Operacion { izq, operador, der } => {
+                if izq.precedencia() < operador.precedencia() {
+                    write!(f, "({})", izq)?;
+                } else {
+                    write!(f, "{}", izq)?;
+                }
+                write!(f, " {} ", operador)?;
+                if der.precedencia() <= operador.precedencia() {
+                    write!(f, "({})", der)
+                } else {
+                    write!(f, "{}", der)
+                }
+            }
+        }
+    }
 }
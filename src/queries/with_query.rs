@@ -0,0 +1,499 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    io::Write,
+    time::Instant,
+};
+
+use crate::{
+    errores::error::ErrorType,
+    executer::{
+        conjuntos::{combinar, copiar_filas, longitud},
+        execute::Execute,
+        manejo_csv::{
+            agregar_path, crear_archivo, eliminar_archivo, nombre_auxiliar, proyectar_archivo,
+        },
+        mediciones::{contar_filas, Medicion, Mediciones},
+    },
+};
+
+use super::{
+    compound_query::{CompoundQuery, Conjunto, OperadorConjunto},
+    select_query::{RenombrarTabla, SelectQuery},
+    where_clause::valor::VisitarValor,
+};
+
+/// Cantidad máxima de iteraciones de una expresión recursiva, si no se indica otra con `OPTION (MAXRECURSION n)`.
+pub const MAX_ITERACIONES: usize = 1000;
+
+/// Consulta que devuelve filas: un SELECT o una combinación de SELECT con operadores de conjuntos.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Consulta {
+    Select(Box<SelectQuery>),
+    Compound(CompoundQuery),
+}
+
+impl Consulta {
+    /// Aplica `renombrar` a cada tabla referenciada por la consulta.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        match self {
+            Consulta::Select(select) => select.renombrar_tablas(renombrar),
+            Consulta::Compound(compound) => compound.conjunto.renombrar_tablas(renombrar),
+        }
+    }
+
+    /// Aplica `visitar` a cada valor de la consulta.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        match self {
+            Consulta::Select(select) => select.visitar_valores(visitar),
+            Consulta::Compound(compound) => compound.conjunto.visitar_valores(visitar),
+        }
+    }
+
+    /// Escribe el resultado de la consulta en un archivo auxiliar con encabezado.
+    /// Devuelve la ruta del archivo, los nombres de las columnas del resultado y sus posiciones en el archivo.
+    pub fn materializar(&self, path: &str) -> Result<(String, Vec<String>, Vec<usize>), ErrorType> {
+        self.materializar_midiendo(path, &mut Mediciones::default(), "")
+    }
+
+    /// Igual que `materializar`, registrando en `mediciones` las filas y el tiempo de cada etapa
+    /// con claves que empiezan con `prefijo`.
+    pub fn materializar_midiendo(
+        &self,
+        path: &str,
+        mediciones: &mut Mediciones,
+        prefijo: &str,
+    ) -> Result<(String, Vec<String>, Vec<usize>), ErrorType> {
+        match self {
+            Consulta::Select(select) => {
+                let (origen, columnas, posiciones) =
+                    select.materializar_midiendo(path, mediciones, prefijo)?;
+                let columnas = columnas.split(',').map(sin_tabla).collect();
+                Ok((origen, columnas, posiciones))
+            }
+            Consulta::Compound(compound) => {
+                let (origen, columnas) =
+                    compound.materializar_midiendo(path, mediciones, prefijo)?;
+                let posiciones = (0..columnas.len()).collect();
+                Ok((origen, columnas, posiciones))
+            }
+        }
+    }
+}
+
+impl Display for Consulta {
+    /// Muestra la consulta con la sintaxis SQL con la que se escribe.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Consulta::Select(select) => write!(f, "{}", select),
+            Consulta::Compound(compound) => write!(f, "{}", compound),
+        }
+    }
+}
+
+impl Execute for Consulta {
+    /// Ejecuta la consulta e imprime su resultado.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        match self {
+            Consulta::Select(select) => select.execute(path),
+            Consulta::Compound(compound) => compound.execute(path),
+        }
+    }
+}
+
+/// Representa una expresión de tabla común `nombre [(columnas)] AS (SELECT ...)` de un WITH.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cte {
+    pub nombre: String,
+    /// Nombres con los que se renombran las columnas del resultado, si se indicaron.
+    pub columnas: Option<Vec<String>>,
+    pub consulta: Consulta,
+}
+
+impl Cte {
+    /// Crea una nueva instancia de `Cte`.
+    pub fn new(nombre: &str, columnas: Option<Vec<String>>, consulta: Consulta) -> Self {
+        Cte {
+            nombre: nombre.to_string(),
+            columnas,
+            consulta,
+        }
+    }
+
+    /// Ejecuta la consulta de la expresión y escribe su resultado, con el encabezado de sus columnas,
+    /// en un archivo auxiliar en `path`. Devuelve el nombre del archivo auxiliar.
+    pub fn materializar(&self, path: &str) -> Result<String, ErrorType> {
+        let (origen, columnas, posiciones) = self.consulta.materializar(path)?;
+        let nombre_aux = nombre_auxiliar("cte");
+        let path_aux = agregar_path(path, &nombre_aux);
+        let resultado = self.columnas(columnas).and_then(|columnas| {
+            let mut archivo = crear_archivo(&path_aux)?;
+            if writeln!(archivo, "{}", columnas.join(",")).is_err() {
+                return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+            }
+            proyectar_archivo(&origen, &posiciones, &path_aux)
+        });
+        eliminar_archivo(&origen)?;
+        match resultado {
+            Ok(_) => Ok(nombre_aux),
+            Err(e) => {
+                let _ = eliminar_archivo(&path_aux);
+                Err(e)
+            }
+        }
+    }
+
+    /// Devuelve si la consulta de la expresión la referencia a ella misma.
+    pub fn es_recursiva(&self) -> bool {
+        match &self.consulta {
+            Consulta::Select(select) => {
+                referencia_a(Conjunto::Select(select.clone()), &self.nombre)
+            }
+            Consulta::Compound(compound) => referencia_a(compound.conjunto.clone(), &self.nombre),
+        }
+    }
+
+    /// Devuelve los dos términos de una expresión recursiva `SELECT ... UNION [ALL] SELECT ...`
+    /// junto con el operador que los combina. Solo el segundo término puede referenciar a la expresión.
+    fn terminos(&self) -> Result<(&Conjunto, &OperadorConjunto, &Conjunto), ErrorType> {
+        let Consulta::Compound(CompoundQuery {
+            conjunto: Conjunto::Operacion { izq, operador, der },
+            order_by: None,
+            limit: None,
+        }) = &self.consulta
+        else {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "La expresión recursiva '{}' debe tener la forma 'SELECT ... UNION [ALL] SELECT ...'",
+                self.nombre
+            )));
+        };
+        if !matches!(
+            operador,
+            OperadorConjunto::Union | OperadorConjunto::UnionAll
+        ) {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "La expresión recursiva '{}' debe combinar sus términos con UNION o UNION ALL",
+                self.nombre
+            )));
+        }
+        if referencia_a(izq.as_ref().clone(), &self.nombre) {
+            return Err(ErrorType::InvalidSyntax(format!(
+                "El primer término de la expresión recursiva '{}' no puede referenciarla",
+                self.nombre
+            )));
+        }
+        Ok((izq, operador, der))
+    }
+
+    /// Ejecuta una expresión recursiva hasta llegar a un punto fijo: el primer término da las filas iniciales,
+    /// y el segundo se ejecuta usando como contenido de la expresión las filas nuevas de la iteración anterior,
+    /// hasta que no agrega filas. Con UNION se descartan las filas que ya estaban en el resultado.
+    /// Devuelve el nombre del archivo auxiliar con el resultado, o un error si hacen falta más de `max_iteraciones`.
+    fn materializar_recursiva(
+        &self,
+        path: &str,
+        max_iteraciones: usize,
+    ) -> Result<String, ErrorType> {
+        let (base, operador, recursivo) = self.terminos()?;
+        let nombre_trabajo = nombre_auxiliar("cte");
+        let mut recursivo = recursivo.clone();
+        recursivo.renombrar_tablas(&mut |tabla, alias| {
+            if *tabla == self.nombre {
+                if alias.is_none() {
+                    *alias = Some(tabla.to_string());
+                }
+                *tabla = nombre_trabajo.to_string();
+            }
+        });
+        let auxiliares = Auxiliares {
+            candidatas: agregar_path(path, &nombre_auxiliar("auxiliar")),
+            nuevas: agregar_path(path, &nombre_auxiliar("auxiliar")),
+            resultado: agregar_path(path, &nombre_auxiliar("auxiliar")),
+            trabajo: agregar_path(path, &nombre_trabajo),
+        };
+        let nombre_aux = nombre_auxiliar("cte");
+        let path_aux = agregar_path(path, &nombre_aux);
+        let resultado = self
+            .iterar(
+                path,
+                base,
+                operador,
+                &recursivo,
+                &auxiliares,
+                max_iteraciones,
+            )
+            .and_then(|columnas| {
+                escribir_con_encabezado(&columnas, &auxiliares.resultado, &path_aux)
+            });
+        for auxiliar in [
+            &auxiliares.candidatas,
+            &auxiliares.nuevas,
+            &auxiliares.resultado,
+            &auxiliares.trabajo,
+        ] {
+            let _ = eliminar_archivo(auxiliar);
+        }
+        match resultado {
+            Ok(_) => Ok(nombre_aux),
+            Err(e) => {
+                let _ = eliminar_archivo(&path_aux);
+                Err(e)
+            }
+        }
+    }
+
+    /// Ejecuta las iteraciones de una expresión recursiva, dejando las filas del resultado, sin encabezado,
+    /// en el archivo `resultado` de los auxiliares. Devuelve los nombres de las columnas de la expresión.
+    fn iterar(
+        &self,
+        path: &str,
+        base: &Conjunto,
+        operador: &OperadorConjunto,
+        recursivo: &Conjunto,
+        auxiliares: &Auxiliares,
+        max_iteraciones: usize,
+    ) -> Result<Vec<String>, ErrorType> {
+        let columnas = base.materializar(path, &auxiliares.candidatas)?;
+        let columnas = self.columnas(columnas.iter().map(|c| sin_tabla(c)).collect())?;
+        crear_archivo(&auxiliares.resultado)?;
+        let mut iteraciones = 0;
+        loop {
+            let nuevas = match operador {
+                OperadorConjunto::UnionAll => &auxiliares.candidatas,
+                _ => {
+                    crear_archivo(&auxiliares.nuevas)?;
+                    combinar(
+                        path,
+                        &auxiliares.candidatas,
+                        &auxiliares.resultado,
+                        &auxiliares.nuevas,
+                        &OperadorConjunto::Except,
+                    )?;
+                    &auxiliares.nuevas
+                }
+            };
+            if longitud(nuevas)? == 0 {
+                return Ok(columnas);
+            }
+            if iteraciones == max_iteraciones {
+                return Err(ErrorType::Error(format!(
+                    "La expresión recursiva '{}' superó el máximo de {} iteraciones",
+                    self.nombre, max_iteraciones
+                )));
+            }
+            iteraciones += 1;
+            copiar_filas(nuevas, &auxiliares.resultado)?;
+            escribir_con_encabezado(&columnas, nuevas, &auxiliares.trabajo)?;
+            let columnas_recursivo = recursivo.materializar(path, &auxiliares.candidatas)?;
+            if columnas_recursivo.len() != columnas.len() {
+                return Err(ErrorType::InvalidSyntax(format!(
+                    "Los términos de la expresión recursiva '{}' deben tener la misma cantidad de columnas ({} y {})",
+                    self.nombre,
+                    columnas.len(),
+                    columnas_recursivo.len()
+                )));
+            }
+        }
+    }
+
+    /// Devuelve los nombres de las columnas de la expresión: los indicados o, si no, los de su consulta.
+    fn columnas(&self, columnas: Vec<String>) -> Result<Vec<String>, ErrorType> {
+        match &self.columnas {
+            None => Ok(columnas),
+            Some(nombres) if nombres.len() == columnas.len() => Ok(nombres.clone()),
+            Some(nombres) => Err(ErrorType::InvalidSyntax(format!(
+                "'{}' en el WITH tiene {} columnas pero su consulta devuelve {}",
+                self.nombre,
+                nombres.len(),
+                columnas.len()
+            ))),
+        }
+    }
+}
+
+/// Archivos auxiliares de una expresión recursiva, todos sin encabezado salvo `trabajo`:
+/// las filas que devolvió la última ejecución de un término, las que no estaban en el resultado,
+/// el resultado acumulado, y las filas nuevas de la iteración anterior como tabla para el término recursivo.
+struct Auxiliares {
+    candidatas: String,
+    nuevas: String,
+    resultado: String,
+    trabajo: String,
+}
+
+/// Devuelve si alguna de las consultas combinadas referencia la tabla.
+fn referencia_a(mut conjunto: Conjunto, tabla: &str) -> bool {
+    let mut referenciada = false;
+    conjunto.renombrar_tablas(&mut |nombre, _| referenciada |= nombre == tabla);
+    referenciada
+}
+
+/// Escribe en la salida el encabezado con las columnas seguido de las filas del archivo sin encabezado.
+fn escribir_con_encabezado(
+    columnas: &[String],
+    origen: &String,
+    salida: &String,
+) -> Result<(), ErrorType> {
+    let mut archivo = crear_archivo(salida)?;
+    if writeln!(archivo, "{}", columnas.join(",")).is_err() {
+        return Err(ErrorType::Error("Error al escribir en archivo".to_string()));
+    }
+    copiar_filas(origen, salida)
+}
+
+/// Devuelve el nombre de una columna del resultado sin el nombre de su tabla adelante.
+fn sin_tabla(columna: &str) -> String {
+    match columna.split_once('.') {
+        Some((_, nombre)) => nombre.to_string(),
+        None => columna.to_string(),
+    }
+}
+
+/// Representa una consulta `WITH a AS (SELECT ...), b AS (SELECT ...) SELECT ...`.
+/// Cada expresión puede usar las que se definen antes que ella, y la consulta principal puede usar todas.
+/// Con `WITH RECURSIVE`, una expresión también puede referenciarse a sí misma en el segundo término de un UNION,
+/// y se itera a lo sumo `max_iteraciones` veces.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WithQuery {
+    pub ctes: Vec<Cte>,
+    pub consulta: Consulta,
+    pub recursiva: bool,
+    pub max_iteraciones: usize,
+}
+
+impl WithQuery {
+    /// Crea una nueva instancia de `WithQuery`, sin expresiones recursivas.
+    pub fn new(ctes: Vec<Cte>, consulta: Consulta) -> Self {
+        WithQuery {
+            ctes,
+            consulta,
+            recursiva: false,
+            max_iteraciones: MAX_ITERACIONES,
+        }
+    }
+
+    /// Aplica `renombrar` a cada tabla referenciada por las expresiones y la consulta principal
+    /// que no sea una de las expresiones del WITH.
+    pub fn renombrar_tablas(&mut self, renombrar: &mut RenombrarTabla) {
+        let nombres: HashSet<String> = self.ctes.iter().map(|cte| cte.nombre.to_string()).collect();
+        let mut renombrar_tabla = |tabla: &mut String, alias: &mut Option<String>| {
+            if !nombres.contains(tabla) {
+                renombrar(tabla, alias);
+            }
+        };
+        for cte in &mut self.ctes {
+            cte.consulta.renombrar_tablas(&mut renombrar_tabla);
+        }
+        self.consulta.renombrar_tablas(&mut renombrar_tabla);
+    }
+
+    /// Aplica `visitar` a cada valor de las expresiones y de la consulta principal.
+    pub fn visitar_valores(&mut self, visitar: &mut VisitarValor) {
+        for cte in &mut self.ctes {
+            cte.consulta.visitar_valores(visitar);
+        }
+        self.consulta.visitar_valores(visitar);
+    }
+
+    /// Devuelve qué expresiones se usan, directamente en la consulta principal o a través de otras expresiones usadas.
+    pub fn usadas(&self) -> Vec<bool> {
+        let mut usadas = vec![false; self.ctes.len()];
+        marcar_usadas(&mut self.consulta.clone(), &self.ctes, &mut usadas);
+        for i in (0..self.ctes.len()).rev() {
+            if usadas[i] {
+                let mut consulta = self.ctes[i].consulta.clone();
+                marcar_usadas(&mut consulta, &self.ctes[..i], &mut usadas);
+            }
+        }
+        usadas
+    }
+
+    /// Ejecuta las expresiones usadas en orden, guardando en `auxiliares` los archivos con sus resultados,
+    /// y devuelve la consulta principal con sus referencias reemplazadas por esos archivos.
+    /// Registra en `mediciones` las filas y el tiempo de cada expresión con la clave `cte{i}`.
+    pub fn materializar_expresiones(
+        &self,
+        path: &str,
+        auxiliares: &mut Vec<String>,
+        mediciones: &mut Mediciones,
+    ) -> Result<Consulta, ErrorType> {
+        let mut tablas: HashMap<String, String> = HashMap::new();
+        for (i, (cte, usada)) in self.ctes.iter().zip(self.usadas()).enumerate() {
+            if !usada {
+                continue;
+            }
+            let mut cte = cte.clone();
+            reemplazar_tablas(&mut cte.consulta, &tablas);
+            let inicio = Instant::now();
+            let nombre_aux = if self.recursiva && cte.es_recursiva() {
+                cte.materializar_recursiva(path, self.max_iteraciones)?
+            } else {
+                cte.materializar(path)?
+            };
+            auxiliares.push(nombre_aux.to_string());
+            if mediciones.activas() {
+                let medicion = Medicion {
+                    filas_entrada: None,
+                    filas_salida: contar_filas(&agregar_path(path, &nombre_aux))?,
+                    tiempo: inicio.elapsed(),
+                };
+                mediciones.registrar(&format!("cte{}", i), medicion);
+            }
+            tablas.insert(cte.nombre, nombre_aux);
+        }
+        let mut consulta = self.consulta.clone();
+        reemplazar_tablas(&mut consulta, &tablas);
+        Ok(consulta)
+    }
+
+    /// Ejecuta las expresiones usadas en orden y luego la consulta principal.
+    fn ejecutar(&self, path: &str, auxiliares: &mut Vec<String>) -> Result<(), ErrorType> {
+        self.materializar_expresiones(path, auxiliares, &mut Mediciones::default())?
+            .execute(path)
+    }
+}
+
+/// Marca como usadas las expresiones referenciadas por la consulta.
+/// Si dos expresiones tienen el mismo nombre, la referencia es a la última.
+fn marcar_usadas(consulta: &mut Consulta, ctes: &[Cte], usadas: &mut [bool]) {
+    consulta.renombrar_tablas(&mut |tabla, _| {
+        if let Some(i) = ctes.iter().rposition(|cte| cte.nombre == *tabla) {
+            usadas[i] = true;
+        }
+    });
+}
+
+/// Reemplaza cada referencia a una expresión por el archivo auxiliar con su resultado.
+fn reemplazar_tablas(consulta: &mut Consulta, tablas: &HashMap<String, String>) {
+    consulta.renombrar_tablas(&mut reemplazo(tablas));
+}
+
+/// Devuelve una función para `renombrar_tablas` que reemplaza cada tabla de `tablas` por su archivo auxiliar,
+/// conservando el nombre original como alias para calificar sus columnas.
+pub fn reemplazo(
+    tablas: &HashMap<String, String>,
+) -> impl FnMut(&mut String, &mut Option<String>) + '_ {
+    |tabla, alias| {
+        if let Some(nombre_aux) = tablas.get(tabla) {
+            if alias.is_none() {
+                *alias = Some(tabla.to_string());
+            }
+            *tabla = nombre_aux.to_string();
+        }
+    }
+}
+
+impl Execute for WithQuery {
+    /// Ejecuta la consulta principal usando los resultados de las expresiones del WITH.
+    /// Cada expresión usada se ejecuta una única vez y su resultado se guarda en un archivo auxiliar,
+    /// de modo que referenciarla varias veces no vuelve a leer las tablas de las que sale.
+    /// Los archivos auxiliares se eliminan al terminar.
+    fn execute(&self, path: &str) -> Result<(), ErrorType> {
+        let mut auxiliares = Vec::new();
+        let resultado = self.ejecutar(path, &mut auxiliares);
+        for nombre_aux in &auxiliares {
+            eliminar_archivo(&agregar_path(path, nombre_aux))?;
+        }
+        resultado
+    }
+}
@@ -6,7 +6,10 @@ use crate::{
     parsers::parser::parser,
     queries::{
         order_clause::OrderDirection,
-        where_clause::{operador_comparacion::OperadorComparacion, valor::Valor},
+        where_clause::{
+            funciones::es_funcion_sin_argumentos, operador_comparacion::OperadorComparacion,
+            valor::Valor,
+        },
     },
 };
 /// Procesa una consulta SQL: analiza, convierte y ejecuta.
@@ -32,6 +35,7 @@ pub fn printear(rest: &[Operador]) {
                 }
             }
             Operador::Comparador(c) => println!("{}Comparador: {}", padding, c),
+            Operador::Parametro(n) => println!("{}Parametro: ${}", padding, n),
         }
     }
 
@@ -53,9 +57,10 @@ pub fn string_to_number(s: String) -> Result<Datos, ErrorType> {
     }
 }
 
-/// Transforma un String o texto en Dato
+/// Transforma un String o texto en Dato. `NULL` se representa como un texto vacío, igual que una celda vacía del CSV.
 pub fn operador_to_dato(operador: &Operador) -> Result<Datos, ErrorType> {
     match operador {
+        Operador::String(s) if s == "NULL" => Ok(Datos::String("".to_string())),
         Operador::String(s) => Ok(string_to_number(s.to_string())?),
         Operador::Texto(s) => Ok(Datos::String(s.to_string())),
         _ => Err(ErrorType::InvalidSyntax(
@@ -64,6 +69,14 @@ pub fn operador_to_dato(operador: &Operador) -> Result<Datos, ErrorType> {
     }
 }
 
+/// Error de un parámetro `$n` escrito donde solo se acepta un valor constante.
+fn parametro_inesperado(numero: usize) -> ErrorType {
+    ErrorType::InvalidSyntax(format!(
+        "No se puede usar el parámetro ${} en este lugar de la consulta",
+        numero
+    ))
+}
+
 /// Extrae el String mas interno de la lista si no tiene mas de un elemento
 pub fn extraer_interno_lista(operador: &[Operador]) -> Result<Datos, ErrorType> {
     if operador.len() != 1 {
@@ -72,12 +85,12 @@ pub fn extraer_interno_lista(operador: &[Operador]) -> Result<Datos, ErrorType>
         ));
     }
     match &operador[0] {
-        Operador::String(s) => Ok(string_to_number(s.to_string())?),
-        Operador::Texto(s) => Ok(Datos::String(s.to_string())),
+        Operador::String(_) | Operador::Texto(_) => operador_to_dato(&operador[0]),
         Operador::Lista(operador) => extraer_interno_lista(operador.as_slice()),
         Operador::Comparador(_) => Err(ErrorType::InvalidSyntax(
             "Comparador inesperado.".to_string(),
         )),
+        Operador::Parametro(numero) => Err(parametro_inesperado(*numero)),
     }
 }
 
@@ -89,6 +102,7 @@ pub fn operador_to_single_dato(operador: &Operador) -> Result<Datos, ErrorType>
         Operador::Comparador(_) => Err(ErrorType::InvalidSyntax(
             "Comparador inesperado.".to_string(),
         )),
+        Operador::Parametro(numero) => Err(parametro_inesperado(*numero)),
     }
 }
 
@@ -106,18 +120,24 @@ pub fn extraer_interno_lista_valor(operador: &[Operador]) -> Result<Valor, Error
         Operador::Comparador(_) => Err(ErrorType::InvalidSyntax(
             "Comparador inesperado.".to_string(),
         )),
+        Operador::Parametro(numero) => Ok(Valor::Parametro(*numero)),
     }
 }
 
-/// Recibe cualquier operador y lo convierte en un Valor -> String o Literal
+/// Recibe cualquier operador y lo convierte en un Valor -> String, Literal, Parametro o Funcion sin argumentos
 pub fn operador_to_single_valor(operador: &Operador) -> Result<Valor, ErrorType> {
     match operador {
+        Operador::String(s) if es_funcion_sin_argumentos(s) => Ok(Valor::Funcion {
+            nombre: s.to_string(),
+            argumentos: Vec::new(),
+        }),
         Operador::String(s) => Ok(Valor::String(s.to_string())),
         Operador::Texto(s) => Ok(Valor::Literal(s.to_string())),
         Operador::Lista(list) => extraer_interno_lista_valor(list.as_slice()),
         Operador::Comparador(_) => Err(ErrorType::InvalidSyntax(
             "Comparador inesperado.".to_string(),
         )),
+        Operador::Parametro(numero) => Ok(Valor::Parametro(*numero)),
     }
 }
 
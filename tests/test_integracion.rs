@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod integration_tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, fs, path::Path};
 
     use tp1::{
         dato::Datos,
@@ -8,6 +8,7 @@ mod integration_tests {
         parsers::parser::parser,
         queries::{
             order_clause::{OrderClause, OrderDirection},
+            prepared_statement::PreparedStatement,
             sql_query::SQLQuery,
             where_clause::{
                 expresion_booleana::ExpresionBooleana, operador_comparacion::OperadorComparacion,
@@ -17,6 +18,52 @@ mod integration_tests {
         utils::procesar_consulta,
     };
 
+    /// Crea un directorio temporal para la prueba con una tabla `ordenes` de ejemplo.
+    fn preparar_directorio(nombre: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("sql_rustico_{}", nombre));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("ordenes.csv"),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,1\n102,2,Telefono,2\n103,1,Monitor,1\n",
+        )
+        .unwrap();
+        dir.to_string_lossy().to_string()
+    }
+
+    /// Agrega al directorio de la prueba una tabla `clientes` referenciada por `ordenes`.
+    fn agregar_clientes(path: &str) {
+        fs::write(
+            format!("{}/clientes.csv", path),
+            "id,nombre\n1,Juan\n2,Ana\n3,Luis\n",
+        )
+        .unwrap();
+        let create = "CREATE TABLE clientes (id INT PRIMARY KEY, nombre TEXT)";
+        assert!(procesar_consulta(&create.to_string(), path).is_ok());
+    }
+
+    /// Ejecuta el programa con las consultas dadas y devuelve lo que imprime por pantalla.
+    fn salida_consulta(path: &str, consulta: &str) -> String {
+        let salida = std::process::Command::new(env!("CARGO_BIN_EXE_tp1"))
+            .args([path, consulta])
+            .output()
+            .unwrap();
+        String::from_utf8(salida.stdout).unwrap()
+    }
+
+    /// Lee el contenido completo del CSV de una tabla.
+    fn leer_tabla(path: &str, tabla: &str) -> String {
+        fs::read_to_string(format!("{}/{}.csv", path, tabla)).unwrap()
+    }
+
+    /// Prepara la consulta sobre las tablas del directorio de la prueba.
+    fn preparar(path: &str, consulta: &String) -> PreparedStatement {
+        match PreparedStatement::preparar(path, consulta) {
+            Ok(preparada) => preparada,
+            Err(e) => panic!("Error al preparar '{}': {}", consulta, e),
+        }
+    }
+
     /// LEXER Y PARSER
     #[test]
     fn test_parser_insert_query() {
@@ -24,7 +71,7 @@ mod integration_tests {
         let query: Vec<Operador> = match lexer(&input) {
             Ok(query) => query,
             Err(e) => {
-                println!("{}", e.to_string());
+                println!("{}", e);
                 Vec::new()
             }
         };
@@ -36,16 +83,12 @@ mod integration_tests {
             "producto".to_string(),
             "cantidad".to_string(),
         ];
-        let mut expected_values = Vec::new();
-        let mut row1 = HashMap::new();
-        row1.insert("id".to_string(), Datos::Integer(111));
-        row1.insert("id_cliente".to_string(), Datos::Integer(6));
-        row1.insert(
-            "producto".to_string(),
+        let expected_values = vec![vec![
+            Datos::Integer(111),
+            Datos::Integer(6),
             Datos::String("laptop hola".to_string()),
-        );
-        row1.insert("cantidad".to_string(), Datos::Integer(3));
-        expected_values.push(row1);
+            Datos::Integer(3),
+        ]];
 
         match parser(&query) {
             Ok(insert_query) => match insert_query {
@@ -59,7 +102,7 @@ mod integration_tests {
                 }
                 _ => println!("La prueba falló al matchear con INSERT"),
             },
-            Err(e) => println!("La prueba falló con el error: {}", e.to_string()),
+            Err(e) => println!("La prueba falló con el error: {}", e),
         }
     }
 
@@ -69,7 +112,7 @@ mod integration_tests {
         let query: Vec<Operador> = match lexer(&input) {
             Ok(query) => query,
             Err(e) => {
-                println!("{}", e.to_string());
+                println!("{}", e);
                 Vec::new()
             }
         };
@@ -97,7 +140,7 @@ mod integration_tests {
                 println!("Update query fue exitoso");
             }
             Ok(_) => println!("La prueba falló al matchear con UPDATE"),
-            Err(e) => println!("La prueba falló con el error: {}", e.to_string()),
+            Err(e) => println!("La prueba falló con el error: {}", e),
         }
     }
 
@@ -107,7 +150,7 @@ mod integration_tests {
         let query: Vec<Operador> = match lexer(&input) {
             Ok(query) => query,
             Err(e) => {
-                println!("{}", e.to_string());
+                println!("{}", e);
                 Vec::new()
             }
         };
@@ -126,7 +169,7 @@ mod integration_tests {
                 println!("Delete query fue exitoso");
             }
             Ok(_) => println!("La prueba falló al matchear con DELETE"),
-            Err(e) => println!("La prueba falló con el error: {}", e.to_string()),
+            Err(e) => println!("La prueba falló con el error: {}", e),
         }
     }
 
@@ -137,7 +180,7 @@ mod integration_tests {
         let query: Vec<Operador> = match lexer(&input) {
             Ok(query) => query,
             Err(e) => {
-                println!("{}", e.to_string());
+                println!("{}", e);
                 Vec::new()
             }
         };
@@ -152,6 +195,8 @@ mod integration_tests {
         let expected_order_by = Some(vec![OrderClause {
             column: "nombre".to_string(),
             direccion: OrderDirection::Asc,
+            expresion: None,
+            nulos: None,
         }]);
 
         match parser(&query) {
@@ -163,7 +208,7 @@ mod integration_tests {
                 println!("Select query fue exitoso");
             }
             Ok(_) => println!("La prueba falló al matchear con SELECT"),
-            Err(e) => println!("La prueba falló con el error: {}", e.to_string()),
+            Err(e) => println!("La prueba falló con el error: {}", e),
         }
     }
 
@@ -173,7 +218,7 @@ mod integration_tests {
         let input = "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (110, 6, 'laptop hola' , 3), (111, 6, 'laptop hola' , 3)".to_string();
         let path = "../tp1/pruebas".to_string();
         if let Err(e) = procesar_consulta(&input, &path) {
-            println!("{}", e.to_string());
+            println!("{}", e);
         }
     }
 
@@ -182,7 +227,7 @@ mod integration_tests {
         let input = "DELETE FROM ordenes WHERE id <= 111".to_string();
         let path = "../tp1/pruebas".to_string();
         if let Err(e) = procesar_consulta(&input, &path) {
-            println!("{}", e.to_string());
+            println!("{}", e);
         }
     }
 
@@ -191,7 +236,7 @@ mod integration_tests {
         let input = "UPDATE ordenes SET id = 116 WHERE id = 110".to_string();
         let path = "../tp1/pruebas".to_string();
         if let Err(e) = procesar_consulta(&input, &path) {
-            println!("{}", e.to_string());
+            println!("{}", e);
         }
     }
 
@@ -200,7 +245,1070 @@ mod integration_tests {
         let input = "SELECT * FROM ordenes ORDER BY id".to_string();
         let path = "../tp1/pruebas".to_string();
         if let Err(e) = procesar_consulta(&input, &path) {
-            println!("{}", e.to_string());
+            println!("{}", e);
+        }
+    }
+
+    #[test]
+    fn test_select_enteros_de_64_bits() {
+        let path = preparar_directorio("enteros_64_bits");
+        fs::write(
+            format!("{}/ordenes.csv", path),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,10000000000\n102,2,Telefono,3000000000\n103,1,Monitor,9\n",
+        )
+        .unwrap();
+
+        let consulta = "SELECT id FROM ordenes WHERE cantidad > 2147483647 ORDER BY cantidad";
+        assert_eq!(salida_consulta(&path, consulta), "id\n102\n101\n");
+        let consulta = "SELECT producto FROM ordenes WHERE cantidad = 10000000000";
+        assert_eq!(salida_consulta(&path, consulta), "producto\nLaptop\n");
+    }
+
+    /// RESTRICCIONES
+    #[test]
+    fn test_create_table_nueva() {
+        let path = preparar_directorio("create_table_nueva");
+        let input = "CREATE TABLE clientes (id INT PRIMARY KEY, nombre TEXT, email TEXT UNIQUE)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(leer_tabla(&path, "clientes"), "id,nombre,email\n");
+        let esquema = fs::read_to_string(format!("{}/clientes.esquema", path)).unwrap();
+        assert_eq!(
+            esquema,
+            "CREATE TABLE clientes (id INT, nombre TEXT, email TEXT, PRIMARY KEY (id), UNIQUE (email))\n"
+        );
+    }
+
+    #[test]
+    fn test_primary_key_insert_duplicado() {
+        let path = preparar_directorio("primary_key_insert");
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT, producto TEXT, cantidad INT)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let original = leer_tabla(&path, "ordenes");
+
+        let input = "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (104, 2, 'Mouse', 1), (101, 3, 'Teclado', 1)";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert_eq!(
+                e.to_string(),
+                "[CONSTRAINT_VIOLATION]: Valor duplicado para PRIMARY KEY (id): (101)"
+            ),
+            Ok(_) => panic!("Se esperaba un error de clave primaria duplicada"),
+        }
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+        assert!(!std::path::Path::new(&format!("{}/auxiliar.csv", path)).exists());
+    }
+
+    #[test]
+    fn test_unique_update_duplicado() {
+        let path = preparar_directorio("unique_update");
+        let esquema = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT, producto TEXT UNIQUE, cantidad INT)";
+        fs::write(format!("{}/ordenes.esquema", path), esquema).unwrap();
+        let original = leer_tabla(&path, "ordenes");
+
+        let input = "UPDATE ordenes SET producto = 'Laptop' WHERE id = 103";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().contains("UNIQUE (producto): (Laptop)")),
+            Ok(_) => panic!("Se esperaba un error de valor duplicado"),
+        }
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+
+        let input = "UPDATE ordenes SET producto = 'Tablet' WHERE id = 103";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert!(leer_tabla(&path, "ordenes").contains("103,1,Tablet,1"));
+    }
+
+    #[test]
+    fn test_foreign_key_insert_sin_padre() {
+        let path = preparar_directorio("foreign_key_insert");
+        agregar_clientes(&path);
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT REFERENCES clientes(id), producto TEXT, cantidad INT)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let original = leer_tabla(&path, "ordenes");
+
+        let input =
+            "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (104, 7, 'Mouse', 1)";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().contains("No existe (7) en clientes")),
+            Ok(_) => panic!("Se esperaba un error de foreign key"),
+        }
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+
+        let input = "UPDATE ordenes SET id_cliente = 9 WHERE id = 101";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+    }
+
+    #[test]
+    fn test_foreign_key_on_delete_restrict() {
+        let path = preparar_directorio("foreign_key_restrict");
+        agregar_clientes(&path);
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT, producto TEXT, cantidad INT, FOREIGN KEY (id_cliente) REFERENCES clientes (id) ON DELETE RESTRICT)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let clientes = leer_tabla(&path, "clientes");
+
+        let input = "DELETE FROM clientes WHERE id = 2";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().starts_with("[CONSTRAINT_VIOLATION]")),
+            Ok(_) => panic!("Se esperaba un error de foreign key"),
+        }
+        let input = "UPDATE clientes SET id = 9 WHERE id = 2";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(leer_tabla(&path, "clientes"), clientes);
+
+        let input = "DELETE FROM clientes WHERE id = 3";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(leer_tabla(&path, "clientes"), "id,nombre\n1,Juan\n2,Ana\n");
+    }
+
+    #[test]
+    fn test_foreign_key_on_delete_cascade_y_set_null() {
+        let path = preparar_directorio("foreign_key_cascade");
+        agregar_clientes(&path);
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT REFERENCES clientes(id) ON DELETE CASCADE, producto TEXT, cantidad INT)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        fs::write(
+            format!("{}/envios.csv", path),
+            "id,id_orden\n1,101\n2,102\n",
+        )
+        .unwrap();
+        let create = "CREATE TABLE envios (id INT PRIMARY KEY, id_orden INT REFERENCES ordenes(id) ON DELETE SET NULL)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+
+        let input = "DELETE FROM clientes WHERE id = 1";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(leer_tabla(&path, "clientes"), "id,nombre\n2,Ana\n3,Luis\n");
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n102,2,Telefono,2\n"
+        );
+        assert_eq!(leer_tabla(&path, "envios"), "id,id_orden\n1,\n2,102\n");
+    }
+
+    #[test]
+    fn test_default_not_null_y_autoincrement() {
+        let path = preparar_directorio("default_autoincrement");
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY AUTOINCREMENT, id_cliente INT DEFAULT 0, producto TEXT NOT NULL, cantidad INT DEFAULT 1)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+
+        let input = "INSERT INTO ordenes (producto) VALUES ('Teclado')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        let input = "INSERT INTO ordenes (id_cliente, producto, cantidad) VALUES (2, 'Mouse', 3)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,1\n102,2,Telefono,2\n103,1,Monitor,1\n104,0,Teclado,1\n105,2,Mouse,3\n"
+        );
+        assert_eq!(
+            fs::read_to_string(format!("{}/ordenes.secuencia", path)).unwrap(),
+            "105\n"
+        );
+
+        let original = leer_tabla(&path, "ordenes");
+        let input = "INSERT INTO ordenes (id_cliente, cantidad) VALUES (3, 2)";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().starts_with("[CONSTRAINT_VIOLATION]")),
+            Ok(_) => panic!("Se esperaba un error de NOT NULL"),
+        }
+        let input = "UPDATE ordenes SET producto = NULL WHERE id = 101";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+
+        let create = "CREATE TABLE notas (a TEXT, b INT, c TEXT DEFAULT 'z')";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let input = "INSERT INTO notas (b) VALUES (5)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(leer_tabla(&path, "notas"), "a,b,c\n,5,z\n");
+        assert_eq!(
+            salida_consulta(&path, "SELECT * FROM notas"),
+            "a,b,c\n,5,z\n"
+        );
+
+        let input = "INSERT INTO ordenes (id, producto) VALUES (9223372036854775807, 'Ultimo')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        let original = leer_tabla(&path, "ordenes");
+        let input = "INSERT INTO ordenes (producto) VALUES ('Otro')";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().starts_with("[CONSTRAINT_VIOLATION]")),
+            Ok(_) => panic!("Se esperaba un error por no quedar valores de AUTOINCREMENT"),
+        }
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+        assert_eq!(
+            fs::read_to_string(format!("{}/ordenes.secuencia", path)).unwrap(),
+            "9223372036854775807\n"
+        );
+
+        agregar_clientes(&path);
+        let input = "INSERT INTO clientes (nombre) VALUES ('nokey')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(
+            leer_tabla(&path, "clientes"),
+            "id,nombre\n1,Juan\n2,Ana\n3,Luis\n"
+        );
+    }
+
+    #[test]
+    fn test_check_insert_y_update() {
+        let path = preparar_directorio("check");
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT, producto TEXT, cantidad INT CHECK (cantidad > 0), CONSTRAINT cantidad_maxima CHECK (cantidad <= 5 OR producto = 'Cable'))";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        assert_eq!(
+            fs::read_to_string(format!("{}/ordenes.esquema", path)).unwrap(),
+            "CREATE TABLE ordenes (id INT, id_cliente INT, producto TEXT, cantidad INT, PRIMARY KEY (id), CONSTRAINT ordenes_check1 CHECK (cantidad > 0), CONSTRAINT cantidad_maxima CHECK (cantidad <= 5 OR producto = 'Cable'))\n"
+        );
+        let original = leer_tabla(&path, "ordenes");
+
+        let input = "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (104, 2, 'Mouse', 1), (105, 2, 'Mouse', 0)";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().contains("ordenes_check1")),
+            Ok(_) => panic!("Se esperaba un error de CHECK"),
+        }
+        let input = "UPDATE ordenes SET cantidad = 10 WHERE id_cliente = 1";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().contains("cantidad_maxima")),
+            Ok(_) => panic!("Se esperaba un error de CHECK"),
+        }
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+
+        let input = "INSERT INTO ordenes (id, id_cliente, producto, cantidad) VALUES (104, 2, 'Cable', 10), (105, 3, 'Mouse', '')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            format!("{}104,2,Cable,10\n105,3,Mouse,\n", original)
+        );
+    }
+
+    #[test]
+    fn test_insert_sin_columnas_y_insert_select() {
+        let path = preparar_directorio("insert_select");
+        let input = "INSERT INTO ordenes VALUES (104, 3, 'Mouse', 5)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        let input = "INSERT INTO ordenes VALUES (105, 3, 'Mouse')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+
+        fs::write(format!("{}/archivo.csv", path), "id,producto\n1,Cable\n").unwrap();
+        let input = "INSERT INTO archivo (producto, id) SELECT producto, id FROM ordenes WHERE id_cliente = 1 ORDER BY id DESC";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        let input = "INSERT INTO archivo SELECT id, producto FROM ordenes WHERE cantidad > 1";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "archivo"),
+            "id,producto\n1,Cable\n103,Monitor\n101,Laptop\n102,Telefono\n104,Mouse\n"
+        );
+
+        let original = leer_tabla(&path, "archivo");
+        let input = "INSERT INTO archivo SELECT * FROM ordenes";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(leer_tabla(&path, "archivo"), original);
+        let restantes: Vec<_> = fs::read_dir(&path).unwrap().collect();
+        assert_eq!(restantes.len(), 2);
+    }
+
+    #[test]
+    fn test_insert_select_por_lotes() {
+        let path = preparar_directorio("insert_por_lotes");
+        let mut contenido = "id,producto\n".to_string();
+        for id in 1..=2500 {
+            let producto = if id == 2400 {
+                String::new()
+            } else {
+                format!("p{}", id)
+            };
+            contenido.push_str(&format!("{},{}\n", id, producto));
         }
+        fs::write(format!("{}/productos.csv", path), contenido).unwrap();
+        let create =
+            "CREATE TABLE archivo (id INT PRIMARY KEY AUTOINCREMENT, producto TEXT NOT NULL)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+
+        let input =
+            "INSERT INTO archivo (producto) SELECT producto FROM productos WHERE id <= 2300";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        let archivo = leer_tabla(&path, "archivo");
+        assert_eq!(archivo.lines().count(), 2301);
+        assert!(archivo.ends_with("2299,p2299\n2300,p2300\n"));
+        let secuencia = format!("{}/archivo.secuencia", path);
+        assert_eq!(fs::read_to_string(&secuencia).unwrap(), "2300\n");
+
+        let input = "INSERT INTO archivo (producto) SELECT producto FROM productos";
+        match procesar_consulta(&input.to_string(), &path) {
+            Err(e) => assert!(e.to_string().starts_with("[CONSTRAINT_VIOLATION]")),
+            Ok(_) => panic!("Se esperaba un error de NOT NULL"),
+        }
+        assert_eq!(leer_tabla(&path, "archivo"), archivo);
+        assert_eq!(fs::read_to_string(&secuencia).unwrap(), "2300\n");
+        assert!(!Path::new(&format!("{}/archivo.insercion", path)).exists());
+    }
+
+    #[test]
+    fn test_insert_recupera_insercion_incompleta() {
+        let path = preparar_directorio("insercion_incompleta");
+        let original = leer_tabla(&path, "ordenes");
+        fs::write(
+            format!("{}/ordenes.csv", path),
+            format!("{}104,2,Mo", original),
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/ordenes.insercion", path),
+            original.len().to_string(),
+        )
+        .unwrap();
+
+        let input = "INSERT INTO ordenes VALUES (104, 2, 'Mouse', 1), (105, 3, 'Cable', 2)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            format!("{}104,2,Mouse,1\n105,3,Cable,2\n", original)
+        );
+        assert!(!std::path::Path::new(&format!("{}/ordenes.insercion", path)).exists());
+
+        fs::write(format!("{}/archivo.csv", path), "id,producto\n1,Cable").unwrap();
+        let input = "INSERT INTO archivo VALUES (2, 'Mouse')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "archivo"),
+            "id,producto\n1,Cable\n2,Mouse\n"
+        );
+    }
+
+    #[test]
+    fn test_returning() {
+        let path = preparar_directorio("returning");
+        let input = "INSERT INTO ordenes (id, producto) VALUES (104, 'Mouse'), (105, 'Cable') RETURNING id, producto";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,producto\n104,Mouse\n105,Cable\n"
+        );
+        let input = "UPDATE ordenes SET cantidad = 5 WHERE id_cliente = 1 RETURNING *";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,5\n103,1,Monitor,5\n"
+        );
+        let input = "DELETE FROM ordenes WHERE cantidad = 5 RETURNING producto";
+        assert_eq!(salida_consulta(&path, input), "producto\nLaptop\nMonitor\n");
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n102,2,Telefono,2\n104,,Mouse,\n105,,Cable,\n"
+        );
+
+        let original = leer_tabla(&path, "ordenes");
+        let input = "DELETE FROM ordenes RETURNING precio";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+    }
+
+    #[test]
+    fn test_insert_on_conflict() {
+        let path = preparar_directorio("on_conflict");
+        let create = "CREATE TABLE ordenes (id INT PRIMARY KEY, id_cliente INT, producto TEXT, cantidad INT)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+
+        let input = "INSERT INTO ordenes VALUES (101, 1, 'Laptop', 4), (104, 2, 'Mouse', 1) ON CONFLICT (id) DO UPDATE SET cantidad = excluded.cantidad RETURNING *";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,4\n104,2,Mouse,1\n"
+        );
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,4\n102,2,Telefono,2\n103,1,Monitor,1\n104,2,Mouse,1\n"
+        );
+
+        let input = "INSERT INTO ordenes VALUES (102, 2, 'Tablet', 9), (105, 3, 'Cable', 2) ON CONFLICT (id) DO NOTHING";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,4\n102,2,Telefono,2\n103,1,Monitor,1\n104,2,Mouse,1\n105,3,Cable,2\n"
+        );
+
+        let original = leer_tabla(&path, "ordenes");
+        let input =
+            "INSERT INTO ordenes VALUES (106, 1, 'Teclado', 1) ON CONFLICT (producto) DO NOTHING";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "INSERT INTO ordenes VALUES (101, 1, 'Laptop', 1), (101, 1, 'Laptop', 2) ON CONFLICT (id) DO UPDATE SET cantidad = excluded.cantidad";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert_eq!(leer_tabla(&path, "ordenes"), original);
+    }
+
+    #[test]
+    fn test_subconsultas() {
+        let path = preparar_directorio("subconsultas");
+        agregar_clientes(&path);
+
+        let input = "SELECT nombre FROM clientes WHERE id IN (SELECT id_cliente FROM ordenes WHERE cantidad > 1)";
+        assert_eq!(salida_consulta(&path, input), "nombre\nAna\n");
+        let input = "SELECT nombre FROM clientes WHERE id NOT IN (SELECT id_cliente FROM ordenes)";
+        assert_eq!(salida_consulta(&path, input), "nombre\nLuis\n");
+        let input = "SELECT nombre FROM clientes WHERE EXISTS (SELECT * FROM ordenes WHERE id_cliente = clientes.id AND producto = 'Monitor')";
+        assert_eq!(salida_consulta(&path, input), "nombre\nJuan\n");
+        let input = "SELECT nombre FROM clientes WHERE NOT EXISTS (SELECT * FROM ordenes WHERE ordenes.id_cliente = clientes.id)";
+        assert_eq!(salida_consulta(&path, input), "nombre\nLuis\n");
+        let input = "SELECT producto FROM ordenes WHERE id_cliente = (SELECT id FROM clientes WHERE nombre = 'Ana')";
+        assert_eq!(salida_consulta(&path, input), "producto\nTelefono\n");
+        let input = "SELECT producto FROM ordenes WHERE cantidad >= (SELECT id FROM clientes WHERE id = id_cliente)";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "producto\nLaptop\nTelefono\nMonitor\n"
+        );
+
+        let input = "SELECT nombre FROM clientes WHERE id = (SELECT id_cliente FROM ordenes)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "SELECT nombre FROM clientes WHERE id IN (SELECT * FROM ordenes)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+
+        let input = "UPDATE ordenes SET cantidad = 0 WHERE id_cliente IN (SELECT id FROM clientes WHERE nombre = 'Juan')";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        let input = "DELETE FROM clientes WHERE NOT EXISTS (SELECT * FROM ordenes WHERE id_cliente = clientes.id)";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,0\n102,2,Telefono,2\n103,1,Monitor,0\n"
+        );
+        assert_eq!(leer_tabla(&path, "clientes"), "id,nombre\n1,Juan\n2,Ana\n");
+    }
+
+    #[test]
+    fn test_union_intersect_except() {
+        let path = preparar_directorio("conjuntos");
+        agregar_clientes(&path);
+
+        let input =
+            "SELECT id_cliente FROM ordenes UNION SELECT id FROM clientes ORDER BY id_cliente DESC";
+        assert_eq!(salida_consulta(&path, input), "id_cliente\n3\n2\n1\n");
+        let input = "SELECT id_cliente FROM ordenes UNION ALL SELECT id FROM clientes WHERE id = 1 ORDER BY id_cliente LIMIT 3";
+        assert_eq!(salida_consulta(&path, input), "id_cliente\n1\n1\n1\n");
+        let input = "SELECT id FROM clientes INTERSECT SELECT id_cliente FROM ordenes ORDER BY id";
+        assert_eq!(salida_consulta(&path, input), "id\n1\n2\n");
+        let input = "SELECT id FROM clientes EXCEPT SELECT id_cliente FROM ordenes";
+        assert_eq!(salida_consulta(&path, input), "id\n3\n");
+        let input = "SELECT id FROM clientes WHERE id = 3 UNION SELECT id FROM clientes INTERSECT SELECT id_cliente FROM ordenes ORDER BY id";
+        assert_eq!(salida_consulta(&path, input), "id\n1\n2\n3\n");
+        let input = "SELECT producto FROM ordenes ORDER BY producto LIMIT 2";
+        assert_eq!(salida_consulta(&path, input), "producto\nLaptop\nMonitor\n");
+        let input = "SELECT id FROM clientes WHERE id > 5 UNION SELECT id_cliente FROM ordenes WHERE id_cliente > 5 ORDER BY id DESC";
+        assert_eq!(salida_consulta(&path, input), "id\n");
+        let input = "SELECT id FROM clientes INTERSECT SELECT cantidad FROM ordenes WHERE cantidad > 5 ORDER BY id";
+        assert_eq!(salida_consulta(&path, input), "id\n");
+        let input = "SELECT id FROM clientes EXCEPT SELECT id FROM clientes ORDER BY id";
+        assert_eq!(salida_consulta(&path, input), "id\n");
+        let input = "SELECT id FROM ordenes WHERE id > 200 ORDER BY id";
+        assert_eq!(salida_consulta(&path, input), "id\n");
+        assert_eq!(fs::read_dir(&path).unwrap().count(), 3);
+
+        let input = "SELECT id, nombre FROM clientes UNION SELECT id FROM ordenes";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "SELECT id FROM clientes ORDER BY id UNION SELECT id FROM ordenes";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+    }
+
+    #[test]
+    fn test_select_distinct_count() {
+        let path = preparar_directorio("distinct");
+        agregar_clientes(&path);
+
+        let input = "SELECT DISTINCT id_cliente FROM ordenes ORDER BY id_cliente DESC";
+        assert_eq!(salida_consulta(&path, input), "id_cliente\n2\n1\n");
+        let input = "SELECT DISTINCT id_cliente, cantidad FROM ordenes WHERE id > 101 ORDER BY id_cliente LIMIT 1";
+        assert_eq!(salida_consulta(&path, input), "id_cliente,cantidad\n1,1\n");
+        let input = "SELECT COUNT(*), COUNT(id_cliente), COUNT(DISTINCT id_cliente) FROM ordenes";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "COUNT(*),COUNT(id_cliente),COUNT(DISTINCT id_cliente)\n3,3,2\n"
+        );
+        let input = "SELECT COUNT(*) FROM ordenes WHERE cantidad > 5";
+        assert_eq!(salida_consulta(&path, input), "COUNT(*)\n0\n");
+        let input = "SELECT nombre FROM clientes WHERE (SELECT COUNT(*) FROM ordenes WHERE id_cliente = clientes.id) > 1";
+        assert_eq!(salida_consulta(&path, input), "nombre\nJuan\n");
+        let input = "SELECT nombre FROM clientes WHERE id IN (SELECT DISTINCT id_cliente FROM ordenes) ORDER BY nombre";
+        assert_eq!(salida_consulta(&path, input), "nombre\nAna\nJuan\n");
+
+        let input = "SELECT DISTINCT id_cliente FROM ordenes ORDER BY producto";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "SELECT COUNT(DISTINCT precio) FROM ordenes";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+    }
+
+    #[test]
+    fn test_case_when() {
+        let path = preparar_directorio("case_when");
+
+        let input = "SELECT id, CASE WHEN cantidad > 1 THEN 'bulk' ELSE 'retail' END FROM ordenes ORDER BY id";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,CASE WHEN cantidad > 1 THEN 'bulk' ELSE 'retail' END\n101,retail\n102,bulk\n103,retail\n"
+        );
+        let input = "SELECT producto FROM ordenes WHERE CASE id_cliente WHEN 1 THEN 'uno' WHEN 2 THEN 'dos' END = 'uno' ORDER BY CASE WHEN producto = 'Monitor' THEN 0 ELSE 1 END";
+        assert_eq!(salida_consulta(&path, input), "producto\nMonitor\nLaptop\n");
+
+        let input = "UPDATE ordenes SET producto = CASE WHEN cantidad > 1 THEN 'Lote' ELSE producto END, cantidad = CASE cantidad WHEN 1 THEN 10 END";
+        assert!(procesar_consulta(&input.to_string(), &path).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n101,1,Laptop,10\n102,2,Lote,\n103,1,Monitor,10\n"
+        );
+
+        let input = "SELECT id FROM ordenes WHERE CASE WHEN cantidad > 1 THEN 'bulk' = 'bulk'";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+    }
+
+    #[test]
+    fn test_with_join() {
+        let path = preparar_directorio("with_join");
+        agregar_clientes(&path);
+
+        let input = "SELECT nombre, producto FROM clientes JOIN ordenes ON clientes.id = id_cliente WHERE cantidad = 1 ORDER BY producto";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "nombre,producto\nJuan,Laptop\nJuan,Monitor\n"
+        );
+        let input = "WITH juan AS (SELECT id, nombre FROM clientes WHERE nombre = 'Juan'), compras AS (SELECT id_cliente, producto FROM ordenes JOIN juan ON juan.id = id_cliente), sin_usar AS (SELECT * FROM no_existe) SELECT juan.nombre, producto FROM juan JOIN compras ON juan.id = compras.id_cliente ORDER BY producto DESC";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "juan.nombre,producto\nJuan,Monitor\nJuan,Laptop\n"
+        );
+        let input = "WITH c (cliente) AS (SELECT DISTINCT id_cliente FROM ordenes) SELECT nombre FROM clientes WHERE id IN (SELECT cliente FROM c) AND id < (SELECT COUNT(*) FROM c) ORDER BY nombre";
+        assert_eq!(salida_consulta(&path, input), "nombre\nJuan\n");
+        let auxiliares = fs::read_dir(&path)
+            .unwrap()
+            .filter(|e| {
+                let nombre = e.as_ref().unwrap().file_name();
+                let nombre = nombre.to_string_lossy();
+                nombre.starts_with("cte_") || nombre.starts_with("auxiliar_")
+            })
+            .count();
+        assert_eq!(auxiliares, 0);
+
+        let input = "SELECT id FROM clientes JOIN ordenes ON clientes.id = id_cliente";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "WITH c (a, b) AS (SELECT id FROM clientes) SELECT a FROM c";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "WITH c AS (SELECT id FROM clientes) DELETE FROM c";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+    }
+
+    #[test]
+    fn test_with_recursive() {
+        let path = preparar_directorio("with_recursive");
+        fs::write(
+            format!("{}/empleados.csv", path),
+            "id,nombre,id_jefe\n1,Ana,\n2,Juan,1\n3,Luis,2\n4,Sofia,2\n5,Pedro,\n",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/ciclo.csv", path),
+            "id,siguiente\n1,2\n2,3\n3,1\n",
+        )
+        .unwrap();
+
+        let input = "WITH RECURSIVE equipo (id, nombre) AS (SELECT id, nombre FROM empleados WHERE id = 2 UNION ALL SELECT empleados.id, empleados.nombre FROM empleados JOIN equipo ON id_jefe = equipo.id) SELECT nombre FROM equipo ORDER BY nombre";
+        assert_eq!(salida_consulta(&path, input), "nombre\nJuan\nLuis\nSofia\n");
+        let input = "WITH RECURSIVE alcanzables (id) AS (SELECT id FROM ciclo WHERE id = 1 UNION SELECT siguiente FROM ciclo JOIN alcanzables ON ciclo.id = alcanzables.id) SELECT COUNT(*) FROM alcanzables";
+        assert_eq!(salida_consulta(&path, input), "COUNT(*)\n3\n");
+
+        let input = "WITH RECURSIVE alcanzables (id) AS (SELECT id FROM ciclo WHERE id = 1 UNION ALL SELECT siguiente FROM ciclo JOIN alcanzables ON ciclo.id = alcanzables.id) SELECT id FROM alcanzables OPTION (MAXRECURSION 20)";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "[ERROR]: La expresión recursiva 'alcanzables' superó el máximo de 20 iteraciones\n"
+        );
+        let input =
+            "WITH RECURSIVE r AS (SELECT id FROM r UNION SELECT id FROM ciclo) SELECT id FROM r";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let auxiliares = fs::read_dir(&path).unwrap().count();
+        assert_eq!(auxiliares, 3);
+    }
+
+    #[test]
+    fn test_vistas() {
+        let path = preparar_directorio("vistas");
+        agregar_clientes(&path);
+
+        let create = "CREATE VIEW compras_juan AS SELECT id, producto FROM ordenes WHERE id_cliente = 1 AND cantidad < 5";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let create = "CREATE VIEW productos AS SELECT producto FROM compras_juan UNION SELECT nombre FROM clientes WHERE id = 3";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        assert!(fs::read_to_string(format!("{}/compras_juan.vista", path))
+            .unwrap()
+            .starts_with("CREATE VIEW compras_juan AS SELECT id, producto FROM ordenes WHERE"));
+
+        let input = "SELECT * FROM compras_juan ORDER BY producto";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,producto\n101,Laptop\n103,Monitor\n"
+        );
+        let input = "SELECT producto FROM productos ORDER BY producto DESC";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "producto\nMonitor\nLuis\nLaptop\n"
+        );
+        let input = "SELECT nombre FROM clientes WHERE EXISTS (SELECT id FROM compras_juan WHERE producto = 'Monitor') AND id IN (SELECT id_cliente FROM ordenes JOIN compras_juan ON ordenes.id = compras_juan.id)";
+        assert_eq!(salida_consulta(&path, input), "nombre\nJuan\n");
+
+        let insert = "INSERT INTO ordenes VALUES (104, 1, 'Mouse', 2)";
+        assert!(procesar_consulta(&insert.to_string(), &path).is_ok());
+        let input = "SELECT COUNT(*) FROM compras_juan";
+        assert_eq!(salida_consulta(&path, input), "COUNT(*)\n3\n");
+
+        let delete = "DELETE FROM compras_juan";
+        assert!(procesar_consulta(&delete.to_string(), &path).is_err());
+        let create = "CREATE VIEW clientes AS SELECT id FROM ordenes";
+        assert!(procesar_consulta(&create.to_string(), &path).is_err());
+        let create = "CREATE VIEW otra AS SELECT id FROM no_existe";
+        assert!(procesar_consulta(&create.to_string(), &path).is_err());
+
+        let drop_base = "DROP VIEW compras_juan".to_string();
+        match procesar_consulta(&drop_base, &path) {
+            Err(e) => assert!(e.to_string().contains("la usa la vista 'productos'")),
+            Ok(_) => panic!("Se esperaba un error por la vista que la usa"),
+        }
+        assert!(Path::new(&format!("{}/compras_juan.vista", path)).exists());
+        let drop = "DROP VIEW productos";
+        assert!(procesar_consulta(&drop.to_string(), &path).is_ok());
+        assert!(procesar_consulta(&drop.to_string(), &path).is_err());
+        let input = "SELECT producto FROM productos";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        assert!(procesar_consulta(&drop_base, &path).is_ok());
+        let auxiliares = fs::read_dir(&path)
+            .unwrap()
+            .filter(|e| {
+                let nombre = e.as_ref().unwrap().file_name();
+                nombre.to_string_lossy().starts_with("cte_")
+            })
+            .count();
+        assert_eq!(auxiliares, 0);
+    }
+
+    #[test]
+    fn test_vistas_materializadas() {
+        let path = preparar_directorio("vistas_materializadas");
+        agregar_clientes(&path);
+
+        let create =
+            "CREATE VIEW juan AS SELECT producto, cantidad FROM ordenes WHERE id_cliente = 1";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let create = "CREATE MATERIALIZED VIEW total_juan AS SELECT COUNT(*) FROM juan";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let create =
+            "CREATE MATERIALIZED VIEW productos AUTO REFRESH AS SELECT producto FROM ordenes";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        assert_eq!(leer_tabla(&path, "total_juan"), "COUNT(*)\n2\n");
+        assert!(fs::read_to_string(format!("{}/total_juan.fuentes", path))
+            .unwrap()
+            .starts_with("ordenes,"));
+
+        let insert = "INSERT INTO ordenes VALUES (104, 1, 'Mouse', 2)";
+        assert!(procesar_consulta(&insert.to_string(), &path).is_ok());
+        let salida = std::process::Command::new(env!("CARGO_BIN_EXE_tp1"))
+            .args([path.as_str(), "SELECT * FROM total_juan"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(salida.stdout).unwrap(), "COUNT(*)\n2\n");
+        assert!(String::from_utf8(salida.stderr)
+            .unwrap()
+            .contains("vista materializada 'total_juan'"));
+        let input = "SELECT producto FROM productos ORDER BY producto";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "producto\nLaptop\nMonitor\nMouse\nTelefono\n"
+        );
+
+        let refresh = "REFRESH MATERIALIZED VIEW total_juan";
+        assert!(procesar_consulta(&refresh.to_string(), &path).is_ok());
+        assert_eq!(
+            salida_consulta(&path, "SELECT * FROM total_juan"),
+            "COUNT(*)\n3\n"
+        );
+
+        let delete = "DELETE FROM total_juan";
+        assert!(procesar_consulta(&delete.to_string(), &path).is_err());
+        let drop = "DROP VIEW total_juan";
+        assert!(procesar_consulta(&drop.to_string(), &path).is_err());
+        let drop = "DROP MATERIALIZED VIEW juan";
+        assert!(procesar_consulta(&drop.to_string(), &path).is_err());
+        let refresh = "REFRESH MATERIALIZED VIEW juan";
+        assert!(procesar_consulta(&refresh.to_string(), &path).is_err());
+
+        let drop_juan = "DROP VIEW juan".to_string();
+        assert!(procesar_consulta(&drop_juan, &path).is_err());
+
+        let drop = "DROP MATERIALIZED VIEW total_juan";
+        assert!(procesar_consulta(&drop.to_string(), &path).is_ok());
+        for extension in ["csv", "vista", "fuentes"] {
+            assert!(!Path::new(&format!("{}/total_juan.{}", path, extension)).exists());
+        }
+        assert!(procesar_consulta(&drop_juan, &path).is_ok());
+    }
+
+    #[test]
+    fn test_funciones_de_ventana() {
+        let path = preparar_directorio("ventanas");
+        let insert = "INSERT INTO ordenes VALUES (104, 1, 'Mouse', 5), (105, 2, 'Cable', 3)";
+        assert!(procesar_consulta(&insert.to_string(), &path).is_ok());
+
+        let input = "SELECT id, ROW_NUMBER() OVER (PARTITION BY id_cliente ORDER BY id), RANK() OVER (ORDER BY cantidad), DENSE_RANK() OVER (ORDER BY cantidad) FROM ordenes ORDER BY id";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,ROW_NUMBER() OVER (PARTITION BY id_cliente ORDER BY id),RANK() OVER (ORDER BY cantidad),DENSE_RANK() OVER (ORDER BY cantidad)\n\
+             101,1,1,1\n102,1,3,2\n103,2,1,1\n104,3,5,4\n105,2,4,3\n"
+        );
+        let input = "SELECT id, LAG(cantidad) OVER (ORDER BY id), LEAD(producto, 2, 'nada') OVER (ORDER BY id) FROM ordenes";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,LAG(cantidad) OVER (ORDER BY id),LEAD(producto 2 'nada') OVER (ORDER BY id)\n\
+             101,,Monitor\n102,1,Mouse\n103,2,Cable\n104,1,nada\n105,5,nada\n"
+        );
+        let input = "SELECT id, SUM(cantidad) OVER (PARTITION BY id_cliente ORDER BY id ROWS BETWEEN 1 PRECEDING AND CURRENT ROW), SUM(cantidad) OVER (), COUNT(*) OVER (PARTITION BY id_cliente) FROM ordenes WHERE id > 101 ORDER BY id";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,SUM(cantidad) OVER (PARTITION BY id_cliente ORDER BY id ROWS BETWEEN 1 PRECEDING AND CURRENT ROW),SUM(cantidad) OVER (),COUNT(*) OVER (PARTITION BY id_cliente)\n\
+             102,2,11,2\n103,1,11,2\n104,6,11,2\n105,5,11,2\n"
+        );
+        let input = "SELECT id, ROW_NUMBER() OVER (PARTITION BY id_cliente ORDER BY id) FROM ordenes WHERE id > 200 ORDER BY id";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,ROW_NUMBER() OVER (PARTITION BY id_cliente ORDER BY id)\n"
+        );
+        assert_eq!(fs::read_dir(&path).unwrap().count(), 1);
+
+        let input = "SELECT id, SUM(producto) OVER () FROM ordenes";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+        let input = "SELECT id, LAG(no_existe) OVER (ORDER BY id) FROM ordenes";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+    }
+
+    #[test]
+    fn test_alias() {
+        let path = preparar_directorio("alias");
+        agregar_clientes(&path);
+
+        let input = "SELECT o.id AS orden, producto AS p FROM ordenes AS o WHERE o.cantidad >= 1 ORDER BY p DESC";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "orden,p\n102,Telefono\n103,Monitor\n101,Laptop\n"
+        );
+        let input = "SELECT c.nombre AS cliente, o.producto FROM ordenes o JOIN clientes AS c ON o.id_cliente = c.id ORDER BY cliente";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "cliente,o.producto\nAna,Telefono\nJuan,Laptop\nJuan,Monitor\n"
+        );
+        let input = "SELECT DISTINCT id_cliente AS cliente FROM ordenes ORDER BY id_cliente DESC";
+        assert_eq!(salida_consulta(&path, input), "cliente\n2\n1\n");
+        let input = "SELECT COUNT(*) AS total, COUNT(DISTINCT id_cliente) AS clientes FROM ordenes";
+        assert_eq!(salida_consulta(&path, input), "total,clientes\n3,2\n");
+        let input = "SELECT nombre FROM clientes c WHERE EXISTS (SELECT id FROM ordenes o WHERE o.id_cliente = c.id AND o.cantidad > 1)";
+        assert_eq!(salida_consulta(&path, input), "nombre\nAna\n");
+
+        let create = "CREATE VIEW productos AS SELECT o.producto AS nombre FROM ordenes o";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let input = "SELECT nombre FROM productos ORDER BY nombre";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "nombre\nLaptop\nMonitor\nTelefono\n"
+        );
+    }
+
+    #[test]
+    fn test_select_sin_from_y_values() {
+        let path = preparar_directorio("sin_from_y_values");
+
+        let input = "SELECT 1 + 2, UPPER('x')";
+        assert_eq!(salida_consulta(&path, input), "1 + 2,UPPER('x')\n3,X\n");
+        let input = "SELECT (1 + 2) * 3 - 8 / 2 AS total, LENGTH('hola') AS largo";
+        assert_eq!(salida_consulta(&path, input), "total,largo\n5,4\n");
+        assert!(procesar_consulta(&"SELECT 1 / 0".to_string(), &path).is_err());
+        assert!(procesar_consulta(&"SELECT *".to_string(), &path).is_err());
+
+        let input = "SELECT * FROM (VALUES (1,'a'), (2,'b')) AS v(id, name)";
+        assert_eq!(salida_consulta(&path, input), "id,name\n1,a\n2,b\n");
+        let input = "SELECT column2 FROM (VALUES (1,'a'), (2,'b')) v WHERE column1 > 1";
+        assert_eq!(salida_consulta(&path, input), "column2\nb\n");
+        let input = "SELECT producto, cantidad * 10 AS decenas FROM ordenes WHERE id_cliente IN (SELECT id FROM (VALUES (1)) AS c(id)) ORDER BY producto";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "producto,decenas\nLaptop,10\nMonitor,10\n"
+        );
+
+        let create = "CREATE VIEW estados AS SELECT * FROM (VALUES (1,'activo'), (2,'baja')) AS e(id, estado)";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        let input = "SELECT estado FROM estados WHERE id = 2";
+        assert_eq!(salida_consulta(&path, input), "estado\nbaja\n");
+        assert!(!Path::new(&format!("{}/e.csv", path)).exists());
+    }
+
+    #[test]
+    fn test_order_by_posiciones_expresiones_y_nulos() {
+        let path = preparar_directorio("order_by_extendido");
+        fs::write(
+            format!("{}/personas.csv", path),
+            "id,nombre,edad\n1,ana,30\n2,Bob,\n3,carla,25\n4,Dani,x\n5,eva,7\n",
+        )
+        .unwrap();
+
+        let input = "SELECT nombre, edad FROM personas ORDER BY 2";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "nombre,edad\nBob,\neva,7\ncarla,25\nana,30\nDani,x\n"
+        );
+        let input = "SELECT nombre, edad FROM personas ORDER BY edad NULLS LAST";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "nombre,edad\neva,7\ncarla,25\nana,30\nDani,x\nBob,\n"
+        );
+        let input = "SELECT nombre, edad FROM personas ORDER BY edad DESC NULLS FIRST LIMIT 3";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "nombre,edad\nBob,\nDani,x\nana,30\n"
+        );
+        let input = "SELECT nombre FROM personas ORDER BY LOWER(nombre)";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "nombre\nana\nBob\ncarla\nDani\neva\n"
+        );
+        let input = "SELECT nombre AS n FROM personas ORDER BY id * -1 LIMIT 2";
+        assert_eq!(salida_consulta(&path, input), "n\neva\nDani\n");
+        let input = "SELECT * FROM personas ORDER BY 1 DESC LIMIT 1";
+        assert_eq!(salida_consulta(&path, input), "id,nombre,edad\n5,eva,7\n");
+        let input = "SELECT id FROM ordenes UNION SELECT cantidad FROM ordenes ORDER BY 1 DESC";
+        assert_eq!(salida_consulta(&path, input), "id\n103\n102\n101\n2\n1\n");
+        let input = "SELECT nombre FROM personas ORDER BY 2";
+        assert!(procesar_consulta(&input.to_string(), &path).is_err());
+    }
+
+    #[test]
+    fn test_order_by_con_limit_usa_las_primeras_filas() {
+        let path = preparar_directorio("primeras_filas");
+        let mut contenido = "id,fecha,valor\n".to_string();
+        for id in 0..500 {
+            contenido.push_str(&format!("{},{},{}\n", id, (id * 37) % 101, id % 7));
+        }
+        fs::write(format!("{}/eventos.csv", path), contenido).unwrap();
+
+        let input = "SELECT id, fecha FROM eventos WHERE valor > 0 ORDER BY fecha DESC, id LIMIT 4";
+        assert_eq!(
+            salida_consulta(&path, input),
+            "id,fecha\n30,100\n131,100\n232,100\n333,100\n"
+        );
+        let input = "SELECT id FROM eventos ORDER BY fecha, id DESC LIMIT 2";
+        assert_eq!(salida_consulta(&path, input), "id\n404\n303\n");
+        let input = "SELECT id FROM ordenes ORDER BY id DESC LIMIT 1000000000000";
+        assert_eq!(salida_consulta(&path, input), "id\n103\n102\n101\n");
+        let input = "SELECT id FROM ordenes ORDER BY id LIMIT 18446744073709551615";
+        assert_eq!(salida_consulta(&path, input), "id\n101\n102\n103\n");
+        let input = "SELECT id FROM eventos ORDER BY id LIMIT 0";
+        assert_eq!(salida_consulta(&path, input), "id\n");
+        let input = "SELECT id FROM eventos WHERE id < 3 UNION SELECT valor FROM eventos ORDER BY id DESC LIMIT 3";
+        assert_eq!(salida_consulta(&path, input), "id\n6\n5\n4\n");
+    }
+
+    #[test]
+    fn test_prepared_statement() {
+        let path = preparar_directorio("prepared_statement");
+        agregar_clientes(&path);
+
+        let insert = "INSERT INTO clientes (id, nombre) VALUES (?, ?)".to_string();
+        let insert = preparar(&path, &insert);
+        assert_eq!(insert.cantidad_parametros(), 2);
+        let nombre = "Eva'); DELETE FROM clientes; --".to_string();
+        for (id, nombre) in [(4, "Marta".to_string()), (5, nombre.clone())] {
+            let parametros = [Datos::Integer(id), Datos::String(nombre)];
+            assert!(insert.ejecutar(&parametros).is_ok());
+        }
+        assert!(leer_tabla(&path, "clientes").ends_with(&format!("4,Marta\n5,{}\n", nombre)));
+
+        let parametros = [
+            Datos::String("seis".to_string()),
+            Datos::String("Sol".to_string()),
+        ];
+        assert!(insert.ejecutar(&parametros).is_err());
+        assert!(insert.ejecutar(&[Datos::Integer(6)]).is_err());
+        let antes = leer_tabla(&path, "clientes");
+        for nombre in ["x\n99,evil", "a,b,c", "Sol\r"] {
+            let parametros = [Datos::Integer(6), Datos::String(nombre.to_string())];
+            assert!(insert.ejecutar(&parametros).is_err());
+        }
+        assert_eq!(leer_tabla(&path, "clientes"), antes);
+
+        let update = "UPDATE clientes SET nombre = $2 WHERE id = $1".to_string();
+        let update = preparar(&path, &update);
+        let parametros = [Datos::Integer(4), Datos::String("Martina".to_string())];
+        assert!(update.ejecutar(&parametros).is_ok());
+        let parametros = [Datos::Integer(5), Datos::Integer(7)];
+        assert!(update.ejecutar(&parametros).is_err());
+        let parametros = [
+            Datos::Integer(5),
+            Datos::String("Eva\n8,Intrusa".to_string()),
+        ];
+        assert!(update.ejecutar(&parametros).is_err());
+
+        let delete = "DELETE FROM clientes WHERE id >= ?".to_string();
+        let delete = preparar(&path, &delete);
+        assert!(delete.ejecutar(&[Datos::Integer(5)]).is_ok());
+        assert_eq!(
+            leer_tabla(&path, "clientes"),
+            "id,nombre\n1,Juan\n2,Ana\n3,Luis\n4,Martina\n"
+        );
+
+        let select = "SELECT id, ? + cantidad FROM ordenes WHERE producto = ?".to_string();
+        let select = preparar(&path, &select);
+        let parametros = [Datos::Integer(10), Datos::String("Laptop".to_string())];
+        assert!(select.ejecutar(&parametros).is_ok());
+
+        let sin_valor = "INSERT INTO clientes VALUES (?, 'Ema')".to_string();
+        assert!(procesar_consulta(&sin_valor, &path).is_err());
+        let salteado = "SELECT * FROM clientes WHERE id = $2".to_string();
+        assert!(PreparedStatement::preparar(&path, &salteado).is_err());
+        assert!(procesar_consulta(&salteado, &path).is_err());
+    }
+
+    #[test]
+    fn test_explain() {
+        let path = preparar_directorio("explain");
+        agregar_clientes(&path);
+
+        let consulta = "EXPLAIN SELECT producto FROM ordenes WHERE cantidad > 1 ORDER BY producto";
+        assert_eq!(
+            salida_consulta(&path, consulta),
+            "Project\n  Columnas: producto\n  -> Sort\n       Orden: producto ASC\n       -> Seq Scan en ordenes\n            Filtro: cantidad > 1\n            Columnas leídas: producto, cantidad\n"
+        );
+
+        let consulta = "EXPLAIN ANALYZE SELECT c.nombre, o.producto FROM ordenes o JOIN clientes c ON o.id_cliente = c.id WHERE o.cantidad = 1";
+        let salida = salida_consulta(&path, consulta);
+        let lineas: Vec<&str> = salida.lines().collect();
+        assert!(lineas[0].starts_with("Project (filas: 2 -> 2, tiempo: "));
+        assert!(lineas[2].starts_with("  -> Result (filas: 2 -> 2, tiempo: "));
+        assert!(lineas[3].starts_with("       -> Block Nested Loop Join (filas: 2 -> 2, tiempo: "));
+        assert!(lineas[5].starts_with("            -> Seq Scan en ordenes o (filas: 2, tiempo: "));
+        assert_eq!(lineas[6], "                 Filtro: o.cantidad = 1");
+        assert!(lineas[8].starts_with("            -> Seq Scan en clientes c (filas: 3, tiempo: "));
+        assert!(lineas[9].starts_with("Tiempo total: "));
+        assert!(!salida.contains("Laptop"));
+
+        let consulta =
+            "EXPLAIN ANALYZE SELECT * FROM ordenes o JOIN clientes c ON o.id_cliente = c.id";
+        let salida = salida_consulta(&path, consulta);
+        let scans: Vec<&str> = salida
+            .lines()
+            .filter(|linea| linea.contains("Seq Scan"))
+            .collect();
+        assert_eq!(scans.len(), 2);
+        assert!(scans[0].ends_with(" ms)"));
+        assert!(scans[0].contains("Seq Scan en ordenes o (filas: 3, tiempo: "));
+        assert!(scans[1].ends_with(" ms)"));
+        assert!(scans[1].contains("Seq Scan en clientes c (filas: 3, tiempo: "));
+
+        let consulta = "EXPLAIN ANALYZE DELETE FROM ordenes WHERE cantidad = 1";
+        let salida = salida_consulta(&path, consulta);
+        assert!(salida.starts_with("Delete en ordenes (filas: 3 -> 1, tiempo: "));
+        assert!(salida.contains("Filtro: cantidad = 1"));
+        assert_eq!(
+            leer_tabla(&path, "ordenes"),
+            "id,id_cliente,producto,cantidad\n102,2,Telefono,2\n"
+        );
+
+        let consulta = "EXPLAIN CREATE VIEW todas AS SELECT * FROM ordenes".to_string();
+        assert!(procesar_consulta(&consulta, &path).is_err());
+    }
+
+    #[test]
+    fn test_optimizador() {
+        let path = preparar_directorio("optimizador");
+        agregar_clientes(&path);
+
+        let consulta = "SELECT c.nombre, o.producto FROM ordenes o JOIN clientes c ON o.id_cliente = c.id AND o.cantidad = 1 WHERE c.id > 0 AND 1 = 1";
+        assert_eq!(
+            salida_consulta(&path, consulta),
+            "c.nombre,o.producto\nJuan,Laptop\nJuan,Monitor\n"
+        );
+        let explain = salida_consulta(&path, &format!("EXPLAIN {}", consulta));
+        assert_eq!(
+            explain,
+            "Project\n  Columnas: c.nombre, o.producto\n  -> Result\n       -> Block Nested Loop Join\n            Condición: o.id_cliente = c.id\n            -> Seq Scan en ordenes o\n                 Filtro: o.cantidad = 1\n                 Columnas leídas: id_cliente, producto, cantidad\n            -> Seq Scan en clientes c\n                 Filtro: c.id > 0\n"
+        );
+
+        let consulta = "SELECT nombre, producto FROM ordenes o JOIN clientes c ON o.id_cliente = c.id WHERE cantidad = 2 OR nombre = 'Juan' ORDER BY producto";
+        assert_eq!(
+            salida_consulta(&path, consulta),
+            "nombre,producto\nJuan,Laptop\nJuan,Monitor\nAna,Telefono\n"
+        );
+        let explain = salida_consulta(&path, &format!("EXPLAIN {}", consulta));
+        assert!(explain
+            .contains("Condición: o.id_cliente = c.id AND (cantidad = 2 OR nombre = 'Juan')\n"));
+
+        let consulta =
+            "SELECT * FROM ordenes o JOIN clientes c ON o.id_cliente = c.id WHERE c.nombre = 'Ana'";
+        assert_eq!(
+            salida_consulta(&path, consulta),
+            "o.id,o.id_cliente,o.producto,o.cantidad,c.id,c.nombre\n102,2,Telefono,2,2,Ana\n"
+        );
+
+        let consulta = "SELECT id FROM ordenes WHERE NOT NOT cantidad = 1 OR 2 < 1";
+        assert_eq!(salida_consulta(&path, consulta), "id\n101\n103\n");
+        let explain = salida_consulta(&path, &format!("EXPLAIN {}", consulta));
+        assert!(explain.ends_with("Filtro: cantidad = 1\n       Columnas leídas: id, cantidad\n"));
+
+        let consulta = "SELECT producto FROM ordenes WHERE cantidad = 1 ORDER BY id DESC LIMIT 1";
+        assert_eq!(salida_consulta(&path, consulta), "producto\nMonitor\n");
+        let explain = salida_consulta(&path, &format!("EXPLAIN {}", consulta));
+        assert!(explain.contains("Columnas leídas: id, producto, cantidad\n"));
+        let select = match lexer(&consulta.to_string()).and_then(|tokens| parser(&tokens)) {
+            Ok(SQLQuery::Select(select)) => select,
+            _ => panic!("Se esperaba un SELECT"),
+        };
+        let Ok((path_aux, columnas, _)) = select.materializar(&path) else {
+            panic!("Error al ejecutar '{}'", consulta);
+        };
+        let filas = fs::read_to_string(&path_aux).unwrap();
+        fs::remove_file(&path_aux).unwrap();
+        assert_eq!(columnas, "producto");
+        assert_eq!(filas, "id,producto,cantidad\n103,Monitor,1\n");
+
+        let archivos = fs::read_dir(&path).unwrap().count();
+        assert_eq!(archivos, 3);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let path = preparar_directorio("analyze");
+        agregar_clientes(&path);
+        fs::write(
+            format!("{}/productos.csv", path),
+            "producto,precio\nLaptop,1000\nTelefono,500\nMonitor,200\nTeclado,50\n",
+        )
+        .unwrap();
+        let consulta = "SELECT c.nombre, p.precio FROM productos p JOIN ordenes o ON o.producto = p.producto JOIN clientes c ON o.id_cliente = c.id WHERE p.precio > 100 ORDER BY p.precio";
+        let sin_analizar = salida_consulta(&path, consulta);
+        assert_eq!(
+            sin_analizar,
+            "c.nombre,p.precio\nJuan,200\nAna,500\nJuan,1000\n"
+        );
+
+        assert!(procesar_consulta(&"ANALYZE ordenes".to_string(), &path).is_ok());
+        assert_eq!(
+            fs::read_to_string(format!("{}/ordenes.estadisticas", path)).unwrap(),
+            "columna,filas,distintos,nulos,minimo,maximo,histograma\nid,3,3,0,101,103,101..101:1;102..102:1;103..103:1\nid_cliente,3,2,0,1,2,1..1:2;2..2:1\nproducto,3,3,0,Laptop,Telefono,\ncantidad,3,2,0,1,2,1..1:2;2..2:1\n"
+        );
+        assert!(!Path::new(&format!("{}/clientes.estadisticas", path)).exists());
+
+        assert!(procesar_consulta(&"ANALYZE".to_string(), &path).is_ok());
+        let catalogo = "SELECT tabla, columna, filas, distintos, minimo, maximo FROM estadisticas WHERE columna = 'id' OR columna = 'precio'";
+        assert_eq!(
+            salida_consulta(&path, catalogo),
+            "tabla,columna,filas,distintos,minimo,maximo\nclientes,id,3,3,1,3\nordenes,id,3,3,101,103\nproductos,precio,4,4,50,1000\n"
+        );
+
+        let explain = salida_consulta(
+            &path,
+            "EXPLAIN SELECT producto FROM ordenes WHERE cantidad = 1",
+        );
+        assert_eq!(
+            explain,
+            "Project\n  Columnas: producto\n  -> Seq Scan en ordenes\n       Filtro: cantidad = 1\n       Columnas leídas: producto, cantidad\n       Filas estimadas: 2\n"
+        );
+        assert_eq!(salida_consulta(&path, consulta), sin_analizar);
+
+        let create = "CREATE VIEW compras AS SELECT producto FROM ordenes";
+        assert!(procesar_consulta(&create.to_string(), &path).is_ok());
+        assert!(procesar_consulta(&"ANALYZE compras".to_string(), &path).is_err());
+        assert!(procesar_consulta(&"ANALYZE nada".to_string(), &path).is_err());
+
+        let archivos = fs::read_dir(&path).unwrap().count();
+        assert_eq!(archivos, 8);
     }
 }